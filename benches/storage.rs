@@ -0,0 +1,218 @@
+use async_ecs::{
+    component::ComponentHooks,
+    entity::Entity,
+    storage::{
+        BTreeStorage, DefaultVecStorage, DenseVecStorage, HashMapStorage, PagedStorage,
+        SparseSetStorage, VecStorage,
+    },
+    Component, Join, World, WriteStorage,
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Total number of entities that are created for each benchmark.
+const ENTITIES: u32 = 10_000;
+
+/// Size of the clusters of entities that own the component in the
+/// clustered workloads.
+const CLUSTER: u32 = 256;
+
+macro_rules! components {
+    ($($name:ident => $storage:ident),* $(,)?) => {
+        $(
+            #[derive(Default, Clone, Copy)]
+            struct $name(u64);
+
+            impl Component for $name {
+                type Storage = $storage<Self>;
+            }
+
+            impl From<u64> for $name {
+                fn from(value: u64) -> Self {
+                    Self(value)
+                }
+            }
+
+            impl Value for $name {
+                fn value(&self) -> u64 {
+                    self.0
+                }
+
+                fn value_mut(&mut self) -> &mut u64 {
+                    &mut self.0
+                }
+            }
+        )*
+    };
+}
+
+trait Value: Component + From<u64> + Send + Sync {
+    fn value(&self) -> u64;
+    fn value_mut(&mut self) -> &mut u64;
+}
+
+components! {
+    BTreeValue => BTreeStorage,
+    DefaultVecValue => DefaultVecStorage,
+    DenseVecValue => DenseVecStorage,
+    HashMapValue => HashMapStorage,
+    PagedValue => PagedStorage,
+    SparseSetValue => SparseSetStorage,
+    VecValue => VecStorage,
+}
+
+/// Layout of the entities that own the component.
+#[derive(Clone, Copy)]
+enum Layout {
+    /// All entities own the component.
+    Dense,
+
+    /// Only every second cluster of `CLUSTER` entities owns the component.
+    Clustered,
+}
+
+impl Layout {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Dense => "dense",
+            Self::Clustered => "clustered",
+        }
+    }
+
+    fn contains(&self, index: u32) -> bool {
+        match self {
+            Self::Dense => true,
+            Self::Clustered => (index / CLUSTER) % 2 == 0,
+        }
+    }
+}
+
+fn setup<T: Value>(layout: Layout) -> (World, Vec<Entity>)
+where
+    T::Storage: Default,
+{
+    let mut world = World::default();
+    world.register_component::<T>();
+
+    let entities = world
+        .create_iter()
+        .take(ENTITIES as usize)
+        .enumerate()
+        .filter(|(i, _)| layout.contains(*i as u32))
+        .map(|(_, entity)| entity)
+        .collect();
+
+    (world, entities)
+}
+
+fn insert_all<T: Value>(storage: &mut WriteStorage<T>, entities: &[Entity]) {
+    for (i, entity) in entities.iter().enumerate() {
+        storage.insert(*entity, T::from(i as u64)).unwrap();
+    }
+}
+
+fn bench_storage<T: Value>(c: &mut Criterion, name: &str)
+where
+    T::Storage: Default,
+{
+    for &layout in &[Layout::Dense, Layout::Clustered] {
+        let (world, entities) = setup::<T>(layout);
+
+        c.bench_with_input(
+            BenchmarkId::new(format!("insert/{}", layout.name()), name),
+            &entities,
+            |b, entities| {
+                b.iter(|| {
+                    let mut storage = world.component_mut::<T>();
+                    insert_all(&mut storage, entities);
+                    storage.clear();
+                })
+            },
+        );
+
+        c.bench_with_input(
+            BenchmarkId::new(format!("replace/{}", layout.name()), name),
+            &entities,
+            |b, entities| {
+                let mut storage = world.component_mut::<T>();
+                insert_all(&mut storage, entities);
+
+                b.iter(|| insert_all(&mut storage, entities));
+
+                storage.clear();
+            },
+        );
+
+        c.bench_with_input(
+            BenchmarkId::new(format!("remove/{}", layout.name()), name),
+            &entities,
+            |b, entities| {
+                b.iter(|| {
+                    let mut storage = world.component_mut::<T>();
+                    insert_all(&mut storage, entities);
+                    for entity in entities {
+                        black_box(storage.remove(*entity));
+                    }
+                })
+            },
+        );
+
+        insert_all(&mut world.component_mut::<T>(), &entities);
+
+        c.bench_function(&format!("iterate/{}/{}", layout.name(), name), |b| {
+            let storage = world.component::<T>();
+
+            b.iter(|| black_box((&storage).join().map(Value::value).sum::<u64>()))
+        });
+
+        c.bench_function(&format!("iterate_mut/{}/{}", layout.name(), name), |b| {
+            let mut storage = world.component_mut::<T>();
+
+            b.iter(|| {
+                for value in (&mut storage).join() {
+                    *value.value_mut() += 1;
+                }
+            })
+        });
+    }
+}
+
+/// Compares the insertion of new components with and without an insert hook.
+fn bench_hooks(c: &mut Criterion) {
+    let hooks = || ComponentHooks::<VecValue>::new().on_insert(|_, value| value.0 += 1);
+
+    for &hooked in &[false, true] {
+        let mut world = World::default();
+        if hooked {
+            world.register_component_with_hooks(hooks());
+        } else {
+            world.register_component::<VecValue>();
+        }
+
+        let entities = world
+            .create_iter()
+            .take(ENTITIES as usize)
+            .collect::<Vec<_>>();
+        let name = if hooked { "hooked" } else { "plain" };
+
+        c.bench_function(&format!("insert_hook/{}", name), |b| {
+            b.iter(|| {
+                let mut storage = world.component_mut::<VecValue>();
+                insert_all(&mut storage, &entities);
+                storage.clear();
+            })
+        });
+    }
+}
+
+fn storages(c: &mut Criterion) {
+    bench_storage::<BTreeValue>(c, "btree");
+    bench_storage::<DefaultVecValue>(c, "default_vec");
+    bench_storage::<DenseVecValue>(c, "dense_vec");
+    bench_storage::<HashMapValue>(c, "hash_map");
+    bench_storage::<PagedValue>(c, "paged");
+    bench_storage::<SparseSetValue>(c, "sparse_set");
+    bench_storage::<VecValue>(c, "vec");
+}
+
+criterion_group!(benches, storages, bench_hooks);
+criterion_main!(benches);
@@ -0,0 +1,112 @@
+use async_ecs::{
+    dispatcher, Builder, ChangeTracker, Component, Dispatcher, Entities, FlaggedStorage, Join,
+    ReadStorage, System, VecStorage, World, WriteStorage,
+};
+use tokio::runtime;
+
+#[derive(Debug)]
+pub struct Transform {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Transform {
+    // Flagged storage to be able to join over changed transforms only.
+    type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+}
+
+#[derive(Debug, Default)]
+pub struct BoundingBox {
+    min: (f32, f32),
+    max: (f32, f32),
+}
+
+impl Component for BoundingBox {
+    type Storage = VecStorage<Self>;
+}
+
+/// System that moves some of the transforms.
+struct MoveSystem(usize);
+
+impl<'s> System<'s> for MoveSystem {
+    type SystemData = (Entities<'s>, WriteStorage<'s, Transform>);
+
+    fn run(&mut self, (entities, mut transforms): Self::SystemData) {
+        // Only move every second transform, the others stay untouched.
+        // Every mutable access flags a component as changed, so we select
+        // the entities to move before accessing the transforms mutably.
+        let moved = (&entities, &transforms)
+            .join()
+            .map(|(entity, _)| entity)
+            .skip(self.0 % 2)
+            .step_by(2)
+            .collect::<Vec<_>>();
+
+        for entity in moved {
+            let transform = transforms.get_mut(entity).unwrap();
+            transform.x += 1.0;
+            transform.y += 1.0;
+        }
+
+        self.0 += 1;
+    }
+}
+
+/// System that updates the bounding boxes of moved transforms only.
+#[derive(Default)]
+struct BoundingBoxSystem {
+    tracker: ChangeTracker,
+}
+
+impl<'s> System<'s> for BoundingBoxSystem {
+    type SystemData = (ReadStorage<'s, Transform>, WriteStorage<'s, BoundingBox>);
+
+    fn run(&mut self, (transforms, mut boxes): Self::SystemData) {
+        let changed = transforms.changed_since(&mut self.tracker);
+
+        for (transform, bbox) in (changed, &mut boxes).join() {
+            bbox.min = (transform.x - 0.5, transform.y - 0.5);
+            bbox.max = (transform.x + 0.5, transform.y + 0.5);
+
+            eprintln!("updated bounding box: {:?}", bbox);
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let rt = runtime::Builder::new_multi_thread().build()?;
+
+    rt.block_on(async {
+        let mut world = World::default();
+
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with(MoveSystem(0), "move_system", &[])?
+            .with(
+                BoundingBoxSystem::default(),
+                "bounding_box_system",
+                &["move_system"],
+            )?
+            .build();
+
+        for i in 0..4 {
+            world
+                .create_entity()
+                .with(Transform {
+                    x: i as f32,
+                    y: 0.0,
+                })
+                .with(BoundingBox::default())
+                .build();
+        }
+
+        for n in 0..3 {
+            eprintln!("Iteration {}", n);
+            dispatcher.dispatch(&world).await?;
+            eprintln!();
+        }
+
+        Result::<(), dispatcher::Error>::Ok(())
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,268 @@
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    resource::{Cell, Resource, ResourceCache, ResourceId, Resources},
+    system::{DynamicSystemData, SystemData},
+    world::{SetupHandler, World},
+};
+
+use super::Read;
+
+/// System data that only reads resources and can therefore be fetched from
+/// the cells cached by a `ResourceCache`. See `Cached` for details.
+pub trait CachedSystemData<'a>: SystemData<'a> {
+    /// Fetches the system data from the passed cells. The cells are in the
+    /// order of the ids returned by `SystemData::reads`, each cell is `None`
+    /// if the resource does not exist.
+    fn fetch_cached<I>(cells: &mut I) -> Self
+    where
+        I: Iterator<Item = Option<&'a Cell<Box<dyn Resource>>>>;
+}
+
+/// Wraps system data that only reads resources and fetches it using the
+/// `ResourceCache` of the system.
+///
+/// Fetching system data usually looks up each resource in the `Resources`
+/// of the world. For hot loops with many small systems, these lookups can
+/// be avoided by storing a `ResourceCache` in the system and returning it
+/// from `System::accessor`. The cache only looks up the resources again
+/// after resources were inserted into or removed from the world.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{
+/// #     access::{AccessorCow, Cached},
+/// #     resource::ResourceCache,
+/// #     system::DynamicSystemData,
+/// #     *,
+/// # };
+/// #
+/// #[derive(Default)]
+/// struct Gravity(f32);
+///
+/// #[derive(Default)]
+/// struct Scale(f32);
+///
+/// type Data<'a> = (Read<'a, Gravity>, Read<'a, Scale>);
+///
+/// struct Force {
+///     cache: ResourceCache,
+///     force: f32,
+/// }
+///
+/// impl<'a> System<'a> for Force {
+///     type SystemData = Cached<'a, Data<'a>>;
+///
+///     fn run(&mut self, data: Self::SystemData) {
+///         let (gravity, scale) = &*data;
+///
+///         self.force = gravity.0 * scale.0;
+///     }
+///
+///     fn accessor<'b>(&'b self) -> AccessorCow<'a, 'b, Self::SystemData> {
+///         AccessorCow::Borrow(&self.cache)
+///     }
+/// }
+///
+/// let mut world = World::default();
+/// world.insert(Gravity(9.81));
+/// world.insert(Scale(2.0));
+///
+/// let mut force = Force {
+///     cache: ResourceCache::of::<Data>(),
+///     force: 0.0,
+/// };
+///
+/// let data = Cached::<Data>::fetch(&force.cache, &world);
+/// force.run(data);
+///
+/// assert_eq!(force.force, 19.62);
+/// ```
+pub struct Cached<'a, T> {
+    inner: T,
+    marker: PhantomData<&'a ()>,
+}
+
+impl<'a, T> Cached<'a, T> {
+    /// Returns the wrapped system data.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<'a, T> Deref for Cached<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<'a, T> DerefMut for Cached<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<'a, T> DynamicSystemData<'a> for Cached<'a, T>
+where
+    T: CachedSystemData<'a>,
+{
+    type Accessor = ResourceCache;
+
+    fn setup(_: &ResourceCache, world: &mut World) {
+        T::setup(world)
+    }
+
+    fn fetch(cache: &ResourceCache, world: &'a World) -> Self {
+        // The cells are borrowed without checking their type, so a cache of
+        // other resources must never be used to fetch the data.
+        assert_eq!(cache.ids(), &T::reads()[..], "Cache does not match data");
+
+        Self {
+            inner: T::fetch_cached(&mut cache.cells(world)),
+            marker: PhantomData,
+        }
+    }
+}
+
+/* CachedSystemData */
+
+impl<'a, T, F> CachedSystemData<'a> for Read<'a, T, F>
+where
+    T: Resource,
+    F: SetupHandler<T>,
+{
+    fn fetch_cached<I>(cells: &mut I) -> Self
+    where
+        I: Iterator<Item = Option<&'a Cell<Box<dyn Resource>>>>,
+    {
+        let cell = cells.next().flatten();
+
+        Self::new(Resources::borrow_cell(&ResourceId::new::<T>(), cell))
+    }
+}
+
+impl<'a, T, F> CachedSystemData<'a> for Option<Read<'a, T, F>>
+where
+    T: Resource,
+{
+    fn fetch_cached<I>(cells: &mut I) -> Self
+    where
+        I: Iterator<Item = Option<&'a Cell<Box<dyn Resource>>>>,
+    {
+        let cell = cells.next().flatten();
+
+        Resources::try_borrow_cell(&ResourceId::new::<T>(), cell).map(Into::into)
+    }
+}
+
+mod impl_cached_system_data {
+    use super::*;
+
+    macro_rules! impl_cached_system_data {
+        ( $($ty:ident),* ) => {
+            impl<'a, $($ty),*> CachedSystemData<'a> for ( $( $ty , )* )
+                where $( $ty : CachedSystemData<'a> ),*
+                {
+                    fn fetch_cached<Cells>(cells: &mut Cells) -> Self
+                    where
+                        Cells: Iterator<Item = Option<&'a Cell<Box<dyn Resource>>>>,
+                    {
+                        ( $( <$ty as CachedSystemData<'a>>::fetch_cached(cells), )* )
+                    }
+                }
+        };
+    }
+
+    impl_cached_system_data!(A);
+    impl_cached_system_data!(A, B);
+    impl_cached_system_data!(A, B, C);
+    impl_cached_system_data!(A, B, C, D);
+    impl_cached_system_data!(A, B, C, D, E);
+    impl_cached_system_data!(A, B, C, D, E, F);
+    impl_cached_system_data!(A, B, C, D, E, F, G);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V);
+    impl_cached_system_data!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W);
+    impl_cached_system_data!(
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X
+    );
+    impl_cached_system_data!(
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y
+    );
+    impl_cached_system_data!(
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resource::ResourceCache;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct ResA(u32);
+
+    #[derive(Default)]
+    struct ResB(u32);
+
+    type Data<'a> = (Read<'a, ResA>, Option<Read<'a, ResB>>);
+
+    #[test]
+    fn refresh_on_structural_change() {
+        let mut world = World::default();
+        world.insert(ResA(1));
+
+        let cache = ResourceCache::of::<Data>();
+        assert_send(&cache);
+
+        let data = Cached::<Data>::fetch(&cache, &world);
+        assert_eq!(data.0 .0, 1);
+        assert!(data.1.is_none());
+        drop(data);
+
+        world.insert(ResB(2));
+
+        let data = Cached::<Data>::fetch(&cache, &world);
+        assert_eq!(data.1.as_ref().unwrap().0, 2);
+        drop(data);
+
+        world.resource_mut::<ResA>().0 = 3;
+        world.remove::<ResB>();
+
+        let data = Cached::<Data>::fetch(&cache, &world);
+        assert_eq!(data.0 .0, 3);
+        assert!(data.1.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cache does not match data")]
+    fn mismatched_cache() {
+        let mut world = World::default();
+        world.insert(ResA(1));
+        world.insert(ResB(2));
+
+        let cache = ResourceCache::new(vec![ResourceId::new::<ResB>()]);
+
+        Cached::<Read<ResA>>::fetch(&cache, &world);
+    }
+
+    fn assert_send<T: Send>(_: &T) {}
+}
@@ -0,0 +1,227 @@
+use std::marker::PhantomData;
+
+use hashbrown::HashMap;
+
+use crate::{
+    component::{Component, DynamicId},
+    entity::Entities,
+    resource::ResourceId,
+    system::DynamicSystemData,
+    world::World,
+};
+
+use super::{Accessor, ReadStorage, WriteStorage};
+
+/// System data that fetches dynamic component storages (see
+/// `World::register_dynamic_component`) of the component `T`.
+///
+/// The storages to fetch are defined at run-time by the
+/// `DynamicStorageAccessor`, which has to be returned by the `accessor`
+/// method of the system.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::*;
+/// # use async_ecs::access::{AccessorCow, DynamicStorageAccessor, DynamicStorages};
+/// # use async_ecs::component::DynamicId;
+/// #
+/// struct Value(f32);
+///
+/// impl Component for Value {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// /// Adds the value of one dynamic storage to another one.
+/// struct Add {
+///     accessor: DynamicStorageAccessor<Value>,
+///     source: DynamicId,
+///     target: DynamicId,
+/// }
+///
+/// impl<'a> System<'a> for Add {
+///     type SystemData = DynamicStorages<'a, Value>;
+///
+///     fn run(&mut self, mut storages: Self::SystemData) {
+///         let (source, target) = storages.split(self.source, self.target);
+///
+///         for (source, target) in (source.unwrap(), target.unwrap()).join() {
+///             target.0 += source.0;
+///         }
+///     }
+///
+///     fn accessor<'b>(&'b self) -> AccessorCow<'a, 'b, Self::SystemData> {
+///         AccessorCow::Borrow(&self.accessor)
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let source = DynamicId::from("regeneration");
+/// let target = DynamicId::from("health");
+///
+/// let mut world = World::default();
+/// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with(
+///         Add {
+///             accessor: DynamicStorageAccessor::new().read(source).write(target),
+///             source,
+///             target,
+///         },
+///         "add",
+///         &[],
+///     )
+///     .unwrap()
+///     .build();
+///
+/// let entity = world.create_entity().build();
+/// world
+///     .dynamic_component_mut::<Value, _>(source)
+///     .insert(entity, Value(1.0))
+///     .unwrap();
+/// world
+///     .dynamic_component_mut::<Value, _>(target)
+///     .insert(entity, Value(10.0))
+///     .unwrap();
+///
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// let health = world.dynamic_component::<Value, _>(target);
+/// assert_eq!(health.get(entity).unwrap().0, 11.0);
+/// # }
+/// ```
+pub struct DynamicStorages<'a, T: Component> {
+    reads: HashMap<DynamicId, ReadStorage<'a, T>>,
+    writes: HashMap<DynamicId, WriteStorage<'a, T>>,
+}
+
+impl<'a, T> DynamicStorages<'a, T>
+where
+    T: Component,
+{
+    /// Returns the storage with the passed id, if it was requested for
+    /// reading by the accessor.
+    pub fn read(&self, id: DynamicId) -> Option<&ReadStorage<'a, T>> {
+        self.reads.get(&id)
+    }
+
+    /// Returns the storage with the passed id, if it was requested for
+    /// writing by the accessor.
+    pub fn write(&mut self, id: DynamicId) -> Option<&mut WriteStorage<'a, T>> {
+        self.writes.get_mut(&id)
+    }
+
+    /// Returns the storage `read` that was requested for reading and the
+    /// storage `write` that was requested for writing at once.
+    pub fn split(
+        &mut self,
+        read: DynamicId,
+        write: DynamicId,
+    ) -> (
+        Option<&ReadStorage<'a, T>>,
+        Option<&mut WriteStorage<'a, T>>,
+    ) {
+        (self.reads.get(&read), self.writes.get_mut(&write))
+    }
+}
+
+impl<'a, T> DynamicSystemData<'a> for DynamicStorages<'a, T>
+where
+    T: Component,
+    T::Storage: Default,
+{
+    type Accessor = DynamicStorageAccessor<T>;
+
+    fn setup(accessor: &Self::Accessor, world: &mut World) {
+        for id in accessor.reads.iter().chain(&accessor.writes) {
+            world.register_dynamic_component::<T, _>(*id);
+        }
+    }
+
+    fn fetch(accessor: &Self::Accessor, world: &'a World) -> Self {
+        let reads = accessor
+            .reads
+            .iter()
+            .map(|id| (*id, world.dynamic_component(*id)))
+            .collect();
+        let writes = accessor
+            .writes
+            .iter()
+            .map(|id| (*id, world.dynamic_component_mut(*id)))
+            .collect();
+
+        Self { reads, writes }
+    }
+}
+
+/* DynamicStorageAccessor */
+
+/// Accessor of `DynamicStorages`, that defines which dynamic storages of the
+/// component `T` are read and written by a system.
+pub struct DynamicStorageAccessor<T> {
+    reads: Vec<DynamicId>,
+    writes: Vec<DynamicId>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> DynamicStorageAccessor<T>
+where
+    T: Component,
+{
+    /// Creates a new accessor that does not access any storage.
+    pub fn new() -> Self {
+        Self {
+            reads: Vec::new(),
+            writes: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Adds the storage with the passed id to the storages that are read.
+    pub fn read<I>(mut self, id: I) -> Self
+    where
+        I: Into<DynamicId>,
+    {
+        self.reads.push(id.into());
+
+        self
+    }
+
+    /// Adds the storage with the passed id to the storages that are written.
+    pub fn write<I>(mut self, id: I) -> Self
+    where
+        I: Into<DynamicId>,
+    {
+        self.writes.push(id.into());
+
+        self
+    }
+}
+
+impl<T> Default for DynamicStorageAccessor<T>
+where
+    T: Component,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Accessor for DynamicStorageAccessor<T>
+where
+    T: Component,
+{
+    fn reads(&self) -> Vec<ResourceId> {
+        let mut reads = vec![ResourceId::new::<Entities>()];
+        reads.extend(self.reads.iter().map(DynamicId::resource_id::<T>));
+
+        reads
+    }
+
+    fn writes(&self) -> Vec<ResourceId> {
+        self.writes
+            .iter()
+            .map(DynamicId::resource_id::<T>)
+            .collect()
+    }
+}
@@ -0,0 +1,155 @@
+use hibitset::BitSet;
+
+use crate::{
+    entity::{Entities, Entity, Index},
+    error::Error,
+    join::{Join, ParJoin},
+    resource::{Ref, ResourceId},
+    system::SystemData,
+    world::World,
+};
+
+/// Snapshot of the living entities, captured when the view is fetched.
+///
+/// Joining `&Entities` only yields entities that were maintained, but
+/// entities deleted atomically using `Entities::delete` are still yielded
+/// until the world is maintained. The view captures the living entities
+/// without the atomically deleted ones at fetch time, so entities that are
+/// created or deleted atomically by other tasks while the view is joined
+/// (in parallel or not) do not change the set of yielded entities.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{access::EntitiesView, system::SystemData, *};
+/// #
+/// let mut world = World::default();
+///
+/// let e1 = world.create_entity().build();
+/// let e2 = world.create_entity().build();
+///
+/// world.entities().delete(e1).unwrap();
+///
+/// let view = EntitiesView::fetch(&world);
+/// let e3 = world.entities().create();
+/// world.entities().delete(e2).unwrap();
+///
+/// assert!(!view.contains(e1));
+/// assert!(view.contains(e2));
+/// assert!(!view.contains(e3));
+/// assert_eq!(view.join().collect::<Vec<_>>(), vec![e2]);
+/// ```
+pub struct EntitiesView<'a> {
+    entities: Ref<'a, Entities>,
+    alive: BitSet,
+}
+
+impl<'a> EntitiesView<'a> {
+    pub fn new(entities: Ref<'a, Entities>) -> Self {
+        let alive = entities.alive_mask();
+
+        Self { entities, alive }
+    }
+
+    /// Returns `true` if the passed entity was alive when the view was
+    /// fetched.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.alive.contains(entity.index()) && self.entities.is_alive(entity)
+    }
+
+    /// Returns the captured mask of the living entities.
+    pub fn mask(&self) -> &BitSet {
+        &self.alive
+    }
+
+    /// Returns the entities the view was captured from.
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+}
+
+impl<'a, 'e> Join for &'a EntitiesView<'e> {
+    type Mask = &'a BitSet;
+    type Type = Entity;
+    type Value = &'a Entities;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (&self.alive, &self.entities)
+    }
+
+    unsafe fn get(v: &mut Self::Value, index: Index) -> Entity {
+        <&Entities as Join>::get(v, index)
+    }
+}
+
+impl<'a, 'e> ParJoin for &'a EntitiesView<'e> {}
+
+impl<'a> SystemData<'a> for EntitiesView<'a> {
+    fn setup(_: &mut World) {}
+
+    fn fetch(world: &'a World) -> Self {
+        Self::new(world.borrow())
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        Ok(Self::new(world.fetch()?))
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        vec![ResourceId::new::<Entities>()]
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use asparit::{Driver, ParallelIterator};
+
+    use crate::{component::Component, entity::Builder, storage::VecStorage};
+
+    use super::*;
+
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn par_join_with_atomic_changes() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let entities = (0..100)
+            .map(|i| world.create_entity().with(Pos(i)).build())
+            .collect::<Vec<_>>();
+
+        world.entities().delete(entities[0]).unwrap();
+
+        let view = EntitiesView::fetch(&world);
+        let pos = world.component::<Pos>();
+        let created = Mutex::new(Vec::new());
+
+        (&view, &pos)
+            .par_join()
+            .for_each(|(entity, pos)| {
+                if pos.0 % 2 == 0 {
+                    view.entities().delete(entity).unwrap();
+                }
+
+                created.lock().unwrap().push(view.entities().create());
+            })
+            .exec();
+
+        let created = created.into_inner().unwrap();
+
+        assert_eq!(created.len(), 99);
+        assert!(created.iter().all(|e| !view.contains(*e)));
+        assert_eq!((&view, &pos).join().count(), 99);
+    }
+}
@@ -0,0 +1,189 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    error::Error,
+    resource::{
+        cell::{Ref as CellRef, RefMut as CellRefMut},
+        LocalResources, Ref, ResourceId,
+    },
+    system::SystemData,
+    world::World,
+};
+
+/// Allows to fetch a resource of the `LocalResources` in a system immutably.
+/// **This will panic if the resource does not exist.**
+///
+/// Systems that use this accessor are always executed on the local task set
+/// of the dispatcher, even if they were added using `add` or `add_async`.
+///
+/// ## Examples
+///
+/// ```
+/// # use std::rc::Rc;
+/// # use async_ecs::*;
+/// # use tokio::task::LocalSet;
+/// #
+/// struct Window(Rc<String>);
+///
+/// struct Render;
+///
+/// impl<'a> System<'a> for Render {
+///     type SystemData = (LocalRead<'a, Window>, LocalWrite<'a, Vec<String>>);
+///
+///     fn run(&mut self, (window, mut log): Self::SystemData) {
+///         log.push(window.0.to_string());
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// # LocalSet::new().run_until(async {
+/// let mut world = World::default();
+/// world.insert_local(Window(Rc::new("main".into())));
+/// world.insert_local(Vec::<String>::new());
+///
+/// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with(Render, "render", &[])
+///     .unwrap()
+///     .build();
+///
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// assert_eq!(*world.local_resource::<Vec<String>>(), vec!["main"]);
+/// # }).await;
+/// # }
+/// ```
+pub struct LocalRead<'a, T: 'static> {
+    inner: CellRef<'a, T>,
+    _resources: Ref<'a, LocalResources>,
+}
+
+impl<'a, T> LocalRead<'a, T>
+where
+    T: 'static,
+{
+    fn new(resources: Ref<'a, LocalResources>) -> Result<Self, Error> {
+        // SAFETY: `_resources` keeps the store borrowed as long as the
+        // returned value exists and it is dropped after `inner`.
+        let local = unsafe { &*(&*resources as *const LocalResources) };
+
+        Ok(Self {
+            inner: local.fetch()?,
+            _resources: resources,
+        })
+    }
+}
+
+impl<'a, T> Deref for LocalRead<'a, T>
+where
+    T: 'static,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<'a, T> SystemData<'a> for LocalRead<'a, T>
+where
+    T: 'static,
+{
+    fn setup(world: &mut World) {
+        world
+            .entry::<LocalResources>()
+            .or_insert_with(Default::default);
+    }
+
+    fn fetch(world: &'a World) -> Self {
+        Self::try_fetch(world).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        Self::new(world.fetch()?)
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        vec![
+            ResourceId::new::<LocalResources>(),
+            ResourceId::new_local::<T>(),
+        ]
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        vec![]
+    }
+}
+
+/// Allows to fetch a resource of the `LocalResources` in a system mutably.
+/// **This will panic if the resource does not exist.**
+///
+/// Like `LocalRead`, systems that use this accessor are always executed on
+/// the local task set of the dispatcher.
+pub struct LocalWrite<'a, T: 'static> {
+    inner: CellRefMut<'a, T>,
+    _resources: Ref<'a, LocalResources>,
+}
+
+impl<'a, T> LocalWrite<'a, T>
+where
+    T: 'static,
+{
+    fn new(resources: Ref<'a, LocalResources>) -> Result<Self, Error> {
+        // SAFETY: `_resources` keeps the store borrowed as long as the
+        // returned value exists and it is dropped after `inner`.
+        let local = unsafe { &*(&*resources as *const LocalResources) };
+
+        Ok(Self {
+            inner: local.fetch_mut()?,
+            _resources: resources,
+        })
+    }
+}
+
+impl<'a, T> Deref for LocalWrite<'a, T>
+where
+    T: 'static,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<'a, T> DerefMut for LocalWrite<'a, T>
+where
+    T: 'static,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<'a, T> SystemData<'a> for LocalWrite<'a, T>
+where
+    T: 'static,
+{
+    fn setup(world: &mut World) {
+        world
+            .entry::<LocalResources>()
+            .or_insert_with(Default::default);
+    }
+
+    fn fetch(world: &'a World) -> Self {
+        Self::try_fetch(world).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        Self::new(world.fetch()?)
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        vec![ResourceId::new::<LocalResources>()]
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        vec![ResourceId::new_local::<T>()]
+    }
+}
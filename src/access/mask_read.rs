@@ -0,0 +1,160 @@
+use std::ops::Not;
+
+use hibitset::{BitSet, BitSetLike};
+
+use crate::{
+    component::Component,
+    entity::{Entities, Entity, Index},
+    error::Error,
+    join::{Join, MaskGeneration, ParJoin},
+    misc::TryDefault,
+    resource::{Ref, ResourceId},
+    storage::{AntiStorage, MaskedStorage},
+    system::SystemData,
+    world::World,
+};
+
+/// Read access to the mask of a component storage.
+///
+/// In contrast to `ReadStorage` the components themselves can not be
+/// accessed, only the information which entities have a component. This is
+/// useful for systems that only need presence checks, and makes this
+/// requirement visible in the system data.
+///
+/// The mask can be joined with other storages to filter them by the
+/// component, without fetching the component itself.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{access::MaskRead, system::SystemData, *};
+/// #
+/// # #[derive(Default)]
+/// # struct Frozen;
+/// # impl Component for Frozen { type Storage = NullStorage<Self>; }
+/// #
+/// # #[derive(Debug, PartialEq)]
+/// # struct Pos(u32);
+/// # impl Component for Pos { type Storage = VecStorage<Self>; }
+/// #
+/// let mut world = World::default();
+/// world.register_component::<Pos>();
+/// world.register_component::<Frozen>();
+///
+/// let e1 = world.create_entity().with(Pos(1)).with(Frozen).build();
+/// let e2 = world.create_entity().with(Pos(2)).build();
+///
+/// let frozen = MaskRead::<Frozen>::fetch(&world);
+/// let mut pos = world.component_mut::<Pos>();
+///
+/// assert!(frozen.contains(e1));
+/// assert!(!frozen.contains(e2));
+///
+/// for (pos, ()) in (&mut pos, !&frozen).join() {
+///     pos.0 += 10;
+/// }
+///
+/// assert_eq!(pos.get(e1), Some(&Pos(1)));
+/// assert_eq!(pos.get(e2), Some(&Pos(12)));
+/// ```
+pub struct MaskRead<'a, T: Component> {
+    data: Ref<'a, MaskedStorage<T>>,
+    entities: Ref<'a, Entities>,
+}
+
+impl<'a, T> MaskRead<'a, T>
+where
+    T: Component,
+{
+    pub fn new(data: Ref<'a, MaskedStorage<T>>, entities: Ref<'a, Entities>) -> Self {
+        Self { data, entities }
+    }
+
+    /// Returns true if the storage has a component for this entity, and that
+    /// entity is alive.
+    pub fn contains(&self, e: Entity) -> bool {
+        self.data.mask().contains(e.index()) && self.entities.is_alive(e)
+    }
+
+    /// Returns a reference to the bitset of the storage.
+    pub fn mask(&self) -> &BitSet {
+        self.data.mask()
+    }
+
+    /// Computes the number of components in the storage by counting the
+    /// bits in the bit set. This operation will never be performed in
+    /// constant time.
+    pub fn count(&self) -> usize {
+        self.mask().iter().count()
+    }
+
+    /// Checks whether the storage is empty. This operation is very cheap.
+    pub fn is_empty(&self) -> bool {
+        self.mask().is_empty()
+    }
+}
+
+impl<'a, 'e, T> Not for &'a MaskRead<'e, T>
+where
+    T: Component,
+{
+    type Output = AntiStorage<'a>;
+
+    fn not(self) -> Self::Output {
+        AntiStorage(self.data.mask())
+    }
+}
+
+impl<'a, 'e, T> Join for &'a MaskRead<'e, T>
+where
+    T: Component,
+{
+    type Mask = &'a BitSet;
+    type Type = ();
+    type Value = ();
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (self.data.mask(), ())
+    }
+
+    unsafe fn get(_: &mut Self::Value, _: Index) {}
+}
+
+impl<'a, 'e, T> ParJoin for &'a MaskRead<'e, T> where T: Component {}
+
+impl<'a, 'e, T> MaskGeneration for &'a MaskRead<'e, T>
+where
+    T: Component,
+{
+    fn mask_generation(&self) -> u64 {
+        self.data.generation()
+    }
+}
+
+impl<'a, T> SystemData<'a> for MaskRead<'a, T>
+where
+    T: Component,
+{
+    fn setup(world: &mut World) {
+        world.register_component_with_storage::<T, _>(TryDefault::unwrap_default);
+    }
+
+    fn fetch(world: &'a World) -> Self {
+        Self::new(world.borrow(), world.borrow())
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        Ok(Self::new(world.fetch()?, world.fetch()?))
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        vec![
+            ResourceId::new::<Entities>(),
+            ResourceId::new::<MaskedStorage<T>>(),
+        ]
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        vec![]
+    }
+}
@@ -1,11 +1,25 @@
 pub mod accessor;
+pub mod cached;
+pub mod dynamic_storage;
+pub mod entities_view;
+pub mod local;
+pub mod mask_read;
+pub mod query;
 pub mod read;
 pub mod read_storage;
+pub mod singleton;
 pub mod write;
 pub mod write_storage;
 
 pub use accessor::{Accessor, AccessorCow, AccessorType, StaticAccessor};
-pub use read::Read;
+pub use cached::{Cached, CachedSystemData};
+pub use dynamic_storage::{DynamicStorageAccessor, DynamicStorages};
+pub use entities_view::EntitiesView;
+pub use local::{LocalRead, LocalWrite};
+pub use mask_read::MaskRead;
+pub use query::Query;
+pub use read::{Read, ReadExpect};
 pub use read_storage::ReadStorage;
-pub use write::Write;
+pub use singleton::{ReadSingleton, WriteSingleton};
+pub use write::{Write, WriteExpect};
 pub use write_storage::WriteStorage;
@@ -0,0 +1,624 @@
+use std::marker::PhantomData;
+
+use hibitset::{AtomicBitSet, BitSet, BitSetAll, BitSetAnd, BitSetLike, BitSetOr};
+
+use crate::{
+    component::Component,
+    entity::Index,
+    error::Error,
+    join::{Join, ParJoin},
+    misc::{BitAnd, BitSetEither, BitSetNot},
+    resource::{Resource, ResourceId},
+    storage::{ChangeMasks, MaskedStorage},
+    system::SystemData,
+    world::World,
+};
+
+use super::{MaskRead, Read, ReadStorage, WriteStorage};
+
+/// A higher-level alternative to joining a tuple of storages by hand.
+///
+/// The query fetches the storages of `D` together with the data that is
+/// needed by the filter `F`, and can be joined like any other storage by
+/// using `&mut query`. The mask of the data is intersected with the mask of
+/// the filter, so only the components of the entities that match the filter
+/// are yielded.
+///
+/// `D` is a tuple of `ReadStorage`, `WriteStorage` and `Entities`, `F` is
+/// either `()`, one of the filters `With`, `Without` and `Changed` or a
+/// tuple of filters that all need to match.
+///
+/// ## Aliasing
+///
+/// The filters fetch the storage of their component immutably. If the
+/// storage is also written by the data of the query (for example
+/// `Query<WriteStorage<Pos>, Changed<Pos>>`), the filter copies the mask it
+/// needs when the query is fetched instead of keeping the storage borrowed.
+/// The filter then does not see changes that are done by the query itself.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{access::query::Without, *};
+/// #
+/// # #[derive(Debug, PartialEq)]
+/// # struct Pos(u32);
+/// # impl Component for Pos { type Storage = VecStorage<Self>; }
+/// #
+/// # struct Vel(u32);
+/// # impl Component for Vel { type Storage = VecStorage<Self>; }
+/// #
+/// # #[derive(Default)]
+/// # struct Frozen;
+/// # impl Component for Frozen { type Storage = NullStorage<Self>; }
+/// #
+/// struct Movement;
+///
+/// impl<'a> System<'a> for Movement {
+///     type SystemData = Query<'a, (WriteStorage<'a, Pos>, ReadStorage<'a, Vel>), Without<Frozen>>;
+///
+///     fn run(&mut self, mut query: Self::SystemData) {
+///         for (pos, vel) in (&mut query).join() {
+///             pos.0 += vel.0;
+///         }
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut world = World::default();
+///     let mut dispatcher = Dispatcher::setup_builder(&mut world)
+///         .with(Movement, "movement", &[])
+///         .unwrap()
+///         .build();
+///
+///     let e1 = world.create_entity().with(Pos(0)).with(Vel(1)).build();
+///     let e2 = world.create_entity().with(Pos(0)).with(Vel(1)).with(Frozen).build();
+///
+///     dispatcher.dispatch(&mut world).await;
+///
+///     let pos = world.component::<Pos>();
+///     assert_eq!(pos.get(e1), Some(&Pos(1)));
+///     assert_eq!(pos.get(e2), Some(&Pos(0)));
+/// }
+/// ```
+pub struct Query<'a, D, F = ()>
+where
+    F: QueryFilter<'a>,
+{
+    data: D,
+    filter: F::Data,
+}
+
+impl<'a, D, F> Query<'a, D, F>
+where
+    F: QueryFilter<'a>,
+{
+    pub fn new(data: D, filter: F::Data) -> Self {
+        Self { data, filter }
+    }
+
+    /// Get a reference to the fetched storages.
+    pub fn data(&self) -> &D {
+        &self.data
+    }
+
+    /// Get a mutable reference to the fetched storages.
+    pub fn data_mut(&mut self) -> &mut D {
+        &mut self.data
+    }
+}
+
+impl<'q, 'a, D, F> Join for &'q mut Query<'a, D, F>
+where
+    D: QueryData<'q>,
+    F: FilterMask<'q, 'a>,
+{
+    type Type = <D::Join as Join>::Type;
+    type Value = <D::Join as Join>::Value;
+    type Mask = BitSetAnd<<D::Join as Join>::Mask, F::Mask>;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let Query { data, filter } = self;
+        let (mask, value) = data.as_join().open();
+
+        (BitSetAnd(mask, F::mask(filter)), value)
+    }
+
+    unsafe fn get(value: &mut Self::Value, index: Index) -> Self::Type {
+        D::Join::get(value, index)
+    }
+
+    #[inline]
+    fn is_unconstrained() -> bool {
+        D::Join::is_unconstrained()
+    }
+}
+
+impl<'q, 'a, D, F> ParJoin for &'q mut Query<'a, D, F>
+where
+    D: QueryData<'q>,
+    D::Join: ParJoin,
+    F: FilterMask<'q, 'a>,
+{
+}
+
+impl<'a, D, F> SystemData<'a> for Query<'a, D, F>
+where
+    D: SystemData<'a>,
+    F: QueryFilter<'a>,
+{
+    fn setup(world: &mut World) {
+        D::setup(world);
+        F::setup(world);
+    }
+
+    fn fetch(world: &'a World) -> Self {
+        // The filter is fetched first, so it can copy the masks of the
+        // storages that are written by the data.
+        let filter = F::fetch(world, &D::writes());
+
+        Self::new(D::fetch(world), filter)
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        let filter = F::try_fetch(world, &D::writes())?;
+
+        Ok(Self::new(D::try_fetch(world)?, filter))
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        let mut reads = D::reads();
+        reads.extend(F::reads());
+
+        reads
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        D::writes()
+    }
+}
+
+/* QueryData */
+
+/// Data of a `Query` that can be borrowed as `Join` for the lifetime `'q`.
+pub trait QueryData<'q> {
+    /// The join that is created from the borrowed data.
+    type Join: Join;
+
+    /// Borrows the data as join.
+    fn as_join(&'q mut self) -> Self::Join;
+}
+
+impl<'q, 'a: 'q, T> QueryData<'q> for ReadStorage<'a, T>
+where
+    T: Component,
+{
+    type Join = &'q Self;
+
+    fn as_join(&'q mut self) -> Self::Join {
+        self
+    }
+}
+
+impl<'q, 'a: 'q, T> QueryData<'q> for WriteStorage<'a, T>
+where
+    T: Component,
+{
+    type Join = &'q mut Self;
+
+    fn as_join(&'q mut self) -> Self::Join {
+        self
+    }
+}
+
+impl<'q, 'a: 'q, T> QueryData<'q> for Read<'a, T>
+where
+    T: Resource,
+    &'q T: Join,
+{
+    type Join = &'q Self;
+
+    fn as_join(&'q mut self) -> Self::Join {
+        self
+    }
+}
+
+macro_rules! define_tuple_query_data {
+    ($($from:ident),*) => {
+        impl<'q, $($from,)*> QueryData<'q> for ($($from),*,)
+            where $($from: QueryData<'q>),*,
+                  ($($from::Join,)*): Join,
+        {
+            type Join = ($($from::Join,)*);
+
+            #[allow(non_snake_case)]
+            fn as_join(&'q mut self) -> Self::Join {
+                let ($($from,)*) = self;
+
+                ($($from.as_join(),)*)
+            }
+        }
+    }
+}
+
+define_tuple_query_data! { A }
+define_tuple_query_data! { A, B }
+define_tuple_query_data! { A, B, C }
+define_tuple_query_data! { A, B, C, D }
+define_tuple_query_data! { A, B, C, D, E }
+define_tuple_query_data! { A, B, C, D, E, F }
+define_tuple_query_data! { A, B, C, D, E, F, G }
+define_tuple_query_data! { A, B, C, D, E, F, G, H }
+
+/* QueryFilter */
+
+/// Filter of a `Query` that restricts the joined entities.
+pub trait QueryFilter<'a> {
+    /// Data that is fetched from the world to evaluate the filter.
+    type Data;
+
+    /// Sets up the data of the filter.
+    fn setup(world: &mut World);
+
+    /// Fetches the data of the filter. The storages in `writes` are written
+    /// by the data of the query, so the filter must not keep them borrowed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data could not be fetched.
+    fn fetch(world: &'a World, writes: &[ResourceId]) -> Self::Data {
+        match Self::try_fetch(world, writes) {
+            Ok(data) => data,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Same as `fetch`, but returns an error instead of panicking.
+    fn try_fetch(world: &'a World, writes: &[ResourceId]) -> Result<Self::Data, Error>;
+
+    /// Returns the resources that are read by the filter.
+    fn reads() -> Vec<ResourceId>;
+}
+
+/// Creates the mask of a `QueryFilter` from its fetched data.
+pub trait FilterMask<'q, 'a: 'q>: QueryFilter<'a> {
+    /// Mask of all indices that match the filter.
+    type Mask: BitSetLike;
+
+    /// Creates the mask from the passed data.
+    fn mask(data: &'q Self::Data) -> Self::Mask;
+}
+
+/// Data of the filters `With`, `Without` and `Changed`.
+pub enum FilterData<S> {
+    /// The storage of the component, borrowed from the world.
+    Borrowed(S),
+
+    /// Copy of the mask of the filter, because the storage of the component
+    /// is written by the data of the query.
+    Copied(BitSet),
+}
+
+impl<S> FilterData<S> {
+    fn fetch<'a, T, F>(world: &'a World, writes: &[ResourceId], copy: F) -> Result<Self, Error>
+    where
+        T: Component,
+        S: SystemData<'a>,
+        F: FnOnce(&S) -> BitSet,
+    {
+        let storage = S::try_fetch(world)?;
+
+        if writes.contains(&ResourceId::new::<MaskedStorage<T>>()) {
+            Ok(Self::Copied(copy(&storage)))
+        } else {
+            Ok(Self::Borrowed(storage))
+        }
+    }
+}
+
+impl<'a> QueryFilter<'a> for () {
+    type Data = ();
+
+    fn setup(_: &mut World) {}
+
+    fn try_fetch(_: &'a World, _: &[ResourceId]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        vec![]
+    }
+}
+
+impl<'q, 'a: 'q> FilterMask<'q, 'a> for () {
+    type Mask = BitSetAll;
+
+    fn mask(_: &'q ()) -> Self::Mask {
+        BitSetAll
+    }
+}
+
+/// Filter that only matches entities that have a component `T`.
+pub struct With<T>(PhantomData<T>);
+
+impl<'a, T> QueryFilter<'a> for With<T>
+where
+    T: Component,
+{
+    type Data = FilterData<MaskRead<'a, T>>;
+
+    fn setup(world: &mut World) {
+        MaskRead::<T>::setup(world);
+    }
+
+    fn try_fetch(world: &'a World, writes: &[ResourceId]) -> Result<Self::Data, Error> {
+        FilterData::fetch::<T, _>(world, writes, |data: &MaskRead<T>| data.mask().clone())
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        MaskRead::<T>::reads()
+    }
+}
+
+impl<'q, 'a: 'q, T> FilterMask<'q, 'a> for With<T>
+where
+    T: Component,
+{
+    type Mask = &'q BitSet;
+
+    fn mask(data: &'q Self::Data) -> Self::Mask {
+        match data {
+            FilterData::Borrowed(data) => data.mask(),
+            FilterData::Copied(mask) => mask,
+        }
+    }
+}
+
+/// Filter that only matches entities that do not have a component `T`.
+pub struct Without<T>(PhantomData<T>);
+
+impl<'a, T> QueryFilter<'a> for Without<T>
+where
+    T: Component,
+{
+    type Data = FilterData<MaskRead<'a, T>>;
+
+    fn setup(world: &mut World) {
+        MaskRead::<T>::setup(world);
+    }
+
+    fn try_fetch(world: &'a World, writes: &[ResourceId]) -> Result<Self::Data, Error> {
+        FilterData::fetch::<T, _>(world, writes, |data: &MaskRead<T>| data.mask().clone())
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        MaskRead::<T>::reads()
+    }
+}
+
+impl<'q, 'a: 'q, T> FilterMask<'q, 'a> for Without<T>
+where
+    T: Component,
+{
+    type Mask = BitSetNot<&'q BitSet>;
+
+    fn mask(data: &'q Self::Data) -> Self::Mask {
+        match data {
+            FilterData::Borrowed(data) => BitSetNot(data.mask()),
+            FilterData::Copied(mask) => BitSetNot(mask),
+        }
+    }
+}
+
+/// Filter that only matches entities whose component `T` was inserted or
+/// modified since the last `World::maintain`. The storage of `T` needs to
+/// track its changes, see `TrackedStorage` for details.
+pub struct Changed<T>(PhantomData<T>);
+
+impl<'a, T> QueryFilter<'a> for Changed<T>
+where
+    T: Component,
+    T::Storage: ChangeMasks,
+{
+    type Data = FilterData<ReadStorage<'a, T>>;
+
+    fn setup(world: &mut World) {
+        ReadStorage::<T>::setup(world);
+    }
+
+    fn try_fetch(world: &'a World, writes: &[ResourceId]) -> Result<Self::Data, Error> {
+        FilterData::fetch::<T, _>(world, writes, |data: &ReadStorage<T>| {
+            BitSetOr(data.inserted(), data.modified()).iter().collect()
+        })
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        ReadStorage::<T>::reads()
+    }
+}
+
+impl<'q, 'a: 'q, T> FilterMask<'q, 'a> for Changed<T>
+where
+    T: Component,
+    T::Storage: ChangeMasks,
+{
+    type Mask = BitSetEither<BitSetOr<&'q BitSet, &'q AtomicBitSet>, &'q BitSet>;
+
+    fn mask(data: &'q Self::Data) -> Self::Mask {
+        match data {
+            FilterData::Borrowed(data) => {
+                BitSetEither::Left(BitSetOr(data.inserted(), data.modified()))
+            }
+            FilterData::Copied(mask) => BitSetEither::Right(mask),
+        }
+    }
+}
+
+macro_rules! define_tuple_filter {
+    ($($from:ident),*) => {
+        impl<'a, $($from,)*> QueryFilter<'a> for ($($from),*,)
+            where $($from: QueryFilter<'a>),*,
+        {
+            type Data = ($($from::Data,)*);
+
+            fn setup(world: &mut World) {
+                $($from::setup(world);)*
+            }
+
+            fn try_fetch(world: &'a World, writes: &[ResourceId]) -> Result<Self::Data, Error> {
+                Ok(($($from::try_fetch(world, writes)?,)*))
+            }
+
+            fn reads() -> Vec<ResourceId> {
+                let mut reads = Vec::new();
+                $(reads.extend($from::reads());)*
+
+                reads
+            }
+        }
+
+        impl<'q, 'a: 'q, $($from,)*> FilterMask<'q, 'a> for ($($from),*,)
+            where $($from: FilterMask<'q, 'a>),*,
+                  ($($from::Mask,)*): BitAnd,
+        {
+            type Mask = <($($from::Mask,)*) as BitAnd>::Value;
+
+            #[allow(non_snake_case)]
+            fn mask(data: &'q Self::Data) -> Self::Mask {
+                let ($($from,)*) = data;
+
+                ($($from::mask($from),)*).and()
+            }
+        }
+    }
+}
+
+define_tuple_filter! { A }
+define_tuple_filter! { A, B }
+define_tuple_filter! { A, B, C }
+define_tuple_filter! { A, B, C, D }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        entity::Builder,
+        storage::{MaskedStorage, NullStorage, TrackedStorage, VecStorage},
+        Entities,
+    };
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = TrackedStorage<Self, VecStorage<Self>>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Vel(u32);
+
+    impl Component for Vel {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Default)]
+    struct Frozen;
+
+    impl Component for Frozen {
+        type Storage = NullStorage<Self>;
+    }
+
+    #[tokio::test]
+    async fn filters() {
+        type Data<'a> = Query<
+            'a,
+            (Entities<'a>, ReadStorage<'a, Pos>),
+            (Changed<Pos>, With<Vel>, Without<Frozen>),
+        >;
+
+        let mut world = World::default();
+        Data::setup(&mut world);
+
+        let e1 = world.create_entity().with(Pos(1)).with(Vel(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).with(Vel(2)).build();
+        let e3 = world.create_entity().with(Pos(3)).build();
+        let e4 = world
+            .create_entity()
+            .with(Pos(4))
+            .with(Vel(4))
+            .with(Frozen)
+            .build();
+
+        world.maintain().await;
+        world.component_mut::<Pos>().get_mut(e2).unwrap().0 = 5;
+        world.component_mut::<Pos>().get_mut(e3).unwrap().0 = 6;
+        world.component_mut::<Pos>().get_mut(e4).unwrap().0 = 7;
+
+        let mut query = Data::fetch(&world);
+
+        let changed = (&mut query).join().collect::<Vec<_>>();
+        assert_eq!(changed, vec![(e2, &Pos(5))]);
+        assert!(changed.iter().all(|(e, _)| *e != e1));
+    }
+
+    #[tokio::test]
+    async fn filter_written_storage() {
+        type Data<'a> = Query<'a, (WriteStorage<'a, Pos>, WriteStorage<'a, Vel>), Changed<Pos>>;
+        type Moving<'a> = Query<'a, (WriteStorage<'a, Vel>,), (With<Vel>, Without<Frozen>)>;
+
+        let mut world = World::default();
+        Data::setup(&mut world);
+        Moving::setup(&mut world);
+
+        let e1 = world.create_entity().with(Pos(1)).with(Vel(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).with(Vel(2)).build();
+
+        world.maintain().await;
+        world.component_mut::<Pos>().get_mut(e2).unwrap().0 = 5;
+
+        {
+            let mut query = Data::fetch(&world);
+            for (pos, vel) in (&mut query).join() {
+                pos.0 += vel.0;
+            }
+
+            assert_eq!(query.data().0.get(e1), Some(&Pos(1)));
+            assert_eq!(query.data().0.get(e2), Some(&Pos(7)));
+        }
+
+        assert!(Data::reads().contains(&ResourceId::new::<MaskedStorage<Pos>>()));
+        assert!(Data::writes().contains(&ResourceId::new::<MaskedStorage<Pos>>()));
+
+        let mut query = Moving::fetch(&world);
+        assert_eq!((&mut query).join().count(), 2);
+    }
+
+    #[test]
+    fn write_and_dependencies() {
+        type Data<'a> = Query<'a, (WriteStorage<'a, Vel>, ReadStorage<'a, Pos>), Without<Frozen>>;
+
+        let mut world = World::default();
+        Data::setup(&mut world);
+
+        let e1 = world.create_entity().with(Pos(1)).with(Vel(1)).build();
+        let e2 = world.create_entity().with(Vel(2)).build();
+
+        {
+            let mut query = Data::fetch(&world);
+            for (vel, pos) in (&mut query).join() {
+                vel.0 += pos.0;
+            }
+
+            assert_eq!(query.data().0.get(e1), Some(&Vel(2)));
+            assert_eq!(query.data().0.get(e2), Some(&Vel(2)));
+        }
+
+        assert!(Data::reads().contains(&ResourceId::new::<MaskedStorage<Frozen>>()));
+        assert_eq!(
+            Data::writes(),
+            vec![ResourceId::new::<MaskedStorage<Vel>>()]
+        );
+    }
+}
@@ -2,7 +2,9 @@ use std::marker::PhantomData;
 use std::ops::Deref;
 
 use crate::{
+    error::Error,
     resource::{Ref, Resource, ResourceId},
+    storage::Tick,
     system::SystemData,
     world::{DefaultSetupHandler, PanicHandler, SetupHandler, World},
 };
@@ -10,6 +12,54 @@ use crate::{
 /// Allows to fetch a resource in a system immutably.
 /// **This will panic if the resource does not exist.**
 /// Usage of `Read` or `Option<Read>` is therefore recommended.
+///
+/// ## Examples
+///
+/// The expecting and the optional accessors can be mixed with the other
+/// accessors in a derived `SystemData`. The reads and writes of all fields
+/// are reported, so the dispatcher orders the systems correctly even if an
+/// optional resource does not exist (yet).
+///
+/// ```
+/// # use async_ecs::{system::SystemData, *};
+/// #
+/// # #[derive(Default)]
+/// # struct Config;
+/// # #[derive(Default)]
+/// # struct Input;
+/// # #[derive(Default)]
+/// # struct Stats;
+/// # #[derive(Default)]
+/// # struct Output;
+/// #
+/// #[derive(SystemData)]
+/// struct Data<'a> {
+///     config: ReadExpect<'a, Config>,
+///     input: Option<Read<'a, Input>>,
+///     stats: Option<Write<'a, Stats>>,
+///     output: WriteExpect<'a, Output>,
+/// }
+///
+/// let mut reads = Data::reads();
+/// reads.sort();
+/// let mut expected = vec![ResourceId::new::<Config>(), ResourceId::new::<Input>()];
+/// expected.sort();
+/// assert_eq!(reads, expected);
+///
+/// let mut writes = Data::writes();
+/// writes.sort();
+/// let mut expected = vec![ResourceId::new::<Stats>(), ResourceId::new::<Output>()];
+/// expected.sort();
+/// assert_eq!(writes, expected);
+///
+/// let mut world = World::default();
+/// world.insert(Config);
+/// world.insert(Output);
+///
+/// let data = Data::fetch(&world);
+/// assert!(data.input.is_none());
+/// assert!(data.stats.is_none());
+/// ```
 pub type ReadExpect<'a, T> = Read<'a, T, PanicHandler>;
 
 /// Allows to fetch a resource in a system immutably.
@@ -32,6 +82,71 @@ impl<'a, T, F> Read<'a, T, F> {
             marker: PhantomData,
         }
     }
+
+    /// Fetches the resource with the passed id. This allows to fetch
+    /// resources with a non-zero dynamic id (see `ResourceId`) inside of
+    /// `DynamicSystemData::fetch`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist or is borrowed mutably.
+    pub fn fetch_by_id(world: &'a World, id: &ResourceId) -> Self
+    where
+        T: Resource,
+    {
+        Self::new(world.borrow_by_id(id))
+    }
+
+    /// Returns the tick the resource was last modified at.
+    pub fn last_changed(&self) -> Tick {
+        Ref::last_changed(&self.inner)
+    }
+
+    /// Returns `true` if the resource was modified at or after the passed
+    /// tick. Use a `SystemTick` to skip the work of a system, if none of its
+    /// resources was modified since its last run.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::{system::{SystemData, SystemTick}, *};
+    /// #
+    /// #[derive(Default)]
+    /// struct Config(u32);
+    ///
+    /// #[derive(Default)]
+    /// struct ApplyConfig {
+    ///     tick: SystemTick,
+    ///     applied: usize,
+    /// }
+    ///
+    /// impl<'a> System<'a> for ApplyConfig {
+    ///     type SystemData = Read<'a, Config>;
+    ///
+    ///     fn run(&mut self, config: Self::SystemData) {
+    ///         if config.is_changed_since(self.tick.update()) {
+    ///             self.applied += 1;
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut world = World::default();
+    /// world.insert(Config(1));
+    ///
+    /// let mut system = ApplyConfig::default();
+    /// system.run(SystemData::fetch(&world));
+    /// system.run(SystemData::fetch(&world));
+    /// assert_eq!(system.applied, 1);
+    ///
+    /// world.resource_mut::<Config>().0 = 2;
+    ///
+    /// system.run(SystemData::fetch(&world));
+    /// system.run(SystemData::fetch(&world));
+    /// assert_eq!(system.applied, 2);
+    /// ```
+    pub fn is_changed_since(&self, tick: Tick) -> bool {
+        self.last_changed() >= tick
+    }
 }
 
 impl<'a, T, F> From<Ref<'a, T>> for Read<'a, T, F> {
@@ -67,6 +182,10 @@ where
         Self::new(world.borrow())
     }
 
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        world.fetch().map(Self::new)
+    }
+
     fn reads() -> Vec<ResourceId> {
         vec![ResourceId::new::<T>()]
     }
@@ -86,6 +205,14 @@ where
         world.try_borrow().map(Into::into)
     }
 
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        match world.fetch::<T>() {
+            Ok(inner) => Ok(Some(inner.into())),
+            Err(Error::ResourceNotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
     fn reads() -> Vec<ResourceId> {
         vec![ResourceId::new::<T>()]
     }
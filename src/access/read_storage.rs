@@ -1,6 +1,9 @@
+use std::any::type_name;
+
 use crate::{
     component::Component,
     entity::Entities,
+    error::Error,
     misc::TryDefault,
     resource::{Ref, ResourceId},
     storage::{MaskedStorage, StorageWrapper},
@@ -139,7 +142,15 @@ where
     }
 
     fn fetch(world: &'a World) -> Self {
-        Self::new(world.borrow(), world.borrow())
+        let storage = world
+            .try_borrow()
+            .unwrap_or_else(|| unregistered_panic::<T>());
+
+        Self::new(storage, world.borrow())
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        Ok(Self::new(world.fetch()?, world.fetch()?))
     }
 
     fn reads() -> Vec<ResourceId> {
@@ -153,3 +164,12 @@ where
         vec![]
     }
 }
+
+/// Panics with a helpful message, because the component `T` was not
+/// registered.
+pub(crate) fn unregistered_panic<T: Component>() -> ! {
+    panic!(
+        "Component `{}` is not registered. You may register it using `World::register_component`!",
+        type_name::<T>(),
+    );
+}
@@ -0,0 +1,362 @@
+use std::any::type_name;
+use std::ops::{Deref, DerefMut};
+
+use hibitset::{BitSet, BitSetLike};
+
+use crate::{
+    component::Component,
+    entity::Entity,
+    error::Error,
+    misc::TryDefault,
+    resource::{Ref, ResourceId},
+    system::SystemData,
+    world::{SingletonEntity, World},
+};
+
+use super::{ReadStorage, WriteStorage};
+
+/// Read access to the singleton component `T`, that was inserted using
+/// `World::insert_singleton`.
+///
+/// The component is accessed directly through the entity stored by the
+/// world, so no join over the storage is needed. Fetching fails if the
+/// singleton does not exist, use `Option<ReadSingleton>` if the singleton
+/// is optional. Fetching also fails with `Error::SingletonNotUnique` if the
+/// component was attached to more than one entity.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{system::SystemData, *};
+/// #
+/// struct Score(u32);
+///
+/// impl Component for Score {
+///     type Storage = HashMapStorage<Self>;
+/// }
+///
+/// let mut world = World::default();
+/// world.insert_singleton(Score(10)).unwrap();
+///
+/// WriteSingleton::<Score>::fetch(&world).0 += 5;
+///
+/// let score = ReadSingleton::<Score>::fetch(&world);
+/// assert_eq!(score.0, 15);
+/// ```
+pub struct ReadSingleton<'a, T: Component> {
+    storage: ReadStorage<'a, T>,
+    entity: Entity,
+}
+
+impl<'a, T> ReadSingleton<'a, T>
+where
+    T: Component,
+{
+    /// Returns the entity the singleton component is attached to.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+}
+
+impl<'a, T> Deref for ReadSingleton<'a, T>
+where
+    T: Component,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.storage.get(self.entity).unwrap()
+    }
+}
+
+impl<'a, T> SystemData<'a> for ReadSingleton<'a, T>
+where
+    T: Component,
+{
+    fn setup(world: &mut World) {
+        world.register_component_with_storage::<T, _>(TryDefault::unwrap_default);
+    }
+
+    fn fetch(world: &'a World) -> Self {
+        match Self::try_fetch(world) {
+            Ok(singleton) => singleton,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        let storage = ReadStorage::try_fetch(world)?;
+        let entity = singleton_entity::<T>(world, storage.mask())?;
+
+        Ok(Self { storage, entity })
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        let mut reads = ReadStorage::<T>::reads();
+        reads.push(ResourceId::new::<SingletonEntity<T>>());
+
+        reads
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        vec![]
+    }
+}
+
+impl<'a, T> SystemData<'a> for Option<ReadSingleton<'a, T>>
+where
+    T: Component,
+{
+    fn setup(world: &mut World) {
+        ReadSingleton::<T>::setup(world);
+    }
+
+    fn fetch(world: &'a World) -> Self {
+        ReadSingleton::try_fetch(world).ok()
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        optional(ReadSingleton::try_fetch(world))
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        ReadSingleton::<T>::reads()
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        vec![]
+    }
+}
+
+/// Write access to the singleton component `T`, that was inserted using
+/// `World::insert_singleton`.
+///
+/// See `ReadSingleton` for details.
+pub struct WriteSingleton<'a, T: Component> {
+    storage: WriteStorage<'a, T>,
+    entity: Entity,
+}
+
+impl<'a, T> WriteSingleton<'a, T>
+where
+    T: Component,
+{
+    /// Returns the entity the singleton component is attached to.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+}
+
+impl<'a, T> Deref for WriteSingleton<'a, T>
+where
+    T: Component,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.storage.get(self.entity).unwrap()
+    }
+}
+
+impl<'a, T> DerefMut for WriteSingleton<'a, T>
+where
+    T: Component,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        self.storage.get_mut(self.entity).unwrap()
+    }
+}
+
+impl<'a, T> SystemData<'a> for WriteSingleton<'a, T>
+where
+    T: Component,
+{
+    fn setup(world: &mut World) {
+        world.register_component_with_storage::<T, _>(TryDefault::unwrap_default);
+    }
+
+    fn fetch(world: &'a World) -> Self {
+        match Self::try_fetch(world) {
+            Ok(singleton) => singleton,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        let storage = WriteStorage::try_fetch(world)?;
+        let entity = singleton_entity::<T>(world, storage.mask())?;
+
+        Ok(Self { storage, entity })
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        let mut reads = WriteStorage::<T>::reads();
+        reads.push(ResourceId::new::<SingletonEntity<T>>());
+
+        reads
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        WriteStorage::<T>::writes()
+    }
+}
+
+impl<'a, T> SystemData<'a> for Option<WriteSingleton<'a, T>>
+where
+    T: Component,
+{
+    fn setup(world: &mut World) {
+        WriteSingleton::<T>::setup(world);
+    }
+
+    fn fetch(world: &'a World) -> Self {
+        WriteSingleton::try_fetch(world).ok()
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        optional(WriteSingleton::try_fetch(world))
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        WriteSingleton::<T>::reads()
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        WriteSingleton::<T>::writes()
+    }
+}
+
+fn singleton_entity<T>(world: &World, mask: &BitSet) -> Result<Entity, Error>
+where
+    T: Component,
+{
+    let singleton: Ref<SingletonEntity<T>> = match world.fetch() {
+        Ok(singleton) => singleton,
+        Err(Error::ResourceNotFound(_)) => return Err(Error::SingletonNotFound(type_name::<T>())),
+        Err(err) => return Err(err),
+    };
+
+    let entity = singleton.entity();
+
+    if !mask.contains(entity.index()) {
+        Err(Error::SingletonNotFound(type_name::<T>()))
+    } else if mask.iter().nth(1).is_some() {
+        Err(Error::SingletonNotUnique(type_name::<T>()))
+    } else {
+        Ok(entity)
+    }
+}
+
+fn optional<S>(result: Result<S, Error>) -> Result<Option<S>, Error> {
+    match result {
+        Ok(singleton) => Ok(Some(singleton)),
+        Err(Error::SingletonNotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use crate::{component::ComponentHooks, entity::Builder, storage::VecStorage};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Camera(u32);
+
+    impl Component for Camera {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn unique_component() {
+        let mut world = World::default();
+        world.register_component::<Camera>();
+
+        assert!(Option::<ReadSingleton<Camera>>::fetch(&world).is_none());
+        assert!(matches!(
+            ReadSingleton::<Camera>::try_fetch(&world),
+            Err(Error::SingletonNotFound(_))
+        ));
+
+        let other = world.create_entity().with(Camera(0)).build();
+        assert!(matches!(
+            world.insert_singleton(Camera(1)),
+            Err(Error::SingletonNotUnique(_))
+        ));
+
+        world.component_mut::<Camera>().remove(other);
+
+        let camera = world.insert_singleton(Camera(1)).unwrap();
+        assert_eq!(world.component::<Camera>().count(), 1);
+        assert_eq!(world.insert_singleton(Camera(2)).unwrap(), camera);
+
+        {
+            let mut singleton = WriteSingleton::<Camera>::fetch(&world);
+            assert_eq!(singleton.entity(), camera);
+
+            singleton.0 += 1;
+        }
+
+        assert_eq!(*ReadSingleton::<Camera>::fetch(&world), Camera(3));
+        assert_eq!(world.component::<Camera>().get(camera), Some(&Camera(3)));
+
+        assert!(
+            ReadSingleton::<Camera>::reads()
+                .contains(&ResourceId::new::<SingletonEntity<Camera>>())
+        );
+        assert_eq!(
+            WriteSingleton::<Camera>::writes(),
+            WriteStorage::<Camera>::writes()
+        );
+
+        world.delete_entity(camera).unwrap();
+        assert!(Option::<WriteSingleton<Camera>>::fetch(&world).is_none());
+        assert_eq!(world.singleton_entity::<Camera>(), None);
+
+        let camera = world.insert_singleton(Camera(4)).unwrap();
+        assert_eq!(world.remove_singleton::<Camera>(), Some(Camera(4)));
+        assert!(world.is_alive(camera));
+        assert!(world.component::<Camera>().is_empty());
+    }
+
+    #[test]
+    fn second_entity_is_rejected() {
+        let mut world = World::default();
+        world.register_component_with_hooks(
+            ComponentHooks::new().on_insert(|_, camera: &mut Camera| camera.0 += 10),
+        );
+
+        let camera = world.insert_singleton(Camera(1)).unwrap();
+        let other = world.create_entity().build();
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            world
+                .component_mut::<Camera>()
+                .insert(other, Camera(2))
+                .unwrap();
+        }));
+        assert!(result.is_err());
+        assert_eq!(world.component::<Camera>().get(other), None);
+        assert_eq!(world.component::<Camera>().count(), 1);
+
+        world.insert_singleton(Camera(3)).unwrap();
+        assert_eq!(*ReadSingleton::<Camera>::fetch(&world), Camera(13));
+
+        world.delete_entity(camera).unwrap();
+        world
+            .component_mut::<Camera>()
+            .insert(other, Camera(4))
+            .unwrap();
+        assert!(world.insert_singleton(Camera(5)).is_err());
+
+        world.delete_entity(other).unwrap();
+        world.insert_singleton(Camera(6)).unwrap();
+        world.remove_singleton::<Camera>();
+
+        let other = world.create_entity().with(Camera(7)).build();
+        assert_eq!(world.component::<Camera>().get(other), Some(&Camera(17)));
+    }
+}
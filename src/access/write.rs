@@ -2,6 +2,7 @@ use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 
 use crate::{
+    error::Error,
     resource::{RefMut, Resource, ResourceId},
     system::SystemData,
     world::{DefaultSetupHandler, PanicHandler, SetupHandler, World},
@@ -32,6 +33,29 @@ impl<'a, T, F> Write<'a, T, F> {
             marker: PhantomData,
         }
     }
+
+    /// Fetches the resource with the passed id mutably. This allows to
+    /// fetch resources with a non-zero dynamic id (see `ResourceId`) inside
+    /// of `DynamicSystemData::fetch`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist or is already borrowed.
+    pub fn fetch_by_id(world: &'a World, id: &ResourceId) -> Self
+    where
+        T: Resource,
+    {
+        Self::new(world.borrow_mut_by_id(id))
+    }
+}
+
+impl<'a, T, F> From<RefMut<'a, T>> for Write<'a, T, F> {
+    fn from(inner: RefMut<'a, T>) -> Self {
+        Write {
+            inner,
+            marker: PhantomData,
+        }
+    }
 }
 
 impl<'a, T, F> Deref for Write<'a, T, F>
@@ -67,6 +91,37 @@ where
         Self::new(world.borrow_mut())
     }
 
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        world.fetch_mut().map(Self::new)
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        vec![]
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        vec![ResourceId::new::<T>()]
+    }
+}
+
+impl<'a, T, F> SystemData<'a> for Option<Write<'a, T, F>>
+where
+    T: Resource,
+{
+    fn setup(_: &mut World) {}
+
+    fn fetch(world: &'a World) -> Self {
+        world.try_borrow_mut().map(Into::into)
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        match world.fetch_mut::<T>() {
+            Ok(inner) => Ok(Some(inner.into())),
+            Err(Error::ResourceNotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
     fn reads() -> Vec<ResourceId> {
         vec![]
     }
@@ -1,6 +1,7 @@
 use crate::{
     component::Component,
     entity::Entities,
+    error::Error,
     misc::TryDefault,
     resource::{RefMut, ResourceId},
     storage::{MaskedStorage, StorageWrapper},
@@ -8,6 +9,8 @@ use crate::{
     world::World,
 };
 
+use super::read_storage::unregistered_panic;
+
 /// A storage with read and write access.
 ///
 /// Additionally to what `ReadStorage` can do a storage with mutable access
@@ -80,7 +83,15 @@ where
     }
 
     fn fetch(world: &'a World) -> Self {
-        Self::new(world.borrow_mut(), world.borrow())
+        let storage = world
+            .try_borrow_mut()
+            .unwrap_or_else(|| unregistered_panic::<T>());
+
+        Self::new(storage, world.borrow())
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        Ok(Self::new(world.fetch_mut()?, world.fetch()?))
     }
 
     fn reads() -> Vec<ResourceId> {
@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Failed to load asset: {0}!")]
+    LoadFailed(String),
+
+    #[error("Asset loader panicked: {0}!")]
+    LoaderPanicked(String),
+}
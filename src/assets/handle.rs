@@ -0,0 +1,68 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{component::Component, storage::DenseVecStorage};
+
+/// Handle of an asset of type `A`, that is stored in the `AssetStorage<A>`.
+///
+/// The handle is returned immediately when the asset is loaded using the
+/// `Loader`, the asset itself is available after it was published by
+/// `World::maintain`. Handles are cheap to copy and can be used as
+/// component, to attach an asset to an entity.
+pub struct Handle<A> {
+    id: u64,
+    _marker: PhantomData<fn() -> A>,
+}
+
+impl<A> Handle<A> {
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        Self {
+            id: NEXT.fetch_add(1, Ordering::Relaxed),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the unique id of the handle.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<A> Clone for Handle<A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A> Copy for Handle<A> {}
+
+impl<A> PartialEq for Handle<A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<A> Eq for Handle<A> {}
+
+impl<A> Hash for Handle<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<A> Debug for Handle<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("Handle").field(&self.id).finish()
+    }
+}
+
+impl<A> Component for Handle<A>
+where
+    A: 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
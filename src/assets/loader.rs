@@ -0,0 +1,199 @@
+use std::fmt::Display;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+
+use crossbeam_queue::SegQueue;
+use futures::future::{Future, FutureExt};
+use hashbrown::HashMap;
+use tokio::sync::watch::{channel, Receiver as WatchReceiver};
+
+use crate::{
+    dispatcher::{task::panic_message, Spawner, TokioSpawner},
+    world::World,
+};
+
+use super::{AssetStorage, Error, Handle};
+
+type Publish = Box<dyn FnOnce(&mut World) + Send>;
+
+/// Resource that loads assets asynchronous.
+///
+/// Each load is executed as a separate task, using the `Spawner` of the
+/// loader (the `TokioSpawner` by default), so the IO does not block the
+/// systems. The result of a finished load is published to the
+/// `AssetStorage` of the asset by the next `World::maintain`, so the
+/// assets never change while the systems are dispatched.
+///
+/// See the [module documentation](index.html) for an example.
+pub struct Loader {
+    spawner: Arc<dyn Spawner + Send + Sync>,
+    completed: Arc<SegQueue<(u64, Publish)>>,
+    loading: Mutex<HashMap<u64, WatchReceiver<bool>>>,
+}
+
+impl Loader {
+    /// Create a new loader that spawns its tasks using the `TokioSpawner`.
+    pub fn new() -> Self {
+        Self::with_spawner(Arc::new(TokioSpawner))
+    }
+
+    /// Create a new loader that spawns its tasks using the passed spawner.
+    pub fn with_spawner(spawner: Arc<dyn Spawner + Send + Sync>) -> Self {
+        Self {
+            spawner,
+            completed: Arc::new(SegQueue::new()),
+            loading: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Starts loading an asset using the passed future and returns the
+    /// handle of the asset.
+    ///
+    /// The future is spawned immediately. If it fails (or panics), the
+    /// error is published to the `AssetStorage` instead of the asset.
+    pub fn load<A, F, E>(&self, future: F) -> Handle<A>
+    where
+        A: Send + Sync + 'static,
+        F: Future<Output = Result<A, E>> + Send + 'static,
+        E: Display,
+    {
+        let handle = Handle::next();
+        let id = handle.id();
+
+        let (sender, receiver) = channel(false);
+        self.loading.lock().unwrap().insert(id, receiver);
+
+        let completed = self.completed.clone();
+        self.spawner.spawn(Box::pin(async move {
+            let result = match AssertUnwindSafe(future).catch_unwind().await {
+                Ok(Ok(asset)) => Ok(asset),
+                Ok(Err(err)) => Err(Error::LoadFailed(err.to_string())),
+                Err(err) => Err(Error::LoaderPanicked(panic_message(&err))),
+            };
+
+            let publish: Publish = Box::new(move |world| {
+                world
+                    .entry::<AssetStorage<A>>()
+                    .or_insert_with(Default::default)
+                    .publish(id, result);
+            });
+
+            completed.push((id, publish));
+
+            let _ = sender.send(true);
+        }));
+
+        handle
+    }
+
+    /// Returns `true` if the asset of the passed handle is still loading,
+    /// or if it was loaded but is not published yet.
+    pub fn is_loading<A>(&self, handle: &Handle<A>) -> bool {
+        self.loading.lock().unwrap().contains_key(&handle.id())
+    }
+
+    /// Returns the number of assets that are loading or not published yet.
+    pub fn pending(&self) -> usize {
+        self.loading.lock().unwrap().len()
+    }
+
+    /// Waits until the load of the passed handle is finished. The asset is
+    /// published to the `AssetStorage` by the next `World::maintain`.
+    pub async fn wait<A>(&self, handle: &Handle<A>) {
+        let receiver = self.loading.lock().unwrap().get(&handle.id()).cloned();
+
+        if let Some(receiver) = receiver {
+            Self::wait_for(receiver).await;
+        }
+    }
+
+    /// Waits until all loads that were started so far are finished.
+    pub async fn wait_all(&self) {
+        let receivers = self
+            .loading
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for receiver in receivers {
+            Self::wait_for(receiver).await;
+        }
+    }
+
+    /// Takes the finished loads, so they can be published to the world.
+    pub(crate) fn take_completed(&self) -> Vec<Publish> {
+        let mut loading = self.loading.lock().unwrap();
+        let mut completed = Vec::new();
+
+        while let Some((id, publish)) = self.completed.pop() {
+            loading.remove(&id);
+            completed.push(publish);
+        }
+
+        completed
+    }
+
+    async fn wait_for(mut receiver: WatchReceiver<bool>) {
+        while !*receiver.borrow() {
+            if receiver.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl Default for Loader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Texture(&'static str);
+
+    #[tokio::test]
+    async fn published_at_maintain() {
+        let mut world = World::default();
+        world.insert(Loader::default());
+
+        let (grass, missing, broken) = {
+            let loader = world.resource::<Loader>();
+
+            let grass = loader.load(async { Ok::<_, String>(Texture("grass")) });
+            let missing = loader.load(async { Err::<Texture, _>("not found") });
+            let broken = loader.load::<Texture, _, String>(async { panic!("Corrupt file") });
+
+            loader.wait_all().await;
+
+            assert!(loader.is_loading(&grass));
+            assert_eq!(loader.pending(), 3);
+
+            (grass, missing, broken)
+        };
+
+        assert!(!world.contains::<AssetStorage<Texture>>());
+
+        world.maintain().await;
+
+        assert!(!world.resource::<Loader>().is_loading(&grass));
+        assert_eq!(world.resource::<Loader>().pending(), 0);
+
+        let textures = world.resource::<AssetStorage<Texture>>();
+        assert_eq!(textures.get(&grass), Some(&Texture("grass")));
+        assert_eq!(textures.len(), 1);
+        assert!(matches!(
+            textures.error(&missing),
+            Some(Error::LoadFailed(message)) if message == "not found"
+        ));
+        assert!(matches!(
+            textures.error(&broken),
+            Some(Error::LoaderPanicked(message)) if message == "Corrupt file"
+        ));
+    }
+}
@@ -0,0 +1,74 @@
+//! Asynchronous loading of assets.
+//!
+//! Assets (like textures, sounds or level data) are loaded using the
+//! `Loader` resource, that executes each load as a separate task. The
+//! loader returns a `Handle` immediately, that can be stored in a system or
+//! attached to an entity (handles are components). Finished loads are
+//! published to the `AssetStorage` of the asset type by `World::maintain`,
+//! so systems poll the storage using the handle, or await the load using
+//! `Loader::wait` (for example in `AsyncSystem::init_async`).
+//!
+//! ## Examples
+//!
+//! ```
+//! use async_ecs::{
+//!     assets::{AssetStorage, Handle, Loader},
+//!     *,
+//! };
+//!
+//! struct Texture(String);
+//!
+//! struct Render;
+//!
+//! impl<'a> System<'a> for Render {
+//!     type SystemData = (
+//!         ReadStorage<'a, Handle<Texture>>,
+//!         Read<'a, AssetStorage<Texture>>,
+//!         Write<'a, Vec<String>>,
+//!     );
+//!
+//!     fn run(&mut self, (sprites, textures, mut drawn): Self::SystemData) {
+//!         for handle in sprites.join() {
+//!             if let Some(texture) = textures.get(handle) {
+//!                 drawn.push(texture.0.clone());
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let mut world = World::default();
+//! world.insert(Loader::default());
+//! world.register_component::<Handle<Texture>>();
+//!
+//! let mut dispatcher = Dispatcher::setup_builder(&mut world)
+//!     .with(Render, "render", &[])
+//!     .unwrap()
+//!     .build();
+//!
+//! let grass = world.resource::<Loader>().load(async {
+//!     // read the file using async IO
+//!     Ok::<_, std::io::Error>(Texture("grass".into()))
+//! });
+//! world.create_entity().with(grass).build();
+//!
+//! world.resource::<Loader>().wait(&grass).await;
+//! dispatcher.dispatch(&world).await.unwrap();
+//! assert!(world.resource::<Vec<String>>().is_empty());
+//!
+//! world.maintain().await;
+//! dispatcher.dispatch(&world).await.unwrap();
+//! assert_eq!(*world.resource::<Vec<String>>(), vec!["grass"]);
+//! # }
+//! ```
+
+mod error;
+mod handle;
+mod loader;
+mod storage;
+
+pub use error::Error;
+pub use handle::Handle;
+pub use loader::Loader;
+pub use storage::AssetStorage;
@@ -0,0 +1,83 @@
+use hashbrown::HashMap;
+
+use super::{Error, Handle};
+
+/// Resource that stores the assets of type `A`.
+///
+/// Assets are either inserted directly using `insert`, or loaded
+/// asynchronous using the `Loader`. Loaded assets (or the error of a
+/// failed load) are published to the storage by `World::maintain`, so
+/// systems can poll the storage using the handle returned by the loader.
+pub struct AssetStorage<A> {
+    assets: HashMap<u64, A>,
+    errors: HashMap<u64, Error>,
+}
+
+impl<A> AssetStorage<A> {
+    /// Inserts an asset that is already available and returns its handle.
+    pub fn insert(&mut self, asset: A) -> Handle<A> {
+        let handle = Handle::next();
+
+        self.assets.insert(handle.id(), asset);
+
+        handle
+    }
+
+    /// Returns the asset of the passed handle, if it is loaded.
+    pub fn get(&self, handle: &Handle<A>) -> Option<&A> {
+        self.assets.get(&handle.id())
+    }
+
+    /// Returns the asset of the passed handle mutably, if it is loaded.
+    pub fn get_mut(&mut self, handle: &Handle<A>) -> Option<&mut A> {
+        self.assets.get_mut(&handle.id())
+    }
+
+    /// Returns `true` if the asset of the passed handle is loaded.
+    pub fn contains(&self, handle: &Handle<A>) -> bool {
+        self.assets.contains_key(&handle.id())
+    }
+
+    /// Returns the error of the passed handle, if loading the asset failed.
+    pub fn error(&self, handle: &Handle<A>) -> Option<&Error> {
+        self.errors.get(&handle.id())
+    }
+
+    /// Removes the asset (or the error) of the passed handle from the
+    /// storage. Returns the asset if it was loaded.
+    pub fn remove(&mut self, handle: &Handle<A>) -> Option<A> {
+        self.errors.remove(&handle.id());
+        self.assets.remove(&handle.id())
+    }
+
+    /// Returns the number of loaded assets.
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    /// Returns `true` if no asset is loaded.
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    /// Stores the result of a load that was finished by the `Loader`.
+    pub(crate) fn publish(&mut self, id: u64, result: Result<A, Error>) {
+        match result {
+            Ok(asset) => {
+                self.assets.insert(id, asset);
+            }
+            Err(err) => {
+                self.errors.insert(id, err);
+            }
+        }
+    }
+}
+
+impl<A> Default for AssetStorage<A> {
+    fn default() -> Self {
+        Self {
+            assets: HashMap::new(),
+            errors: HashMap::new(),
+        }
+    }
+}
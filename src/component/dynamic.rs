@@ -0,0 +1,75 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::{resource::ResourceId, storage::MaskedStorage};
+
+use super::Component;
+
+/// Runtime identifier of a dynamic component storage.
+///
+/// Dynamic components allow to store many independent storages of the same
+/// component type in the `World`, each identified by its `DynamicId`. The id
+/// is either a plain number or derived from a name, so components can be
+/// defined by config files or scripts.
+///
+/// Ids are never zero, because the dynamic id zero is used by the regular
+/// storage of the component type (see `ResourceId::new`).
+///
+/// See `World::register_dynamic_component` for details.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct DynamicId(u64);
+
+impl DynamicId {
+    /// Creates a new id from the passed number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is zero.
+    pub fn new(id: u64) -> Self {
+        assert!(id != 0, "Dynamic component id must not be zero");
+
+        Self(id)
+    }
+
+    /// Creates a new id from the passed name. The same name always results
+    /// in the same id.
+    pub fn from_name(name: &str) -> Self {
+        // FNV-1a, which is stable across builds and platforms
+        let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+
+        Self(hash.max(1))
+    }
+
+    /// Returns the numeric value of the id.
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the id of the resource that stores the dynamic components of
+    /// type `T` with this id.
+    pub fn resource_id<T>(&self) -> ResourceId
+    where
+        T: Component,
+    {
+        ResourceId::new_with_dynamic_id::<MaskedStorage<T>>(self.0)
+    }
+}
+
+impl From<u64> for DynamicId {
+    fn from(id: u64) -> Self {
+        Self::new(id)
+    }
+}
+
+impl From<&str> for DynamicId {
+    fn from(name: &str) -> Self {
+        Self::from_name(name)
+    }
+}
+
+impl Display for DynamicId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:#018x}", self.0)
+    }
+}
@@ -0,0 +1,277 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+use crate::entity::Index;
+
+type InsertHook<T> = Box<dyn Fn(Index, &mut T) + Send + Sync>;
+type RemoveHook<T> = Box<dyn Fn(Index, &T) + Send + Sync>;
+
+/// Callbacks that are invoked by the storage of a component, whenever a
+/// component is inserted, removed or dropped.
+///
+/// This allows to keep external state (like GPU buffers or bodies of a
+/// physics engine) in sync with the lifetime of the components. The hooks
+/// receive the index of the entity the component belongs to.
+///
+/// Use `World::register_component_with_hooks` to register the hooks of a
+/// component.
+///
+/// ## Examples
+///
+/// ```
+/// # use std::sync::{Arc, Mutex};
+/// #
+/// # use async_ecs::{component::ComponentHooks, *};
+/// #
+/// # struct Body(u32);
+/// # impl Component for Body { type Storage = VecStorage<Self>; }
+/// #
+/// let bodies = Arc::new(Mutex::new(Vec::new()));
+///
+/// let mut world = World::default();
+/// world.register_component_with_hooks::<Body>(
+///     ComponentHooks::new()
+///         .on_insert({
+///             let bodies = bodies.clone();
+///             move |_, body: &mut Body| bodies.lock().unwrap().push(body.0)
+///         })
+///         .on_drop({
+///             let bodies = bodies.clone();
+///             move |_, body: &Body| bodies.lock().unwrap().retain(|b| *b != body.0)
+///         }),
+/// );
+///
+/// let e1 = world.create_entity().with(Body(1)).build();
+/// world.create_entity().with(Body(2)).build();
+///
+/// assert_eq!(*bodies.lock().unwrap(), vec![1, 2]);
+///
+/// world.delete_entity(e1).unwrap();
+///
+/// assert_eq!(*bodies.lock().unwrap(), vec![2]);
+/// ```
+pub struct ComponentHooks<T> {
+    on_insert: Option<InsertHook<T>>,
+    on_remove: Option<RemoveHook<T>>,
+    on_drop: Option<RemoveHook<T>>,
+}
+
+impl<T> ComponentHooks<T> {
+    /// Creates hooks that do not invoke any callback.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the callback that is invoked before a component is inserted into
+    /// the storage.
+    ///
+    /// If the entity already had a component, the replaced component is
+    /// passed to the `on_remove` hook first.
+    pub fn on_insert<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Index, &mut T) + Send + Sync + 'static,
+    {
+        self.on_insert = Some(Box::new(f));
+
+        self
+    }
+
+    /// Sets the callback that is invoked when a component is removed from
+    /// the storage, before it is returned to the caller (like `Storage::remove`, or
+    /// when a component is replaced by a new one).
+    pub fn on_remove<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Index, &T) + Send + Sync + 'static,
+    {
+        self.on_remove = Some(Box::new(f));
+
+        self
+    }
+
+    /// Sets the callback that is invoked before a component is dropped by
+    /// the storage. This happens if the entity of the component is deleted
+    /// (including the deletions that are applied by `World::maintain`), or
+    /// if the storage is cleared.
+    ///
+    /// The hook is not invoked for the components that are still stored
+    /// when the storage itself is dropped.
+    pub fn on_drop<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Index, &T) + Send + Sync + 'static,
+    {
+        self.on_drop = Some(Box::new(f));
+
+        self
+    }
+
+    /// Returns `true` if no callback is set.
+    pub fn is_empty(&self) -> bool {
+        self.on_insert.is_none() && self.on_remove.is_none() && self.on_drop.is_none()
+    }
+
+    pub(crate) fn inserted(&self, index: Index, component: &mut T) {
+        if let Some(f) = &self.on_insert {
+            f(index, component);
+        }
+    }
+
+    pub(crate) fn removed(&self, index: Index, component: &T) {
+        if let Some(f) = &self.on_remove {
+            f(index, component);
+        }
+    }
+
+    pub(crate) fn dropped(&self, index: Index, component: &T) {
+        if let Some(f) = &self.on_drop {
+            f(index, component);
+        }
+    }
+
+    /// Adds a callback that is invoked before the current `on_insert` hook.
+    pub(crate) fn prepend_insert<F>(&mut self, f: F)
+    where
+        T: 'static,
+        F: Fn(Index, &mut T) + Send + Sync + 'static,
+    {
+        let next = self.on_insert.take();
+
+        self.on_insert = Some(Box::new(move |index, component| {
+            f(index, component);
+
+            if let Some(next) = &next {
+                next(index, component);
+            }
+        }));
+    }
+
+    /// Adds a callback that is invoked after the current `on_drop` hook.
+    pub(crate) fn append_drop<F>(&mut self, f: F)
+    where
+        T: 'static,
+        F: Fn(Index, &T) + Send + Sync + 'static,
+    {
+        let prev = self.on_drop.take();
+
+        self.on_drop = Some(Box::new(move |index, component| {
+            if let Some(prev) = &prev {
+                prev(index, component);
+            }
+
+            f(index, component);
+        }));
+    }
+
+    pub(crate) fn has_insert(&self) -> bool {
+        self.on_insert.is_some()
+    }
+
+    pub(crate) fn has_drop(&self) -> bool {
+        self.on_drop.is_some()
+    }
+}
+
+impl<T> Default for ComponentHooks<T> {
+    fn default() -> Self {
+        Self {
+            on_insert: None,
+            on_remove: None,
+            on_drop: None,
+        }
+    }
+}
+
+impl<T> Debug for ComponentHooks<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("ComponentHooks")
+            .field("on_insert", &self.on_insert.is_some())
+            .field("on_remove", &self.on_remove.is_some())
+            .field("on_drop", &self.on_drop.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::{Arc, Mutex};
+
+    use crate::{component::Component, entity::Builder, storage::VecStorage, world::World};
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[tokio::test]
+    async fn hooks() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let hook = |event: &'static str| {
+            let log = log.clone();
+
+            move |_: Index, pos: &Pos| log.lock().unwrap().push((event, pos.0))
+        };
+        let insert = hook("insert");
+
+        let mut world = World::default();
+        world.register_component_with_hooks(
+            ComponentHooks::new()
+                .on_insert(move |index, pos: &mut Pos| insert(index, pos))
+                .on_remove(hook("remove"))
+                .on_drop(hook("drop")),
+        );
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+        let e3 = world.create_entity().with(Pos(3)).build();
+
+        {
+            let mut pos = world.component_mut::<Pos>();
+            pos.insert(e1, Pos(4)).unwrap();
+            pos.remove(e2).unwrap();
+        }
+
+        world.entities().delete(e1).unwrap();
+        world.maintain().await;
+
+        world.component_mut::<Pos>().clear();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                ("insert", 1),
+                ("insert", 2),
+                ("insert", 3),
+                ("remove", 1),
+                ("insert", 4),
+                ("remove", 2),
+                ("drop", 4),
+                ("drop", 3),
+            ]
+        );
+        assert!(!world.is_alive(e1));
+        assert!(world.is_alive(e3));
+    }
+
+    #[test]
+    fn panicking_insert_hook() {
+        let mut world = World::default();
+        world.register_component_with_hooks(ComponentHooks::new().on_insert(|_, pos: &mut Pos| {
+            if pos.0 == 0 {
+                panic!("Invalid position");
+            }
+        }));
+
+        let entity = world.create_entity().build();
+
+        let mut pos = world.component_mut::<Pos>();
+        let result = catch_unwind(AssertUnwindSafe(|| pos.insert(entity, Pos(0))));
+
+        assert!(result.is_err());
+        assert_eq!(pos.get(entity), None);
+        assert_eq!(pos.insert(entity, Pos(1)).unwrap(), None);
+        assert_eq!(pos.get(entity), Some(&Pos(1)));
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt::Debug;
+
+use super::Component;
+
+/// Component that can be inspected by debug tools, like in-game editors or
+/// terminal inspectors.
+///
+/// Register the component using `World::register_inspect` and use the
+/// `WorldView` returned by `World::fetch_all` to iterate the entities and the
+/// fields of their inspectable components.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::{component::{Field, InspectComponent}, *};
+///
+/// struct Health {
+///     current: f32,
+///     max: f32,
+/// }
+///
+/// impl Component for Health {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// impl InspectComponent for Health {
+///     fn fields(&self) -> Vec<Field> {
+///         vec![
+///             Field::new("current", self.current),
+///             Field::new("max", self.max),
+///         ]
+///     }
+/// }
+///
+/// let mut world = World::default();
+/// world.register_inspect::<Health>();
+///
+/// let entity = world
+///     .create_entity()
+///     .with(Health { current: 3.0, max: 5.0 })
+///     .build();
+///
+/// let view = world.fetch_all();
+/// let components = view.components(entity);
+///
+/// assert!(components[0].name.ends_with("Health"));
+/// assert_eq!(components[0].fields[1].name, "max");
+/// assert_eq!(components[0].fields[1].value, "5.0");
+/// ```
+pub trait InspectComponent: Component {
+    /// Returns the fields of the component.
+    fn fields(&self) -> Vec<Field>;
+}
+
+/// Field of a component returned by `InspectComponent::fields`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// Name of the field.
+    pub name: &'static str,
+
+    /// Value of the field, formatted using `Debug`.
+    pub value: String,
+}
+
+impl Field {
+    /// Creates a new field with the passed name, formatting the value using
+    /// `Debug`.
+    pub fn new<T: Debug>(name: &'static str, value: T) -> Self {
+        Self {
+            name,
+            value: format!("{:?}", value),
+        }
+    }
+}
@@ -1,6 +1,14 @@
+mod dynamic;
+mod hooks;
+mod inspect;
+
+pub use dynamic::DynamicId;
+pub use hooks::ComponentHooks;
+pub use inspect::{Field, InspectComponent};
+
 use std::any::Any;
 
-use crate::storage::Storage;
+use crate::{storage::Storage, world::World};
 
 /// Abstract component type.
 /// Doesn't have to be Copy or even Clone.
@@ -60,7 +68,59 @@ use crate::storage::Storage;
 ///     type Storage = HashMapStorage<Self>;
 /// }
 /// ```
+///
+/// Using the `derive` feature, the implementation can be derived. The
+/// storage is selected with the `storage` attribute and defaults to
+/// `DenseVecStorage`:
+///
+/// ```
+/// use async_ecs::*;
+///
+/// #[derive(Component)]
+/// #[storage(VecStorage)]
+/// pub struct Velocity {
+///     pub x: f32,
+///     pub y: f32,
+/// }
+/// ```
+///
+/// ## Dependencies
+///
+/// Components that are always used together with other components can
+/// register them in `setup`. It is called when the component is registered
+/// in the world (for example by `World::register_component` or the setup
+/// of a `ReadStorage` or `WriteStorage`).
+///
+/// ```
+/// use async_ecs::*;
+///
+/// #[derive(Component)]
+/// pub struct Position(f32, f32);
+///
+/// pub struct Body {
+///     mass: f32,
+/// }
+///
+/// impl Component for Body {
+///     type Storage = VecStorage<Self>;
+///
+///     fn setup(world: &mut World) {
+///         world.register_component::<Position>();
+///     }
+/// }
+///
+/// let mut world = World::default();
+/// world.register_component::<Body>();
+///
+/// assert_eq!(world.component::<Position>().count(), 0);
+/// ```
 pub trait Component: Any + Sized {
     /// Associated storage type for this component.
     type Storage: Storage<Self> + Any + Send + Sync;
+
+    /// Registers the components and resources this component depends on.
+    /// Called once, when the component is registered in the world.
+    fn setup(world: &mut World) {
+        let _ = world;
+    }
 }
@@ -0,0 +1,140 @@
+use std::cell::Cell;
+
+use crate::system::System;
+
+use super::{Builder, Error};
+
+/// Adds a system to a dispatcher `Builder` and selects the execution based
+/// on the type of the system: systems that are `Send` are added using
+/// `Builder::add`, all other systems are added using `Builder::add_local`.
+///
+/// The selection is done at compile time, so the type of the system has to
+/// be known where the macro is used (it can not be used with a generic
+/// system type). Systems that access `LocalResources` are always executed on
+/// the local task set, independent of how they were added.
+///
+/// ## Examples
+///
+/// ```
+/// # use std::rc::Rc;
+/// # use async_ecs::*;
+/// # use tokio::task::LocalSet;
+/// #
+/// #[derive(Default)]
+/// struct Counter(usize);
+///
+/// struct Increment;
+///
+/// impl<'a> System<'a> for Increment {
+///     type SystemData = Write<'a, Counter>;
+///
+///     fn run(&mut self, mut counter: Self::SystemData) {
+///         counter.0 += 1;
+///     }
+/// }
+///
+/// struct Shared(Rc<usize>);
+///
+/// impl<'a> System<'a> for Shared {
+///     type SystemData = Write<'a, Counter>;
+///
+///     fn run(&mut self, mut counter: Self::SystemData) {
+///         counter.0 += *self.0;
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// # LocalSet::new().run_until(async {
+/// let mut world = World::default();
+/// let mut builder = Dispatcher::setup_builder(&mut world);
+///
+/// add_auto!(builder, Increment, "increment", &[]).unwrap();
+/// add_auto!(builder, Shared(Rc::new(2)), "shared", &["increment"]).unwrap();
+///
+/// let mut dispatcher = builder.build();
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// assert_eq!(world.resource::<Counter>().0, 3);
+/// # }).await;
+/// # }
+/// ```
+#[macro_export]
+macro_rules! add_auto {
+    ($builder:expr, $system:expr, $name:expr, $dependencies:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::dispatcher::auto::{AddLocal as _, AddThread as _};
+
+        (&$crate::dispatcher::auto::AutoSystem::new($system)).add_auto(
+            &mut $builder,
+            $name,
+            $dependencies,
+        )
+    }};
+}
+
+/// Wrapper of a system that is added using `add_auto!`.
+///
+/// The wrapper implements `AddThread` if the system is `Send` and a
+/// reference to the wrapper implements `AddLocal` for any system. Because
+/// the method resolution prefers the receiver that needs the fewest
+/// references, `AddThread` is selected whenever it is implemented.
+pub struct AutoSystem<S>(Cell<Option<S>>);
+
+impl<S> AutoSystem<S> {
+    pub fn new(system: S) -> Self {
+        Self(Cell::new(Some(system)))
+    }
+
+    fn take(&self) -> S {
+        self.0.take().expect("System was already added")
+    }
+}
+
+/// Adds the wrapped system using `Builder::add`.
+pub trait AddThread {
+    fn add_auto<'b, 'a>(
+        &self,
+        builder: &'b mut Builder<'a>,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<&'b mut Builder<'a>, Error>;
+}
+
+impl<S> AddThread for AutoSystem<S>
+where
+    S: for<'s> System<'s> + Send + 'static,
+{
+    fn add_auto<'b, 'a>(
+        &self,
+        builder: &'b mut Builder<'a>,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<&'b mut Builder<'a>, Error> {
+        builder.add(self.take(), name, dependencies)
+    }
+}
+
+/// Adds the wrapped system using `Builder::add_local`.
+pub trait AddLocal {
+    fn add_auto<'b, 'a>(
+        &self,
+        builder: &'b mut Builder<'a>,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<&'b mut Builder<'a>, Error>;
+}
+
+impl<S> AddLocal for &AutoSystem<S>
+where
+    S: for<'s> System<'s> + 'static,
+{
+    fn add_auto<'b, 'a>(
+        &self,
+        builder: &'b mut Builder<'a>,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<&'b mut Builder<'a>, Error> {
+        builder.add_local(self.take(), name, dependencies)
+    }
+}
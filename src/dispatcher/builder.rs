@@ -1,28 +1,81 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::mem::take;
+use std::sync::Arc;
+use std::time::Duration;
 
+use futures::future::{Future, FutureExt, RemoteHandle};
 use hashbrown::hash_map::{Entry, HashMap};
-use tokio::{
-    sync::watch::channel,
-    task::{spawn as spawn_task, spawn_local},
-};
+use log::{debug, warn};
+use tokio::sync::watch::channel;
 
 use crate::{
     access::Accessor,
-    resource::ResourceId,
+    resource::{Resource, ResourceId},
     system::{AsyncSystem, System},
-    world::World,
+    world::{Time, World},
 };
 
 use super::{
-    task::{execute_local, execute_local_async, execute_thread, execute_thread_async},
-    Dispatcher, Error, LocalRun, LocalRunAsync, Receiver, Sender, SharedWorld, ThreadRun,
-    ThreadRunAsync,
+    fixed_rate::FixedRate,
+    task::{
+        execute_dispatcher, execute_local, execute_local_async, execute_thread,
+        execute_thread_async, Diagnostics, SystemInfo, Wiring,
+    },
+    Bundle, Condition, ControlReceiver, Dispatcher, Error, LocalRun, LocalRunAsync, Metrics,
+    PanicPolicy, Receiver, Runtime, Sender, SharedWorld, Spawner, SystemHandle, ThreadRun,
+    ThreadRunAsync, TokioSpawner,
 };
 
 /// Id of a system inside the `Dispatcher` and the `Builder`.
 #[derive(Default, Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 struct SystemId(pub usize);
 
+/// Dependency between two systems that was inferred from the resources they
+/// access. See `Builder::inferred_dependencies`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InferredDependency {
+    /// Name of the depending system.
+    pub system: String,
+
+    /// Name of the system that is executed before `system`.
+    pub dependency: String,
+
+    /// Name of the resource that is accessed by both systems.
+    pub resource: &'static str,
+
+    /// How the resource is accessed by `system`.
+    pub system_access: Access,
+
+    /// How the resource is accessed by `dependency`.
+    pub dependency_access: Access,
+}
+
+impl Display for InferredDependency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let system_access = match self.system_access {
+            Access::Read => "reads",
+            Access::Write => "writes",
+        };
+        let dependency_access = match self.dependency_access {
+            Access::Read => "read",
+            Access::Write => "written",
+        };
+
+        write!(
+            f,
+            "{} {} {}, which is {} by {}",
+            self.system, system_access, self.resource, dependency_access, self.dependency
+        )
+    }
+}
+
+/// Kind of access of a system to a resource.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
 /// Builder for the [`Dispatcher`].
 ///
 /// [`Dispatcher`]: struct.Dispatcher.html
@@ -32,6 +85,16 @@ struct SystemId(pub usize);
 /// Barriers are a way of sequentializing parts of
 /// the system execution. See `add_barrier()`/`with_barrier()`.
 ///
+/// All systems that were added before a barrier are guaranteed to be
+/// finished before any system that is added after the barrier is started,
+/// regardless of the resources they access.
+///
+/// ## Resource Locks
+///
+/// By default the execution order of the systems is derived from the
+/// resources they read and write. See `with_resource_locks()` to resolve
+/// conflicting resource accesses at run-time instead.
+///
 /// ## Examples
 ///
 /// This is how you create a dispatcher with
@@ -119,6 +182,13 @@ pub struct Builder<'a> {
     next_id: SystemId,
     items: HashMap<SystemId, Item>,
     names: HashMap<String, SystemId>,
+    barrier: Vec<SystemId>,
+    pending_barrier: bool,
+    resource_locks: bool,
+    panic_policy: PanicPolicy,
+    runtime: Runtime,
+    spawner: Arc<dyn Spawner>,
+    namespace: Option<String>,
 }
 
 impl<'a> Builder<'a> {
@@ -128,13 +198,29 @@ impl<'a> Builder<'a> {
             next_id: Default::default(),
             items: Default::default(),
             names: Default::default(),
+            barrier: Default::default(),
+            pending_barrier: false,
+            resource_locks: false,
+            panic_policy: PanicPolicy::default(),
+            runtime: Runtime::default(),
+            spawner: Arc::new(TokioSpawner),
+            namespace: None,
         }
     }
 
     /// Builds the `Dispatcher`.
     ///
     /// This method will precompute useful information in order to speed up dispatching.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a system depends on itself, directly or through other
+    /// dependencies (see `validate`).
     pub fn build(self) -> Dispatcher {
+        if let Err(err) = self.check_cycles() {
+            panic!("Unable to build dispatcher: {}", err);
+        }
+
         let receivers = self
             .final_systems()
             .into_iter()
@@ -142,47 +228,186 @@ impl<'a> Builder<'a> {
             .collect();
 
         let world = SharedWorld::default();
-        let (sender, receiver) = channel(());
-
-        for (_, item) in self.items.into_iter() {
-            let run = item.run;
-            let name = item.name;
-            let sender = item.sender;
+        let diagnostics = Diagnostics::default();
+        let metrics = Metrics::default();
+        let (sender, start) = channel(());
+
+        let mut items = self.items.into_iter().collect::<Vec<_>>();
+        items.sort_by_key(|(id, _)| *id);
+
+        let mut systems = Vec::with_capacity(items.len());
+        for (_, item) in items {
+            let info = Arc::new(SystemInfo {
+                name: item.name,
+                namespace: item.namespace,
+                labels: item.labels,
+                reads: item.reads,
+                writes: item.writes,
+                resource_locks: self.resource_locks,
+                panic_policy: self.panic_policy,
+                conditions: item.conditions,
+            });
             let receivers = if item.dependencies.is_empty() {
-                vec![receiver.clone()]
+                vec![start.clone()]
             } else {
                 item.receivers
             };
+            let (control, control_receiver) = channel(Wiring::Receivers(receivers));
+            let run = item.run.expect("Item without system");
 
-            match run {
-                RunType::Thread(run) => {
-                    spawn_task(execute_thread(name, run, sender, receivers, world.clone()))
-                }
-                RunType::Local(run) => {
-                    spawn_local(execute_local(name, run, sender, receivers, world.clone()))
-                }
-                RunType::ThreadAsync(run) => spawn_task(execute_thread_async(
-                    name,
-                    run,
-                    sender,
-                    receivers,
-                    world.clone(),
-                )),
-                RunType::LocalAsync(run) => spawn_local(execute_local_async(
-                    name,
+            let (run, handle) = if self.runtime == Runtime::Sequential {
+                (Some(run), None)
+            } else {
+                let handle = spawn(
                     run,
-                    sender,
-                    receivers,
+                    self.runtime,
+                    &*self.spawner,
+                    info.clone(),
+                    item.sender,
+                    control_receiver,
                     world.clone(),
-                )),
+                    diagnostics.clone(),
+                    metrics.clone(),
+                );
+
+                (None, Some(handle))
             };
+
+            systems.push(SystemHandle {
+                info,
+                dependencies: item.dependency_names,
+                barrier: item.barrier,
+                receiver: item.receiver,
+                control,
+                handle,
+                run,
+            });
         }
 
         Dispatcher {
             sender,
+            start,
             receivers,
             world,
+            diagnostics,
+            metrics,
+            systems,
+            resource_locks: self.resource_locks,
+            panic_policy: self.panic_policy,
+            runtime: self.runtime,
+            spawner: self.spawner,
+        }
+    }
+
+    /// Returns the dependencies of the system with the passed name, that were
+    /// inferred from the resources the systems read and write. Each entry
+    /// names the resource that caused the dependency and how both systems
+    /// access it. Dependencies that are implied by other dependencies are
+    /// included as well.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::{dispatcher::Access, *};
+    /// #
+    /// # #[derive(Default)]
+    /// # struct Res;
+    /// #
+    /// # struct Reader;
+    /// #
+    /// # impl<'a> System<'a> for Reader {
+    /// #     type SystemData = Read<'a, Res>;
+    /// #
+    /// #     fn run(&mut self, _: Self::SystemData) {}
+    /// # }
+    /// #
+    /// # struct Writer;
+    /// #
+    /// # impl<'a> System<'a> for Writer {
+    /// #     type SystemData = Write<'a, Res>;
+    /// #
+    /// #     fn run(&mut self, _: Self::SystemData) {}
+    /// # }
+    /// #
+    /// let builder = Dispatcher::builder()
+    ///     .with(Writer, "writer", &[])
+    ///     .unwrap()
+    ///     .with(Reader, "reader", &[])
+    ///     .unwrap();
+    ///
+    /// let inferred = builder.inferred_dependencies("reader").unwrap();
+    ///
+    /// assert_eq!(inferred[0].dependency, "writer");
+    /// assert_eq!(inferred[0].resource, std::any::type_name::<Res>());
+    /// assert_eq!(inferred[0].dependency_access, Access::Write);
+    /// assert_eq!(
+    ///     inferred[0].to_string(),
+    ///     format!("reader reads {}, which is written by writer", std::any::type_name::<Res>()),
+    /// );
+    /// ```
+    pub fn inferred_dependencies(&self, name: &str) -> Result<&[InferredDependency], Error> {
+        self.names
+            .get(name)
+            .map(|id| self.items[id].inferred.as_slice())
+            .ok_or_else(|| Error::SystemWasNotFound(name.into()))
+    }
+
+    /// Validates the dependencies of the added systems.
+    ///
+    /// Returns `Error::CyclicDependency` if a system depends on itself,
+    /// directly or through other explicit or inferred dependencies. `build`
+    /// runs the same check. This method additionally returns
+    /// `Error::UnneededDependency` for explicitly declared
+    /// dependencies that do not access any resource in a conflicting way
+    /// (the dependency writes something the system reads or writes, or the
+    /// system writes something the dependency reads). Systems whose declared
+    /// resources are not accessed by any other system are logged as warnings.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.check_cycles()?;
+
+        let mut items = self.items.iter().collect::<Vec<_>>();
+        items.sort_by_key(|(id, _)| **id);
+
+        for (_, item) in &items {
+            for name in &item.dependency_names {
+                let dependency = &self.items[&self.names[name]];
+
+                let conflicts = |a: &Item, b: &Item| {
+                    a.writes
+                        .iter()
+                        .any(|write| b.reads.contains(write) || b.writes.contains(write))
+                };
+
+                if !conflicts(dependency, item) && !conflicts(item, dependency) {
+                    return Err(Error::UnneededDependency {
+                        system: item.name.clone(),
+                        dependency: name.clone(),
+                    });
+                }
+            }
+        }
+
+        for (id, item) in &items {
+            if item.reads.is_empty() && item.writes.is_empty() {
+                continue;
+            }
+
+            let shared = item.reads.iter().chain(&item.writes).any(|resource| {
+                items.iter().any(|(other_id, other)| {
+                    other_id != id
+                        && (other.reads.contains(resource) || other.writes.contains(resource))
+                })
+            });
+
+            if !shared {
+                warn!(
+                    "System {} does not share any of its resources with other systems!",
+                    item.name
+                );
+            }
         }
+
+        Ok(())
     }
 
     /// Adds a new system with a given name and a list of dependencies.
@@ -300,6 +525,112 @@ impl<'a> Builder<'a> {
         Ok(self)
     }
 
+    /// Adds a new system that is executed at a fixed rate.
+    ///
+    /// Same as [`add_fixed_rate()`](struct.Dispatcher::builder().html#method.add_fixed_rate),
+    /// but returns `self` to enable method chaining.
+    pub fn with_fixed_rate<S>(
+        mut self,
+        system: S,
+        name: &str,
+        dependencies: &[&str],
+        step: Duration,
+    ) -> Result<Self, Error>
+    where
+        S: for<'s> System<'s> + Send + 'static,
+    {
+        self.add_fixed_rate(system, name, dependencies, step)?;
+
+        Ok(self)
+    }
+
+    /// Adds a new system that is executed at a fixed rate.
+    ///
+    /// The dispatcher accumulates the time that elapsed since the last
+    /// dispatch (see `Time::elapsed`) and executes the system once for each
+    /// `step` of the accumulated time. So depending on the rate the
+    /// dispatcher is called with, the system is executed zero or more times
+    /// per dispatch. The remaining time is kept for the next dispatch. If
+    /// the system falls behind by more than a few steps, the exceeding time
+    /// is dropped.
+    ///
+    /// The accumulation starts with the first dispatch, so the system is
+    /// not executed before the second one. If multiple dispatchers are
+    /// dispatched against the same world, each of them updates the `Time`
+    /// resource, but the system still only accumulates the time between
+    /// the dispatches of its own dispatcher.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use async_ecs::*;
+    /// #
+    /// #[derive(Default)]
+    /// struct Steps(u32);
+    ///
+    /// struct Physics;
+    ///
+    /// impl<'a> System<'a> for Physics {
+    ///     type SystemData = Write<'a, Steps>;
+    ///
+    ///     fn run(&mut self, mut steps: Self::SystemData) {
+    ///         steps.0 += 1;
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut world = World::default();
+    /// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+    ///     .with_fixed_rate(Physics, "physics", &[], Duration::from_millis(10))
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// dispatcher.dispatch(&world).await.unwrap();
+    /// std::thread::sleep(Duration::from_millis(25));
+    /// dispatcher.dispatch(&world).await.unwrap();
+    ///
+    /// assert!(world.resource::<Steps>().0 >= 2);
+    /// # }
+    /// ```
+    pub fn add_fixed_rate<S>(
+        &mut self,
+        mut system: S,
+        name: &str,
+        dependencies: &[&str],
+        step: Duration,
+    ) -> Result<&mut Self, Error>
+    where
+        S: for<'s> System<'s> + Send + 'static,
+    {
+        let mut reads = system.accessor().reads();
+        reads.push(ResourceId::new::<Time>());
+
+        self.add_inner(
+            name,
+            dependencies,
+            reads,
+            system.accessor().writes(),
+            |this, id| {
+                if let Some(ref mut w) = this.world {
+                    system.setup(w)
+                }
+
+                match this.items.entry(id) {
+                    Entry::Vacant(e) => e.insert(Item::fixed_rate(name.into(), system, step)),
+                    Entry::Occupied(_) => panic!("Item was already created!"),
+                }
+            },
+        )?;
+
+        Ok(self)
+    }
+
     /// Adds a new thread local system.
     ///
     /// Please only use this if your struct is not `Send` and `Sync`.
@@ -412,6 +743,446 @@ impl<'a> Builder<'a> {
         Ok(self)
     }
 
+    /// Adds an already built dispatcher as a single system with a given
+    /// name and a list of dependencies.
+    ///
+    /// Same as [`add_dispatcher()`](struct.Dispatcher::builder().html#method.add_dispatcher),
+    /// but returns `self` to enable method chaining.
+    pub fn with_dispatcher(
+        mut self,
+        dispatcher: Dispatcher,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<Self, Error> {
+        self.add_dispatcher(dispatcher, name, dependencies)?;
+
+        Ok(self)
+    }
+
+    /// Adds an already built dispatcher as a single system with a given
+    /// name and a list of dependencies.
+    ///
+    /// The nested dispatcher reads and writes all the resources of its
+    /// systems, so it is ordered like any other system. Each time it is
+    /// executed, all of its systems are dispatched using the runtime of
+    /// the nested dispatcher. Disposing the system shuts the nested
+    /// dispatcher down.
+    ///
+    /// The nested dispatcher is driven by a thread local task, so unless
+    /// the sequential runtime is used, the dispatcher must be used inside
+    /// a `LocalSet`.
+    pub fn add_dispatcher(
+        &mut self,
+        dispatcher: Dispatcher,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<&mut Self, Error> {
+        let (reads, writes) = dispatcher.accessed();
+
+        self.add_inner(name, dependencies, reads, writes, |this, id| {
+            match this.items.entry(id) {
+                Entry::Vacant(e) => e.insert(Item::dispatcher(name.into(), dispatcher)),
+                Entry::Occupied(_) => panic!("Item was already created!"),
+            }
+        })?;
+
+        Ok(self)
+    }
+
+    /// Adds the passed bundle to the builder. The bundle may set up the
+    /// passed world and add its systems to the builder.
+    ///
+    /// Same as [`add_bundle()`](struct.Dispatcher::builder().html#method.add_bundle),
+    /// but returns `self` to enable method chaining.
+    pub fn with_bundle<B>(mut self, world: &mut World, bundle: B) -> Result<Self, Error>
+    where
+        B: Bundle,
+    {
+        self.add_bundle(world, bundle)?;
+
+        Ok(self)
+    }
+
+    /// Adds the passed bundle to the builder. The bundle may set up the
+    /// passed world and add its systems to the builder.
+    pub fn add_bundle<B>(&mut self, world: &mut World, bundle: B) -> Result<&mut Self, Error>
+    where
+        B: Bundle,
+    {
+        bundle.build(world, self)?;
+
+        Ok(self)
+    }
+
+    /// Adds a run condition to the system with the given name.
+    ///
+    /// Same as [`add_condition()`](struct.Dispatcher::builder().html#method.add_condition),
+    /// but returns `self` to enable method chaining.
+    pub fn with_condition<F>(mut self, name: &str, condition: F) -> Result<Self, Error>
+    where
+        F: Fn(&World) -> bool + Send + Sync + 'static,
+    {
+        self.add_condition(name, condition)?;
+
+        Ok(self)
+    }
+
+    /// Adds a run condition to the system with the given name.
+    ///
+    /// The condition is evaluated each time the system would be executed.
+    /// If it returns `false`, the system is skipped for this dispatch, but
+    /// the systems that depend on it are executed anyway. If multiple
+    /// conditions are added, all of them have to return `true`.
+    ///
+    /// The condition is evaluated before the system fetches its resources,
+    /// so it should only access resources that are not written by systems
+    /// that may run at the same time.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// #[derive(Default)]
+    /// struct Paused(bool);
+    ///
+    /// #[derive(Default)]
+    /// struct Ticks(u32);
+    ///
+    /// struct Tick;
+    ///
+    /// impl<'a> System<'a> for Tick {
+    ///     type SystemData = Write<'a, Ticks>;
+    ///
+    ///     fn run(&mut self, mut ticks: Self::SystemData) {
+    ///         ticks.0 += 1;
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut world = World::default();
+    /// world.register_resource(Paused(false));
+    ///
+    /// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+    ///     .with(Tick, "tick", &[])
+    ///     .unwrap()
+    ///     .with_condition("tick", |world| !world.resource::<Paused>().0)
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// dispatcher.dispatch(&world).await.unwrap();
+    /// world.resource_mut::<Paused>().0 = true;
+    /// dispatcher.dispatch(&world).await.unwrap();
+    ///
+    /// assert_eq!(world.resource::<Ticks>().0, 1);
+    /// # }
+    /// ```
+    pub fn add_condition<F>(&mut self, name: &str, condition: F) -> Result<&mut Self, Error>
+    where
+        F: Fn(&World) -> bool + Send + Sync + 'static,
+    {
+        let id = self
+            .names
+            .get(name)
+            .ok_or_else(|| Error::SystemWasNotFound(name.into()))?;
+
+        self.items
+            .get_mut(id)
+            .unwrap()
+            .conditions
+            .push(Box::new(condition));
+
+        Ok(self)
+    }
+
+    /// Sets the namespace of the systems that are added afterwards.
+    ///
+    /// Same as [`set_namespace()`](struct.Dispatcher::builder().html#method.set_namespace),
+    /// but returns `self` to enable method chaining.
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.set_namespace(namespace);
+
+        self
+    }
+
+    /// Sets the namespace of the systems that are added afterwards. Passing
+    /// an empty string resets the namespace.
+    ///
+    /// The namespace does not change the name of the system, so systems are
+    /// still referenced by their plain name (for example as dependency). It
+    /// is only used for diagnostics: log messages use the qualified name
+    /// `namespace::name`, and the namespace is reported by the `Graph` and
+    /// the `Metrics` of the dispatcher.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// # struct Dummy;
+    /// #
+    /// # impl<'a> System<'a> for Dummy {
+    /// #     type SystemData = ();
+    /// #
+    /// #     fn run(&mut self, _: ()) {}
+    /// # }
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let dispatcher = Dispatcher::builder()
+    ///     .with_namespace("physics")
+    ///     .with(Dummy, "integrate", &[])
+    ///     .unwrap()
+    ///     .with_labels("integrate", &["fixed"])
+    ///     .unwrap()
+    ///     .with_namespace("")
+    ///     .with(Dummy, "render", &[])
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// let graph = dispatcher.graph();
+    ///
+    /// assert_eq!(graph.systems[0].namespace.as_deref(), Some("physics"));
+    /// assert_eq!(graph.systems[0].labels, vec!["fixed".to_owned()]);
+    /// assert_eq!(graph.systems[1].namespace, None);
+    /// # }
+    /// ```
+    pub fn set_namespace(&mut self, namespace: &str) -> &mut Self {
+        self.namespace = if namespace.is_empty() {
+            None
+        } else {
+            Some(namespace.into())
+        };
+
+        self
+    }
+
+    /// Adds labels to the system with the given name.
+    ///
+    /// Same as [`add_labels()`](struct.Dispatcher::builder().html#method.add_labels),
+    /// but returns `self` to enable method chaining.
+    pub fn with_labels(mut self, name: &str, labels: &[&str]) -> Result<Self, Error> {
+        self.add_labels(name, labels)?;
+
+        Ok(self)
+    }
+
+    /// Adds labels to the system with the given name. Like the namespace
+    /// (see `set_namespace`), the labels are only used for diagnostics.
+    pub fn add_labels(&mut self, name: &str, labels: &[&str]) -> Result<&mut Self, Error> {
+        let id = self
+            .names
+            .get(name)
+            .ok_or_else(|| Error::SystemWasNotFound(name.into()))?;
+
+        self.items
+            .get_mut(id)
+            .unwrap()
+            .labels
+            .extend(labels.iter().map(|label| (*label).to_owned()));
+
+        Ok(self)
+    }
+
+    /// Inserts the passed resource into the world, so it is available when
+    /// the systems that are added afterwards are set up. Resources that
+    /// require constructor arguments can be provided this way instead of
+    /// relying on a `SetupHandler`.
+    ///
+    /// Same as [`add_resource()`](struct.Dispatcher::builder().html#method.add_resource),
+    /// but returns `self` to enable method chaining.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use async_ecs::*;
+    ///
+    /// struct Socket(String);
+    ///
+    /// struct Dummy;
+    ///
+    /// impl<'a> System<'a> for Dummy {
+    ///     type SystemData = ReadExpect<'a, Socket>;
+    ///
+    ///     fn run(&mut self, _: Self::SystemData) {}
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut world = World::default();
+    ///
+    /// let _dispatcher = Dispatcher::setup_builder(&mut world)
+    ///     .with_resource(Socket("127.0.0.1:8080".into()))
+    ///     .with(Dummy, "dummy", &[])
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// assert_eq!(world.resource::<Socket>().0, "127.0.0.1:8080");
+    /// # }
+    /// ```
+    pub fn with_resource<T: Resource>(mut self, resource: T) -> Self {
+        self.add_resource(resource);
+
+        self
+    }
+
+    /// Inserts the passed resource into the world, so it is available when
+    /// the systems that are added afterwards are set up.
+    ///
+    /// Like the setup of the systems, this does nothing if the builder was
+    /// not created using `Dispatcher::setup_builder`.
+    pub fn add_resource<T: Resource>(&mut self, resource: T) -> &mut Self {
+        if let Some(world) = self.world.as_mut() {
+            world.insert(resource);
+        }
+
+        self
+    }
+
+    /// Adds a barrier. All systems that were added before the barrier are
+    /// executed before any system that is added after the barrier.
+    ///
+    /// Same as [`add_barrier()`](struct.Dispatcher::builder().html#method.add_barrier),
+    /// but returns `self` to enable method chaining.
+    pub fn with_barrier(mut self) -> Self {
+        self.add_barrier();
+
+        self
+    }
+
+    /// Adds a barrier. All systems that were added before the barrier are
+    /// executed before any system that is added after the barrier.
+    pub fn add_barrier(&mut self) -> &mut Self {
+        self.barrier = self.final_systems();
+        self.pending_barrier = !self.barrier.is_empty();
+
+        self
+    }
+
+    /// Enables the resource locks of the dispatcher.
+    ///
+    /// Same as [`enable_resource_locks()`](struct.Dispatcher::builder().html#method.enable_resource_locks),
+    /// but returns `self` to enable method chaining.
+    pub fn with_resource_locks(mut self) -> Self {
+        self.enable_resource_locks();
+
+        self
+    }
+
+    /// Enables the resource locks of the dispatcher.
+    ///
+    /// Instead of ordering the systems by the resources they access, each
+    /// system acquires the async locks (see `Resources::lock`) of its
+    /// resources before it is executed. Systems that access the same
+    /// resources in a conflicting way are then executed one after another
+    /// (in an unspecified order), without the need to declare any
+    /// dependencies. Explicit dependencies and barriers are still respected.
+    ///
+    /// The setting is applied when the dispatcher is built, so it affects
+    /// all systems of the builder, no matter if they were added before or
+    /// after this call, and all systems that are added to the built
+    /// dispatcher later on.
+    pub fn enable_resource_locks(&mut self) -> &mut Self {
+        self.resource_locks = true;
+
+        self
+    }
+
+    /// Sets the policy that defines how panicking systems are handled.
+    ///
+    /// Same as [`set_panic_policy()`](struct.Dispatcher::builder().html#method.set_panic_policy),
+    /// but returns `self` to enable method chaining.
+    pub fn with_panic_policy(mut self, panic_policy: PanicPolicy) -> Self {
+        self.set_panic_policy(panic_policy);
+
+        self
+    }
+
+    /// Sets the policy that defines how panicking systems are handled. See
+    /// `PanicPolicy` for the available options. The default is
+    /// `PanicPolicy::Fail`.
+    ///
+    /// The policy is applied to all systems of the dispatcher when it is
+    /// built, including the systems that were added before this call and
+    /// the systems that are added to the built dispatcher later on. Nested
+    /// dispatchers use their own policy.
+    pub fn set_panic_policy(&mut self, panic_policy: PanicPolicy) -> &mut Self {
+        self.panic_policy = panic_policy;
+
+        self
+    }
+
+    /// Sets the runtime that is used to execute the systems.
+    ///
+    /// Same as [`set_runtime()`](struct.Dispatcher::builder().html#method.set_runtime),
+    /// but returns `self` to enable method chaining.
+    pub fn with_runtime(mut self, runtime: Runtime) -> Self {
+        self.set_runtime(runtime);
+
+        self
+    }
+
+    /// Sets the runtime that is used to execute the systems. See `Runtime`
+    /// for the available options. The default is `Runtime::Parallel`.
+    pub fn set_runtime(&mut self, runtime: Runtime) -> &mut Self {
+        self.runtime = runtime;
+
+        self
+    }
+
+    /// Sets the spawner that is used to spawn the tasks of the systems.
+    ///
+    /// Same as [`set_spawner()`](struct.Dispatcher::builder().html#method.set_spawner),
+    /// but returns `self` to enable method chaining.
+    pub fn with_spawner<S>(mut self, spawner: S) -> Self
+    where
+        S: Spawner + 'static,
+    {
+        self.set_spawner(spawner);
+
+        self
+    }
+
+    /// Sets the spawner that is used to spawn the tasks of the systems. This
+    /// allows to execute the systems on a custom runtime, a dedicated tokio
+    /// `Runtime` (by passing its `Handle`) or to instrument the spawned tasks.
+    /// The default is the `TokioSpawner`.
+    ///
+    /// The spawner is not used by a sequential dispatcher.
+    pub fn set_spawner<S>(&mut self, spawner: S) -> &mut Self
+    where
+        S: Spawner + 'static,
+    {
+        self.spawner = Arc::new(spawner);
+
+        self
+    }
+
+    /// Enables the sequential mode of the dispatcher.
+    ///
+    /// Same as [`enable_sequential()`](struct.Dispatcher::builder().html#method.enable_sequential),
+    /// but returns `self` to enable method chaining.
+    pub fn with_sequential(mut self) -> Self {
+        self.enable_sequential();
+
+        self
+    }
+
+    /// Enables the sequential mode of the dispatcher.
+    ///
+    /// A sequential dispatcher does not spawn a task for each system.
+    /// Instead all systems are executed one after another on the task that
+    /// calls `Dispatcher::dispatch`, in the order they were added (which
+    /// always satisfies their dependencies). This gives a deterministic
+    /// execution order, which is useful for debugging, as baseline for
+    /// benchmarks, or on platforms that are not able to spawn tasks.
+    ///
+    /// This is a shortcut for `set_runtime(Runtime::Sequential)`.
+    pub fn enable_sequential(&mut self) -> &mut Self {
+        self.set_runtime(Runtime::Sequential)
+    }
+
     fn add_inner<F>(
         &mut self,
         name: &str,
@@ -423,9 +1194,13 @@ impl<'a> Builder<'a> {
     where
         F: FnOnce(&mut Self, SystemId) -> &mut Item,
     {
+        if dependencies.contains(&name) {
+            return Err(Error::CyclicDependency(name.into()));
+        }
+
         let name = name.to_owned();
         let id = self.next_id();
-        let id = match self.names.entry(name) {
+        let id = match self.names.entry(name.clone()) {
             Entry::Vacant(e) => Ok(*e.insert(id)),
             Entry::Occupied(e) => Err(Error::NameAlreadyRegistered(e.key().into())),
         }?;
@@ -436,32 +1211,59 @@ impl<'a> Builder<'a> {
         reads.dedup();
         writes.dedup();
 
+        let dependency_names = dependencies.iter().map(|name| (*name).to_owned()).collect();
         let mut dependencies = dependencies
             .iter()
-            .map(|name| {
+            .map(|dependency| {
                 self.names
-                    .get(*name)
-                    .map(Clone::clone)
-                    .ok_or_else(|| Error::DependencyWasNotFound((*name).into()))
+                    .get(*dependency)
+                    .copied()
+                    .ok_or_else(|| Error::DependencyWasNotFound {
+                        system: name.clone(),
+                        dependency: (*dependency).into(),
+                    })
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        for read in &reads {
-            for (key, value) in &self.items {
-                if value.writes.contains(read) {
+        let mut inferred = Vec::new();
+        if !self.resource_locks {
+            let mut items = self.items.iter().collect::<Vec<_>>();
+            items.sort_by_key(|(id, _)| **id);
+
+            for (key, value) in items {
+                let mut infer = |resource: &ResourceId, system_access, dependency_access| {
+                    let dependency = InferredDependency {
+                        system: name.clone(),
+                        dependency: value.name.clone(),
+                        resource: resource.name(),
+                        system_access,
+                        dependency_access,
+                    };
+
+                    debug!("Inferred dependency: {}", dependency);
+
                     dependencies.push(*key);
+                    inferred.push(dependency);
+                };
+
+                for read in &reads {
+                    if value.writes.contains(read) {
+                        infer(read, Access::Read, Access::Write);
+                    }
                 }
-            }
-        }
 
-        for write in &writes {
-            for (key, value) in &self.items {
-                if value.reads.contains(write) || value.writes.contains(write) {
-                    dependencies.push(*key);
+                for write in &writes {
+                    if value.writes.contains(write) {
+                        infer(write, Access::Write, Access::Write);
+                    } else if value.reads.contains(write) {
+                        infer(write, Access::Write, Access::Read);
+                    }
                 }
             }
         }
 
+        dependencies.extend(&self.barrier);
+
         self.reduce_dependencies(&mut dependencies);
 
         let receivers = dependencies
@@ -469,16 +1271,74 @@ impl<'a> Builder<'a> {
             .map(|id| self.items.get(id).unwrap().receiver.clone())
             .collect();
 
+        let barrier = take(&mut self.pending_barrier);
+        let namespace = self.namespace.clone();
         let item = f(self, id);
 
         item.reads = reads;
         item.writes = writes;
         item.receivers = receivers;
         item.dependencies = dependencies;
+        item.dependency_names = dependency_names;
+        item.inferred = inferred;
+        item.barrier = barrier;
+        item.namespace = namespace;
 
         Ok(self)
     }
 
+    /// Computes the dependencies of the passed systems of an already built
+    /// dispatcher. Returns the indices of the direct dependencies for each
+    /// system and the indices of the final systems.
+    pub(super) fn wire(
+        systems: &[SystemHandle],
+        resource_locks: bool,
+    ) -> Result<(Vec<Vec<usize>>, Vec<usize>), Error> {
+        let mut builder = Builder::new(None);
+        builder.resource_locks = resource_locks;
+
+        for system in systems {
+            if system.barrier {
+                builder.add_barrier();
+            }
+
+            let name = &system.info.name;
+            let dependencies = system
+                .dependencies
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+
+            builder.add_inner(
+                name,
+                &dependencies,
+                system.info.reads.clone(),
+                system.info.writes.clone(),
+                |this, id| {
+                    this.items
+                        .entry(id)
+                        .or_insert_with(|| Item::new(name.clone(), None))
+                },
+            )?;
+        }
+
+        // ids are assigned sequentially, starting at 1
+        let index = |id: &SystemId| id.0 - 1;
+
+        let dependencies = (1..=systems.len())
+            .map(|id| {
+                builder.items[&SystemId(id)]
+                    .dependencies
+                    .iter()
+                    .map(index)
+                    .collect()
+            })
+            .collect();
+        let finals = builder.final_systems().iter().map(index).collect();
+
+        Ok((dependencies, finals))
+    }
+
     fn final_systems(&self) -> Vec<SystemId> {
         let mut ret = self.items.keys().map(Clone::clone).collect();
 
@@ -487,6 +1347,50 @@ impl<'a> Builder<'a> {
         ret
     }
 
+    /// Returns `Error::CyclicDependency` with the name of a system that is
+    /// part of a cycle, if the dependencies of the systems contain one.
+    fn check_cycles(&self) -> Result<(), Error> {
+        #[derive(Clone, Copy, Eq, PartialEq)]
+        enum State {
+            Visiting,
+            Done,
+        }
+
+        fn visit(
+            items: &HashMap<SystemId, Item>,
+            states: &mut HashMap<SystemId, State>,
+            id: SystemId,
+        ) -> Result<(), Error> {
+            match states.get(&id) {
+                Some(State::Done) => return Ok(()),
+                Some(State::Visiting) => {
+                    return Err(Error::CyclicDependency(items[&id].name.clone()));
+                }
+                None => (),
+            }
+
+            states.insert(id, State::Visiting);
+
+            for dependency in &items[&id].dependencies {
+                visit(items, states, *dependency)?;
+            }
+
+            states.insert(id, State::Done);
+
+            Ok(())
+        }
+
+        let mut ids = self.items.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+
+        let mut states = HashMap::new();
+        for id in ids {
+            visit(&self.items, &mut states, id)?;
+        }
+
+        Ok(())
+    }
+
     fn reduce_dependencies(&self, dependencies: &mut Vec<SystemId>) {
         dependencies.sort();
         dependencies.dedup();
@@ -534,18 +1438,112 @@ impl<'a> Builder<'a> {
     }
 }
 
+/// Spawns the task that executes the passed system.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn spawn(
+    run: RunType,
+    runtime: Runtime,
+    spawner: &dyn Spawner,
+    info: Arc<SystemInfo>,
+    sender: Sender,
+    control: ControlReceiver,
+    world: SharedWorld,
+    diagnostics: Diagnostics,
+    metrics: Metrics,
+) -> RemoteHandle<()> {
+    match run.localize(&info.reads, &info.writes) {
+        RunType::Thread(run) => spawn_send(
+            runtime,
+            spawner,
+            execute_thread(info, run, sender, control, world, diagnostics, metrics),
+        ),
+        RunType::Local(run) => spawn_local(
+            spawner,
+            execute_local(info, run, sender, control, world, diagnostics, metrics),
+        ),
+        RunType::ThreadAsync(run) => spawn_send(
+            runtime,
+            spawner,
+            execute_thread_async(info, run, sender, control, world, diagnostics, metrics),
+        ),
+        RunType::LocalAsync(run) => spawn_local(
+            spawner,
+            execute_local_async(info, run, sender, control, world, diagnostics, metrics),
+        ),
+        RunType::Dispatcher(dispatcher) => spawn_local(
+            spawner,
+            execute_dispatcher(
+                info,
+                dispatcher,
+                sender,
+                control,
+                world,
+                diagnostics,
+                metrics,
+            ),
+        ),
+    }
+}
+
+/// Spawns a task that is `Send`, using `spawn_local` of the spawner if the
+/// dispatcher uses the `Local` runtime.
+fn spawn_send<F>(runtime: Runtime, spawner: &dyn Spawner, future: F) -> RemoteHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (future, handle) = future.remote_handle();
+
+    match runtime {
+        Runtime::Local => spawner.spawn_local(future.boxed_local()),
+        _ => spawner.spawn(future.boxed()),
+    }
+
+    handle
+}
+
+/// Spawns a task that is not `Send`.
+fn spawn_local<F>(spawner: &dyn Spawner, future: F) -> RemoteHandle<()>
+where
+    F: Future<Output = ()> + 'static,
+{
+    let (future, handle) = future.remote_handle();
+
+    spawner.spawn_local(future.boxed_local());
+
+    handle
+}
+
 /// Defines how to execute the `System` with the `Dispatcher`.
-enum RunType {
+pub(super) enum RunType {
     Thread(ThreadRun),
     Local(LocalRun),
     ThreadAsync(ThreadRunAsync),
     LocalAsync(LocalRunAsync),
+    Dispatcher(Box<Dispatcher>),
+}
+
+impl RunType {
+    /// Turns systems that access local resources (see `LocalResources`) into
+    /// local systems, so they are executed on the local task set. Nested
+    /// dispatchers are always driven by a local task, so the local systems
+    /// of a nested dispatcher are executed on the local task set as well.
+    fn localize(self, reads: &[ResourceId], writes: &[ResourceId]) -> Self {
+        if !reads.iter().chain(writes).any(ResourceId::is_local) {
+            return self;
+        }
+
+        match self {
+            RunType::Thread(run) => RunType::Local(run),
+            RunType::ThreadAsync(run) => RunType::LocalAsync(run),
+            run => run,
+        }
+    }
 }
 
 /// Item that wraps all information of a 'System` within the `Builder`.
 struct Item {
     name: String,
-    run: RunType,
+    run: Option<RunType>,
 
     sender: Sender,
     receiver: Receiver,
@@ -554,10 +1552,16 @@ struct Item {
     reads: Vec<ResourceId>,
     writes: Vec<ResourceId>,
     dependencies: Vec<SystemId>,
+    dependency_names: Vec<String>,
+    inferred: Vec<InferredDependency>,
+    barrier: bool,
+    conditions: Vec<Condition>,
+    namespace: Option<String>,
+    labels: Vec<String>,
 }
 
 impl Item {
-    fn new(name: String, run: RunType) -> Self {
+    fn new(name: String, run: Option<RunType>) -> Self {
         let (sender, receiver) = channel(());
 
         Self {
@@ -571,6 +1575,12 @@ impl Item {
             reads: Vec::new(),
             writes: Vec::new(),
             dependencies: Vec::new(),
+            dependency_names: Vec::new(),
+            inferred: Vec::new(),
+            barrier: false,
+            conditions: Vec::new(),
+            namespace: None,
+            labels: Vec::new(),
         }
     }
 
@@ -578,28 +1588,41 @@ impl Item {
     where
         S: for<'s> System<'s> + Send + 'static,
     {
-        Self::new(name, RunType::Thread(Box::new(system)))
+        Self::new(name, Some(RunType::Thread(Box::new(system))))
+    }
+
+    fn fixed_rate<S>(name: String, system: S, step: Duration) -> Self
+    where
+        S: for<'s> System<'s> + Send + 'static,
+    {
+        let run = FixedRate::new(system, step);
+
+        Self::new(name, Some(RunType::Thread(Box::new(run))))
     }
 
     fn local<S>(name: String, system: S) -> Self
     where
         S: for<'s> System<'s> + 'static,
     {
-        Self::new(name, RunType::Local(Box::new(system)))
+        Self::new(name, Some(RunType::Local(Box::new(system))))
     }
 
     fn thread_async<S>(name: String, system: S) -> Self
     where
         S: for<'s> AsyncSystem<'s> + Send + 'static,
     {
-        Self::new(name, RunType::ThreadAsync(Box::new(system)))
+        Self::new(name, Some(RunType::ThreadAsync(Box::new(system))))
     }
 
     fn local_async<S>(name: String, system: S) -> Self
     where
         S: for<'s> AsyncSystem<'s> + 'static,
     {
-        Self::new(name, RunType::LocalAsync(Box::new(system)))
+        Self::new(name, Some(RunType::LocalAsync(Box::new(system))))
+    }
+
+    fn dispatcher(name: String, dispatcher: Dispatcher) -> Self {
+        Self::new(name, Some(RunType::Dispatcher(Box::new(dispatcher))))
     }
 }
 
@@ -703,6 +1726,161 @@ mod tests {
         assert_eq!(dispatcher.final_systems(), vec![SystemId(5)]);
     }
 
+    #[test]
+    fn dependencies_on_barrier() {
+        struct ResA;
+        struct ResB;
+
+        let sys1 = TestSystem::new(vec![ResourceId::new::<ResA>()], vec![]);
+        let sys2 = TestSystem::new(vec![ResourceId::new::<ResB>()], vec![]);
+        let sys3 = TestSystem::new(vec![], vec![]);
+        let sys4 = TestSystem::new(vec![], vec![]);
+        let sys5 = TestSystem::new(vec![ResourceId::new::<ResA>()], vec![]);
+
+        let dispatcher = Dispatcher::builder()
+            .with(sys1, "sys1", &[])
+            .unwrap()
+            .with(sys2, "sys2", &[])
+            .unwrap()
+            .with_barrier()
+            .with(sys3, "sys3", &[])
+            .unwrap()
+            .with(sys4, "sys4", &[])
+            .unwrap()
+            .with_barrier()
+            .with_barrier()
+            .with(sys5, "sys5", &[])
+            .unwrap();
+
+        let sys1 = dispatcher.items.get(&SystemId(1)).unwrap();
+        let sys2 = dispatcher.items.get(&SystemId(2)).unwrap();
+        let sys3 = dispatcher.items.get(&SystemId(3)).unwrap();
+        let sys4 = dispatcher.items.get(&SystemId(4)).unwrap();
+        let sys5 = dispatcher.items.get(&SystemId(5)).unwrap();
+
+        assert_eq!(sys1.dependencies, vec![]);
+        assert_eq!(sys2.dependencies, vec![]);
+        assert_eq!(sys3.dependencies, vec![SystemId(1), SystemId(2)]);
+        assert_eq!(sys4.dependencies, vec![SystemId(1), SystemId(2)]);
+        assert_eq!(sys5.dependencies, vec![SystemId(3), SystemId(4)]);
+        assert_eq!(dispatcher.final_systems(), vec![SystemId(5)]);
+    }
+
+    #[test]
+    fn dependencies_on_dynamic_ids() {
+        struct Res;
+
+        let res1 = ResourceId::new_with_dynamic_id::<Res>(1);
+        let res2 = ResourceId::new_with_dynamic_id::<Res>(2);
+
+        let sys1 = TestSystem::new(vec![], vec![res1.clone()]);
+        let sys2 = TestSystem::new(vec![], vec![res2]);
+        let sys3 = TestSystem::new(vec![res1], vec![]);
+
+        let dispatcher = Dispatcher::builder()
+            .with(sys1, "sys1", &[])
+            .unwrap()
+            .with(sys2, "sys2", &[])
+            .unwrap()
+            .with(sys3, "sys3", &[])
+            .unwrap();
+
+        let sys2 = dispatcher.items.get(&SystemId(2)).unwrap();
+        let sys3 = dispatcher.items.get(&SystemId(3)).unwrap();
+
+        assert_eq!(sys2.dependencies, vec![]);
+        assert_eq!(sys3.dependencies, vec![SystemId(1)]);
+    }
+
+    #[test]
+    fn validate_dependencies() {
+        struct ResA;
+        struct ResB;
+
+        let writer = || TestSystem::new(vec![], vec![ResourceId::new::<ResA>()]);
+        let reader = || TestSystem::new(vec![ResourceId::new::<ResA>()], vec![]);
+        let other = || TestSystem::new(vec![ResourceId::new::<ResB>()], vec![]);
+
+        let builder = Dispatcher::builder()
+            .with(writer(), "writer", &[])
+            .unwrap()
+            .with(reader(), "reader", &["writer"])
+            .unwrap();
+        assert!(builder.validate().is_ok());
+
+        let builder = Dispatcher::builder()
+            .with(reader(), "reader", &[])
+            .unwrap()
+            .with(writer(), "writer", &["reader"])
+            .unwrap();
+        assert!(builder.validate().is_ok());
+
+        let builder = Dispatcher::builder()
+            .with(writer(), "writer", &[])
+            .unwrap()
+            .with(other(), "other", &["writer"])
+            .unwrap();
+        assert!(matches!(
+            builder.validate(),
+            Err(Error::UnneededDependency { system, dependency })
+                if system == "other" && dependency == "writer"
+        ));
+
+        assert!(matches!(
+            Dispatcher::builder().with(writer(), "writer", &["writer"]),
+            Err(Error::CyclicDependency(name)) if name == "writer"
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unable to build dispatcher")]
+    fn build_rejects_cycles() {
+        struct ResA;
+
+        let writer = || TestSystem::new(vec![], vec![ResourceId::new::<ResA>()]);
+
+        let mut builder = Dispatcher::builder()
+            .with(writer(), "a", &[])
+            .unwrap()
+            .with(writer(), "b", &["a"])
+            .unwrap();
+        assert!(builder.validate().is_ok());
+
+        builder
+            .items
+            .get_mut(&SystemId(1))
+            .unwrap()
+            .dependencies
+            .push(SystemId(2));
+        assert!(matches!(
+            builder.validate(),
+            Err(Error::CyclicDependency(name)) if name == "a"
+        ));
+
+        builder.build();
+    }
+
+    #[test]
+    fn add_auto_selects_run_type() {
+        struct NotSend(std::rc::Rc<()>);
+
+        impl<'a> System<'a> for NotSend {
+            type SystemData = ();
+
+            fn run(&mut self, _: Self::SystemData) {}
+        }
+
+        let mut builder = Dispatcher::builder();
+        crate::add_auto!(builder, TestSystem::new(vec![], vec![]), "thread", &[]).unwrap();
+        crate::add_auto!(builder, NotSend(Default::default()), "local", &[]).unwrap();
+
+        let thread = builder.items.get(&SystemId(1)).unwrap();
+        let local = builder.items.get(&SystemId(2)).unwrap();
+
+        assert!(matches!(thread.run, Some(RunType::Thread(_))));
+        assert!(matches!(local.run, Some(RunType::Local(_))));
+    }
+
     struct TestSystem {
         accessor: TestAccessor,
     }
@@ -0,0 +1,59 @@
+use crate::world::World;
+
+use super::{Builder, Error};
+
+/// A bundle is a reusable pack of components, resources and systems, that
+/// can be added to a dispatcher [`Builder`] in one call.
+///
+/// Library authors can use bundles to ship their systems together with the
+/// setup of the world they need.
+///
+/// [`Builder`]: struct.Builder.html
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{dispatcher::{Builder, Bundle, Error}, *};
+/// #
+/// #[derive(Default)]
+/// struct Counter(usize);
+///
+/// struct Increment;
+///
+/// impl<'a> System<'a> for Increment {
+///     type SystemData = Write<'a, Counter>;
+///
+///     fn run(&mut self, mut counter: Self::SystemData) {
+///         counter.0 += 1;
+///     }
+/// }
+///
+/// struct CounterBundle;
+///
+/// impl Bundle for CounterBundle {
+///     fn build(self, world: &mut World, builder: &mut Builder) -> Result<(), Error> {
+///         world.register_resource(Counter::default());
+///         builder.add(Increment, "increment", &[])?;
+///
+///         Ok(())
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// let mut dispatcher = Dispatcher::builder()
+///     .with_bundle(&mut world, CounterBundle)
+///     .unwrap()
+///     .build();
+///
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// assert_eq!(world.resource::<Counter>().0, 1);
+/// # }
+/// ```
+pub trait Bundle {
+    /// Sets up the world and adds the systems of the bundle to the passed
+    /// dispatcher builder.
+    fn build(self, world: &mut World, builder: &mut Builder) -> Result<(), Error>;
+}
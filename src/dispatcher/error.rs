@@ -7,12 +7,44 @@ pub enum Error {
     #[error("A System with this name was already registered: {0}!")]
     NameAlreadyRegistered(String),
 
-    #[error("Dependency of the given system was not found: {0}!")]
-    DependencyWasNotFound(String),
+    #[error("System {system} depends on {dependency}, but a system with this name was not found!")]
+    DependencyWasNotFound { system: String, dependency: String },
+
+    #[error("System depends on itself: {0}!")]
+    CyclicDependency(String),
+
+    #[error("System {system} depends on {dependency}, but they do not access a common resource!")]
+    UnneededDependency { system: String, dependency: String },
+
+    #[error("A System with this name was not found: {0}!")]
+    SystemWasNotFound(String),
+
+    #[error(
+        "System {system} can not be removed, because these systems depend on it: {dependents:?}!"
+    )]
+    SystemIsRequired {
+        system: String,
+        dependents: Vec<String>,
+    },
 
     #[error("Unable to start dispatching!")]
     DispatchSend,
 
     #[error("Unable to wait for systems to finish!")]
     DispatchReceive,
+
+    #[error("Dispatch was cancelled before these systems were finished: {systems:?}!")]
+    DispatchCancelled { systems: Vec<String> },
+
+    #[error(
+        "System {system} was unable to borrow resource {resource}, conflicting systems: {conflicts:?}!"
+    )]
+    BorrowConflict {
+        system: String,
+        resource: &'static str,
+        conflicts: Vec<String>,
+    },
+
+    #[error("System {system} panicked: {message}!")]
+    SystemPanicked { system: String, message: String },
 }
@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use crate::{
+    system::System,
+    world::{Time, World},
+};
+
+use super::Run;
+
+/// Maximum number of steps a fixed rate system is executed per dispatch.
+/// Time that exceeds these steps is dropped, so a slow system does not
+/// fall further and further behind.
+const MAX_STEPS: u32 = 8;
+
+/// Wrapper that executes the wrapped system at a fixed rate, see
+/// `Builder::add_fixed_rate`.
+pub struct FixedRate<S> {
+    system: S,
+    step: Duration,
+    accumulator: Duration,
+    last: Option<Duration>,
+}
+
+impl<S> FixedRate<S> {
+    /// Wrap the passed system, that is executed once per `step`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    pub fn new(system: S, step: Duration) -> Self {
+        assert!(
+            !step.is_zero(),
+            "Step of a fixed rate system must not be zero"
+        );
+
+        Self {
+            system,
+            step,
+            accumulator: Duration::default(),
+            last: None,
+        }
+    }
+
+    /// Adds the passed delta to the accumulator and returns the number of
+    /// steps that need to be executed.
+    fn steps(&mut self, delta: Duration) -> u32 {
+        self.accumulator += delta;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < MAX_STEPS {
+            self.accumulator -= self.step;
+
+            steps += 1;
+        }
+
+        if steps == MAX_STEPS {
+            self.accumulator = self.accumulator.min(self.step);
+        }
+
+        steps
+    }
+}
+
+impl<'a, S> Run<'a> for FixedRate<S>
+where
+    S: System<'a>,
+{
+    fn run(&mut self, world: &'a World) {
+        // The elapsed time is used instead of the delta, because the delta
+        // only covers the time since the last update of any dispatcher.
+        let elapsed = world
+            .try_borrow::<Time>()
+            .map(|time| time.elapsed())
+            .unwrap_or_default();
+        let last = self.last.replace(elapsed).unwrap_or(elapsed);
+        let delta = elapsed.saturating_sub(last);
+
+        for _ in 0..self.steps(delta) {
+            <S as Run<'a>>::run(&mut self.system, world);
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        System::setup(&mut self.system, world)
+    }
+
+    fn dispose(self: Box<Self>, world: &mut World) {
+        System::dispose(self.system, world)
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt::Write;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Resolved dependency graph of the systems of a [`Dispatcher`].
+///
+/// The dependencies of a system contain the explicit dependencies, the
+/// dependencies that are inferred from the accessed resources and the
+/// dependencies caused by barriers. Transitive dependencies are omitted.
+///
+/// Use `to_dot()` to get a graphviz representation of the graph, or
+/// serialize it (using the `serde` feature) to get a machine readable one.
+///
+/// [`Dispatcher`]: struct.Dispatcher.html
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::*;
+/// #
+/// # #[derive(Default)]
+/// # struct Res;
+/// #
+/// # struct Reader;
+/// #
+/// # impl<'a> System<'a> for Reader {
+/// #     type SystemData = Read<'a, Res>;
+/// #
+/// #     fn run(&mut self, _: Self::SystemData) {}
+/// # }
+/// #
+/// # struct Writer;
+/// #
+/// # impl<'a> System<'a> for Writer {
+/// #     type SystemData = Write<'a, Res>;
+/// #
+/// #     fn run(&mut self, _: Self::SystemData) {}
+/// # }
+/// #
+/// # #[tokio::main]
+/// # async fn main() {
+/// let dispatcher = Dispatcher::builder()
+///     .with(Writer, "writer", &[])
+///     .unwrap()
+///     .with(Reader, "reader", &[])
+///     .unwrap()
+///     .build();
+///
+/// let graph = dispatcher.graph();
+///
+/// assert_eq!(graph.systems[1].dependencies, vec!["writer".to_owned()]);
+/// assert!(graph.to_dot().contains("\"writer\" -> \"reader\";"));
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Graph {
+    /// Systems of the dispatcher, in the order they were added.
+    pub systems: Vec<GraphSystem>,
+}
+
+/// A single system of the dependency [`Graph`].
+///
+/// [`Graph`]: struct.Graph.html
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct GraphSystem {
+    /// Name of the system.
+    pub name: String,
+
+    /// Namespace the system was added in, see `Builder::with_namespace`.
+    pub namespace: Option<String>,
+
+    /// Labels of the system, see `Builder::with_labels`.
+    pub labels: Vec<String>,
+
+    /// Names of the resources the system reads.
+    pub reads: Vec<&'static str>,
+
+    /// Names of the resources the system writes.
+    pub writes: Vec<&'static str>,
+
+    /// Names of the systems this system directly depends on.
+    pub dependencies: Vec<String>,
+}
+
+impl Graph {
+    /// Returns the graph in the DOT format of graphviz. The edges point from
+    /// a system to the systems that depend on it. Systems of the same
+    /// namespace are grouped in a cluster.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+
+        dot.push_str("digraph dispatcher {\n");
+
+        let mut namespaces = Vec::new();
+        for system in &self.systems {
+            if !namespaces.contains(&&system.namespace) {
+                namespaces.push(&system.namespace);
+            }
+        }
+
+        for (index, namespace) in namespaces.into_iter().enumerate() {
+            let indent = match namespace {
+                Some(namespace) => {
+                    writeln!(dot, "    subgraph cluster_{} {{", index).unwrap();
+                    writeln!(dot, "        label=\"{}\";", escape(namespace)).unwrap();
+
+                    "        "
+                }
+                None => "    ",
+            };
+
+            for system in &self.systems {
+                if &system.namespace == namespace {
+                    writeln!(dot, "{}{}", indent, system.to_dot_node()).unwrap();
+                }
+            }
+
+            if namespace.is_some() {
+                dot.push_str("    }\n");
+            }
+        }
+
+        for system in &self.systems {
+            for dependency in &system.dependencies {
+                writeln!(
+                    dot,
+                    "    \"{}\" -> \"{}\";",
+                    escape(dependency),
+                    escape(&system.name)
+                )
+                .unwrap();
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+impl GraphSystem {
+    fn to_dot_node(&self) -> String {
+        let mut label = escape(&self.name);
+        if !self.labels.is_empty() {
+            label.push_str("\\nlabels: ");
+            label.push_str(&escape(&self.labels.join(", ")));
+        }
+        if !self.reads.is_empty() {
+            label.push_str("\\nreads: ");
+            label.push_str(&escape(&self.reads.join(", ")));
+        }
+        if !self.writes.is_empty() {
+            label.push_str("\\nwrites: ");
+            label.push_str(&escape(&self.writes.join(", ")));
+        }
+
+        format!("\"{}\" [label=\"{}\"];", escape(&self.name), label)
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,178 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use hashbrown::HashMap;
+
+use crate::util::Instant;
+
+use super::task::SystemInfo;
+
+/// Execution metrics of the systems of a `Dispatcher`.
+///
+/// This is a cheap handle to the metrics that are updated by the dispatcher
+/// with each dispatch. Use `Dispatcher::metrics` to get it. The handle may
+/// also be inserted into the `World` as resource, so systems are able to
+/// read the metrics.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::*;
+/// #
+/// # struct Dummy;
+/// #
+/// # impl<'a> System<'a> for Dummy {
+/// #     type SystemData = ();
+/// #
+/// #     fn run(&mut self, _: ()) {}
+/// # }
+/// #
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with(Dummy, "dummy", &[])
+///     .unwrap()
+///     .build();
+///
+/// world.insert(dispatcher.metrics());
+///
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// let metrics = world.resource::<dispatcher::Metrics>();
+/// assert_eq!(metrics.dispatches(), 1);
+/// assert_eq!(metrics.system("dummy").unwrap().runs, 1);
+/// # }
+/// ```
+#[derive(Default, Clone)]
+pub struct Metrics(Arc<Mutex<MetricsInner>>);
+
+#[derive(Default)]
+struct MetricsInner {
+    dispatches: u64,
+    dispatch_started: Option<Instant>,
+    systems: HashMap<String, SystemMetrics>,
+}
+
+/// Execution metrics of a single system.
+#[derive(Default, Clone, Debug)]
+pub struct SystemMetrics {
+    /// Namespace the system was added in, see `Builder::with_namespace`.
+    pub namespace: Option<String>,
+
+    /// Labels of the system, see `Builder::with_labels`.
+    pub labels: Vec<String>,
+
+    /// Number of times the system was executed.
+    pub runs: u64,
+
+    /// Total time the system was running.
+    pub run_time: Duration,
+
+    /// Time the system was running in the last dispatch.
+    pub last_run_time: Duration,
+
+    /// Total time the system was waiting for its dependencies (and resource
+    /// locks) after a dispatch was started.
+    pub wait_time: Duration,
+
+    /// Time the system was waiting for its dependencies (and resource locks)
+    /// in the last dispatch.
+    pub last_wait_time: Duration,
+}
+
+impl Metrics {
+    /// Returns the number of dispatches that were started.
+    pub fn dispatches(&self) -> u64 {
+        self.0.lock().unwrap().dispatches
+    }
+
+    /// Returns the metrics of the system with the passed name.
+    pub fn system(&self, name: &str) -> Option<SystemMetrics> {
+        self.0.lock().unwrap().systems.get(name).cloned()
+    }
+
+    /// Returns the metrics of all systems that were executed at least once,
+    /// sorted by the name of the system.
+    pub fn systems(&self) -> Vec<(String, SystemMetrics)> {
+        let mut systems = self
+            .0
+            .lock()
+            .unwrap()
+            .systems
+            .iter()
+            .map(|(name, metrics)| (name.clone(), metrics.clone()))
+            .collect::<Vec<_>>();
+
+        systems.sort_by(|a, b| a.0.cmp(&b.0));
+
+        systems
+    }
+
+    /// Returns the metrics of all systems of the passed namespace that were
+    /// executed at least once, sorted by the name of the system.
+    pub fn namespace(&self, namespace: &str) -> Vec<(String, SystemMetrics)> {
+        self.systems()
+            .into_iter()
+            .filter(|(_, metrics)| metrics.namespace.as_deref() == Some(namespace))
+            .collect()
+    }
+
+    /// Resets all metrics.
+    pub fn reset(&self) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner.dispatches = 0;
+        inner.systems.clear();
+    }
+
+    pub(super) fn dispatch_started(&self) {
+        let mut inner = self.0.lock().unwrap();
+
+        inner.dispatches += 1;
+        inner.dispatch_started = Some(Instant::now());
+    }
+
+    pub(super) fn record(&self, info: &SystemInfo, started: Instant, finished: Instant) {
+        let mut inner = self.0.lock().unwrap();
+        let name = info.name.as_str();
+
+        let wait_time = inner
+            .dispatch_started
+            .map(|dispatch_started| started.saturating_duration_since(dispatch_started))
+            .unwrap_or_default();
+        let run_time = finished.saturating_duration_since(started);
+
+        if !inner.systems.contains_key(name) {
+            let metrics = SystemMetrics {
+                namespace: info.namespace.clone(),
+                labels: info.labels.clone(),
+                ..Default::default()
+            };
+
+            inner.systems.insert(name.to_owned(), metrics);
+        }
+
+        let metrics = inner.systems.get_mut(name).unwrap();
+        metrics.runs += 1;
+        metrics.run_time += run_time;
+        metrics.last_run_time = run_time;
+        metrics.wait_time += wait_time;
+        metrics.last_wait_time = wait_time;
+    }
+
+    pub(super) fn remove(&self, name: &str) {
+        self.0.lock().unwrap().systems.remove(name);
+    }
+}
+
+impl SystemMetrics {
+    /// Returns the average time the system was running.
+    pub fn average_run_time(&self) -> Duration {
+        if self.runs == 0 {
+            Duration::default()
+        } else {
+            self.run_time / self.runs as u32
+        }
+    }
+}
@@ -1,30 +1,78 @@
+pub mod auto;
 pub mod builder;
+pub mod bundle;
 pub mod error;
+mod fixed_rate;
+pub mod graph;
+pub mod metrics;
+pub mod panic_policy;
 pub mod run;
+pub mod runtime;
+pub mod shared_world;
+pub mod spawner;
 pub mod task;
 
-pub use builder::Builder;
+pub use builder::{Access, Builder, InferredDependency};
+pub use bundle::Bundle;
 pub use error::Error;
-pub use run::{LocalRun, LocalRunAsync, Run, RunAsync, ThreadRun, ThreadRunAsync};
+pub use graph::{Graph, GraphSystem};
+pub use metrics::{Metrics, SystemMetrics};
+pub use panic_policy::PanicPolicy;
+pub use run::{Condition, LocalRun, LocalRunAsync, Run, RunAsync, ThreadRun, ThreadRunAsync};
+pub use runtime::Runtime;
+pub use shared_world::SharedWorld;
+pub use spawner::{Spawner, TokioSpawner};
 
-use std::cell::RefCell;
-use std::ops::Deref;
-use std::ptr::null;
+use std::mem::take;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
+#[cfg(feature = "time")]
+use std::time::Duration;
 
-use tokio::sync::watch::{Receiver as WatchReceiver, Sender as WatchSender};
+use futures::future::{pending, select, Either, Future, FutureExt, RemoteHandle};
+use tokio::sync::{
+    mpsc::unbounded_channel,
+    watch::{channel, Receiver as WatchReceiver, Sender as WatchSender},
+};
+#[cfg(feature = "time")]
+use tokio::time::sleep;
 
-use crate::world::World;
+use crate::{
+    access::Accessor,
+    resource::ResourceId,
+    system::{AsyncSystem, System},
+    world::{Time, World},
+};
+
+use builder::{spawn, RunType};
+use task::{
+    dispose_seq, execute_seq, initialize_seq, setup_seq, Diagnostics, SystemInfo, Wiring,
+};
 
 type Sender = WatchSender<()>;
 type Receiver = WatchReceiver<()>;
 
+type ControlSender = WatchSender<Wiring>;
+type ControlReceiver = WatchReceiver<Wiring>;
+
 /// The dispatcher struct, allowing
 /// systems to be executed in parallel.
+///
+/// Systems can be added to or removed from an already built dispatcher
+/// using `add()` and `remove()`. The dependencies of all systems are
+/// recalculated in that case.
 pub struct Dispatcher {
     sender: Sender,
+    start: Receiver,
     receivers: Vec<Receiver>,
     world: SharedWorld,
+    diagnostics: Diagnostics,
+    metrics: Metrics,
+    systems: Vec<SystemHandle>,
+    resource_locks: bool,
+    panic_policy: PanicPolicy,
+    runtime: Runtime,
+    spawner: Arc<dyn Spawner>,
 }
 
 impl Dispatcher {
@@ -43,9 +91,124 @@ impl Dispatcher {
     /// and then run thread local systems.
     ///
     /// Please note that this method assumes that no resource
-    /// is currently borrowed. If that's the case, it panics.
+    /// is currently borrowed. If a system is unable to borrow its
+    /// resources, or panics for any other reason, the dispatching is
+    /// finished anyway and the error of the failed system is returned.
+    ///
+    /// The `Time` resource (if present) is updated before any system is
+    /// executed.
+    ///
+    /// The returned future must be driven to completion. Dropping it while
+    /// systems are still running aborts the process, because the systems
+    /// still use the passed `world`. Use `dispatch_with_timeout` or
+    /// `dispatch_with_cancel` to stop a dispatch early.
     pub async fn dispatch(&mut self, world: &World) -> Result<(), Error> {
-        let _guard = self.world.set(world);
+        self.dispatch_with_cancel(world, pending()).await
+    }
+
+    /// Dispatch all the systems like `dispatch`, but cancel the dispatch if
+    /// it is not finished within the passed `timeout`.
+    ///
+    /// The timeout is driven by the timer of the tokio runtime, so the time
+    /// driver of the runtime has to be enabled. See `dispatch_with_cancel`
+    /// for details about the cancellation.
+    ///
+    /// Requires the `time` feature.
+    #[cfg(feature = "time")]
+    pub async fn dispatch_with_timeout(
+        &mut self,
+        world: &World,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        self.dispatch_with_cancel(world, sleep(timeout)).await
+    }
+
+    /// Dispatch all the systems like `dispatch`, but cancel the dispatch as
+    /// soon as the passed `cancel` future is resolved.
+    ///
+    /// If the dispatch is cancelled, the asynchronous systems that are still
+    /// running are aborted (their future is dropped), and all systems that
+    /// did not start yet are skipped. Synchronous systems can not be
+    /// interrupted, the dispatcher waits for them to finish. The dispatch
+    /// then fails with `Error::DispatchCancelled`, naming the systems that
+    /// did not complete.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// #
+    /// # use async_ecs::{dispatcher::Error, *};
+    /// # use futures::future::{pending, BoxFuture, FutureExt};
+    /// # use tokio::time::sleep;
+    /// #
+    /// struct Hanging;
+    ///
+    /// impl<'a> AsyncSystem<'a> for Hanging {
+    ///     type SystemData = ();
+    ///
+    ///     fn run_async(&mut self, (): ()) -> BoxFuture<'a, ()> {
+    ///         pending().boxed()
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut world = World::default();
+    /// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+    ///     .with_async(Hanging, "hanging", &[])
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// let err = dispatcher
+    ///     .dispatch_with_cancel(&world, sleep(Duration::from_millis(10)))
+    ///     .await
+    ///     .unwrap_err();
+    ///
+    /// match err {
+    ///     Error::DispatchCancelled { systems } => assert_eq!(systems, vec!["hanging"]),
+    ///     err => panic!("Unexpected error: {}", err),
+    /// }
+    /// # }
+    /// ```
+    pub async fn dispatch_with_cancel<C>(&mut self, world: &World, cancel: C) -> Result<(), Error>
+    where
+        C: Future<Output = ()>,
+    {
+        if let Some(mut time) = world.try_borrow_mut::<Time>() {
+            time.update();
+        }
+
+        self.dispatch_nested(world, cancel).await
+    }
+
+    /// Dispatches the systems without updating the `Time` resource. Used
+    /// for dispatchers that are nested into another dispatcher.
+    async fn dispatch_nested<C>(&mut self, world: &World, cancel: C) -> Result<(), Error>
+    where
+        C: Future<Output = ()>,
+    {
+        let diagnostics = self.diagnostics.clone();
+        let dispatch = Box::pin(self.dispatch_inner(world));
+
+        match select(dispatch, Box::pin(cancel)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(((), dispatch)) => {
+                diagnostics.cancel();
+
+                dispatch.await
+            }
+        }
+    }
+
+    async fn dispatch_inner(&mut self, world: &World) -> Result<(), Error> {
+        if self.runtime == Runtime::Sequential {
+            return self.dispatch_seq(world).await;
+        }
+
+        let _guard = self.world.set(world, &self.diagnostics);
+
+        self.metrics.dispatch_started();
 
         match self.sender.send(()) {
             Ok(()) => (),
@@ -59,54 +222,1695 @@ impl Dispatcher {
             }
         }
 
+        match self.diagnostics.take_error() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds a new system to the already built dispatcher. The system is
+    /// executed after all systems that were already added, if it depends on
+    /// them (explicitly or by the accessed resources).
+    ///
+    /// Same as `Builder::add`, but the system is set up using the passed
+    /// `world`.
+    pub fn add<S>(
+        &mut self,
+        world: &mut World,
+        mut system: S,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<&mut Self, Error>
+    where
+        S: for<'s> System<'s> + Send + 'static,
+    {
+        let reads = system.accessor().reads();
+        let writes = system.accessor().writes();
+
+        system.setup(world);
+
+        self.insert(name, dependencies, reads, writes, || {
+            RunType::Thread(Box::new(system))
+        })
+    }
+
+    /// Adds a new asynchronous system to the already built dispatcher.
+    ///
+    /// See `add()` for details.
+    pub fn add_async<S>(
+        &mut self,
+        world: &mut World,
+        mut system: S,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<&mut Self, Error>
+    where
+        S: for<'s> AsyncSystem<'s> + Send + 'static,
+    {
+        let reads = system.accessor().reads();
+        let writes = system.accessor().writes();
+
+        system.setup(world);
+
+        self.insert(name, dependencies, reads, writes, || {
+            RunType::ThreadAsync(Box::new(system))
+        })
+    }
+
+    /// Adds a new thread local system to the already built dispatcher.
+    ///
+    /// See `add()` for details.
+    pub fn add_local<S>(
+        &mut self,
+        world: &mut World,
+        mut system: S,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<&mut Self, Error>
+    where
+        S: for<'s> System<'s> + 'static,
+    {
+        let reads = system.accessor().reads();
+        let writes = system.accessor().writes();
+
+        system.setup(world);
+
+        self.insert(name, dependencies, reads, writes, || {
+            RunType::Local(Box::new(system))
+        })
+    }
+
+    /// Adds a new thread local asynchronous system to the already built
+    /// dispatcher.
+    ///
+    /// See `add()` for details.
+    pub fn add_local_async<S>(
+        &mut self,
+        world: &mut World,
+        mut system: S,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<&mut Self, Error>
+    where
+        S: for<'s> AsyncSystem<'s> + 'static,
+    {
+        let reads = system.accessor().reads();
+        let writes = system.accessor().writes();
+
+        system.setup(world);
+
+        self.insert(name, dependencies, reads, writes, || {
+            RunType::LocalAsync(Box::new(system))
+        })
+    }
+
+    /// Adds an already built dispatcher as a single system to this
+    /// dispatcher.
+    ///
+    /// See `Builder::add_dispatcher` for details.
+    pub fn add_dispatcher(
+        &mut self,
+        dispatcher: Dispatcher,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Result<&mut Self, Error> {
+        let (reads, writes) = dispatcher.accessed();
+
+        self.insert(name, dependencies, reads, writes, || {
+            RunType::Dispatcher(Box::new(dispatcher))
+        })
+    }
+
+    /// Removes the system with the given name from the dispatcher and
+    /// disposes it (see `System::dispose`) using the passed `world`.
+    ///
+    /// The dependencies that are inferred from the accessed resources are
+    /// recalculated. If other systems depend explicitly on the system, it is
+    /// not removed and `Error::SystemIsRequired` is returned instead.
+    pub async fn remove(&mut self, world: &mut World, name: &str) -> Result<&mut Self, Error> {
+        let index = self
+            .systems
+            .iter()
+            .position(|system| system.info.name == name)
+            .ok_or_else(|| Error::SystemWasNotFound(name.into()))?;
+
+        let dependents = self
+            .systems
+            .iter()
+            .filter(|system| system.dependencies.iter().any(|d| d == name))
+            .map(|system| system.info.name.clone())
+            .collect::<Vec<_>>();
+        if !dependents.is_empty() {
+            return Err(Error::SystemIsRequired {
+                system: name.into(),
+                dependents,
+            });
+        }
+
+        let system = self.systems.remove(index);
+        if system.barrier {
+            if let Some(next) = self.systems.get_mut(index) {
+                next.barrier = true;
+            }
+        }
+
+        self.metrics.remove(name);
+
+        self.rewire()?;
+        self.dispose(system, world).await;
+
+        match self.diagnostics.take_error() {
+            Some(err) => Err(err),
+            None => Ok(self),
+        }
+    }
+
+    /// Sets up all systems of the dispatcher for the passed `world`.
+    ///
+    /// The systems are set up for the world that is passed to
+    /// `Dispatcher::setup_builder` or `Dispatcher::add`. Use this method to
+    /// prepare another world, so the same dispatcher can be dispatched
+    /// against different worlds (for example one world per match of a game
+    /// server). Systems that cache state related to the world can use the
+    /// `WorldId` to find out which world they are currently serving.
+    ///
+    /// Please note that this calls `System::setup` of each system again,
+    /// which also calls `System::init`.
+    pub async fn setup(&mut self, world: &mut World) -> Result<(), Error> {
+        let wiring = self.wire()?;
+
+        for (system, receivers) in self.systems.iter_mut().zip(wiring) {
+            if let Some(run) = &mut system.run {
+                setup_seq(&system.info, run, world, &self.diagnostics).await;
+
+                continue;
+            }
+
+            let (done, mut finished) = unbounded_channel();
+
+            let _guard = self.world.set_mut(world, &self.diagnostics);
+            let _ = system.control.send(Wiring::Setup(receivers, done));
+            let _ = finished.recv().await;
+        }
+
+        match self.diagnostics.take_error() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Initializes all systems of the dispatcher, by running their
+    /// asynchronous initialization (see `AsyncSystem::init_async`).
+    ///
+    /// The systems are initialized one after another, in the order of their
+    /// dependencies, so a system can rely on the initialization of the
+    /// systems it depends on. Call this once before the first dispatch, if
+    /// some systems have to load assets or open connections before they are
+    /// able to run. Synchronous systems do not have an asynchronous
+    /// initialization and are skipped.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// # use futures::future::{BoxFuture, FutureExt};
+    /// #
+    /// #[derive(Default)]
+    /// struct Assets(Vec<String>);
+    ///
+    /// struct Render {
+    ///     textures: Vec<String>,
+    /// }
+    ///
+    /// impl<'a> AsyncSystem<'a> for Render {
+    ///     type SystemData = Read<'a, Assets>;
+    ///
+    ///     fn init_async<'b>(&'b mut self, world: &'b World) -> BoxFuture<'b, ()> {
+    ///         async move {
+    ///             // load the textures from disk or network
+    ///             self.textures = world.resource::<Assets>().0.clone();
+    ///         }
+    ///         .boxed()
+    ///     }
+    ///
+    ///     fn run_async(&mut self, _assets: Self::SystemData) -> BoxFuture<'a, ()> {
+    ///         assert_eq!(self.textures, vec!["grass.png"]);
+    ///
+    ///         async {}.boxed()
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut world = World::default();
+    /// world.insert(Assets(vec!["grass.png".into()]));
+    ///
+    /// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+    ///     .with_async(Render { textures: Vec::new() }, "render", &[])
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// dispatcher.initialize(&world).await.unwrap();
+    /// dispatcher.dispatch(&world).await.unwrap();
+    /// # }
+    /// ```
+    pub async fn initialize(&mut self, world: &World) -> Result<(), Error> {
+        let wiring = self.wire()?;
+
+        for (system, receivers) in self.systems.iter_mut().zip(wiring) {
+            if let Some(run) = &mut system.run {
+                initialize_seq(&system.info, run, world, &self.diagnostics).await;
+
+                continue;
+            }
+
+            let (done, mut finished) = unbounded_channel();
+
+            let _guard = self.world.set(world, &self.diagnostics);
+            let _ = system.control.send(Wiring::Initialize(receivers, done));
+            let _ = finished.recv().await;
+        }
+
+        match self.diagnostics.take_error() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Shuts the dispatcher down.
+    ///
+    /// All system tasks are signaled to exit and this method waits until
+    /// they are finished. Each system is disposed (see `System::dispose`)
+    /// using the passed `world`, in the order the systems were added.
+    ///
+    /// Dropping the dispatcher also stops the system tasks, but does neither
+    /// wait for them nor dispose the systems.
+    pub async fn shutdown(mut self, world: &mut World) -> Result<(), Error> {
+        for system in take(&mut self.systems) {
+            self.dispose(system, world).await;
+        }
+
+        match self.diagnostics.take_error() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the execution metrics of the systems of this dispatcher.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.clone()
+    }
+
+    /// Returns the resolved dependency graph of the systems of this
+    /// dispatcher. See [`Graph`] for details.
+    ///
+    /// [`Graph`]: graph/struct.Graph.html
+    pub fn graph(&self) -> Graph {
+        let (dependencies, _) = Builder::wire(&self.systems, self.resource_locks)
+            .expect("Dispatcher with invalid dependencies");
+
+        let systems = self
+            .systems
+            .iter()
+            .zip(dependencies)
+            .map(|(system, dependencies)| GraphSystem {
+                name: system.info.name.clone(),
+                namespace: system.info.namespace.clone(),
+                labels: system.info.labels.clone(),
+                reads: system.info.reads.iter().map(ResourceId::name).collect(),
+                writes: system.info.writes.iter().map(ResourceId::name).collect(),
+                dependencies: dependencies
+                    .into_iter()
+                    .map(|index| self.systems[index].info.name.clone())
+                    .collect(),
+            })
+            .collect();
+
+        Graph { systems }
+    }
+
+    /// Returns `true` if a system with the given name is part of the
+    /// dispatcher.
+    pub fn contains(&self, name: &str) -> bool {
+        self.systems.iter().any(|system| system.info.name == name)
+    }
+
+    /// Returns `true` if the systems of this dispatcher acquire the locks of
+    /// their resources before they are executed.
+    fn uses_resource_locks(&self) -> bool {
+        self.resource_locks && self.runtime != Runtime::Sequential
+    }
+
+    /// Returns the resources that are read and written by all systems of
+    /// this dispatcher.
+    fn accessed(&self) -> (Vec<ResourceId>, Vec<ResourceId>) {
+        let reads = self
+            .systems
+            .iter()
+            .flat_map(|system| system.info.reads.iter().cloned())
+            .collect();
+        let writes = self
+            .systems
+            .iter()
+            .flat_map(|system| system.info.writes.iter().cloned())
+            .collect();
+
+        (reads, writes)
+    }
+
+    fn insert<F>(
+        &mut self,
+        name: &str,
+        dependencies: &[&str],
+        mut reads: Vec<ResourceId>,
+        mut writes: Vec<ResourceId>,
+        run: F,
+    ) -> Result<&mut Self, Error>
+    where
+        F: FnOnce() -> RunType,
+    {
+        if self.contains(name) {
+            return Err(Error::NameAlreadyRegistered(name.into()));
+        }
+
+        reads.sort();
+        writes.sort();
+
+        reads.dedup();
+        writes.dedup();
+
+        let info = Arc::new(SystemInfo {
+            name: name.into(),
+            namespace: None,
+            labels: Vec::new(),
+            reads,
+            writes,
+            resource_locks: self.resource_locks,
+            panic_policy: self.panic_policy,
+            conditions: Vec::new(),
+        });
+        let (sender, receiver) = channel(());
+        let (control, control_receiver) = channel(Wiring::Receivers(Vec::new()));
+
+        self.systems.push(SystemHandle {
+            info: info.clone(),
+            dependencies: dependencies.iter().map(|name| (*name).into()).collect(),
+            barrier: false,
+            receiver,
+            control,
+            handle: None,
+            run: None,
+        });
+
+        if let Err(err) = self.rewire() {
+            self.systems.pop();
+
+            return Err(err);
+        }
+
+        if self.runtime == Runtime::Sequential {
+            self.systems.last_mut().unwrap().run = Some(run());
+
+            return Ok(self);
+        }
+
+        let handle = spawn(
+            run(),
+            self.runtime,
+            &*self.spawner,
+            info,
+            sender,
+            control_receiver,
+            self.world.clone(),
+            self.diagnostics.clone(),
+            self.metrics.clone(),
+        );
+
+        self.systems.last_mut().unwrap().handle = Some(handle);
+
+        Ok(self)
+    }
+
+    /// Executes all systems one after another on the current task.
+    async fn dispatch_seq(&mut self, world: &World) -> Result<(), Error> {
+        self.metrics.dispatch_started();
+
+        for system in &mut self.systems {
+            if let Some(run) = &mut system.run {
+                execute_seq(&system.info, run, world, &self.diagnostics, &self.metrics).await;
+            }
+        }
+
+        match self.diagnostics.take_error() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Recalculates the dependencies of all systems and sends the new
+    /// wiring to the tasks of the systems.
+    fn rewire(&mut self) -> Result<(), Error> {
+        let wiring = self.wire()?;
+
+        for (system, receivers) in self.systems.iter().zip(wiring) {
+            let _ = system.control.send(Wiring::Receivers(receivers));
+        }
+
         Ok(())
     }
-}
 
-/// Helper type to share the world parameter passed to `Dispatcher::dispatch`.
-#[derive(Clone)]
-pub struct SharedWorld(Arc<RefCell<*const World>>);
+    /// Computes the receivers each system has to wait for, and updates the
+    /// receivers of the final systems.
+    fn wire(&mut self) -> Result<Vec<Vec<Receiver>>, Error> {
+        let (dependencies, finals) = Builder::wire(&self.systems, self.resource_locks)?;
+
+        // Mark the current state as seen, so the new receivers only notice
+        // the next dispatch.
+        let _ = self.start.changed().now_or_never();
+        for system in &mut self.systems {
+            let _ = system.receiver.changed().now_or_never();
+        }
 
-impl SharedWorld {
-    fn set(&mut self, world: &World) -> WorldGuard {
-        *self.0.borrow_mut() = world as *const _;
+        let wiring = dependencies
+            .into_iter()
+            .map(|dependencies| {
+                if dependencies.is_empty() {
+                    vec![self.start.clone()]
+                } else {
+                    dependencies
+                        .into_iter()
+                        .map(|index| self.systems[index].receiver.clone())
+                        .collect()
+                }
+            })
+            .collect();
 
-        WorldGuard(self)
+        self.receivers = finals
+            .into_iter()
+            .map(|index| self.systems[index].receiver.clone())
+            .collect();
+
+        Ok(wiring)
     }
 
-    fn clear(&mut self) {
-        *self.0.borrow_mut() = null();
+    /// Stops the task of the passed system and disposes the system.
+    async fn dispose(&mut self, mut system: SystemHandle, world: &mut World) {
+        if let Some(run) = system.run.take() {
+            dispose_seq(&system.info, run, world, &self.diagnostics).await;
+
+            return;
+        }
+
+        let _guard = self.world.set_mut(world, &self.diagnostics);
+        let _ = system.control.send(Wiring::Dispose);
+
+        if let Some(handle) = system.handle.take() {
+            let _ = AssertUnwindSafe(handle).catch_unwind().await;
+        }
     }
 }
 
-unsafe impl Send for SharedWorld {}
-unsafe impl Sync for SharedWorld {}
+/// Handle of a system that was added to a `Dispatcher`.
+struct SystemHandle {
+    info: Arc<SystemInfo>,
+    dependencies: Vec<String>,
+    barrier: bool,
+    receiver: Receiver,
+    control: ControlSender,
+    handle: Option<RemoteHandle<()>>,
+    run: Option<RunType>,
+}
 
-impl Default for SharedWorld {
-    fn default() -> Self {
-        Self(Arc::new(RefCell::new(null())))
+impl Drop for SystemHandle {
+    fn drop(&mut self) {
+        // Detach the task, it exits as soon as it notices that the control
+        // channel was closed.
+        if let Some(handle) = self.handle.take() {
+            handle.forget();
+        }
     }
 }
 
-impl Deref for SharedWorld {
-    type Target = World;
+#[cfg(test)]
+mod tests {
+    use std::any::type_name;
+    use std::panic::catch_unwind;
+    use std::rc::Rc;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use futures::{
+        executor::block_on,
+        future::{pending, BoxFuture, FutureExt},
+    };
+    use tokio::task::{yield_now, LocalSet};
+
+    use crate::{
+        access::{LocalRead, LocalWrite, Write},
+        system::{AsyncSystem, RunOnce, System},
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter(usize);
+
+    struct Increment;
 
-    fn deref(&self) -> &Self::Target {
-        let world = self.0.borrow();
+    impl<'a> System<'a> for Increment {
+        type SystemData = Write<'a, Counter>;
 
-        if world.is_null() {
-            panic!("No World assigned!");
+        fn run(&mut self, mut counter: Self::SystemData) {
+            counter.0 += 1;
         }
+    }
+
+    struct Panic;
 
-        unsafe { &**world }
+    impl<'a> System<'a> for Panic {
+        type SystemData = ();
+
+        fn run(&mut self, _: Self::SystemData) {
+            panic!("Something went wrong");
+        }
     }
-}
 
-/// Guard to share the world parameter passed to `Dispatcher::dispatch`.
-struct WorldGuard<'a>(&'a mut SharedWorld);
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
 
-impl Drop for WorldGuard<'_> {
-    fn drop(&mut self) {
-        self.0.clear()
+    struct Append(&'static str);
+
+    impl<'a> System<'a> for Append {
+        type SystemData = Write<'a, Log>;
+
+        fn run(&mut self, mut log: Self::SystemData) {
+            log.0.push(self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn add_and_remove_systems() {
+        let mut world = World::default();
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with(Append("a"), "a", &[])
+            .unwrap()
+            .with_barrier()
+            .with(Append("b"), "b", &["a"])
+            .unwrap()
+            .build();
+
+        dispatcher.dispatch(&world).await.unwrap();
+
+        dispatcher
+            .add(&mut world, Append("c"), "c", &["b"])
+            .unwrap()
+            .add(&mut world, Increment, "increment", &[])
+            .unwrap();
+
+        assert!(matches!(
+            dispatcher.add(&mut world, Append("c"), "c", &[]),
+            Err(Error::NameAlreadyRegistered(_))
+        ));
+        assert!(matches!(
+            dispatcher.add(&mut world, Append("d"), "d", &["unknown"]),
+            Err(Error::DependencyWasNotFound { system, dependency })
+                if system == "d" && dependency == "unknown"
+        ));
+
+        dispatcher.dispatch(&world).await.unwrap();
+
+        assert!(matches!(
+            dispatcher.remove(&mut world, "b").await,
+            Err(Error::SystemIsRequired { system, dependents })
+                if system == "b" && dependents == vec!["c"]
+        ));
+        assert!(dispatcher.contains("b"));
+
+        dispatcher.remove(&mut world, "c").await.unwrap();
+        dispatcher.remove(&mut world, "b").await.unwrap();
+        assert!(!dispatcher.contains("b"));
+        assert!(matches!(
+            dispatcher.remove(&mut world, "b").await,
+            Err(Error::SystemWasNotFound(_))
+        ));
+
+        dispatcher.dispatch(&world).await.unwrap();
+
+        dispatcher.remove(&mut world, "a").await.unwrap();
+        dispatcher.dispatch(&world).await.unwrap();
+
+        dispatcher.add(&mut world, Append("a"), "a", &[]).unwrap();
+        dispatcher.dispatch(&world).await.unwrap();
+
+        // removed systems are disposed
+        dispatcher
+            .add(&mut world, Disposable("d"), "d", &[])
+            .unwrap();
+        dispatcher.remove(&mut world, "d").await.unwrap();
+
+        assert_eq!(
+            world.resource::<Log>().0,
+            vec!["a", "b", "a", "b", "c", "a", "a", "d"]
+        );
+        assert_eq!(world.resource::<Counter>().0, 4);
+    }
+
+    #[tokio::test]
+    async fn run_once() {
+        let mut world = World::default();
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with(RunOnce::new(Append("once")), "once", &[])
+            .unwrap()
+            .with(Append("always"), "always", &[])
+            .unwrap()
+            .build();
+
+        for _ in 0..3 {
+            dispatcher.dispatch(&world).await.unwrap();
+        }
+
+        assert_eq!(
+            world.resource::<Log>().0,
+            vec!["once", "always", "always", "always"]
+        );
+    }
+
+    struct SlowIncrement;
+
+    impl<'a> AsyncSystem<'a> for SlowIncrement {
+        type SystemData = Write<'a, Counter>;
+
+        fn run_async(&mut self, mut counter: Self::SystemData) -> BoxFuture<'a, ()> {
+            async move {
+                yield_now().await;
+
+                counter.0 += 1;
+            }
+            .boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn resource_locks() {
+        let mut world = World::default();
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with_resource_locks()
+            .with_async(SlowIncrement, "slow_1", &[])
+            .unwrap()
+            .with_async(SlowIncrement, "slow_2", &[])
+            .unwrap()
+            .with(Increment, "increment", &[])
+            .unwrap()
+            .build();
+
+        // all systems are started at once, because they do not depend on
+        // each other, and wait for the lock of the counter instead
+        assert_eq!(dispatcher.receivers.len(), 3);
+
+        for _ in 0..10 {
+            dispatcher.dispatch(&world).await.unwrap();
+        }
+
+        dispatcher
+            .add_async(&mut world, SlowIncrement, "slow_3", &[])
+            .unwrap();
+        dispatcher.dispatch(&world).await.unwrap();
+
+        assert_eq!(world.resource::<Counter>().0, 34);
+    }
+
+    #[tokio::test]
+    async fn nested_resource_locks() {
+        LocalSet::new()
+            .run_until(async {
+                let mut world = World::default();
+
+                let nested = Dispatcher::setup_builder(&mut world)
+                    .with_resource_locks()
+                    .with_async(SlowIncrement, "slow_1", &[])
+                    .unwrap()
+                    .with(Increment, "increment", &[])
+                    .unwrap()
+                    .build();
+
+                let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                    .with_resource_locks()
+                    .with_async(SlowIncrement, "slow_2", &[])
+                    .unwrap()
+                    .with_dispatcher(nested, "nested", &[])
+                    .unwrap()
+                    .build();
+
+                // the nested systems acquire the locks on their own, the
+                // nested dispatcher itself does not hold them
+                for _ in 0..10 {
+                    dispatcher.dispatch(&world).await.unwrap();
+                }
+
+                assert_eq!(world.resource::<Counter>().0, 30);
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn local_resource_locks() {
+        struct SlowPush;
+
+        impl<'a> AsyncSystem<'a> for SlowPush {
+            type SystemData = LocalWrite<'a, Vec<usize>>;
+
+            fn run_async(&mut self, mut frames: Self::SystemData) -> BoxFuture<'a, ()> {
+                async move {
+                    yield_now().await;
+
+                    let len = frames.len();
+                    frames.push(len);
+                }
+                .boxed()
+            }
+        }
+
+        LocalSet::new()
+            .run_until(async {
+                let mut world = World::default();
+                world.insert_local(Vec::<usize>::new());
+
+                let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                    .with_resource_locks()
+                    .with_async(SlowPush, "slow_1", &[])
+                    .unwrap()
+                    .with_async(SlowPush, "slow_2", &[])
+                    .unwrap()
+                    .build();
+
+                for _ in 0..5 {
+                    dispatcher.dispatch(&world).await.unwrap();
+                }
+
+                assert_eq!(
+                    *world.local_resource::<Vec<usize>>(),
+                    (0..10).collect::<Vec<_>>()
+                );
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn nested_local_resources() {
+        struct Window(Rc<usize>);
+
+        struct Present;
+
+        impl<'a> System<'a> for Present {
+            type SystemData = (LocalRead<'a, Window>, Write<'a, Counter>);
+
+            fn run(&mut self, (window, mut counter): Self::SystemData) {
+                counter.0 += *window.0;
+            }
+        }
+
+        LocalSet::new()
+            .run_until(async {
+                let mut world = World::default();
+                world.insert_local(Window(Rc::new(2)));
+
+                let nested = Dispatcher::setup_builder(&mut world)
+                    .with(Present, "present", &[])
+                    .unwrap()
+                    .build();
+
+                let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                    .with(Increment, "increment", &[])
+                    .unwrap()
+                    .with_dispatcher(nested, "nested", &[])
+                    .unwrap()
+                    .build();
+
+                dispatcher.dispatch(&world).await.unwrap();
+                dispatcher.dispatch(&world).await.unwrap();
+
+                assert_eq!(world.resource::<Counter>().0, 6);
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn graph() {
+        let mut world = World::default();
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with(Increment, "a", &[])
+            .unwrap()
+            .with(Append("b"), "b", &[])
+            .unwrap()
+            .with(Increment, "c", &[])
+            .unwrap()
+            .with_barrier()
+            .with(Panic, "d", &[])
+            .unwrap()
+            .build();
+
+        let graph = dispatcher.graph();
+        let dependencies = graph
+            .systems
+            .iter()
+            .map(|system| (system.name.as_str(), system.dependencies.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            dependencies,
+            vec![
+                ("a", vec![]),
+                ("b", vec![]),
+                ("c", vec!["a".to_owned()]),
+                ("d", vec!["b".to_owned(), "c".to_owned()]),
+            ]
+        );
+        assert_eq!(graph.systems[0].writes, vec![type_name::<Counter>()]);
+        assert!(graph.to_dot().contains("\"a\" -> \"c\";"));
+
+        dispatcher.remove(&mut world, "a").await.unwrap();
+
+        assert!(dispatcher.graph().systems[1].dependencies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn namespaces() {
+        let mut world = World::default();
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with_namespace("physics")
+            .with(Increment, "increment", &[])
+            .unwrap()
+            .with_labels("increment", &["counter"])
+            .unwrap()
+            .with_namespace("")
+            .with(Append("a"), "append", &["increment"])
+            .unwrap()
+            .build();
+
+        dispatcher.dispatch(&world).await.unwrap();
+
+        let physics = dispatcher.metrics().namespace("physics");
+        assert_eq!(physics.len(), 1);
+        assert_eq!(physics[0].0, "increment");
+        assert_eq!(physics[0].1.labels, vec!["counter".to_owned()]);
+
+        let dot = dispatcher.graph().to_dot();
+        assert!(dot.contains("subgraph cluster_0 {\n        label=\"physics\";"));
+        assert!(dot.contains("\"increment\" [label=\"increment\\nlabels: counter"));
+        assert!(dot.contains("\"increment\" -> \"append\";"));
+    }
+
+    #[tokio::test]
+    async fn metrics() {
+        let mut world = World::default();
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with(Increment, "increment", &[])
+            .unwrap()
+            .with_async(SlowIncrement, "slow", &[])
+            .unwrap()
+            .build();
+
+        let metrics = dispatcher.metrics();
+
+        for _ in 0..3 {
+            dispatcher.dispatch(&world).await.unwrap();
+        }
+
+        assert_eq!(metrics.dispatches(), 3);
+
+        let names = metrics
+            .systems()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["increment", "slow"]);
+
+        let slow = metrics.system("slow").unwrap();
+        assert_eq!(slow.runs, 3);
+        assert!(slow.wait_time >= slow.last_wait_time);
+        assert!(slow.run_time >= slow.last_run_time);
+
+        dispatcher.remove(&mut world, "increment").await.unwrap();
+        assert!(metrics.system("increment").is_none());
+
+        metrics.reset();
+        assert_eq!(metrics.dispatches(), 0);
+        assert!(metrics.systems().is_empty());
+    }
+
+    struct Disposable(&'static str);
+
+    impl<'a> System<'a> for Disposable {
+        type SystemData = Write<'a, Counter>;
+
+        fn run(&mut self, mut counter: Self::SystemData) {
+            counter.0 += 1;
+        }
+
+        fn dispose(self, world: &mut World) {
+            world.resource_mut::<Log>().0.push(self.0);
+        }
+    }
+
+    impl<'a> AsyncSystem<'a> for Disposable {
+        type SystemData = Write<'a, Counter>;
+
+        fn run_async(&mut self, mut counter: Self::SystemData) -> BoxFuture<'a, ()> {
+            counter.0 += 1;
+
+            async move {}.boxed()
+        }
+
+        fn dispose(self, world: &mut World) {
+            world.resource_mut::<Log>().0.push(self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn shutdown() {
+        let mut world = World::default();
+        world.insert(Log::default());
+
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with(Disposable("a"), "a", &[])
+            .unwrap()
+            .with_async(Disposable("b"), "b", &[])
+            .unwrap()
+            .build();
+
+        dispatcher
+            .add(&mut world, Disposable("c"), "c", &[])
+            .unwrap();
+
+        dispatcher.dispatch(&world).await.unwrap();
+        dispatcher.shutdown(&mut world).await.unwrap();
+
+        assert_eq!(world.resource::<Counter>().0, 3);
+        assert_eq!(world.resource::<Log>().0, vec!["a", "b", "c"]);
+    }
+
+    struct Loader {
+        name: &'static str,
+        loaded: bool,
+    }
+
+    impl<'a> AsyncSystem<'a> for Loader {
+        type SystemData = Write<'a, Log>;
+
+        fn init_async<'b>(&'b mut self, world: &'b World) -> BoxFuture<'b, ()> {
+            async move {
+                tokio::task::yield_now().await;
+
+                if self.name == "panic" {
+                    panic!("Unable to load");
+                }
+
+                world.resource_mut::<Log>().0.push(self.name);
+                self.loaded = true;
+            }
+            .boxed()
+        }
+
+        fn run_async(&mut self, mut log: Self::SystemData) -> BoxFuture<'a, ()> {
+            assert!(self.loaded);
+
+            log.0.push("run");
+
+            async move {}.boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn initialize() {
+        for runtime in [Runtime::Parallel, Runtime::Sequential] {
+            let loader = |name| Loader {
+                name,
+                loaded: false,
+            };
+
+            let mut world = World::default();
+            let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                .with_runtime(runtime)
+                .with_async(loader("a"), "a", &[])
+                .unwrap()
+                .with(Append("b"), "b", &["a"])
+                .unwrap()
+                .with_async(loader("c"), "c", &["b"])
+                .unwrap()
+                .build();
+
+            dispatcher.initialize(&world).await.unwrap();
+            assert_eq!(world.resource::<Log>().0, vec!["a", "c"]);
+
+            dispatcher.dispatch(&world).await.unwrap();
+            assert_eq!(world.resource::<Log>().0, vec!["a", "c", "run", "b", "run"]);
+
+            dispatcher
+                .add_async(&mut world, loader("panic"), "panic", &[])
+                .unwrap();
+
+            match dispatcher.initialize(&world).await {
+                Err(Error::SystemPanicked { system, message }) => {
+                    assert_eq!(system, "panic");
+                    assert_eq!(message, "Unable to load");
+                }
+                r => panic!("Unexpected result: {:?}", r),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn sequential() {
+        let mut world = World::default();
+        world.insert(Log::default());
+
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with_sequential()
+            .with(Append("a"), "a", &[])
+            .unwrap()
+            .with_local(Append("b"), "b", &[])
+            .unwrap()
+            .with_async(Disposable("c"), "c", &[])
+            .unwrap()
+            .with(Panic, "panic", &[])
+            .unwrap()
+            .build();
+
+        dispatcher
+            .add_local_async(&mut world, Disposable("d"), "d", &[])
+            .unwrap();
+
+        assert!(dispatcher.dispatch(&world).await.is_err());
+        assert_eq!(world.resource::<Log>().0, vec!["a", "b"]);
+        assert_eq!(world.resource::<Counter>().0, 2);
+        assert_eq!(dispatcher.metrics().dispatches(), 1);
+
+        dispatcher.remove(&mut world, "panic").await.unwrap();
+        dispatcher.dispatch(&world).await.unwrap();
+
+        assert_eq!(world.resource::<Counter>().0, 4);
+
+        dispatcher.shutdown(&mut world).await.unwrap();
+
+        assert_eq!(
+            world.resource::<Log>().0,
+            vec!["a", "b", "a", "b", "c", "d"]
+        );
+    }
+
+    #[test]
+    fn sequential_without_runtime() {
+        let mut world = World::default();
+        world.insert(Log::default());
+
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with_sequential()
+            .with(Append("a"), "a", &[])
+            .unwrap()
+            .with_local(Append("b"), "b", &["a"])
+            .unwrap()
+            .with_async(Disposable("c"), "c", &[])
+            .unwrap()
+            .build();
+
+        block_on(async {
+            dispatcher.dispatch(&world).await.unwrap();
+            dispatcher.dispatch(&world).await.unwrap();
+        });
+
+        assert_eq!(world.resource::<Log>().0, vec!["a", "b", "a", "b"]);
+        assert_eq!(world.resource::<Counter>().0, 2);
+        assert_eq!(world.resource::<Time>().frame(), 2);
+
+        block_on(dispatcher.shutdown(&mut world)).unwrap();
+
+        assert_eq!(world.resource::<Log>().0, vec!["a", "b", "a", "b", "c"]);
+    }
+
+    struct ThreadName;
+
+    impl<'a> System<'a> for ThreadName {
+        type SystemData = Write<'a, Log>;
+
+        fn run(&mut self, mut log: Self::SystemData) {
+            let name = std::thread::current().name().map(String::from);
+
+            log.0.push(if name.as_deref() == Some("dedicated") {
+                "dedicated"
+            } else {
+                "other"
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn dedicated_runtime() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("dedicated")
+            .build()
+            .unwrap();
+
+        let mut world = World::default();
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with_spawner(runtime.handle().clone())
+            .with(ThreadName, "thread_name", &[])
+            .unwrap()
+            .build();
+
+        dispatcher.dispatch(&world).await.unwrap();
+        dispatcher.shutdown(&mut world).await.unwrap();
+
+        assert_eq!(world.resource::<Log>().0, vec!["dedicated"]);
+
+        runtime.shutdown_background();
+    }
+
+    #[tokio::test]
+    async fn multiple_worlds() {
+        use crate::world::WorldId;
+
+        struct Worlds(Vec<WorldId>);
+
+        impl<'a> System<'a> for Worlds {
+            type SystemData = (WorldId, Write<'a, Counter>);
+
+            fn run(&mut self, (id, mut counter): Self::SystemData) {
+                self.0.push(id);
+
+                counter.0 = self.0.iter().filter(|i| **i == id).count();
+            }
+        }
+
+        LocalSet::new()
+            .run_until(async {
+                for runtime in [Runtime::Parallel, Runtime::Sequential] {
+                    let mut a = World::default();
+                    let mut b = World::default();
+
+                    let nested = Dispatcher::setup_builder(&mut a)
+                        .with_runtime(runtime)
+                        .with(Append("nested"), "nested", &[])
+                        .unwrap()
+                        .build();
+
+                    let mut dispatcher = Dispatcher::setup_builder(&mut a)
+                        .with_runtime(runtime)
+                        .with(Append("a"), "a", &[])
+                        .unwrap()
+                        .with(Worlds(Vec::new()), "worlds", &["a"])
+                        .unwrap()
+                        .with_dispatcher(nested, "dispatcher", &["worlds"])
+                        .unwrap()
+                        .build();
+
+                    assert!(!b.contains::<Log>());
+
+                    dispatcher.setup(&mut b).await.unwrap();
+
+                    assert!(b.resource::<Log>().0.is_empty());
+                    assert_eq!(b.resource::<Counter>().0, 0);
+
+                    dispatcher.dispatch(&a).await.unwrap();
+                    dispatcher.dispatch(&b).await.unwrap();
+                    dispatcher.dispatch(&a).await.unwrap();
+
+                    assert_eq!(a.resource::<Log>().0, vec!["a", "nested", "a", "nested"]);
+                    assert_eq!(b.resource::<Log>().0, vec!["a", "nested"]);
+                    assert_eq!(a.resource::<Counter>().0, 2);
+                    assert_eq!(b.resource::<Counter>().0, 1);
+                }
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn local_runtime() {
+        LocalSet::new()
+            .run_until(async {
+                let mut world = World::default();
+                world.insert(Log::default());
+
+                let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                    .with_runtime(Runtime::Local)
+                    .with(Append("a"), "a", &[])
+                    .unwrap()
+                    .with_local(Append("b"), "b", &["a"])
+                    .unwrap()
+                    .with_async(Disposable("c"), "c", &["b"])
+                    .unwrap()
+                    .build();
+
+                dispatcher.dispatch(&world).await.unwrap();
+
+                assert_eq!(world.resource::<Log>().0, vec!["a", "b"]);
+                assert_eq!(world.resource::<Counter>().0, 1);
+
+                dispatcher.shutdown(&mut world).await.unwrap();
+
+                assert_eq!(world.resource::<Log>().0, vec!["a", "b", "c"]);
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn local_resources() {
+        struct Window(Rc<usize>);
+
+        struct Present;
+
+        impl<'a> System<'a> for Present {
+            type SystemData = (LocalRead<'a, Window>, LocalWrite<'a, Vec<usize>>);
+
+            fn run(&mut self, (window, mut frames): Self::SystemData) {
+                frames.push(*window.0);
+            }
+        }
+
+        LocalSet::new()
+            .run_until(async {
+                let mut world = World::default();
+                world.insert_local(Window(Rc::new(1)));
+                world.insert_local(Vec::<usize>::new());
+
+                let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                    .with(Increment, "increment", &[])
+                    .unwrap()
+                    .with(Present, "present", &[])
+                    .unwrap()
+                    .build();
+
+                dispatcher.dispatch(&world).await.unwrap();
+                dispatcher.dispatch(&world).await.unwrap();
+
+                assert_eq!(world.resource::<Counter>().0, 2);
+                assert_eq!(*world.local_resource::<Vec<usize>>(), vec![1, 1]);
+                assert_eq!(world.remove_local::<Vec<usize>>(), Some(vec![1, 1]));
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn nested_dispatcher() {
+        LocalSet::new()
+            .run_until(async {
+                let mut world = World::default();
+                world.insert(Log::default());
+
+                let nested = Dispatcher::setup_builder(&mut world)
+                    .with(Append("a"), "a", &[])
+                    .unwrap()
+                    .with(Disposable("b"), "b", &[])
+                    .unwrap()
+                    .build();
+
+                let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                    .with(Increment, "increment", &[])
+                    .unwrap()
+                    .with_dispatcher(nested, "nested", &[])
+                    .unwrap()
+                    .with(Append("c"), "c", &[])
+                    .unwrap()
+                    .build();
+
+                let graph = dispatcher.graph();
+                let nested = &graph.systems[1];
+
+                assert_eq!(nested.name, "nested");
+                assert_eq!(nested.dependencies, vec!["increment"]);
+                assert_eq!(nested.writes.len(), 2);
+                assert!(nested.writes.contains(&type_name::<Counter>()));
+                assert!(nested.writes.contains(&type_name::<Log>()));
+                assert_eq!(graph.systems[2].dependencies, vec!["nested"]);
+
+                dispatcher.dispatch(&world).await.unwrap();
+                dispatcher.dispatch(&world).await.unwrap();
+
+                assert_eq!(world.resource::<Log>().0, vec!["a", "c", "a", "c"]);
+                assert_eq!(world.resource::<Counter>().0, 4);
+                assert_eq!(dispatcher.metrics().system("nested").unwrap().runs, 2);
+
+                dispatcher.shutdown(&mut world).await.unwrap();
+
+                assert_eq!(world.resource::<Log>().0, vec!["a", "c", "a", "c", "b"]);
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn nested_dispatcher_error() {
+        let mut world = World::default();
+
+        let nested = Dispatcher::setup_builder(&mut world)
+            .with_sequential()
+            .with(Panic, "panic", &[])
+            .unwrap()
+            .build();
+
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with_sequential()
+            .with_dispatcher(nested, "nested", &[])
+            .unwrap()
+            .build();
+
+        match dispatcher.dispatch(&world).await {
+            Err(Error::SystemPanicked { system, .. }) => assert_eq!(system, "panic"),
+            _ => panic!("Expected the error of the nested dispatcher"),
+        }
+    }
+
+    #[tokio::test]
+    async fn borrow_conflict() {
+        let mut world = World::default();
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with(Increment, "increment", &[])
+            .unwrap()
+            .build();
+
+        {
+            let _counter = world.resource::<Counter>();
+
+            match dispatcher.dispatch(&world).await {
+                Err(Error::BorrowConflict {
+                    system,
+                    resource,
+                    conflicts,
+                }) => {
+                    assert_eq!(system, "increment");
+                    assert!(resource.ends_with("Counter"));
+                    assert!(conflicts.is_empty());
+                }
+                r => panic!("Unexpected result: {:?}", r),
+            }
+        }
+
+        dispatcher.dispatch(&world).await.unwrap();
+
+        assert_eq!(world.resource::<Counter>().0, 1);
+    }
+
+    #[tokio::test]
+    async fn stale_borrow_conflict() {
+        let mut world = World::default();
+        world.insert(Counter::default());
+
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with_sequential()
+            .with(Panic, "panic", &[])
+            .unwrap()
+            .build();
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _read = world.resource::<Counter>();
+            let _write = world.resource_mut::<Counter>();
+        }));
+        assert!(result.is_err());
+
+        match dispatcher.dispatch(&world).await {
+            Err(Error::SystemPanicked { system, .. }) => assert_eq!(system, "panic"),
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn system_panicked() {
+        let mut world = World::default();
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with(Panic, "panic", &[])
+            .unwrap()
+            .with(Increment, "increment", &[])
+            .unwrap()
+            .build();
+
+        for _ in 0..2 {
+            match dispatcher.dispatch(&world).await {
+                Err(Error::SystemPanicked { system, message }) => {
+                    assert_eq!(system, "panic");
+                    assert_eq!(message, "Something went wrong");
+                }
+                r => panic!("Unexpected result: {:?}", r),
+            }
+        }
+
+        assert_eq!(world.resource::<Counter>().0, 2);
+    }
+
+    #[tokio::test]
+    async fn panic_policy() {
+        /// Panics until it was executed the passed number of times.
+        struct Flaky(usize);
+
+        impl<'a> System<'a> for Flaky {
+            type SystemData = Write<'a, Log>;
+
+            fn run(&mut self, mut log: Self::SystemData) {
+                log.0.push("flaky");
+
+                if self.0 > 0 {
+                    self.0 -= 1;
+
+                    panic!("Not yet");
+                }
+            }
+        }
+
+        let policies = [
+            (PanicPolicy::Skip, 1, true),
+            (PanicPolicy::Restart { attempts: 3 }, 3, true),
+            (PanicPolicy::Restart { attempts: 2 }, 2, false),
+        ];
+
+        for (policy, runs, success) in policies {
+            for runtime in [Runtime::Parallel, Runtime::Sequential] {
+                let mut world = World::default();
+                let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                    .with_runtime(runtime)
+                    .with_panic_policy(policy)
+                    .with(Flaky(2), "flaky", &[])
+                    .unwrap()
+                    .with(Increment, "increment", &["flaky"])
+                    .unwrap()
+                    .build();
+
+                match dispatcher.dispatch(&world).await {
+                    Ok(()) => assert!(success),
+                    Err(Error::SystemPanicked { system, message }) => {
+                        assert!(!success);
+                        assert_eq!(system, "flaky");
+                        assert_eq!(message, "Not yet");
+                    }
+                    r => panic!("Unexpected result: {:?}", r),
+                }
+
+                assert_eq!(world.resource::<Log>().0.len(), runs);
+                assert_eq!(world.resource::<Counter>().0, 1);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn run_conditions() {
+        struct Enabled(bool);
+
+        for runtime in [Runtime::Parallel, Runtime::Sequential] {
+            let mut world = World::default();
+            world.register_resource(Enabled(false));
+
+            let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                .with_runtime(runtime)
+                .with(Append("a"), "a", &[])
+                .unwrap()
+                .with(Append("b"), "b", &[])
+                .unwrap()
+                .with_condition("a", |world| world.resource::<Enabled>().0)
+                .unwrap()
+                .build();
+
+            dispatcher.dispatch(&world).await.unwrap();
+            world.resource_mut::<Enabled>().0 = true;
+            dispatcher.dispatch(&world).await.unwrap();
+
+            assert_eq!(world.resource::<Log>().0, vec!["b", "a", "b"]);
+        }
+
+        assert!(matches!(
+            Dispatcher::builder().with_condition("unknown", |_| true),
+            Err(Error::SystemWasNotFound(name)) if name == "unknown"
+        ));
+    }
+
+    #[tokio::test]
+    async fn fixed_rate() {
+        for runtime in [Runtime::Parallel, Runtime::Sequential] {
+            let mut world = World::default();
+            let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                .with_runtime(runtime)
+                .with_fixed_rate(Increment, "increment", &[], Duration::from_millis(10))
+                .unwrap()
+                .build();
+
+            assert!(
+                dispatcher.graph().systems[0]
+                    .reads
+                    .contains(&type_name::<Time>())
+            );
+
+            // the accumulation starts with the first dispatch
+            dispatcher.dispatch(&world).await.unwrap();
+            assert_eq!(world.resource::<Counter>().0, 0);
+        }
+
+        let mut world = World::default();
+        world.insert(Counter::default());
+
+        let mut system = fixed_rate::FixedRate::new(Increment, Duration::from_millis(10));
+        let mut advance = |delta| {
+            world
+                .resource_mut::<Time>()
+                .advance(Duration::from_millis(delta));
+            Run::run(&mut system, &world);
+
+            world.resource::<Counter>().0
+        };
+
+        assert_eq!(advance(5), 0);
+        assert_eq!(advance(30), 3);
+
+        // the system is executed at most a few times, even if it falls behind
+        assert_eq!(advance(200), 11);
+        assert_eq!(advance(5), 12);
+        assert_eq!(advance(5), 13);
+
+        // the time of updates by other dispatchers is accumulated as well
+        world
+            .resource_mut::<Time>()
+            .advance(Duration::from_millis(10));
+        Run::run(&mut system, &world);
+        assert_eq!(world.resource::<Counter>().0, 14);
+    }
+
+    struct HangOnce(bool);
+
+    impl<'a> AsyncSystem<'a> for HangOnce {
+        type SystemData = ();
+
+        fn run_async(&mut self, _: Self::SystemData) -> BoxFuture<'a, ()> {
+            if take(&mut self.0) {
+                pending().boxed()
+            } else {
+                async {}.boxed()
+            }
+        }
+    }
+
+    #[cfg(feature = "time")]
+    #[tokio::test]
+    async fn dispatch_timeout() {
+        for runtime in [Runtime::Parallel, Runtime::Sequential] {
+            let mut world = World::default();
+            let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                .with_runtime(runtime)
+                .with_async(HangOnce(true), "hang", &[])
+                .unwrap()
+                .with(Increment, "increment", &["hang"])
+                .unwrap()
+                .build();
+
+            // the first run of `hang` never finishes, so any timeout expires
+            let timeout = Duration::from_millis(10);
+
+            assert!(matches!(
+                dispatcher.dispatch_with_timeout(&world, timeout).await,
+                Err(Error::DispatchCancelled { systems }) if systems == ["hang", "increment"]
+            ));
+            assert_eq!(world.resource::<Counter>().0, 0);
+
+            // generous margin, so the dispatch finishes even on a loaded machine
+            let timeout = Duration::from_secs(60);
+
+            dispatcher
+                .dispatch_with_timeout(&world, timeout)
+                .await
+                .unwrap();
+            assert_eq!(world.resource::<Counter>().0, 1);
+        }
+    }
+
+    /// Drops a dispatch while an asynchronous system is still running on a
+    /// multi threaded runtime.
+    fn drop_dispatch_in_flight() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let mut world = World::default();
+            let mut dispatcher = Dispatcher::setup_builder(&mut world)
+                .with_async(HangOnce(true), "hang", &[])
+                .unwrap()
+                .build();
+
+            let timeout = Duration::from_millis(100);
+            let result = tokio::time::timeout(timeout, dispatcher.dispatch(&world)).await;
+
+            assert!(result.is_err());
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "debug-world")]
+    #[should_panic(expected = "World was released while it was still used by a system!")]
+    fn drop_in_flight_dispatch() {
+        drop_dispatch_in_flight();
+    }
+
+    #[test]
+    #[cfg(not(feature = "debug-world"))]
+    fn drop_in_flight_dispatch() {
+        use std::env::{current_exe, var_os};
+        use std::process::Command;
+
+        // The process is aborted, so the dispatch is dropped in a child
+        // process that executes only this test.
+        if var_os("ASYNC_ECS_DROP_IN_FLIGHT").is_some() {
+            return drop_dispatch_in_flight();
+        }
+
+        let output = Command::new(current_exe().unwrap())
+            .args(&["--exact", "--nocapture", "dispatcher::tests::drop_in_flight_dispatch"])
+            .env("ASYNC_ECS_DROP_IN_FLIGHT", "1")
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr)
+            .contains("World was released while it was still used by a system!"));
+    }
+
+    #[tokio::test]
+    async fn time() {
+        let mut world = World::default();
+        let nested = Dispatcher::setup_builder(&mut world)
+            .with_sequential()
+            .with(Increment, "increment", &[])
+            .unwrap()
+            .build();
+        let mut dispatcher = Dispatcher::builder()
+            .with_sequential()
+            .with_dispatcher(nested, "nested", &[])
+            .unwrap()
+            .build();
+
+        dispatcher.dispatch(&world).await.unwrap();
+        sleep(Duration::from_millis(5));
+        dispatcher.dispatch(&world).await.unwrap();
+
+        let time = world.resource::<Time>();
+
+        assert_eq!(world.resource::<Counter>().0, 2);
+        assert_eq!(time.frame(), 2);
+        assert!(time.delta() >= Duration::from_millis(5));
+        assert_eq!(time.elapsed(), time.delta());
     }
 }
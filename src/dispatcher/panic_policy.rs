@@ -0,0 +1,25 @@
+/// Defines how the `Dispatcher` handles a panicking system.
+///
+/// Use `Builder::with_panic_policy` to select the panic policy of a
+/// dispatcher. The policy only applies to panics of the systems and their
+/// run conditions. Borrow conflicts (see `Error::BorrowConflict`) are caused
+/// by a wrong setup of the dispatcher and always fail the dispatch.
+#[derive(Default, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PanicPolicy {
+    /// The dispatch is completed, but fails with `Error::SystemPanicked`,
+    /// naming the system that panicked.
+    #[default]
+    Fail,
+
+    /// The panic is logged and the dispatch continues like the system was
+    /// executed successfully.
+    Skip,
+
+    /// The system is executed again on its task, up to the passed number of
+    /// attempts. If all attempts panic, the dispatch fails with
+    /// `Error::SystemPanicked`.
+    ///
+    /// Please note that the system is not reset, so it should be able to
+    /// recover from a panic that occurred in the middle of its execution.
+    Restart { attempts: usize },
+}
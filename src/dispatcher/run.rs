@@ -3,6 +3,7 @@ use std::ops::Deref;
 use futures::future::BoxFuture;
 
 use crate::{
+    resource::BorrowConflict,
     system::{AsyncSystem, DynamicSystemData, System},
     world::World,
 };
@@ -13,6 +14,9 @@ pub type LocalRun = Box<dyn for<'a> Run<'a>>;
 pub type ThreadRunAsync = Box<dyn for<'a> RunAsync<'a> + Send>;
 pub type LocalRunAsync = Box<dyn for<'a> RunAsync<'a>>;
 
+/// Run condition of a system, see `Builder::add_condition`.
+pub type Condition = Box<dyn Fn(&World) -> bool + Send + Sync>;
+
 /// Trait for fetching data and running systems.
 /// Automatically implemented for systems.
 pub trait Run<'a> {
@@ -25,6 +29,12 @@ pub trait Run<'a> {
     /// (tries to read from a resource which is already written to or
     /// tries to write to a resource which is read from).
     fn run(&mut self, world: &'a World);
+
+    /// Sets up the system, see `System::setup`.
+    fn setup(&mut self, world: &mut World);
+
+    /// Disposes the system, see `System::dispose`.
+    fn dispose(self: Box<Self>, world: &mut World);
 }
 
 impl<'a, T> Run<'a> for T
@@ -32,10 +42,20 @@ where
     T: System<'a>,
 {
     fn run(&mut self, world: &'a World) {
+        BorrowConflict::reset();
+
         let data = T::SystemData::fetch(self.accessor().deref(), world);
 
         self.run(data)
     }
+
+    fn setup(&mut self, world: &mut World) {
+        System::setup(self, world)
+    }
+
+    fn dispose(self: Box<Self>, world: &mut World) {
+        System::dispose(*self, world)
+    }
 }
 
 /// Trait for fetching data and running systems with async/await.
@@ -50,6 +70,15 @@ pub trait RunAsync<'a> {
     /// (tries to read from a resource which is already written to or
     /// tries to write to a resource which is read from).
     fn run(&mut self, world: &'a World) -> BoxFuture<'a, ()>;
+
+    /// Initializes the system, see `AsyncSystem::init_async`.
+    fn init_async<'b>(&'b mut self, world: &'b World) -> BoxFuture<'b, ()>;
+
+    /// Sets up the system, see `AsyncSystem::setup`.
+    fn setup(&mut self, world: &mut World);
+
+    /// Disposes the system, see `AsyncSystem::dispose`.
+    fn dispose(self: Box<Self>, world: &mut World);
 }
 
 impl<'a, T> RunAsync<'a> for T
@@ -57,8 +86,22 @@ where
     T: AsyncSystem<'a>,
 {
     fn run(&mut self, world: &'a World) -> BoxFuture<'a, ()> {
+        BorrowConflict::reset();
+
         let data = T::SystemData::fetch(self.accessor().deref(), world);
 
         self.run_async(data)
     }
+
+    fn init_async<'b>(&'b mut self, world: &'b World) -> BoxFuture<'b, ()> {
+        AsyncSystem::init_async(self, world)
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        AsyncSystem::setup(self, world)
+    }
+
+    fn dispose(self: Box<Self>, world: &mut World) {
+        AsyncSystem::dispose(*self, world)
+    }
 }
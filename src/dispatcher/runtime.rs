@@ -0,0 +1,35 @@
+/// Defines how the `Dispatcher` executes its systems.
+///
+/// Use `Builder::with_runtime` to select the runtime of a dispatcher. The
+/// tasks are spawned using the `Spawner` of the dispatcher, the following
+/// describes the default `TokioSpawner`.
+#[derive(Default, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Runtime {
+    /// Each system is executed in its own task. Systems that are `Send` are
+    /// spawned using `tokio::spawn`, so they may run in parallel on the
+    /// multi-threaded tokio runtime. All other systems (see
+    /// `Builder::add_local`) are spawned using `tokio::task::spawn_local`,
+    /// which requires the dispatcher to be built and used inside a
+    /// `LocalSet`.
+    #[default]
+    Parallel,
+
+    /// Each system is executed in its own task, but all tasks are spawned
+    /// using `tokio::task::spawn_local`. The dispatcher must be built and
+    /// used inside a `LocalSet`, which can be driven by the current-thread
+    /// tokio runtime. Resources are still accessed concurrently by systems
+    /// that await something.
+    Local,
+
+    /// No tasks are spawned. All systems are executed one after another on
+    /// the task that calls `Dispatcher::dispatch`, in the order they were
+    /// added (which always satisfies their dependencies). This gives a
+    /// deterministic execution order and works without any tokio runtime,
+    /// so the dispatch may be driven by any executor (like
+    /// `futures::executor::block_on` or `wasm_bindgen_futures::spawn_local`).
+    ///
+    /// To use the dispatcher on `wasm32-unknown-unknown`, disable the
+    /// `multi-thread` and `time` features. The execution time of the systems
+    /// is then measured using the clock of the JavaScript host.
+    Sequential,
+}
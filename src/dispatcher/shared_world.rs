@@ -0,0 +1,210 @@
+use std::ops::{Deref, DerefMut};
+use std::process::abort;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::panicking;
+
+use crate::world::World;
+
+use super::task::Diagnostics;
+
+/// Helper type to share the world parameter passed to `Dispatcher::dispatch`
+/// with the tasks of the systems.
+///
+/// The world is only accessible through the `WorldRef` guards returned by
+/// `get`, which keep track of the number of tasks that currently use the
+/// world. Guards requested after the world was released panic.
+///
+/// The dispatcher waits for all systems to finish before it releases the
+/// world, so usually no guard is left when the world is released. This is
+/// only the case if the future returned by `Dispatcher::dispatch` is dropped
+/// while systems are still running. The release happens on an executor
+/// thread, so it can not wait for these systems without risking a deadlock.
+/// Instead the running systems are cancelled and the process is aborted,
+/// because the systems would otherwise access a world that no longer exists.
+/// Use `Dispatcher::dispatch_with_timeout` or
+/// `Dispatcher::dispatch_with_cancel` to stop a dispatch early.
+///
+/// If the `debug-world` feature is enabled, releasing the world while it is
+/// still used panics instead of aborting.
+#[derive(Default, Clone)]
+pub struct SharedWorld(Arc<Inner>);
+
+#[derive(Default)]
+struct Inner {
+    world: AtomicPtr<World>,
+    users: AtomicUsize,
+}
+
+impl SharedWorld {
+    /// Shares the passed world until the returned guard is dropped.
+    pub(super) fn set<'a>(
+        &'a mut self,
+        world: &World,
+        diagnostics: &'a Diagnostics,
+    ) -> WorldGuard<'a> {
+        self.0
+            .world
+            .store(world as *const World as *mut World, Ordering::Release);
+
+        WorldGuard(self, diagnostics)
+    }
+
+    /// Shares the passed world mutably until the returned guard is dropped.
+    pub(super) fn set_mut<'a>(
+        &'a mut self,
+        world: &mut World,
+        diagnostics: &'a Diagnostics,
+    ) -> WorldGuard<'a> {
+        self.0.world.store(world, Ordering::Release);
+
+        WorldGuard(self, diagnostics)
+    }
+
+    /// Returns the shared world.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no world is shared at the moment.
+    pub(super) fn get(&self) -> WorldRef<'_> {
+        WorldRef(self.acquire())
+    }
+
+    /// Returns the shared world mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no world is shared at the moment.
+    ///
+    /// # Safety
+    ///
+    /// The world must be shared using `set_mut`, and the returned reference
+    /// must be the only reference to the world.
+    pub(super) unsafe fn get_mut(&self) -> WorldRefMut<'_> {
+        WorldRefMut(self.acquire())
+    }
+
+    fn acquire(&self) -> Usage<'_> {
+        // The counter is incremented before the world is loaded, and `clear`
+        // resets the world before it reads the counter. Together with the
+        // sequential consistent ordering this guarantees that `clear` either
+        // sees this usage, or this usage sees the released world.
+        self.0.users.fetch_add(1, Ordering::SeqCst);
+
+        let usage = Usage(&self.0, self.0.world.load(Ordering::SeqCst));
+
+        if usage.1.is_null() {
+            panic!("No World assigned!");
+        }
+
+        usage
+    }
+
+    fn clear(&mut self, diagnostics: &Diagnostics) {
+        self.0.world.store(null_mut(), Ordering::SeqCst);
+
+        if self.0.users.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        // The systems that still use the world may run on this very thread,
+        // so waiting for them would never finish. Cancel them, so they do
+        // not start new work, and fail before the world is gone.
+        diagnostics.cancel();
+
+        let message = "World was released while it was still used by a system! \
+            Use `Dispatcher::dispatch_with_cancel` to stop a dispatch early.";
+
+        if cfg!(feature = "debug-world") && !panicking() {
+            panic!("{}", message);
+        }
+
+        eprintln!("{}", message);
+
+        abort();
+    }
+}
+
+/// Guard to share the world parameter passed to `Dispatcher::dispatch`.
+pub(super) struct WorldGuard<'a>(&'a mut SharedWorld, &'a Diagnostics);
+
+impl Drop for WorldGuard<'_> {
+    fn drop(&mut self) {
+        self.0.clear(self.1)
+    }
+}
+
+/// Shared access to the world of a `SharedWorld`.
+pub struct WorldRef<'a>(Usage<'a>);
+
+impl Deref for WorldRef<'_> {
+    type Target = World;
+
+    fn deref(&self) -> &World {
+        unsafe { &*self.0 .1 }
+    }
+}
+
+/// Mutable access to the world of a `SharedWorld`.
+pub struct WorldRefMut<'a>(Usage<'a>);
+
+impl Deref for WorldRefMut<'_> {
+    type Target = World;
+
+    fn deref(&self) -> &World {
+        unsafe { &*self.0 .1 }
+    }
+}
+
+impl DerefMut for WorldRefMut<'_> {
+    fn deref_mut(&mut self) -> &mut World {
+        unsafe { &mut *self.0 .1 }
+    }
+}
+
+/// Counts a user of the shared world until it is dropped.
+struct Usage<'a>(&'a Inner, *mut World);
+
+// SAFETY: The usage only gives access to the world, which is `Sync`.
+unsafe impl Send for Usage<'_> {}
+unsafe impl Sync for Usage<'_> {}
+
+impl Drop for Usage<'_> {
+    fn drop(&mut self) {
+        self.0.users.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "debug-world")]
+    #[should_panic(expected = "World was released while it was still used by a system!")]
+    fn released_while_used() {
+        let world = World::default();
+        let diagnostics = Diagnostics::default();
+        let mut shared = SharedWorld::default();
+        let used = shared.clone();
+
+        let guard = shared.set(&world, &diagnostics);
+        let _world_ref = used.get();
+
+        drop(guard);
+    }
+
+    #[test]
+    #[should_panic(expected = "No World assigned!")]
+    fn get_after_release() {
+        let world = World::default();
+        let diagnostics = Diagnostics::default();
+        let mut shared = SharedWorld::default();
+        let used = shared.clone();
+
+        drop(shared.set(&world, &diagnostics));
+
+        used.get();
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use futures::future::{BoxFuture, LocalBoxFuture};
+use tokio::{runtime::Handle, task::spawn_local};
+
+/// Spawns the tasks that execute the systems of a `Dispatcher`.
+///
+/// The dispatcher keeps track of the spawned tasks itself, so the spawner
+/// does not need to return a handle. Use `Builder::with_spawner` to select
+/// the spawner of a dispatcher. The default is the `TokioSpawner`.
+///
+/// ## Examples
+///
+/// Spawner that counts the spawned tasks:
+///
+/// ```
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// # use std::sync::Arc;
+/// #
+/// # use async_ecs::{dispatcher::{Spawner, TokioSpawner}, *};
+/// # use futures::future::{BoxFuture, LocalBoxFuture};
+/// #
+/// # struct Dummy;
+/// # impl<'a> System<'a> for Dummy {
+/// #     type SystemData = ();
+/// #     fn run(&mut self, _: ()) {}
+/// # }
+/// #
+/// #[derive(Default)]
+/// struct Counting(AtomicUsize);
+///
+/// impl Spawner for Counting {
+///     fn spawn(&self, future: BoxFuture<'static, ()>) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///
+///         TokioSpawner.spawn(future);
+///     }
+///
+///     fn spawn_local(&self, future: LocalBoxFuture<'static, ()>) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///
+///         TokioSpawner.spawn_local(future);
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let spawner = Arc::new(Counting::default());
+///
+/// let mut world = World::default();
+/// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with_spawner(spawner.clone())
+///     .with(Dummy, "a", &[])
+///     .unwrap()
+///     .with(Dummy, "b", &[])
+///     .unwrap()
+///     .build();
+///
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// assert_eq!(spawner.0.load(Ordering::Relaxed), 2);
+/// # }
+/// ```
+pub trait Spawner {
+    /// Spawns a task that is `Send` and may be executed on any thread.
+    fn spawn(&self, future: BoxFuture<'static, ()>);
+
+    /// Spawns a task that is not `Send` and must be executed on the current
+    /// thread.
+    fn spawn_local(&self, future: LocalBoxFuture<'static, ()>);
+}
+
+/// Spawner that uses the tokio runtime the dispatcher is built in.
+///
+/// Tasks that are `Send` are spawned using `tokio::spawn`, all other tasks
+/// are spawned using `tokio::task::spawn_local`, which requires the
+/// dispatcher to be built inside a `LocalSet`.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct TokioSpawner;
+
+impl Spawner for TokioSpawner {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        tokio::spawn(future);
+    }
+
+    fn spawn_local(&self, future: LocalBoxFuture<'static, ()>) {
+        spawn_local(future);
+    }
+}
+
+/// Spawns the tasks that are `Send` on the runtime of the handle, for
+/// example a dedicated tokio `Runtime`. Tasks that are not `Send` are
+/// still spawned using `tokio::task::spawn_local`.
+impl Spawner for Handle {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        Handle::spawn(self, future);
+    }
+
+    fn spawn_local(&self, future: LocalBoxFuture<'static, ()>) {
+        spawn_local(future);
+    }
+}
+
+impl<S> Spawner for Arc<S>
+where
+    S: Spawner + ?Sized,
+{
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        (**self).spawn(future)
+    }
+
+    fn spawn_local(&self, future: LocalBoxFuture<'static, ()>) {
+        (**self).spawn_local(future)
+    }
+}
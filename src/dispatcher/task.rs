@@ -1,114 +1,1052 @@
-use log::info;
+use std::any::Any;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::mem::take;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+use futures::future::{pending, select, Either, FutureExt};
+use log::{error, info, warn};
+use tokio::sync::{
+    mpsc::UnboundedSender,
+    watch::{channel, error::RecvError, Receiver as WatchReceiver, Sender as WatchSender},
+};
+
+use crate::{
+    resource::{BorrowConflict, ResourceId, ResourceLocks},
+    util::Instant,
+    world::World,
+};
 
 use super::{
-    LocalRun, LocalRunAsync, Receiver, Run, RunAsync, Sender, SharedWorld, ThreadRun,
-    ThreadRunAsync,
+    builder::RunType, Condition, ControlReceiver, Dispatcher, Error, LocalRun, LocalRunAsync,
+    Metrics, PanicPolicy, Receiver, Run, RunAsync, Sender, SharedWorld, ThreadRun, ThreadRunAsync,
 };
 
 /// Long running task of a `System` that is executed in a separate thread.
 pub async fn execute_thread(
-    name: String,
+    info: Arc<SystemInfo>,
     mut run: ThreadRun,
     sender: Sender,
-    receivers: Vec<Receiver>,
+    control: ControlReceiver,
     world: SharedWorld,
+    diagnostics: Diagnostics,
+    metrics: Metrics,
 ) {
-    info!("System started: {}", &name);
+    info!("System started: {}", info);
 
-    execute_inner(run.as_mut(), sender, receivers, world).await;
+    let exit = execute_inner(
+        &info,
+        run.as_mut(),
+        sender,
+        control,
+        world.clone(),
+        diagnostics.clone(),
+        metrics,
+    )
+    .await;
 
-    info!("System finished: {}", &name);
+    if let Exit::Dispose = exit {
+        execute_mut(&info, &world, &diagnostics, move |world| run.dispose(world));
+    }
+
+    info!("System finished: {}", info);
 }
 
 /// Long running task of a `System` that is executed in the thread local context.
 pub async fn execute_local(
-    name: String,
+    info: Arc<SystemInfo>,
     mut run: LocalRun,
     sender: Sender,
-    receivers: Vec<Receiver>,
+    control: ControlReceiver,
     world: SharedWorld,
+    diagnostics: Diagnostics,
+    metrics: Metrics,
 ) {
-    info!("System started (local): {}", &name);
+    info!("System started (local): {}", info);
+
+    let exit = execute_inner(
+        &info,
+        run.as_mut(),
+        sender,
+        control,
+        world.clone(),
+        diagnostics.clone(),
+        metrics,
+    )
+    .await;
 
-    execute_inner(run.as_mut(), sender, receivers, world).await;
+    if let Exit::Dispose = exit {
+        execute_mut(&info, &world, &diagnostics, move |world| run.dispose(world));
+    }
 
-    info!("System finished (local): {}", &name);
+    info!("System finished (local): {}", info);
 }
 
 /// Long running task of a `System` that is executed in a separate thread.
 pub async fn execute_thread_async(
-    name: String,
+    info: Arc<SystemInfo>,
     mut run: ThreadRunAsync,
     sender: Sender,
-    receivers: Vec<Receiver>,
+    control: ControlReceiver,
     world: SharedWorld,
+    diagnostics: Diagnostics,
+    metrics: Metrics,
 ) {
-    info!("System started: {}", &name);
+    info!("System started: {}", info);
+
+    let exit = execute_inner_async(
+        &info,
+        run.as_mut(),
+        sender,
+        control,
+        world.clone(),
+        diagnostics.clone(),
+        metrics,
+    )
+    .await;
 
-    execute_inner_async(run.as_mut(), sender, receivers, world).await;
+    if let Exit::Dispose = exit {
+        execute_mut(&info, &world, &diagnostics, move |world| run.dispose(world));
+    }
 
-    info!("System finished: {}", &name);
+    info!("System finished: {}", info);
 }
 
 /// Long running task of a `System` that is executed in the thread local context.
 pub async fn execute_local_async(
-    name: String,
+    info: Arc<SystemInfo>,
     mut run: LocalRunAsync,
     sender: Sender,
-    receivers: Vec<Receiver>,
+    control: ControlReceiver,
+    world: SharedWorld,
+    diagnostics: Diagnostics,
+    metrics: Metrics,
+) {
+    info!("System started (local): {}", info);
+
+    let exit = execute_inner_async(
+        &info,
+        run.as_mut(),
+        sender,
+        control,
+        world.clone(),
+        diagnostics.clone(),
+        metrics,
+    )
+    .await;
+
+    if let Exit::Dispose = exit {
+        execute_mut(&info, &world, &diagnostics, move |world| run.dispose(world));
+    }
+
+    info!("System finished (local): {}", info);
+}
+
+/// Long running task of a nested `Dispatcher` that is executed in the
+/// thread local context.
+pub async fn execute_dispatcher(
+    info: Arc<SystemInfo>,
+    mut dispatcher: Box<Dispatcher>,
+    sender: Sender,
+    control: ControlReceiver,
     world: SharedWorld,
+    diagnostics: Diagnostics,
+    metrics: Metrics,
+) {
+    info!("Dispatcher started: {}", info);
+
+    let exit = execute_inner_dispatcher(
+        &info,
+        &mut dispatcher,
+        sender,
+        control,
+        world.clone(),
+        diagnostics.clone(),
+        metrics,
+    )
+    .await;
+
+    if let Exit::Dispose = exit {
+        // The dispatcher only requests the disposal of one system at a time,
+        // while it holds the mutable reference to the world.
+        let mut world = unsafe { world.get_mut() };
+
+        dispose_dispatcher(&info, dispatcher, &mut world, &diagnostics).await;
+    }
+
+    info!("Dispatcher finished: {}", info);
+}
+
+/// Runs the system once on the current task. Used by the sequential
+/// dispatcher, that does not spawn a task for each system.
+pub(super) async fn execute_seq(
+    info: &Arc<SystemInfo>,
+    run: &mut RunType,
+    world: &World,
+    diagnostics: &Diagnostics,
+    metrics: &Metrics,
+) {
+    if !check(info, world, diagnostics) {
+        return;
+    }
+
+    let started = Instant::now();
+
+    diagnostics.started(info);
+
+    let mut attempt = 0;
+    let result = loop {
+        let result = match run {
+            RunType::Thread(run) => Some(catch_unwind(AssertUnwindSafe(|| run.run(world)))),
+            RunType::Local(run) => Some(catch_unwind(AssertUnwindSafe(|| run.run(world)))),
+            RunType::ThreadAsync(run) => {
+                run_async_cancellable(run.as_mut(), world, diagnostics).await
+            }
+            RunType::LocalAsync(run) => {
+                run_async_cancellable(run.as_mut(), world, diagnostics).await
+            }
+            RunType::Dispatcher(dispatcher) => {
+                let cancelled = diagnostics.cancelled();
+                let result = Box::pin(dispatcher.dispatch_nested(world, cancelled)).await;
+
+                diagnostics.finished_nested(info, result);
+                metrics.record(info, started, Instant::now());
+
+                return;
+            }
+        };
+
+        let result = match result {
+            Some(result) => result,
+            None => return diagnostics.aborted(info),
+        };
+
+        if !restart(info, &result, &mut attempt) {
+            break result;
+        }
+    };
+
+    diagnostics.finished(info, result);
+    metrics.record(info, started, Instant::now());
+}
+
+/// Disposes the system of the sequential dispatcher.
+pub(super) async fn dispose_seq(
+    info: &Arc<SystemInfo>,
+    run: RunType,
+    world: &mut World,
+    diagnostics: &Diagnostics,
+) {
+    match run {
+        RunType::Thread(run) => execute_mut_with(info, world, diagnostics, move |w| run.dispose(w)),
+        RunType::Local(run) => execute_mut_with(info, world, diagnostics, move |w| run.dispose(w)),
+        RunType::ThreadAsync(run) => {
+            execute_mut_with(info, world, diagnostics, move |w| run.dispose(w))
+        }
+        RunType::LocalAsync(run) => {
+            execute_mut_with(info, world, diagnostics, move |w| run.dispose(w))
+        }
+        RunType::Dispatcher(dispatcher) => {
+            dispose_dispatcher(info, dispatcher, world, diagnostics).await
+        }
+    }
+}
+
+/// Sets up the system of the sequential dispatcher.
+pub(super) async fn setup_seq(
+    info: &Arc<SystemInfo>,
+    run: &mut RunType,
+    world: &mut World,
+    diagnostics: &Diagnostics,
+) {
+    match run {
+        RunType::Thread(run) => execute_mut_with(info, world, diagnostics, |w| run.setup(w)),
+        RunType::Local(run) => execute_mut_with(info, world, diagnostics, |w| run.setup(w)),
+        RunType::ThreadAsync(run) => execute_mut_with(info, world, diagnostics, |w| run.setup(w)),
+        RunType::LocalAsync(run) => execute_mut_with(info, world, diagnostics, |w| run.setup(w)),
+        RunType::Dispatcher(dispatcher) => {
+            setup_dispatcher(info, dispatcher, world, diagnostics).await
+        }
+    }
+}
+
+/// Initializes the system of the sequential dispatcher.
+pub(super) async fn initialize_seq(
+    info: &Arc<SystemInfo>,
+    run: &mut RunType,
+    world: &World,
+    diagnostics: &Diagnostics,
+) {
+    match run {
+        RunType::Thread(_) | RunType::Local(_) => (),
+        RunType::ThreadAsync(run) => initialize_async(info, run.as_mut(), world, diagnostics).await,
+        RunType::LocalAsync(run) => initialize_async(info, run.as_mut(), world, diagnostics).await,
+        RunType::Dispatcher(dispatcher) => {
+            initialize_dispatcher(info, dispatcher, world, diagnostics).await
+        }
+    }
+}
+
+/// Runs the asynchronous initialization of the passed system and reports
+/// its panics to the diagnostics.
+async fn initialize_async<'a, R: RunAsync<'a> + ?Sized>(
+    info: &Arc<SystemInfo>,
+    run: &mut R,
+    world: &World,
+    diagnostics: &Diagnostics,
+) {
+    diagnostics.started(info);
+
+    // The closure has to take the system, so the future can borrow it.
+    let init = move || {
+        let run = run;
+
+        run.init_async(world)
+    };
+
+    let result = match catch_unwind(AssertUnwindSafe(init)) {
+        Ok(future) => AssertUnwindSafe(future).catch_unwind().await,
+        Err(err) => Err(err),
+    };
+
+    diagnostics.finished(info, result);
+}
+
+/// Initializes the systems of the passed nested dispatcher.
+async fn initialize_dispatcher(
+    info: &Arc<SystemInfo>,
+    dispatcher: &mut Dispatcher,
+    world: &World,
+    diagnostics: &Diagnostics,
+) {
+    diagnostics.started(info);
+
+    let result = Box::pin(dispatcher.initialize(world)).await;
+
+    diagnostics.finished_nested(info, result);
+}
+
+/// Sets up the systems of the passed nested dispatcher.
+async fn setup_dispatcher(
+    info: &Arc<SystemInfo>,
+    dispatcher: &mut Dispatcher,
+    world: &mut World,
+    diagnostics: &Diagnostics,
 ) {
-    info!("System started (local): {}", &name);
+    diagnostics.started(info);
 
-    execute_inner_async(run.as_mut(), sender, receivers, world).await;
+    let result = Box::pin(dispatcher.setup(world)).await;
 
-    info!("System finished (local): {}", &name);
+    diagnostics.finished_nested(info, result);
+}
+
+/// Shuts the passed nested dispatcher down.
+async fn dispose_dispatcher(
+    info: &Arc<SystemInfo>,
+    dispatcher: Box<Dispatcher>,
+    world: &mut World,
+    diagnostics: &Diagnostics,
+) {
+    diagnostics.started(info);
+
+    let result = Box::pin(dispatcher.shutdown(world)).await;
+
+    diagnostics.finished_nested(info, result);
 }
 
 /// Actual tasks that is running the system.
 async fn execute_inner<R: for<'a> Run<'a> + ?Sized>(
+    info: &Arc<SystemInfo>,
     run: &mut R,
     sender: Sender,
-    mut receivers: Vec<Receiver>,
-    world: SharedWorld,
-) {
+    mut control: ControlReceiver,
+    shared: SharedWorld,
+    diagnostics: Diagnostics,
+    metrics: Metrics,
+) -> Exit {
+    let mut receivers = match &*control.borrow() {
+        Wiring::Receivers(receivers) => receivers.clone(),
+        Wiring::Setup(..) | Wiring::Initialize(..) => Vec::new(), // handled by `wait`
+        Wiring::Stop => return Exit::Stop,
+        Wiring::Dispose => return Exit::Dispose,
+    };
+
     loop {
-        for receiver in &mut receivers {
-            match receiver.changed().await {
-                Ok(()) => (),
-                Err(_) => return,
+        match wait(&mut receivers, &mut control).await {
+            Signal::Run => (),
+            Signal::Rewire(new) => {
+                receivers = new;
+
+                continue;
+            }
+            Signal::Setup(new, done) => {
+                receivers = new;
+
+                execute_mut(info, &shared, &diagnostics, |world| run.setup(world));
+
+                let _ = done.send(());
+
+                continue;
+            }
+            Signal::Initialize(new, done) => {
+                receivers = new;
+
+                let _ = done.send(());
+
+                continue;
+            }
+            Signal::Stop => return Exit::Stop,
+            Signal::Dispose => return Exit::Dispose,
+        }
+
+        let world = shared.get();
+
+        if !check(info, &world, &diagnostics) {
+            drop(world);
+
+            match sender.send(()) {
+                Ok(()) => continue,
+                Err(_) => return Exit::Stop,
             }
         }
 
-        run.run(&world);
+        let locks = lock(info, &world).await;
+        let started = Instant::now();
+
+        diagnostics.started(info);
+
+        let mut attempt = 0;
+        let result = loop {
+            let result = catch_unwind(AssertUnwindSafe(|| run.run(&world)));
+
+            if !restart(info, &result, &mut attempt) {
+                break result;
+            }
+        };
+
+        diagnostics.finished(info, result);
+        metrics.record(info, started, Instant::now());
+
+        drop(locks);
+        drop(world);
 
         match sender.send(()) {
             Ok(()) => (),
-            Err(_) => return,
+            Err(_) => return Exit::Stop,
         }
     }
 }
 
 /// Actual tasks that is running the system.
 async fn execute_inner_async<R: for<'a> RunAsync<'a> + ?Sized>(
+    info: &Arc<SystemInfo>,
     run: &mut R,
     sender: Sender,
-    mut receivers: Vec<Receiver>,
-    world: SharedWorld,
-) {
+    mut control: ControlReceiver,
+    shared: SharedWorld,
+    diagnostics: Diagnostics,
+    metrics: Metrics,
+) -> Exit {
+    let mut receivers = match &*control.borrow() {
+        Wiring::Receivers(receivers) => receivers.clone(),
+        Wiring::Setup(..) | Wiring::Initialize(..) => Vec::new(), // handled by `wait`
+        Wiring::Stop => return Exit::Stop,
+        Wiring::Dispose => return Exit::Dispose,
+    };
+
+    loop {
+        match wait(&mut receivers, &mut control).await {
+            Signal::Run => (),
+            Signal::Rewire(new) => {
+                receivers = new;
+
+                continue;
+            }
+            Signal::Setup(new, done) => {
+                receivers = new;
+
+                execute_mut(info, &shared, &diagnostics, |world| run.setup(world));
+
+                let _ = done.send(());
+
+                continue;
+            }
+            Signal::Initialize(new, done) => {
+                receivers = new;
+
+                let world = shared.get();
+
+                initialize_async(info, run, &world, &diagnostics).await;
+
+                drop(world);
+
+                let _ = done.send(());
+
+                continue;
+            }
+            Signal::Stop => return Exit::Stop,
+            Signal::Dispose => return Exit::Dispose,
+        }
+
+        let world = shared.get();
+
+        if !check(info, &world, &diagnostics) {
+            drop(world);
+
+            match sender.send(()) {
+                Ok(()) => continue,
+                Err(_) => return Exit::Stop,
+            }
+        }
+
+        let locks = lock(info, &world).await;
+        let started = Instant::now();
+
+        diagnostics.started(info);
+
+        let mut attempt = 0;
+        let result = loop {
+            let result = match run_async_cancellable(run, &world, &diagnostics).await {
+                Some(result) => result,
+                None => break None,
+            };
+
+            if !restart(info, &result, &mut attempt) {
+                break Some(result);
+            }
+        };
+
+        match result {
+            Some(result) => {
+                diagnostics.finished(info, result);
+                metrics.record(info, started, Instant::now());
+            }
+            None => diagnostics.aborted(info),
+        }
+
+        drop(locks);
+        drop(world);
+
+        match sender.send(()) {
+            Ok(()) => (),
+            Err(_) => return Exit::Stop,
+        }
+    }
+}
+
+/// Actual tasks that is running the nested dispatcher.
+async fn execute_inner_dispatcher(
+    info: &Arc<SystemInfo>,
+    dispatcher: &mut Dispatcher,
+    sender: Sender,
+    mut control: ControlReceiver,
+    shared: SharedWorld,
+    diagnostics: Diagnostics,
+    metrics: Metrics,
+) -> Exit {
+    let mut receivers = match &*control.borrow() {
+        Wiring::Receivers(receivers) => receivers.clone(),
+        Wiring::Setup(..) | Wiring::Initialize(..) => Vec::new(), // handled by `wait`
+        Wiring::Stop => return Exit::Stop,
+        Wiring::Dispose => return Exit::Dispose,
+    };
+
     loop {
-        for receiver in &mut receivers {
-            match receiver.changed().await {
-                Ok(()) => (),
-                Err(_) => return,
+        match wait(&mut receivers, &mut control).await {
+            Signal::Run => (),
+            Signal::Rewire(new) => {
+                receivers = new;
+
+                continue;
+            }
+            Signal::Setup(new, done) => {
+                receivers = new;
+
+                // The dispatcher only requests the setup of one system at a
+                // time, while it holds the mutable reference to the world.
+                let mut world = unsafe { shared.get_mut() };
+
+                setup_dispatcher(info, dispatcher, &mut world, &diagnostics).await;
+
+                drop(world);
+
+                let _ = done.send(());
+
+                continue;
+            }
+            Signal::Initialize(new, done) => {
+                receivers = new;
+
+                let world = shared.get();
+
+                initialize_dispatcher(info, dispatcher, &world, &diagnostics).await;
+
+                drop(world);
+
+                let _ = done.send(());
+
+                continue;
             }
+            Signal::Stop => return Exit::Stop,
+            Signal::Dispose => return Exit::Dispose,
         }
 
-        run.run(&world).await;
+        let world = shared.get();
+
+        if !check(info, &world, &diagnostics) {
+            drop(world);
+
+            match sender.send(()) {
+                Ok(()) => continue,
+                Err(_) => return Exit::Stop,
+            }
+        }
+
+        // The systems of a nested dispatcher that uses resource locks acquire
+        // the locks of their resources on their own. Holding the locks of all
+        // of them here would deadlock the nested systems.
+        let locks = if dispatcher.uses_resource_locks() {
+            None
+        } else {
+            lock(info, &world).await
+        };
+        let started = Instant::now();
+
+        diagnostics.started(info);
+
+        let cancelled = diagnostics.cancelled();
+        let result = Box::pin(dispatcher.dispatch_nested(&world, cancelled)).await;
+
+        diagnostics.finished_nested(info, result);
+        metrics.record(info, started, Instant::now());
+
+        drop(locks);
+        drop(world);
 
         match sender.send(()) {
             Ok(()) => (),
-            Err(_) => return,
+            Err(_) => return Exit::Stop,
+        }
+    }
+}
+
+/// Runs the passed asynchronous system and catches its panics.
+async fn run_async<'a, R: RunAsync<'a> + ?Sized>(
+    run: &mut R,
+    world: &'a World,
+) -> Result<(), Box<dyn Any + Send>> {
+    match catch_unwind(AssertUnwindSafe(|| run.run(world))) {
+        Ok(future) => AssertUnwindSafe(future).catch_unwind().await,
+        Err(err) => Err(err),
+    }
+}
+
+/// Runs the passed asynchronous system until it is finished, or the dispatch
+/// is cancelled. Returns `None` if the system was aborted.
+async fn run_async_cancellable<'a, R: RunAsync<'a> + ?Sized>(
+    run: &mut R,
+    world: &'a World,
+    diagnostics: &Diagnostics,
+) -> Option<Result<(), Box<dyn Any + Send>>> {
+    match select(
+        Box::pin(run_async(run, world)),
+        Box::pin(diagnostics.cancelled()),
+    )
+    .await
+    {
+        Either::Left((result, _)) => Some(result),
+        Either::Right(((), _)) => None,
+    }
+}
+
+/// Returns `true` if the system should be executed again, because it
+/// panicked and its panic policy allows another attempt.
+fn restart(
+    info: &SystemInfo,
+    result: &Result<(), Box<dyn Any + Send>>,
+    attempt: &mut usize,
+) -> bool {
+    let payload = match result {
+        Ok(()) => return false,
+        Err(payload) => payload,
+    };
+
+    match info.panic_policy {
+        PanicPolicy::Restart { attempts } if *attempt + 1 < attempts => {
+            if BorrowConflict::is_pending() {
+                return false;
+            }
+
+            *attempt += 1;
+
+            warn!(
+                "System {} panicked: {}! Restarting (attempt {} of {})",
+                info,
+                panic_message(payload),
+                *attempt + 1,
+                attempts
+            );
+
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Waits until all dependencies of the system are finished, or the system
+/// was rewired by the dispatcher.
+async fn wait(receivers: &mut [Receiver], control: &mut ControlReceiver) -> Signal {
+    let dependencies = async {
+        for receiver in receivers.iter_mut() {
+            receiver.changed().await?;
+        }
+
+        Ok::<_, RecvError>(())
+    };
+
+    // The control channel is polled first, so a new wiring always wins
+    // against a dispatch that was started after the rewiring.
+    let ready = match select(Box::pin(control.changed()), Box::pin(dependencies)).await {
+        Either::Left((Ok(()), _)) => None,
+        Either::Left((Err(_), _)) => return Signal::Stop,
+        Either::Right((result, _)) => Some(result.is_ok()),
+    };
+
+    match ready {
+        Some(true) => return Signal::Run,
+        // a dependency was removed, so we wait for the new wiring
+        Some(false) if control.changed().await.is_err() => return Signal::Stop,
+        _ => (),
+    }
+
+    match &*control.borrow() {
+        Wiring::Receivers(receivers) => Signal::Rewire(receivers.clone()),
+        Wiring::Setup(receivers, done) => Signal::Setup(receivers.clone(), done.clone()),
+        Wiring::Initialize(receivers, done) => {
+            Signal::Initialize(receivers.clone(), done.clone())
+        }
+        Wiring::Stop => Signal::Stop,
+        Wiring::Dispose => Signal::Dispose,
+    }
+}
+
+/// Evaluates the run conditions of the system. A panicking condition is
+/// reported like a panicking system and skips the system. All systems are
+/// skipped if the dispatch was cancelled.
+fn check(info: &Arc<SystemInfo>, world: &World, diagnostics: &Diagnostics) -> bool {
+    if diagnostics.is_cancelled() {
+        diagnostics.skipped(info);
+
+        return false;
+    }
+
+    if info.conditions.is_empty() {
+        return true;
+    }
+
+    match catch_unwind(AssertUnwindSafe(|| {
+        info.conditions.iter().all(|condition| condition(world))
+    })) {
+        Ok(run) => run,
+        Err(err) => {
+            diagnostics.started(info);
+            diagnostics.finished(info, Err(err));
+
+            false
+        }
+    }
+}
+
+/// Acquires the resource locks of the system, if they are enabled.
+async fn lock<'a>(info: &SystemInfo, world: &'a World) -> Option<ResourceLocks<'a>> {
+    if info.resource_locks {
+        Some(world.lock(&info.reads, &info.writes).await)
+    } else {
+        None
+    }
+}
+
+/// Sets up or disposes the system of the task. The error of a panicking
+/// system is reported to the diagnostics.
+fn execute_mut<F>(info: &Arc<SystemInfo>, world: &SharedWorld, diagnostics: &Diagnostics, f: F)
+where
+    F: FnOnce(&mut World),
+{
+    // The dispatcher only requests the setup or disposal of one system at a
+    // time, while it holds the mutable reference to the world.
+    let mut world = unsafe { world.get_mut() };
+
+    execute_mut_with(info, &mut world, diagnostics, f);
+}
+
+fn execute_mut_with<F>(info: &Arc<SystemInfo>, world: &mut World, diagnostics: &Diagnostics, f: F)
+where
+    F: FnOnce(&mut World),
+{
+    diagnostics.started(info);
+
+    let result = catch_unwind(AssertUnwindSafe(|| f(world)));
+
+    diagnostics.finished(info, result);
+}
+
+enum Signal {
+    Run,
+    Rewire(Vec<Receiver>),
+    Setup(Vec<Receiver>, UnboundedSender<()>),
+    Initialize(Vec<Receiver>, UnboundedSender<()>),
+    Stop,
+    Dispose,
+}
+
+/// Reason why a task has left its execution loop.
+enum Exit {
+    Stop,
+    Dispose,
+}
+
+/* Wiring */
+
+/// Tells the task of a system which systems it has to wait for, that it
+/// should set up or initialize the system (and wait for the passed systems
+/// afterwards), or that it should stop (and dispose the system).
+#[derive(Clone)]
+pub enum Wiring {
+    Receivers(Vec<Receiver>),
+    Setup(Vec<Receiver>, UnboundedSender<()>),
+    Initialize(Vec<Receiver>, UnboundedSender<()>),
+    Stop,
+    Dispose,
+}
+
+/* SystemInfo */
+
+/// Information about a system that is used to diagnose failed runs.
+pub struct SystemInfo {
+    pub name: String,
+    pub namespace: Option<String>,
+    pub labels: Vec<String>,
+    pub reads: Vec<ResourceId>,
+    pub writes: Vec<ResourceId>,
+    pub resource_locks: bool,
+    pub panic_policy: PanicPolicy,
+    pub conditions: Vec<Condition>,
+}
+
+impl Debug for SystemInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("SystemInfo")
+            .field("name", &self.name)
+            .field("namespace", &self.namespace)
+            .field("labels", &self.labels)
+            .field("reads", &self.reads)
+            .field("writes", &self.writes)
+            .field("resource_locks", &self.resource_locks)
+            .field("panic_policy", &self.panic_policy)
+            .field("conditions", &self.conditions.len())
+            .finish()
+    }
+}
+
+/// Formats the name of the system, prefixed by its namespace and followed by
+/// its labels, to be used in log messages.
+impl Display for SystemInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if let Some(namespace) = &self.namespace {
+            write!(f, "{}::", namespace)?;
+        }
+
+        write!(f, "{}", self.name)?;
+
+        if !self.labels.is_empty() {
+            write!(f, " [{}]", self.labels.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/* Diagnostics */
+
+/// Keeps track of the currently running systems and records the errors of
+/// failed system runs, so they can be reported by `Dispatcher::dispatch`.
+///
+/// The diagnostics are also used to cancel the current dispatch, see
+/// `Dispatcher::dispatch_with_cancel`.
+#[derive(Clone)]
+pub struct Diagnostics {
+    inner: Arc<Mutex<DiagnosticsInner>>,
+    cancel: Arc<WatchSender<bool>>,
+    cancelled: WatchReceiver<bool>,
+}
+
+#[derive(Default)]
+struct DiagnosticsInner {
+    running: Vec<Arc<SystemInfo>>,
+    error: Option<Error>,
+    cancelled: bool,
+    unfinished: Vec<String>,
+}
+
+impl Diagnostics {
+    /// Takes the first error that occurred since the last call. If the
+    /// dispatch was cancelled, `Error::DispatchCancelled` is returned instead
+    /// and the cancellation is reset.
+    pub fn take_error(&self) -> Option<Error> {
+        let mut inner = self.inner.lock().unwrap();
+
+        let error = inner.error.take();
+
+        if !inner.cancelled {
+            return error;
+        }
+
+        inner.cancelled = false;
+
+        let _ = self.cancel.send(false);
+
+        Some(Error::DispatchCancelled {
+            systems: take(&mut inner.unfinished),
+        })
+    }
+
+    /// Cancels the current dispatch. Asynchronous systems that are currently
+    /// running are aborted, all other systems are skipped until the dispatch
+    /// is finished.
+    pub(super) fn cancel(&self) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.cancelled = true;
+        inner.unfinished = inner
+            .running
+            .iter()
+            .map(|running| running.name.clone())
+            .collect();
+
+        let _ = self.cancel.send(true);
+    }
+
+    /// Returns `true` if the current dispatch was cancelled.
+    fn is_cancelled(&self) -> bool {
+        self.inner.lock().unwrap().cancelled
+    }
+
+    /// Resolves as soon as the current dispatch is cancelled.
+    async fn cancelled(&self) {
+        let mut cancelled = self.cancelled.clone();
+
+        while !*cancelled.borrow() {
+            if cancelled.changed().await.is_err() {
+                return pending().await;
+            }
+        }
+    }
+
+    fn started(&self, info: &Arc<SystemInfo>) {
+        self.inner.lock().unwrap().running.push(info.clone());
+    }
+
+    /// Records a system that was not executed, because the dispatch was
+    /// cancelled.
+    fn skipped(&self, info: &Arc<SystemInfo>) {
+        self.inner
+            .lock()
+            .unwrap()
+            .unfinished
+            .push(info.name.clone());
+    }
+
+    /// Records a system that was aborted, because the dispatch was
+    /// cancelled.
+    fn aborted(&self, info: &Arc<SystemInfo>) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.running.retain(|running| !Arc::ptr_eq(running, info));
+
+        if !inner.unfinished.contains(&info.name) {
+            inner.unfinished.push(info.name.clone());
+        }
+    }
+
+    fn finished(&self, info: &Arc<SystemInfo>, result: Result<(), Box<dyn Any + Send>>) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.running.retain(|running| !Arc::ptr_eq(running, info));
+
+        let payload = match result {
+            Ok(()) => return,
+            Err(payload) => payload,
+        };
+
+        let err = match BorrowConflict::take_last() {
+            Some(conflict) => {
+                let conflicts = inner
+                    .running
+                    .iter()
+                    .filter(|running| {
+                        running.writes.contains(&conflict.id)
+                            || (!conflict.mutably && running.reads.contains(&conflict.id))
+                    })
+                    .map(|running| running.name.clone())
+                    .collect();
+
+                Error::BorrowConflict {
+                    system: info.name.clone(),
+                    resource: conflict.name,
+                    conflicts,
+                }
+            }
+            None if info.panic_policy == PanicPolicy::Skip => {
+                warn!(
+                    "System {} panicked: {}! Skipped",
+                    info,
+                    panic_message(&payload)
+                );
+
+                return;
+            }
+            None => Error::SystemPanicked {
+                system: info.name.clone(),
+                message: panic_message(&payload),
+            },
+        };
+
+        error!("{}", err);
+
+        inner.error.get_or_insert(err);
+    }
+
+    /// Records the result of a nested dispatcher. The error of the nested
+    /// dispatcher was already logged, so it is only forwarded.
+    fn finished_nested(&self, info: &Arc<SystemInfo>, result: Result<(), Error>) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.running.retain(|running| !Arc::ptr_eq(running, info));
+
+        if let Err(err) = result {
+            inner.error.get_or_insert(err);
         }
     }
 }
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        let (cancel, cancelled) = channel(false);
+
+        Self {
+            inner: Default::default(),
+            cancel: Arc::new(cancel),
+            cancelled,
+        }
+    }
+}
+
+pub(crate) fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).into()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".into()
+    }
+}
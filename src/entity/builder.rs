@@ -1,4 +1,13 @@
-use crate::{access::WriteStorage, component::Component, system::SystemData, world::World};
+use std::mem::take;
+
+#[cfg(feature = "serde")]
+use crate::saveload::{Marker, MarkerAllocator};
+use crate::{
+    access::WriteStorage,
+    component::Component,
+    system::SystemData,
+    world::{Command, World},
+};
 
 use super::Entity;
 
@@ -17,6 +26,42 @@ pub trait Builder {
     /// `World`.
     fn with<C: Component + Send + Sync>(self, component: C) -> Self;
 
+    /// Registers a function that is called with the entity and the world
+    /// right after the entity was built. This can be used for initialization
+    /// that depends on the entity, like registering it in an index.
+    ///
+    /// Hooks are executed in the order they were registered.
+    ///
+    /// # Panics
+    ///
+    /// The default implementation panics, because it has no access to the
+    /// built entity. All builders of this crate override it.
+    fn with_fn<F>(self, _f: F) -> Self
+    where
+        Self: Sized,
+        F: FnOnce(Entity, &World) + Send + Sync + 'static,
+    {
+        panic!("Builder does not support post-build hooks")
+    }
+
+    /// Marks the entity with a new marker of type `M`, that is allocated
+    /// by the `M::Allocator` resource.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the marker component or its allocator hasn't been
+    /// registered in the `World`.
+    ///
+    /// The default implementation always panics, because it has no access
+    /// to the allocator. All builders of this crate override it.
+    #[cfg(feature = "serde")]
+    fn marked<M: Marker>(self) -> Self
+    where
+        Self: Sized,
+    {
+        panic!("Builder does not support markers")
+    }
+
     /// Finishes the building and returns the entity.
     fn build(self) -> Entity;
 }
@@ -90,23 +135,53 @@ pub trait Builder {
 ///
 /// let entity = entitybuilder.build();
 /// ```
+///
+/// ### Post-Build Hooks
+///
+/// ```
+/// use async_ecs::{entity::Entity, *};
+///
+/// #[derive(Default)]
+/// struct Spawned(Vec<Entity>);
+///
+/// let mut world = World::default();
+/// world.register_resource(Spawned::default());
+///
+/// let entity = world
+///     .create_entity()
+///     .with_fn(|entity, world| world.resource_mut::<Spawned>().0.push(entity))
+///     .build();
+///
+/// assert_eq!(world.resource::<Spawned>().0, vec![entity]);
+/// ```
 pub struct EntityBuilder<'a> {
     world: &'a World,
     entity: Entity,
+    hooks: Vec<Hook>,
     built: bool,
 }
 
+type Hook = Box<dyn FnOnce(Entity, &World) + Send + Sync>;
+
 impl<'a> EntityBuilder<'a> {
     /// Create new entity builder.
     pub fn new(world: &'a World) -> Self {
         let entity = world.entities_mut().allocate();
 
+        world.record(|log| log.push(Command::Create(entity)));
+
         Self {
             world,
             entity,
+            hooks: Vec::new(),
             built: false,
         }
     }
+
+    /// Returns the entity that is built by this builder.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
 }
 
 impl<'a> Builder for EntityBuilder<'a> {
@@ -116,6 +191,8 @@ impl<'a> Builder for EntityBuilder<'a> {
     /// overwrite the previous component.
     #[inline]
     fn with<T: Component>(self, c: T) -> Self {
+        self.world.record(|log| log.insert(self.entity, &c));
+
         {
             let mut storage = WriteStorage::<T>::fetch(&self.world);
 
@@ -125,12 +202,41 @@ impl<'a> Builder for EntityBuilder<'a> {
         self
     }
 
+    /// Registers a function that is called right after `build`.
+    fn with_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Entity, &World) + Send + Sync + 'static,
+    {
+        self.hooks.push(Box::new(f));
+
+        self
+    }
+
+    /// Marks the entity with a new marker of type `M`.
+    #[cfg(feature = "serde")]
+    fn marked<M: Marker>(self) -> Self {
+        {
+            let mut allocator = self.world.resource_mut::<M::Allocator>();
+            let mut storage = WriteStorage::<M>::fetch(self.world);
+
+            allocator.mark(self.entity, &mut storage);
+        }
+
+        self
+    }
+
     /// Finishes the building and returns the entity. As opposed to
     /// `LazyBuilder`, the components are available immediately.
+    ///
+    /// The registered hooks are executed before this method returns.
     #[inline]
     fn build(mut self) -> Entity {
         self.built = true;
 
+        for hook in take(&mut self.hooks) {
+            hook(self.entity, self.world);
+        }
+
         self.entity
     }
 }
@@ -142,3 +248,108 @@ impl Drop for EntityBuilder<'_> {
         }
     }
 }
+
+/// Builder to create many entities with the same set of components at once.
+/// Each storage is only fetched once for all entities, which is much faster
+/// than building the entities one by one.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::*;
+///
+/// #[derive(Clone)]
+/// struct Health(f32);
+///
+/// impl Component for Health {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Pos(usize);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::default();
+/// world.register_component::<Health>();
+/// world.register_component::<Pos>();
+///
+/// let entities = world
+///     .create_entities(1000)
+///     .with(Health(4.0))
+///     .with_each(|i, _| Pos(i))
+///     .build();
+///
+/// assert_eq!(entities.len(), 1000);
+/// ```
+pub struct BatchBuilder<'a> {
+    world: &'a World,
+    entities: Vec<Entity>,
+    built: bool,
+}
+
+impl<'a> BatchBuilder<'a> {
+    /// Create new batch builder that creates `count` entities.
+    pub fn new(world: &'a World, count: usize) -> Self {
+        let entities = world.entities_mut().allocate_batch(count);
+
+        world.record(|log| {
+            for entity in &entities {
+                log.push(Command::Create(*entity));
+            }
+        });
+
+        Self {
+            world,
+            entities,
+            built: false,
+        }
+    }
+
+    /// Inserts a clone of the passed component for each entity.
+    pub fn with<T: Component + Clone>(self, c: T) -> Self {
+        self.with_each(|_, _| c.clone())
+    }
+
+    /// Inserts the component returned by `f` for each entity. `f` is called
+    /// with the position of the entity inside the batch and the entity.
+    pub fn with_each<T, F>(self, mut f: F) -> Self
+    where
+        T: Component,
+        F: FnMut(usize, Entity) -> T,
+    {
+        {
+            let mut storage = WriteStorage::<T>::fetch(self.world);
+
+            for (i, entity) in self.entities.iter().enumerate() {
+                let component = f(i, *entity);
+
+                self.world.record(|log| log.insert(*entity, &component));
+
+                storage.insert(*entity, component).unwrap();
+            }
+        }
+
+        self
+    }
+
+    /// Finishes the building and returns the entities.
+    pub fn build(mut self) -> Vec<Entity> {
+        self.built = true;
+
+        take(&mut self.entities)
+    }
+}
+
+impl Drop for BatchBuilder<'_> {
+    fn drop(&mut self) {
+        if !self.built {
+            let entities = self.world.entities_mut();
+
+            for entity in &self.entities {
+                entities.delete(*entity).unwrap();
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
+use std::collections::VecDeque;
 use std::iter::Iterator;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 
 use hibitset::{AtomicBitSet, BitSet, BitSetLike};
 use thiserror::Error;
@@ -7,10 +8,17 @@ use thiserror::Error;
 use crate::{
     access::WriteStorage,
     component::Component,
-    join::{Join, ParJoin},
+    event::EventChannel,
+    join::{Join, JoinIter, ParJoin},
+    world::{Lazy, World},
+};
+#[cfg(feature = "serde")]
+use crate::{
+    saveload::{Marker, MarkerAllocator},
+    system::SystemData,
 };
 
-use super::{Entity, Index};
+use super::{Builder, Entity, Index, WeakEntity};
 
 /// The entities of this ECS. This is a resource, stored in the `World`.
 /// If you just want to access it in your system, you can also use
@@ -22,6 +30,10 @@ use super::{Entity, Index};
 ///
 /// You need to call `World::maintain` after creating / deleting
 /// entities with this struct.
+///
+/// The indices of deleted entities are reused for new entities, the
+/// generation of the entity is incremented each time. When exactly an index
+/// is reused is controlled by the `RecyclePolicy`.
 #[derive(Default)]
 pub struct Entities {
     alive: BitSet,
@@ -31,21 +43,62 @@ pub struct Entities {
     cache: IndexCache,
     generations: Vec<u32>,
     max_index: AtomicU32,
+
+    policy: RecyclePolicy,
+    maintains: u64,
+    pending: VecDeque<(u64, Vec<Index>)>,
+
+    len: usize,
+    allocated: AtomicU64,
+    recycled: AtomicU64,
+
+    deleted: EventChannel<Entity>,
 }
 
 impl Entities {
+    /// Creates new entities that recycle indices using the passed policy.
+    pub fn with_recycle_policy(policy: RecyclePolicy) -> Self {
+        Self {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the policy that controls when the indices of deleted entities
+    /// are reused.
+    pub fn recycle_policy(&self) -> RecyclePolicy {
+        self.policy
+    }
+
+    /// Sets the policy that controls when the indices of deleted entities are
+    /// reused. The new policy only applies to entities deleted afterwards.
+    pub fn set_recycle_policy(&mut self, policy: RecyclePolicy) {
+        self.policy = policy;
+    }
+
     /// Creates a new entity. This will be persistent after this call.
     pub fn allocate(&mut self) -> Entity {
-        let index = self.cache.pop().unwrap_or_else(|| {
-            let index = self.max_index.get_mut();
-            *index = index.checked_add(1).expect("No entity left to allocate");
+        let index = match self.cache.pop() {
+            Some(index) => {
+                *self.recycled.get_mut() += 1;
 
-            *index
-        });
+                index
+            }
+            None => {
+                let index = self.max_index.get_mut();
+                *index = index.checked_add(1).expect("No entity left to allocate");
+
+                *self.allocated.get_mut() += 1;
+
+                *index
+            }
+        };
 
         self.update_generations(index as usize);
 
-        self.alive.add(index);
+        if !self.alive.add(index) {
+            self.len += 1;
+        }
 
         let generation = &mut self.generations[index as usize];
         *generation = generation.wrapping_add(1);
@@ -53,6 +106,12 @@ impl Entities {
         Entity::from_parts(index, *generation)
     }
 
+    /// Creates `count` new entities at once. They will be persistent after
+    /// this call.
+    pub fn allocate_batch(&mut self, count: usize) -> Vec<Entity> {
+        (0..count).map(|_| self.allocate()).collect()
+    }
+
     /// Creates a new entity atomically. This will be persistent as soon
     /// as you call `World::maintain`.
     ///
@@ -61,9 +120,19 @@ impl Entities {
     /// In case you have access to the `World`, you can also use `World::create_entity`
     /// which creates the entity and the components immediately.
     pub fn create(&self) -> Entity {
-        let index = self.cache.pop_atomic().unwrap_or_else(|| {
-            atomic_increment(&self.max_index).expect("No entity left to allocate") as Index
-        });
+        let index = match self.cache.pop_atomic() {
+            Some(index) => {
+                self.recycled.fetch_add(1, Ordering::Relaxed);
+
+                index
+            }
+            None => {
+                self.allocated.fetch_add(1, Ordering::Relaxed);
+
+                // `allocate` uses the incremented value as index, so we do too
+                atomic_increment(&self.max_index).expect("No entity left to allocate") + 1
+            }
+        };
 
         self.raised.add_atomic(index);
 
@@ -71,7 +140,7 @@ impl Entities {
             .generations
             .get(index as usize)
             .map(|g| g.wrapping_add(1))
-            .unwrap_or_default();
+            .unwrap_or(1);
 
         Entity::from_parts(index, generation)
     }
@@ -95,6 +164,65 @@ impl Entities {
         }
     }
 
+    /// Like `build_entity`, but components that are not inserted into a
+    /// storage directly are queued using the passed `Lazy`. The returned
+    /// builder implements `Builder`, so it can be used wherever an
+    /// `EntityBuilder` or a `LazyBuilder` is expected.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::{entity::Builder, *};
+    /// #
+    /// struct Pos(f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// struct Vel(f32);
+    ///
+    /// impl Component for Vel {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut world = World::default();
+    /// world.register_component::<Pos>();
+    /// world.register_component::<Vel>();
+    ///
+    /// let entity = {
+    ///     let lazy = world.resource::<Lazy>();
+    ///     let mut pos = world.component_mut::<Pos>();
+    ///
+    ///     world
+    ///         .entities()
+    ///         .build_entity_lazy(&lazy)
+    ///         .with(&mut pos, Pos(1.0))
+    ///         .with_lazy(Vel(2.0))
+    ///         .build()
+    /// };
+    ///
+    /// assert!(world.component::<Pos>().contains(entity));
+    /// assert!(!world.component::<Vel>().contains(entity));
+    ///
+    /// world.maintain().await;
+    ///
+    /// assert!(world.component::<Vel>().contains(entity));
+    /// # }
+    /// ```
+    pub fn build_entity_lazy<'a>(&'a self, lazy: &'a Lazy) -> LazyAtomicBuilder<'a> {
+        let entity = self.create();
+
+        LazyAtomicBuilder {
+            entities: self,
+            lazy,
+            entity,
+            built: false,
+        }
+    }
+
     /// Kills a list of entities immediately.
     pub fn kill(&mut self, delete: &[Entity]) -> Result<(), Error> {
         for &entity in delete {
@@ -107,7 +235,9 @@ impl Entities {
 
             let index = entity.index();
 
-            self.alive.remove(index);
+            if self.alive.remove(index) {
+                self.len -= 1;
+            }
             self.killed.remove(index);
 
             self.update_generations(index as usize);
@@ -118,7 +248,8 @@ impl Entities {
             }
         }
 
-        self.cache.extend(delete.iter().map(Entity::index));
+        self.recycle(delete.iter().map(Entity::index));
+        self.deleted.iter_write(delete.iter().copied());
 
         Ok(())
     }
@@ -149,16 +280,33 @@ impl Entities {
         match self.generations.get(idx as usize) {
             Some(g) if self.raised.contains(idx) => gen == g.wrapping_add(1),
             Some(g) => self.alive.contains(idx) && gen == *g,
-            None if self.raised.contains(idx) => gen == 0,
+            None if self.raised.contains(idx) => gen == 1,
             None => false,
         }
     }
 
+    /// Returns the entity referenced by the passed weak reference, if it is
+    /// still alive.
+    #[inline]
+    pub fn upgrade(&self, weak: WeakEntity) -> Option<Entity> {
+        weak.upgrade(self)
+    }
+
     /// Maintains the allocated entities, mainly dealing with atomically
     /// allocated or killed entities.
     pub fn maintain(&mut self) -> Vec<Entity> {
         let mut deleted = vec![];
 
+        self.maintains += 1;
+        while let Some((maintains, _)) = self.pending.front() {
+            if *maintains > self.maintains {
+                break;
+            }
+
+            let (_, indices) = self.pending.pop_front().unwrap();
+            self.cache.extend(indices);
+        }
+
         let max_index = *self.max_index.get_mut();
         self.update_generations(max_index as usize + 1);
 
@@ -166,20 +314,150 @@ impl Entities {
             let generation = &mut self.generations[index as usize];
             *generation = generation.wrapping_add(1);
 
-            self.alive.add(index);
+            if !self.alive.add(index) {
+                self.len += 1;
+            }
         }
         self.raised.clear();
 
         for index in (&self.killed).iter() {
-            self.alive.remove(index);
+            if self.alive.remove(index) {
+                self.len -= 1;
+            }
             deleted.push(Entity::from_parts(index, self.generations[index as usize]));
         }
+        self.killed.clear();
 
-        self.cache.extend(deleted.iter().map(Entity::index));
+        self.recycle(deleted.iter().map(Entity::index));
+        self.deleted.iter_write(deleted.iter().copied());
 
         deleted
     }
 
+    /// Returns the number of alive entities. Entities that were created or
+    /// deleted atomically are only considered after `World::maintain` was
+    /// called.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no alive entities.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over all alive entities. This is the same as
+    /// joining over `&Entities`.
+    pub fn iter(&self) -> JoinIter<&Self> {
+        self.join()
+    }
+
+    /// Returns the number of entities that were created using a new index.
+    pub fn allocated(&self) -> u64 {
+        self.allocated.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of entities that were created by reusing the index
+    /// of a deleted entity.
+    pub fn recycled(&self) -> u64 {
+        self.recycled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the channel that receives all entities that were deleted,
+    /// either by `kill` or by `maintain`.
+    ///
+    /// Register a reader using `EventChannel::register_reader` to get
+    /// notified about deleted entities.
+    pub fn deleted(&self) -> &EventChannel<Entity> {
+        &self.deleted
+    }
+
+    /// Captures the state of the entity allocator, so it can be restored
+    /// later using `Entities::restore`.
+    ///
+    /// Entities that were created or deleted atomically and are not
+    /// maintained yet are not part of the snapshot.
+    pub fn snapshot(&self) -> EntitiesSnapshot {
+        let cache_len = self.cache.len.load(Ordering::Relaxed) as usize;
+
+        EntitiesSnapshot {
+            alive: self.alive.clone(),
+            cache: self.cache.cache[..cache_len].to_vec(),
+            generations: self.generations.clone(),
+            max_index: self.max_index.load(Ordering::Relaxed),
+            len: self.len,
+            maintains: self.maintains,
+            pending: self.pending.clone(),
+        }
+    }
+
+    /// Restores the state of the entity allocator from the passed snapshot.
+    ///
+    /// Pending atomic changes are discarded. Entities that were created
+    /// after the snapshot was taken are no longer alive, and entities that
+    /// were deleted after the snapshot was taken are alive again. The
+    /// statistics (`allocated` and `recycled`) are not reset.
+    pub fn restore(&mut self, snapshot: &EntitiesSnapshot) {
+        self.alive = snapshot.alive.clone();
+        self.raised.clear();
+        self.killed.clear();
+
+        self.cache = IndexCache {
+            cache: snapshot.cache.clone(),
+            len: AtomicU32::new(snapshot.cache.len() as u32),
+        };
+        self.generations = snapshot.generations.clone();
+        *self.max_index.get_mut() = snapshot.max_index;
+        self.len = snapshot.len;
+        self.maintains = snapshot.maintains;
+        self.pending = snapshot.pending.clone();
+    }
+
+    /// Returns the mask of the living entities without the entities that
+    /// were deleted atomically.
+    pub(crate) fn alive_mask(&self) -> BitSet {
+        let mut mask = self.alive.clone();
+        for index in (&self.killed).iter() {
+            mask.remove(index);
+        }
+
+        mask
+    }
+
+    /// Returns the entities that were created atomically and are not
+    /// maintained yet.
+    pub(crate) fn raised(&self) -> Vec<Entity> {
+        (&self.raised)
+            .iter()
+            .map(|index| {
+                let generation = self
+                    .generations
+                    .get(index as usize)
+                    .map(|g| g.wrapping_add(1))
+                    .unwrap_or(1);
+
+                Entity::from_parts(index, generation)
+            })
+            .collect()
+    }
+
+    fn recycle<I>(&mut self, indices: I)
+    where
+        I: IntoIterator<Item = Index>,
+    {
+        match self.policy {
+            RecyclePolicy::Immediate | RecyclePolicy::Delayed(0) => self.cache.extend(indices),
+            RecyclePolicy::Delayed(n) => {
+                let indices = indices.into_iter().collect::<Vec<_>>();
+
+                if !indices.is_empty() {
+                    self.pending.push_back((self.maintains + n as u64, indices));
+                }
+            }
+            RecyclePolicy::Never => (),
+        }
+    }
+
     fn update_generations(&mut self, index: usize) {
         if self.generations.len() <= index {
             self.generations.resize(index + 1, 0);
@@ -187,6 +465,9 @@ impl Entities {
     }
 }
 
+/// Joins the maintained living entities. Entities created atomically are not
+/// yielded before `World::maintain`, entities deleted atomically are still
+/// yielded until then. Use `EntitiesView` to skip the deleted ones.
 impl<'a> Join for &'a Entities {
     type Mask = &'a BitSet;
     type Type = Entity;
@@ -207,6 +488,9 @@ impl<'a> Join for &'a Entities {
     }
 }
 
+/// Atomic creates and deletes do not change the joined mask, so `&Entities`
+/// can be joined in parallel while other tasks use `Entities::create` or
+/// `Entities::delete`. See `EntitiesView` for details.
 impl<'a> ParJoin for &'a Entities {}
 
 /* Error */
@@ -263,6 +547,176 @@ impl<'a> Drop for AtomicBuilder<'a> {
     }
 }
 
+/* LazyAtomicBuilder */
+
+/// An entity builder created by `Entities::build_entity_lazy`. Like
+/// `AtomicBuilder` it allows to insert components into storages directly,
+/// but components whose storages are not available are inserted using
+/// `Lazy`.
+pub struct LazyAtomicBuilder<'a> {
+    entities: &'a Entities,
+    lazy: &'a Lazy,
+    entity: Entity,
+    built: bool,
+}
+
+impl<'a> LazyAtomicBuilder<'a> {
+    /// Appends a component and associates it with the entity.
+    pub fn with<T: Component>(self, storage: &mut WriteStorage<T>, component: T) -> Self {
+        storage.insert(self.entity, component).unwrap();
+
+        self
+    }
+
+    /// Appends a component using `Lazy`, so it is associated with the entity
+    /// on the next `World::maintain`.
+    pub fn with_lazy<T>(self, component: T) -> Self
+    where
+        T: Component + Send + Sync,
+    {
+        self.lazy.insert(self.entity, component);
+
+        self
+    }
+
+    /// Finishes the building and returns the entity.
+    pub fn build(mut self) -> Entity {
+        self.built = true;
+
+        self.entity
+    }
+}
+
+impl<'a> Builder for LazyAtomicBuilder<'a> {
+    /// Inserts a component using `Lazy`, see `LazyAtomicBuilder::with_lazy`.
+    fn with<C>(self, component: C) -> Self
+    where
+        C: Component + Send + Sync,
+    {
+        self.with_lazy(component)
+    }
+
+    /// Registers a function using `Lazy`, so it is called with the entity
+    /// on `World::maintain`, after all previously queued updates.
+    fn with_fn<F>(self, f: F) -> Self
+    where
+        F: FnOnce(Entity, &World) + Send + Sync + 'static,
+    {
+        let entity = self.entity;
+
+        self.lazy.exec(move |world| f(entity, world));
+
+        self
+    }
+
+    /// Marks the entity with a new marker of type `M` using `Lazy`.
+    #[cfg(feature = "serde")]
+    fn marked<M: Marker>(self) -> Self {
+        let entity = self.entity;
+
+        self.lazy.exec(move |world| {
+            let mut allocator = world.resource_mut::<M::Allocator>();
+            let mut storage = WriteStorage::<M>::fetch(world);
+
+            allocator.mark(entity, &mut storage);
+        });
+
+        self
+    }
+
+    /// Finishes the building and returns the entity.
+    fn build(self) -> Entity {
+        LazyAtomicBuilder::build(self)
+    }
+}
+
+impl<'a> Drop for LazyAtomicBuilder<'a> {
+    fn drop(&mut self) {
+        if !self.built {
+            self.entities.delete(self.entity).unwrap();
+        }
+    }
+}
+
+/* RecyclePolicy */
+
+/// Controls when the index of a deleted entity is reused by `Entities`.
+///
+/// Each time an index is reused the generation of the entity is incremented,
+/// so outdated `Entity` values are no longer alive. Because the generation
+/// wraps around after `u32::MAX` reuses, an outdated entity could become
+/// alive again (ABA problem). Delaying the reuse of indices reduces this risk
+/// for systems that cache entities across frames.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::entity::{Entities, RecyclePolicy};
+///
+/// let mut entities = Entities::with_recycle_policy(RecyclePolicy::Delayed(2));
+///
+/// let a = entities.allocate();
+/// entities.kill(&[a]).unwrap();
+///
+/// // the index of `a` is not reused before two maintains have passed
+/// assert_ne!(entities.allocate().index(), a.index());
+///
+/// entities.maintain();
+/// entities.maintain();
+///
+/// assert_eq!(entities.allocate().index(), a.index());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RecyclePolicy {
+    /// Indices of deleted entities are reused immediately.
+    #[default]
+    Immediate,
+
+    /// Indices of deleted entities are reused after the passed number of
+    /// calls to `Entities::maintain`.
+    Delayed(usize),
+
+    /// Indices of deleted entities are never reused. Allocating panics once
+    /// all indices are used up.
+    Never,
+}
+
+/* EntitiesSnapshot */
+
+/// State of the entity allocator, captured by `Entities::snapshot`.
+#[derive(Clone, Debug)]
+pub struct EntitiesSnapshot {
+    alive: BitSet,
+    cache: Vec<Index>,
+    generations: Vec<u32>,
+    max_index: Index,
+    len: usize,
+    maintains: u64,
+    pending: VecDeque<(u64, Vec<Index>)>,
+}
+
+impl EntitiesSnapshot {
+    /// Returns `true` if the passed entity was alive when the snapshot was
+    /// taken.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index();
+
+        self.alive.contains(index)
+            && self.generations.get(index as usize) == Some(&entity.generation())
+    }
+
+    /// Returns the number of entities that were alive when the snapshot was
+    /// taken.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no entity was alive when the snapshot was taken.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
 /* IndexCache */
 
 #[derive(Default, Debug)]
@@ -340,3 +794,164 @@ fn atomic_decrement(i: &AtomicU32) -> Option<u32> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entity_count() {
+        let mut entities = Entities::default();
+        assert!(entities.is_empty());
+
+        let e1 = entities.allocate();
+        let e2 = entities.allocate();
+        let e3 = entities.create();
+
+        assert_eq!(entities.len(), 2);
+
+        entities.maintain();
+        assert_eq!(entities.len(), 3);
+        assert_eq!(entities.iter().collect::<Vec<_>>().len(), 3);
+
+        entities.kill(&[e1]).unwrap();
+        entities.delete(e2).unwrap();
+        entities.maintain();
+
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities.iter().collect::<Vec<_>>(), vec![e3]);
+
+        entities.allocate();
+        entities.create();
+
+        assert_eq!(entities.allocated(), 3);
+        assert_eq!(entities.recycled(), 2);
+    }
+
+    #[test]
+    fn create_on_fresh_index() {
+        let mut entities = Entities::default();
+
+        let e1 = entities.create();
+        assert!(entities.is_alive(e1));
+
+        entities.maintain();
+
+        assert!(entities.is_alive(e1));
+        assert_eq!(entities.iter().collect::<Vec<_>>(), vec![e1]);
+    }
+
+    #[test]
+    fn create_after_allocate() {
+        let mut entities = Entities::default();
+
+        let e1 = entities.allocate();
+        let e2 = entities.create();
+        assert_ne!(e1.index(), e2.index());
+
+        entities.maintain();
+
+        let e3 = entities.allocate();
+        assert_ne!(e2.index(), e3.index());
+        assert!(entities.is_alive(e1));
+        assert!(entities.is_alive(e2));
+        assert!(entities.is_alive(e3));
+        assert_eq!(entities.len(), 3);
+    }
+
+    #[test]
+    fn maintain_forgets_deleted() {
+        let mut entities = Entities::default();
+
+        let e1 = entities.create();
+        entities.delete(e1).unwrap();
+        assert_eq!(entities.maintain(), vec![e1]);
+
+        let e2 = entities.create();
+        assert_eq!(e2.index(), e1.index());
+        assert!(entities.maintain().is_empty());
+        assert!(entities.is_alive(e2));
+        assert_eq!(entities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn lazy_atomic_builder_as_builder() {
+        struct Pos(u32);
+
+        impl Component for Pos {
+            type Storage = crate::storage::VecStorage<Self>;
+        }
+
+        fn spawn<B: Builder>(builder: B) -> Entity {
+            builder.with(Pos(1)).build()
+        }
+
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let entity = spawn(
+            world
+                .entities()
+                .build_entity_lazy(&world.resource::<Lazy>()),
+        );
+        world.maintain().await;
+
+        assert_eq!(world.component::<Pos>().get(entity).unwrap().0, 1);
+    }
+
+    #[test]
+    fn recycle_policy() {
+        let mut entities = Entities::with_recycle_policy(RecyclePolicy::Never);
+
+        let e1 = entities.allocate();
+        entities.kill(&[e1]).unwrap();
+
+        let e2 = entities.create();
+        entities.delete(e2).unwrap();
+        entities.maintain();
+
+        assert_eq!(entities.allocate().index(), 3);
+        assert_eq!(entities.recycled(), 0);
+
+        entities.set_recycle_policy(RecyclePolicy::Delayed(1));
+
+        let e4 = entities.allocate();
+        entities.kill(&[e4]).unwrap();
+        assert_eq!(entities.allocate().index(), 5);
+
+        entities.maintain();
+
+        let e6 = entities.allocate();
+        assert_eq!(e6.index(), e4.index());
+        assert_eq!(e6.generation(), e4.generation() + 1);
+        assert!(!entities.is_alive(e4));
+        assert_eq!(entities.recycled(), 1);
+    }
+
+    #[test]
+    fn generation_wrap() {
+        let mut entities = Entities::default();
+
+        let e1 = entities.allocate();
+        entities.generations[e1.index() as usize] = u32::MAX;
+
+        let e1 = Entity::from_parts(e1.index(), u32::MAX);
+        assert!(entities.is_alive(e1));
+
+        entities.kill(&[e1]).unwrap();
+
+        let e2 = entities.allocate();
+        assert_eq!(e2.index(), e1.index());
+        assert_eq!(e2.generation(), 0);
+        assert!(!entities.is_alive(e1));
+        assert!(entities.is_alive(e2));
+
+        entities.kill(&[e2]).unwrap();
+
+        // an outdated entity with the wrapped generation is alive again
+        let stale = Entity::from_parts(e1.index(), 1);
+        let e3 = entities.allocate();
+        assert_eq!(e3, stale);
+        assert!(entities.is_alive(stale));
+    }
+}
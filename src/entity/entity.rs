@@ -1,10 +1,19 @@
 use std::cmp::Ordering;
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
-use std::hash::{Hash, Hasher};
+use std::mem::size_of;
+
+use super::WeakEntity;
 
 /// `Entity` type, as seen by the user.
-#[derive(Clone, Copy)]
-pub struct Entity(EntityRaw);
+///
+/// An entity consists of an index and a generation. Both can be packed into
+/// a single `u64` id (see `Entity::id`), which is independent of the
+/// endianness of the host.
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct Entity {
+    index: Index,
+    generation: Generation,
+}
 
 /// Index of the entity.
 pub type Index = u32;
@@ -12,50 +21,70 @@ pub type Index = u32;
 /// Generation of the entity.
 pub type Generation = u32;
 
-/// Raw data of the entity.
-#[repr(C, packed)]
-#[derive(Clone, Copy)]
-union EntityRaw {
-    id: u64,
-    data: EntityData,
-}
-
-#[repr(C, packed)]
-#[derive(Clone, Copy)]
-struct EntityData {
-    index: Index,
-    generation: Generation,
-}
+/// Make sure the entity keeps the size of its packed id.
+const _: [(); size_of::<u64>()] = [(); size_of::<Entity>()];
 
 impl Entity {
     /// Create new entity with the given ID.
+    ///
+    /// The ID is expected to be in the format returned by `Entity::id`.
+    ///
+    /// ## Migration
+    ///
+    /// Before the id had an explicit layout, it was the raw memory of the
+    /// index and the generation. Ids persisted on little-endian hosts are
+    /// still valid. Ids persisted on big-endian hosts have index and
+    /// generation swapped and can be converted using
+    /// `Entity::from_parts((id >> 32) as u32, id as u32)`.
+    ///
+    /// ```
+    /// # use async_ecs::entity::Entity;
+    /// #
+    /// // index 42 and generation 7, persisted on a big-endian host
+    /// let id: u64 = 0x0000_002A_0000_0007;
+    ///
+    /// let entity = Entity::from_parts((id >> 32) as u32, id as u32);
+    /// assert_eq!(entity.index(), 42);
+    /// assert_eq!(entity.generation(), 7);
+    /// ```
     pub fn from_id(id: u64) -> Self {
-        Self(EntityRaw { id })
+        Self {
+            index: id as Index,
+            generation: (id >> 32) as Generation,
+        }
     }
 
     /// Create new entity with the given given index and generation.
     pub fn from_parts(index: Index, generation: Generation) -> Self {
-        Self(EntityRaw {
-            data: EntityData { index, generation },
-        })
+        Self { index, generation }
     }
 
     /// Get the id of the entity.
+    ///
+    /// The id contains the generation in the upper 32 bits and the index in
+    /// the lower 32 bits: `(generation as u64) << 32 | index as u64`.
     #[inline]
     pub fn id(&self) -> u64 {
-        unsafe { self.0.id }
+        (self.generation as u64) << 32 | self.index as u64
     }
 
     /// Get the index of the entity.
     #[inline]
     pub fn index(&self) -> Index {
-        unsafe { self.0.data.index }
+        self.index
     }
 
     // Get the generation of the entity.
     #[inline]
     pub fn generation(&self) -> Generation {
-        unsafe { self.0.data.generation }
+        self.generation
+    }
+
+    /// Create a weak reference to this entity, that needs to be validated
+    /// using `Entities::upgrade` before it can be used.
+    #[inline]
+    pub fn downgrade(&self) -> WeakEntity {
+        WeakEntity::new(*self)
     }
 }
 
@@ -71,34 +100,11 @@ impl Debug for Entity {
     }
 }
 
-impl Hash for Entity {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.index().hash(state);
-        self.generation().hash(state);
-    }
-}
-
-impl Eq for Entity {}
-
-impl PartialEq<Entity> for Entity {
-    fn eq(&self, other: &Entity) -> bool {
-        self.id() == other.id()
-    }
-}
-
 impl Ord for Entity {
     fn cmp(&self, other: &Self) -> Ordering {
-        if self.generation() < other.generation() {
-            Ordering::Less
-        } else if self.generation() > other.generation() {
-            Ordering::Greater
-        } else if self.index() < other.index() {
-            Ordering::Less
-        } else if self.index() > other.index() {
-            Ordering::Greater
-        } else {
-            Ordering::Equal
-        }
+        self.generation
+            .cmp(&other.generation)
+            .then_with(|| self.index.cmp(&other.index))
     }
 }
 
@@ -107,3 +113,47 @@ impl PartialOrd for Entity {
         Some(Ord::cmp(self, other))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn id_layout() {
+        let entity = Entity::from_parts(0x1234_5678, 0x9ABC_DEF0);
+
+        assert_eq!(entity.id(), 0x9ABC_DEF0_1234_5678);
+        assert_eq!(entity.index(), 0x1234_5678);
+        assert_eq!(entity.generation(), 0x9ABC_DEF0);
+    }
+
+    #[test]
+    fn id_round_trip() {
+        let entity = Entity::from_parts(42, 7);
+
+        assert_eq!(Entity::from_id(entity.id()), entity);
+        assert_eq!(Entity::from_id(0x0000_0007_0000_002A), entity);
+    }
+
+    #[test]
+    fn formatting() {
+        let entity = Entity::from_parts(0x2A, 0x07);
+
+        assert_eq!(format!("{}", entity), "70000002A");
+        assert_eq!(format!("{:?}", entity), "70000002A");
+        assert_eq!(format!("{}", Entity::from_parts(1, 0)), "00000001");
+    }
+
+    #[test]
+    fn ordering() {
+        let a = Entity::from_parts(5, 1);
+        let b = Entity::from_parts(1, 2);
+        let c = Entity::from_parts(2, 2);
+
+        let mut entities = vec![c, b, a];
+        entities.sort();
+
+        assert_eq!(entities, vec![a, b, c]);
+        assert_eq!(a.cmp(&a), Ordering::Equal);
+    }
+}
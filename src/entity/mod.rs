@@ -2,7 +2,9 @@ pub mod builder;
 pub mod entities;
 #[allow(clippy::module_inception)]
 pub mod entity;
+pub mod weak;
 
-pub use builder::{Builder, EntityBuilder};
-pub use entities::Entities;
+pub use builder::{BatchBuilder, Builder, EntityBuilder};
+pub use entities::{Entities, EntitiesSnapshot, RecyclePolicy};
 pub use entity::{Entity, Generation, Index};
+pub use weak::WeakEntity;
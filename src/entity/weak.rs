@@ -0,0 +1,110 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+use super::{Entities, Entity};
+
+/// Weak reference to an entity.
+///
+/// An `Entity` that is stored for a longer time (for example inside a
+/// resource or a component) may be deleted in the meantime. Its index may
+/// even be reused by a new entity with a higher generation. A `WeakEntity`
+/// does not give access to the referenced entity directly, instead it has
+/// to be upgraded using `Entities::upgrade`, which validates the generation
+/// of the entity and returns `None` if it is no longer alive.
+///
+/// The default value is a dangling reference that never upgrades.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::{entity::WeakEntity, Builder, World};
+///
+/// let mut world = World::default();
+///
+/// let entity = world.create_entity().build();
+/// let weak = WeakEntity::new(entity);
+///
+/// assert_eq!(world.entities().upgrade(weak), Some(entity));
+///
+/// world.delete_entity(entity).unwrap();
+///
+/// assert_eq!(world.entities().upgrade(weak), None);
+/// assert_eq!(world.entities().upgrade(WeakEntity::default()), None);
+/// ```
+#[derive(Default, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct WeakEntity(Option<Entity>);
+
+impl WeakEntity {
+    /// Create a new weak reference to the passed entity.
+    pub fn new(entity: Entity) -> Self {
+        Self(Some(entity))
+    }
+
+    /// Create a dangling weak reference, that does not reference any entity.
+    pub fn dangling() -> Self {
+        Self(None)
+    }
+
+    /// Returns `true` if this is a dangling reference.
+    pub fn is_dangling(&self) -> bool {
+        self.0.is_none()
+    }
+
+    /// Returns the referenced entity without checking if it is still alive.
+    pub fn entity_unchecked(&self) -> Option<Entity> {
+        self.0
+    }
+
+    /// Returns the referenced entity if it is still alive.
+    ///
+    /// This is the same as `Entities::upgrade`.
+    pub fn upgrade(&self, entities: &Entities) -> Option<Entity> {
+        self.0.filter(|entity| entities.is_alive(*entity))
+    }
+}
+
+impl From<Entity> for WeakEntity {
+    fn from(entity: Entity) -> Self {
+        Self::new(entity)
+    }
+}
+
+impl From<Option<Entity>> for WeakEntity {
+    fn from(entity: Option<Entity>) -> Self {
+        Self(entity)
+    }
+}
+
+impl Debug for WeakEntity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.0 {
+            Some(entity) => write!(f, "WeakEntity({:?})", entity),
+            None => write!(f, "WeakEntity(dangling)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{entity::Builder, world::World};
+
+    #[test]
+    fn reused_index() {
+        let mut world = World::default();
+
+        let old = world.create_entity().build();
+        let weak = old.downgrade();
+
+        world.delete_entity(old).unwrap();
+
+        let new = world.create_entity().build();
+
+        assert_eq!(new.index(), old.index());
+        assert_eq!(world.entities().upgrade(weak), None);
+        assert_eq!(world.entities().upgrade(new.into()), Some(new));
+        assert_eq!(weak.entity_unchecked(), Some(old));
+        assert!(!weak.is_dangling());
+        assert!(WeakEntity::dangling().is_dangling());
+    }
+}
@@ -1,9 +1,27 @@
 use thiserror::Error;
 
-use crate::entity::Entity;
+use crate::entity::{entities::Error as EntitiesError, Entity};
 
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Entity is not alive: {0}!")]
     EntityIsNotAlive(Entity),
+
+    #[error("Resource does not exist: {0}!")]
+    ResourceNotFound(&'static str),
+
+    #[error("Resource is already borrowed{}: {resource}!", if *.mutably { " mutably" } else { "" })]
+    BorrowConflict {
+        resource: &'static str,
+        mutably: bool,
+    },
+
+    #[error("Singleton component does not exist: {0}!")]
+    SingletonNotFound(&'static str),
+
+    #[error("Singleton component is attached to more than one entity: {0}!")]
+    SingletonNotUnique(&'static str),
+
+    #[error("Entities error: {0}")]
+    EntitiesError(#[from] EntitiesError),
 }
@@ -0,0 +1,174 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+use tokio::sync::broadcast::{
+    channel,
+    error::{RecvError, TryRecvError},
+    Receiver, Sender,
+};
+
+use super::Error;
+
+/// Default number of events an `AsyncEventChannel` can buffer.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// A channel that pushes events to subscribers that can `.await` them.
+///
+/// In contrast to the `EventChannel`, events are written with `&self`, so
+/// the channel can be fetched using `Read` by the writing systems. Each
+/// subscriber receives a clone of every event that is written after it has
+/// subscribed. Subscribers are usually created in the `setup` of a system
+/// and stored inside the system, so an `AsyncSystem` can wait for new events
+/// without borrowing the channel.
+///
+/// The channel buffers a limited number of events. If a subscriber falls
+/// behind, the oldest events are skipped and `Error::Lagged` is returned.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::event::AsyncEventChannel;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let channel = AsyncEventChannel::new();
+/// let mut subscriber = channel.subscribe();
+///
+/// tokio::spawn(async move {
+///     channel.single_write(1);
+///     channel.iter_write(vec![2, 3]);
+/// });
+///
+/// assert_eq!(subscriber.recv().await.unwrap(), 1);
+/// assert_eq!(subscriber.recv().await.unwrap(), 2);
+/// assert_eq!(subscriber.recv().await.unwrap(), 3);
+/// assert!(subscriber.recv().await.is_err());
+/// # }
+/// ```
+pub struct AsyncEventChannel<E> {
+    sender: Sender<E>,
+}
+
+impl<E> AsyncEventChannel<E>
+where
+    E: Clone,
+{
+    /// Create a new channel with the default capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new channel that is able to buffer `capacity` events.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = channel(capacity);
+
+        Self { sender }
+    }
+
+    /// Creates a new subscriber. The subscriber will only receive events
+    /// that are written after it was created.
+    pub fn subscribe(&self) -> EventSubscriber<E> {
+        EventSubscriber(self.sender.subscribe())
+    }
+
+    /// Writes a single event into the channel. The event is dropped if the
+    /// channel has no subscribers.
+    pub fn single_write(&self, event: E) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Writes all events of the passed iterator into the channel.
+    pub fn iter_write<I>(&self, iter: I)
+    where
+        I: IntoIterator<Item = E>,
+    {
+        for event in iter {
+            self.single_write(event);
+        }
+    }
+
+    /// Returns the number of active subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl<E> Default for AsyncEventChannel<E>
+where
+    E: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Debug for AsyncEventChannel<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("AsyncEventChannel")
+            .field("subscribers", &self.sender.receiver_count())
+            .finish()
+    }
+}
+
+/* EventSubscriber */
+
+/// Subscriber of an `AsyncEventChannel`.
+///
+/// Dropping the subscriber unregisters it from the channel.
+pub struct EventSubscriber<E>(Receiver<E>);
+
+impl<E> EventSubscriber<E>
+where
+    E: Clone,
+{
+    /// Waits for the next event.
+    ///
+    /// Returns `Error::Closed` if the channel was dropped and all remaining
+    /// events were received.
+    pub async fn recv(&mut self) -> Result<E, Error> {
+        match self.0.recv().await {
+            Ok(event) => Ok(event),
+            Err(RecvError::Closed) => Err(Error::Closed),
+            Err(RecvError::Lagged(count)) => Err(Error::Lagged(count)),
+        }
+    }
+
+    /// Returns the next event if one is available, without waiting.
+    pub fn try_recv(&mut self) -> Result<Option<E>, Error> {
+        match self.0.try_recv() {
+            Ok(event) => Ok(Some(event)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Closed) => Err(Error::Closed),
+            Err(TryRecvError::Lagged(count)) => Err(Error::Lagged(count)),
+        }
+    }
+}
+
+impl<E> Debug for EventSubscriber<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("EventSubscriber").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lagged_subscriber() {
+        let channel = AsyncEventChannel::with_capacity(2);
+        let mut subscriber = channel.subscribe();
+
+        assert_eq!(subscriber.try_recv().unwrap(), None);
+
+        channel.iter_write(0..4);
+
+        assert!(matches!(subscriber.try_recv(), Err(Error::Lagged(2))));
+        assert_eq!(subscriber.recv().await.unwrap(), 2);
+        assert_eq!(subscriber.recv().await.unwrap(), 3);
+        assert_eq!(subscriber.try_recv().unwrap(), None);
+
+        drop(channel);
+
+        assert!(matches!(subscriber.recv().await, Err(Error::Closed)));
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::vec_deque::{Iter, VecDeque};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::iter::Iterator;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A channel that stores events until every registered reader has read them.
+///
+/// Events are written with `&mut self` and read with `&self`, so the channel
+/// can be stored in the `World` and read from multiple systems in parallel.
+/// Each reader is identified by a `ReaderId` that remembers the position of
+/// the last event it has read. Events that were read by all readers (or that
+/// were written while no reader was registered) are dropped on the next write.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::event::EventChannel;
+///
+/// let mut channel = EventChannel::new();
+/// let mut reader = channel.register_reader();
+///
+/// channel.single_write(1);
+/// channel.iter_write(vec![2, 3]);
+///
+/// assert_eq!(channel.read(&mut reader).copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+/// assert_eq!(channel.read(&mut reader).count(), 0);
+/// ```
+pub struct EventChannel<E> {
+    events: VecDeque<E>,
+    offset: u64,
+    readers: Mutex<Vec<Arc<AtomicU64>>>,
+}
+
+impl<E> EventChannel<E> {
+    /// Create a new empty channel.
+    pub fn new() -> Self {
+        Self {
+            events: VecDeque::new(),
+            offset: 0,
+            readers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers a new reader. The reader will only receive events that
+    /// are written after it was registered.
+    pub fn register_reader(&self) -> ReaderId<E> {
+        let cursor = self.end();
+        let shared = Arc::new(AtomicU64::new(cursor));
+
+        self.readers.lock().unwrap().push(shared.clone());
+
+        ReaderId {
+            cursor,
+            shared,
+            marker: PhantomData,
+        }
+    }
+
+    /// Writes a single event into the channel.
+    pub fn single_write(&mut self, event: E) {
+        self.prune();
+
+        self.events.push_back(event);
+    }
+
+    /// Writes all events of the passed iterator into the channel.
+    pub fn iter_write<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = E>,
+    {
+        self.prune();
+
+        self.events.extend(iter);
+    }
+
+    /// Reads all events the passed reader has not seen yet, and marks
+    /// them as read for this reader.
+    pub fn read(&self, reader: &mut ReaderId<E>) -> EventIter<'_, E> {
+        let start = reader.cursor.max(self.offset) - self.offset;
+        let end = self.end();
+
+        reader.cursor = end;
+        reader.shared.store(end, Ordering::Release);
+
+        EventIter(self.events.range(start as usize..))
+    }
+
+    /// Returns the number of events that are currently stored.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if no events are currently stored.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the position after the last written event.
+    fn end(&self) -> u64 {
+        self.offset + self.events.len() as u64
+    }
+
+    /// Removes all events that were read by every registered reader.
+    fn prune(&mut self) {
+        let end = self.end();
+        let readers = self.readers.get_mut().unwrap();
+
+        readers.retain(|shared| Arc::strong_count(shared) > 1);
+
+        let min = readers
+            .iter()
+            .map(|shared| shared.load(Ordering::Acquire))
+            .min()
+            .unwrap_or(end);
+
+        let count = (min.max(self.offset) - self.offset) as usize;
+
+        self.events.drain(..count);
+        self.offset += count as u64;
+    }
+}
+
+impl<E> Default for EventChannel<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Debug for EventChannel<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("EventChannel")
+            .field("len", &self.events.len())
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+/* ReaderId */
+
+/// Id of a reader of an `EventChannel`. The reader remembers which events
+/// it has already read.
+///
+/// Dropping the reader unregisters it from the channel.
+pub struct ReaderId<E> {
+    cursor: u64,
+    shared: Arc<AtomicU64>,
+    marker: PhantomData<fn(E)>,
+}
+
+impl<E> Debug for ReaderId<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("ReaderId")
+            .field("cursor", &self.cursor)
+            .finish()
+    }
+}
+
+/* EventIter */
+
+/// Iterator over the events of an `EventChannel` returned by
+/// `EventChannel::read`.
+pub struct EventIter<'a, E>(Iter<'a, E>);
+
+impl<'a, E> Iterator for EventIter<'a, E> {
+    type Item = &'a E;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn independent_readers() {
+        let mut channel = EventChannel::new();
+        let mut a = channel.register_reader();
+
+        channel.single_write(1);
+
+        let mut b = channel.register_reader();
+
+        channel.single_write(2);
+
+        assert_eq!(
+            channel.read(&mut a).copied().collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(channel.read(&mut b).copied().collect::<Vec<_>>(), vec![2]);
+
+        channel.single_write(3);
+
+        assert_eq!(channel.read(&mut b).copied().collect::<Vec<_>>(), vec![3]);
+        assert_eq!(channel.read(&mut a).copied().collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn read_events_are_dropped() {
+        let mut channel = EventChannel::new();
+
+        channel.iter_write(0..10);
+        channel.single_write(10);
+        assert_eq!(channel.len(), 1);
+
+        let mut reader = channel.register_reader();
+
+        channel.iter_write(0..10);
+        assert_eq!(channel.len(), 10);
+
+        assert_eq!(channel.read(&mut reader).count(), 10);
+
+        channel.single_write(10);
+        assert_eq!(channel.len(), 1);
+
+        drop(reader);
+
+        channel.single_write(11);
+        assert_eq!(channel.len(), 1);
+    }
+}
@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Event channel was closed!")]
+    Closed,
+
+    #[error("Subscriber lagged behind, {0} events were skipped!")]
+    Lagged(u64),
+}
@@ -0,0 +1,7 @@
+mod async_channel;
+mod channel;
+mod error;
+
+pub use async_channel::{AsyncEventChannel, EventSubscriber};
+pub use channel::{EventChannel, EventIter, ReaderId};
+pub use error::Error;
@@ -0,0 +1,59 @@
+//! Parent/child relations between entities.
+//!
+//! The parent of an entity is stored in its `Parent` component. The
+//! `Hierarchy` resource keeps track of the inverse relation, so the children,
+//! ancestors and descendants of an entity can be looked up efficiently. The
+//! hierarchy is updated from the change events of the `Parent` storage on
+//! each `World::maintain`.
+//!
+//! ## Examples
+//!
+//! ```
+//! use async_ecs::{
+//!     hierarchy::{Hierarchy, Parent},
+//!     *,
+//! };
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let mut world = World::default();
+//! world.register_hierarchy();
+//!
+//! let root = world.create_entity().build();
+//! let child = world.create_entity().with(Parent(root)).build();
+//! let grandchild = world.create_entity().with(Parent(child)).build();
+//!
+//! world.maintain().await;
+//!
+//! {
+//!     let hierarchy = world.resource::<Hierarchy>();
+//!     assert_eq!(hierarchy.children(root), &[child]);
+//!     assert_eq!(hierarchy.ancestors(grandchild).collect::<Vec<_>>(), vec![child, root]);
+//! }
+//!
+//! world.delete_entity_with_descendants(root).unwrap();
+//!
+//! assert!(!world.is_alive(child));
+//! assert!(!world.is_alive(grandchild));
+//! # }
+//! ```
+
+mod tree;
+
+pub use tree::{Ancestors, Hierarchy};
+
+use crate::{
+    component::Component,
+    entity::Entity,
+    storage::{DenseVecStorage, FlaggedStorage},
+};
+
+/// Component that refers to the parent of an entity.
+///
+/// Parents that would create a cycle are ignored by the `Hierarchy`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Parent(pub Entity);
+
+impl Component for Parent {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
@@ -0,0 +1,321 @@
+use std::collections::VecDeque;
+
+use hashbrown::HashMap;
+use hibitset::BitSet;
+use log::warn;
+
+use crate::{
+    access::ReadStorage,
+    entity::{entities::Error as EntitiesError, Entities, Entity, Index},
+    event::ReaderId,
+    join::Join,
+    storage::ComponentEvent,
+};
+
+use super::Parent;
+
+/// Resource that stores the children of each entity, as described by the
+/// `Parent` components.
+///
+/// The hierarchy is updated from the change events of the `Parent` storage
+/// using `maintain`, which is called by `World::maintain` if the hierarchy
+/// was registered using `World::register_hierarchy`. Until then changes of
+/// the `Parent` components are not reflected by the hierarchy.
+#[derive(Default)]
+pub struct Hierarchy {
+    parents: HashMap<Index, (Entity, Entity)>,
+    children: HashMap<Entity, Vec<Entity>>,
+    reader: Option<ReaderId<ComponentEvent>>,
+}
+
+impl Hierarchy {
+    /// Create a new empty hierarchy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the parent of the passed entity.
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        match self.parents.get(&entity.index()) {
+            Some((child, parent)) if *child == entity => Some(*parent),
+            _ => None,
+        }
+    }
+
+    /// Returns the children of the passed entity.
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        self.children.get(&entity).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns an iterator over the ancestors of the passed entity, starting
+    /// with its parent.
+    pub fn ancestors(&self, entity: Entity) -> Ancestors<'_> {
+        Ancestors {
+            hierarchy: self,
+            current: entity,
+        }
+    }
+
+    /// Returns all descendants of the passed entity. Each entity is returned
+    /// after its parent.
+    pub fn descendants(&self, entity: Entity) -> Vec<Entity> {
+        let mut ret = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(entity);
+
+        while let Some(entity) = queue.pop_front() {
+            for child in self.children(entity) {
+                ret.push(*child);
+                queue.push_back(*child);
+            }
+        }
+
+        ret
+    }
+
+    /// Returns all alive entities that have children, but no parent.
+    pub fn roots<'a>(&'a self, entities: &'a Entities) -> impl Iterator<Item = Entity> + 'a {
+        self.children
+            .keys()
+            .copied()
+            .filter(move |entity| entities.is_alive(*entity) && self.parent(*entity).is_none())
+    }
+
+    /// Calls the passed function for each parent/child pair of the
+    /// hierarchy. The pairs of an entity and its children are always visited
+    /// after the pair of the entity and its parent, so this can be used to
+    /// propagate data (like transforms) from the roots to the leaves.
+    pub fn propagate<F>(&self, entities: &Entities, mut f: F)
+    where
+        F: FnMut(Entity, Entity),
+    {
+        for root in self.roots(entities) {
+            let mut queue = VecDeque::new();
+            queue.push_back(root);
+
+            while let Some(parent) = queue.pop_front() {
+                for child in self.children(parent) {
+                    f(parent, *child);
+
+                    queue.push_back(*child);
+                }
+            }
+        }
+    }
+
+    /// Deletes the passed entity and all of its descendants. The entities
+    /// are deleted lazily (see `Entities::delete`).
+    pub fn delete_with_descendants(
+        &self,
+        entity: Entity,
+        entities: &Entities,
+    ) -> Result<(), EntitiesError> {
+        entities.delete(entity)?;
+
+        for entity in self.descendants(entity) {
+            if entities.is_alive(entity) {
+                entities.delete(entity)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Updates the hierarchy with the changes of the `Parent` components
+    /// since the last call to this method.
+    ///
+    /// The hierarchy registers itself as a reader of the storage on the
+    /// first call and rebuilds the hierarchy.
+    ///
+    /// `Parent` components that would create a cycle are ignored and a
+    /// warning is logged, so the hierarchy never contains any cycles.
+    pub fn maintain(&mut self, entities: &Entities, parents: &ReadStorage<Parent>) {
+        let mut changed = BitSet::new();
+
+        match &mut self.reader {
+            Some(reader) => {
+                for event in parents.channel().read(reader) {
+                    match *event {
+                        ComponentEvent::Inserted(index) | ComponentEvent::Modified(index) => {
+                            changed.add(index);
+                        }
+                        ComponentEvent::Removed(index) => {
+                            changed.remove(index);
+                            self.remove_index(index);
+                        }
+                    }
+                }
+            }
+            None => {
+                self.reader = Some(parents.register_reader());
+                self.parents.clear();
+                self.children.clear();
+
+                for (entity, parent) in (entities, parents).join() {
+                    self.insert(entity, parent.0);
+                }
+
+                return;
+            }
+        }
+
+        for (entity, parent, _) in (entities, parents, &changed).join() {
+            self.remove_index(entity.index());
+            self.insert(entity, parent.0);
+        }
+    }
+
+    fn insert(&mut self, child: Entity, parent: Entity) {
+        if parent == child || self.ancestors(parent).any(|ancestor| ancestor == child) {
+            warn!(
+                "Parent {} of entity {} is ignored, because it would create a cycle",
+                parent, child
+            );
+
+            return;
+        }
+
+        self.parents.insert(child.index(), (child, parent));
+        self.children.entry(parent).or_default().push(child);
+    }
+
+    fn remove_index(&mut self, index: Index) {
+        let (child, parent) = match self.parents.remove(&index) {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        if let Some(children) = self.children.get_mut(&parent) {
+            children.retain(|c| *c != child);
+
+            if children.is_empty() {
+                self.children.remove(&parent);
+            }
+        }
+    }
+}
+
+/// Iterator over the ancestors of an entity, returned by
+/// `Hierarchy::ancestors`.
+pub struct Ancestors<'a> {
+    hierarchy: &'a Hierarchy,
+    current: Entity,
+}
+
+impl Iterator for Ancestors<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        self.current = self.hierarchy.parent(self.current)?;
+
+        Some(self.current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{entity::Builder, world::World};
+
+    use super::*;
+
+    fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+        entities.sort_by_key(|e| e.index());
+
+        entities
+    }
+
+    #[tokio::test]
+    async fn maintain_from_events() {
+        let mut world = World::default();
+        world.register_hierarchy();
+
+        let a = world.create_entity().build();
+        let b = world.create_entity().with(Parent(a)).build();
+        let c = world.create_entity().with(Parent(a)).build();
+        let d = world.create_entity().with(Parent(b)).build();
+
+        world.maintain().await;
+
+        {
+            let hierarchy = world.resource::<Hierarchy>();
+
+            assert_eq!(sorted(hierarchy.children(a).to_vec()), vec![b, c]);
+            assert_eq!(hierarchy.parent(d), Some(b));
+            assert_eq!(hierarchy.ancestors(d).collect::<Vec<_>>(), vec![b, a]);
+            assert_eq!(sorted(hierarchy.descendants(a)), vec![b, c, d]);
+            assert_eq!(
+                hierarchy.roots(&world.entities()).collect::<Vec<_>>(),
+                vec![a]
+            );
+
+            let mut pairs = Vec::new();
+            hierarchy.propagate(&world.entities(), |parent, child| {
+                pairs.push((parent, child))
+            });
+            assert_eq!(pairs.len(), 3);
+            assert_eq!(pairs[2], (b, d));
+        }
+
+        world
+            .component_mut::<Parent>()
+            .insert(d, Parent(c))
+            .unwrap();
+        world.component_mut::<Parent>().remove(b);
+        world.maintain().await;
+
+        {
+            let hierarchy = world.resource::<Hierarchy>();
+
+            assert_eq!(hierarchy.children(a), &[c]);
+            assert_eq!(hierarchy.children(b), &[] as &[Entity]);
+            assert_eq!(hierarchy.children(c), &[d]);
+            assert_eq!(hierarchy.parent(b), None);
+        }
+
+        world.delete_entity_with_descendants(a).unwrap();
+
+        assert!(world.is_alive(b));
+        assert!(!world.is_alive(c));
+        assert!(!world.is_alive(d));
+        assert_eq!(world.resource::<Hierarchy>().children(a), &[] as &[Entity]);
+    }
+
+    #[tokio::test]
+    async fn ignore_cycles() {
+        let mut world = World::default();
+        world.register_hierarchy();
+
+        let a = world.create_entity().build();
+        let b = world.create_entity().with(Parent(a)).build();
+        let c = world.create_entity().with(Parent(b)).build();
+        let d = world.create_entity().with(Parent(c)).build();
+
+        world.maintain().await;
+
+        world
+            .component_mut::<Parent>()
+            .insert(a, Parent(c))
+            .unwrap();
+        world
+            .component_mut::<Parent>()
+            .insert(d, Parent(d))
+            .unwrap();
+        world.maintain().await;
+
+        {
+            let hierarchy = world.resource::<Hierarchy>();
+
+            assert_eq!(hierarchy.parent(a), None);
+            assert_eq!(hierarchy.parent(d), None);
+            assert_eq!(hierarchy.ancestors(c).collect::<Vec<_>>(), vec![b, a]);
+            assert_eq!(sorted(hierarchy.descendants(a)), vec![b, c]);
+        }
+
+        // roots that were deleted without their descendants are skipped
+        world.delete_entity(a).unwrap();
+
+        let hierarchy = world.resource::<Hierarchy>();
+        assert_eq!(hierarchy.children(a), &[b]);
+        assert_eq!(hierarchy.roots(&world.entities()).count(), 0);
+    }
+}
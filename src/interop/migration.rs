@@ -0,0 +1,125 @@
+use hashbrown::HashMap;
+use specs::{storage::MaskedStorage as SpecsStorage, Join as _, WorldExt};
+
+use crate::{
+    component::Component,
+    entity::{Builder, Entity},
+    resource::Resource,
+    world::World,
+};
+
+/// Maps the entities of a specs `World` to the entities that were created
+/// for them by the `Migration`.
+///
+/// Components that reference other entities are migrated as they are, use
+/// this map to remap them afterwards.
+#[derive(Default, Debug, Clone)]
+pub struct SpecsEntityMap(HashMap<specs::Entity, Entity>);
+
+impl SpecsEntityMap {
+    /// Returns the entity that was created for the passed specs entity.
+    pub fn get(&self, source: specs::Entity) -> Option<Entity> {
+        self.0.get(&source).copied()
+    }
+
+    /// Returns the number of migrated entities.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if no entity was migrated.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over all pairs of entities, with the specs entity first.
+    pub fn iter(&self) -> impl Iterator<Item = (specs::Entity, Entity)> + '_ {
+        self.0.iter().map(|(source, target)| (*source, *target))
+    }
+}
+
+/// Moves the entities, components and resources of a specs `World` into a
+/// `World` of this crate.
+///
+/// An entity is created for each living entity of the specs world as soon
+/// as the migration is created. The components and resources are moved
+/// type by type, because specs does not allow to enumerate them. Migrated
+/// components are removed from the specs world, so the migration can be
+/// done incrementally.
+pub struct Migration<'a> {
+    source: &'a mut specs::World,
+    target: &'a mut World,
+    entities: SpecsEntityMap,
+}
+
+impl<'a> Migration<'a> {
+    /// Create a new migration from `source` to `target`. Pending changes of
+    /// the specs world are applied using `specs::WorldExt::maintain` first.
+    pub fn new(source: &'a mut specs::World, target: &'a mut World) -> Self {
+        source.maintain();
+
+        let mut entities = SpecsEntityMap::default();
+        for entity in (&source.entities()).join() {
+            entities.0.insert(entity, target.create_entity().build());
+        }
+
+        Self {
+            source,
+            target,
+            entities,
+        }
+    }
+
+    /// Moves the components `C` from the specs world into the target world.
+    /// The component is registered in the target world if needed.
+    pub fn with_component<C>(self) -> Self
+    where
+        C: specs::Component + Component,
+        <C as Component>::Storage: Default,
+    {
+        self.target.register_component::<C>();
+
+        if !self.source.has_value::<SpecsStorage<C>>() {
+            return self;
+        }
+
+        {
+            let entities = self.source.entities();
+            let mut source = self.source.write_storage::<C>();
+            let mut target = self.target.component_mut::<C>();
+
+            for (entity, component) in (&entities, source.drain()).join() {
+                if let Some(entity) = self.entities.get(entity) {
+                    target
+                        .insert(entity, component)
+                        .expect("Migrated entity is not alive");
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Moves the resource `R` from the specs world into the target world,
+    /// if it exists. An existing resource of the target world is replaced.
+    pub fn with_resource<R>(self) -> Self
+    where
+        R: Resource,
+    {
+        if let Some(resource) = self.source.remove::<R>() {
+            self.target.insert(resource);
+        }
+
+        self
+    }
+
+    /// Returns the entities that were created by the migration.
+    pub fn entities(&self) -> &SpecsEntityMap {
+        &self.entities
+    }
+
+    /// Finishes the migration and returns the created entities.
+    pub fn finish(self) -> SpecsEntityMap {
+        self.entities
+    }
+}
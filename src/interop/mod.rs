@@ -0,0 +1,177 @@
+//! Compatibility layer for projects that migrate from `specs`.
+//!
+//! Components that implement `specs::Component` can implement `Component`
+//! using the `specs_component!` macro. The storage of the component is
+//! selected based on its specs storage (see `FromSpecsStorage`), so the
+//! same type can be used with both crates while a project is migrated.
+//!
+//! The `Migration` moves the entities, components and resources of a specs
+//! `World` into an `async_ecs::World`.
+//!
+//! ## Examples
+//!
+//! ```
+//! use async_ecs::{interop::Migration, specs_component, *};
+//! use specs::{Builder as _, WorldExt};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Pos(u32);
+//!
+//! impl specs::Component for Pos {
+//!     type Storage = specs::VecStorage<Self>;
+//! }
+//!
+//! specs_component!(Pos);
+//!
+//! #[derive(Default)]
+//! struct Score(u32);
+//!
+//! let mut source = specs::World::new();
+//! source.register::<Pos>();
+//! source.insert(Score(7));
+//!
+//! let entity = source.create_entity().with(Pos(1)).build();
+//!
+//! let mut world = World::default();
+//! let entities = Migration::new(&mut source, &mut world)
+//!     .with_component::<Pos>()
+//!     .with_resource::<Score>()
+//!     .finish();
+//!
+//! let entity = entities.get(entity).unwrap();
+//! assert_eq!(world.component::<Pos>().get(entity), Some(&Pos(1)));
+//! assert_eq!(world.resource::<Score>().0, 7);
+//! ```
+
+mod migration;
+
+pub use migration::{Migration, SpecsEntityMap};
+
+pub use specs;
+
+use crate::storage::{
+    BTreeStorage, DefaultVecStorage, DenseVecStorage, FlaggedStorage, HashMapStorage, NullStorage,
+    VecStorage,
+};
+
+/// Selects the storage of this crate that corresponds to a storage of
+/// `specs`. Used by `specs_component!`.
+pub trait FromSpecsStorage<T> {
+    /// Storage that is used for the component `T`.
+    type Storage;
+}
+
+macro_rules! define_from_specs_storage {
+    ($($storage:ident),*) => {
+        $(
+            impl<T> FromSpecsStorage<T> for specs::storage::$storage<T> {
+                type Storage = $storage<T>;
+            }
+        )*
+    };
+}
+
+define_from_specs_storage!(
+    BTreeStorage,
+    DefaultVecStorage,
+    DenseVecStorage,
+    HashMapStorage,
+    NullStorage,
+    VecStorage
+);
+
+impl<C, T> FromSpecsStorage<C> for specs::FlaggedStorage<C, T>
+where
+    T: FromSpecsStorage<C>,
+{
+    type Storage = FlaggedStorage<C, T::Storage>;
+}
+
+/// Implements `Component` for types that already implement
+/// `specs::Component`, using the storage that corresponds to the specs
+/// storage of the type.
+///
+/// For usage see the [module documentation](interop/index.html).
+#[macro_export]
+macro_rules! specs_component {
+    ($($component:ty),* $(,)?) => {
+        $(
+            impl $crate::component::Component for $component {
+                type Storage = <
+                    <$component as $crate::interop::specs::Component>::Storage
+                    as $crate::interop::FromSpecsStorage<$component>
+                >::Storage;
+            }
+        )*
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use specs::{Builder as _, WorldExt};
+
+    use crate::{join::Join, storage::Tracked, world::World};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl specs::Component for Pos {
+        type Storage = specs::FlaggedStorage<Self, specs::VecStorage<Self>>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Target(specs::Entity);
+
+    impl specs::Component for Target {
+        type Storage = specs::HashMapStorage<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    impl specs::Component for Name {
+        type Storage = specs::DenseVecStorage<Self>;
+    }
+
+    crate::specs_component!(Pos, Target, Name);
+
+    fn is_tracked<S: Tracked>() {}
+
+    #[test]
+    fn migrate_world() {
+        is_tracked::<<Pos as crate::component::Component>::Storage>();
+
+        let mut source = specs::World::new();
+        source.register::<Pos>();
+        source.register::<Target>();
+
+        let a = source.create_entity().with(Pos(1)).build();
+        let b = source.create_entity().with(Target(a)).build();
+        let c = source.create_entity().with(Pos(3)).build();
+        source.delete_entity(c).unwrap();
+
+        let mut world = World::default();
+        let migration = Migration::new(&mut source, &mut world)
+            .with_component::<Pos>()
+            .with_component::<Name>();
+
+        assert_eq!(migration.entities().len(), 2);
+
+        let entities = migration.with_component::<Target>().finish();
+        let a = entities.get(a).unwrap();
+        let b = entities.get(b).unwrap();
+
+        assert_eq!(world.component::<Pos>().get(a), Some(&Pos(1)));
+        assert_eq!(world.component::<Pos>().count(), 1);
+        assert_eq!(world.component::<Name>().count(), 0);
+
+        let targets = world.component::<Target>();
+        let target = targets.get(b).and_then(|t| entities.get(t.0));
+        assert_eq!(target, Some(a));
+
+        assert_eq!(source.read_storage::<Pos>().count(), 0);
+        assert_eq!((&world.entities()).join().count(), 2);
+    }
+}
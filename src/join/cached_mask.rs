@@ -0,0 +1,205 @@
+use hibitset::BitSet;
+
+use super::Join;
+
+/// Join whose mask is derived from storages that report the generation of
+/// their masks (see `MaskedStorage::generation`).
+pub trait MaskGeneration {
+    /// Returns the combined generation of the masks of this join. The value
+    /// changes whenever one of the masks changes.
+    fn mask_generation(&self) -> u64;
+}
+
+/// Combined mask of a join that is stored by the user and only recomputed
+/// if one of the joined storages was changed.
+///
+/// Systems that run multiple passes over the same set of entities can store
+/// the cached mask and join it instead of the storages that define the set.
+/// Checking if the cached mask is still valid is a cheap comparison of the
+/// generations of the storage masks. The generations are unique across all
+/// storages, so a mask cached for the storages of one world is never valid
+/// for the storages of another one. A cached mask should still only be used
+/// with one single join.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{join::CachedMask, *};
+/// #
+/// # struct Pos(u32);
+/// # impl Component for Pos { type Storage = VecStorage<Self>; }
+/// #
+/// # struct Vel(u32);
+/// # impl Component for Vel { type Storage = VecStorage<Self>; }
+/// #
+/// let mut world = World::default();
+/// world.register_component::<Pos>();
+/// world.register_component::<Vel>();
+///
+/// world.create_entity().with(Pos(0)).with(Vel(1)).build();
+/// world.create_entity().with(Pos(0)).build();
+///
+/// let mut moving = CachedMask::new();
+///
+/// let mut pos = world.component_mut::<Pos>();
+/// let vel = world.component::<Vel>();
+///
+/// for _ in 0..2 {
+///     let mask = moving.update((&pos, &vel));
+///
+///     for (pos, _) in (&mut pos, mask).join() {
+///         pos.0 += 1;
+///     }
+/// }
+///
+/// let values: Vec<_> = (&pos).join().map(|pos| pos.0).collect();
+/// assert_eq!(values, vec![2, 0]);
+/// ```
+#[derive(Default)]
+pub struct CachedMask {
+    mask: BitSet,
+    generation: Option<u64>,
+}
+
+impl CachedMask {
+    /// Create a new, invalid cached mask.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached mask, without checking if it is still valid.
+    pub fn mask(&self) -> &BitSet {
+        &self.mask
+    }
+
+    /// Returns `true` if the cached mask is still the combined mask of the
+    /// passed join.
+    pub fn is_valid<J>(&self, join: &J) -> bool
+    where
+        J: MaskGeneration,
+    {
+        self.generation == Some(join.mask_generation())
+    }
+
+    /// Marks the cached mask as invalid, so it is recomputed on the next
+    /// call to `update`.
+    pub fn invalidate(&mut self) {
+        self.generation = None;
+    }
+
+    /// Recomputes the combined mask of the passed join if the cached mask is
+    /// not valid anymore, and returns it.
+    pub fn update<J>(&mut self, join: J) -> &BitSet
+    where
+        J: Join + MaskGeneration,
+    {
+        let generation = join.mask_generation();
+
+        if self.generation != Some(generation) {
+            self.mask = join.mask();
+            self.generation = Some(generation);
+        }
+
+        &self.mask
+    }
+}
+
+macro_rules! define_tuple_mask_generation {
+    ($($from:ident),*) => {
+        impl<$($from,)*> MaskGeneration for ($($from),*,)
+        where
+            $($from: MaskGeneration),*,
+        {
+            #[allow(non_snake_case)]
+            fn mask_generation(&self) -> u64 {
+                let ($($from,)*) = self;
+
+                // The generations of the masks are unique and each change of
+                // a mask assigns a greater one, so the maximum changes
+                // whenever one of the masks changes.
+                0u64 $( .max($from.mask_generation()) )*
+            }
+        }
+    }
+}
+
+define_tuple_mask_generation! { A }
+define_tuple_mask_generation! { A, B }
+define_tuple_mask_generation! { A, B, C }
+define_tuple_mask_generation! { A, B, C, D }
+define_tuple_mask_generation! { A, B, C, D, E }
+define_tuple_mask_generation! { A, B, C, D, E, F }
+define_tuple_mask_generation! { A, B, C, D, E, F, G }
+define_tuple_mask_generation! { A, B, C, D, E, F, G, H }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        component::Component, entity::Builder, join::Join, storage::VecStorage, world::World,
+    };
+
+    use super::*;
+
+    struct Pos;
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    struct Vel;
+
+    impl Component for Vel {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn recomputed_on_change() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Vel>();
+
+        let e1 = world.create_entity().with(Pos).with(Vel).build();
+        let e2 = world.create_entity().with(Pos).build();
+
+        let mut cached = CachedMask::new();
+        let mut pos = world.component_mut::<Pos>();
+        let mut vel = world.component_mut::<Vel>();
+
+        assert!(!cached.is_valid(&(&pos, &vel)));
+        assert_eq!(cached.update((&pos, &vel)), &(&pos, &vel).mask());
+        assert!(cached.is_valid(&(&pos, &vel)));
+
+        pos.insert(e1, Pos).unwrap();
+        assert!(cached.is_valid(&(&pos, &vel)));
+
+        vel.insert(e2, Vel).unwrap();
+        assert!(!cached.is_valid(&(&pos, &vel)));
+        assert!(cached.update((&pos, &vel)).contains(e2.index()));
+
+        pos.remove(e1);
+        assert!(!cached.is_valid(&(&pos, &vel)));
+        assert!(!cached.update((&pos, &vel)).contains(e1.index()));
+
+        cached.invalidate();
+        assert!(!cached.is_valid(&(&pos, &vel)));
+    }
+
+    #[test]
+    fn invalid_for_other_world() {
+        let mut cached = CachedMask::new();
+
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        let entity = world.create_entity().with(Pos).build();
+
+        let pos = world.component::<Pos>();
+        assert!(cached.update((&pos,)).contains(entity.index()));
+
+        let mut other = World::default();
+        other.register_component::<Pos>();
+
+        let pos = other.component::<Pos>();
+        assert!(!cached.is_valid(&(&pos,)));
+        assert!(!cached.update((&pos,)).contains(entity.index()));
+    }
+}
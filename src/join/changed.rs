@@ -0,0 +1,265 @@
+use hibitset::{BitSet, BitSetLike};
+
+use crate::{
+    entity::Index,
+    storage::{advance_tick, Tick, Tracked},
+};
+
+use super::{Join, ParJoin};
+
+/// Per-system state that remembers which changes of a tracked storage were
+/// already observed.
+///
+/// A tracker should only be used with one single storage.
+#[derive(Default)]
+pub struct ChangeTracker {
+    cursor: Tick,
+    changed: BitSet,
+}
+
+impl ChangeTracker {
+    /// Create a new tracker. All components that are currently stored
+    /// are reported as changed on first use.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks all changes until now as seen, without joining over them.
+    pub fn mark_seen(&mut self) {
+        self.cursor = advance_tick();
+    }
+
+    /// Returns the set of indices that were reported as changed the last
+    /// time this tracker was used in a join.
+    pub fn changed(&self) -> &BitSet {
+        &self.changed
+    }
+
+    fn update<M, S>(&mut self, mask: M, storage: &S)
+    where
+        M: BitSetLike,
+        S: Tracked + ?Sized,
+    {
+        self.changed.clear();
+
+        for index in mask.iter() {
+            if storage.last_changed(index) >= self.cursor {
+                self.changed.add(index);
+            }
+        }
+
+        self.mark_seen();
+    }
+}
+
+/// A `Join`-able structure that only yields the components of the wrapped
+/// storage that were changed since the tracker has seen them the last time.
+///
+/// The tracker is only advanced when the join is actually opened (by
+/// calling `join` or `par_join`), so a system that returns early does not
+/// lose any changes.
+///
+/// For usage see `StorageWrapper::changed_since`.
+pub struct ChangedSince<'t, J> {
+    join: J,
+    tracker: &'t mut ChangeTracker,
+}
+
+impl<'t, J> ChangedSince<'t, J> {
+    /// Create a new join adapter for the passed join and tracker.
+    pub fn new(join: J, tracker: &'t mut ChangeTracker) -> Self {
+        Self { join, tracker }
+    }
+}
+
+impl<'a, 't, J, S> Join for ChangedSince<'t, J>
+where
+    J: Join<Value = &'a S>,
+    S: Tracked + ?Sized + 'a,
+{
+    type Mask = &'t BitSet;
+    type Type = J::Type;
+    type Value = J::Value;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let (mask, value) = self.join.open();
+
+        self.tracker.update(mask, value);
+
+        let tracker: &'t ChangeTracker = self.tracker;
+
+        (&tracker.changed, value)
+    }
+
+    unsafe fn get(value: &mut Self::Value, index: Index) -> Self::Type {
+        J::get(value, index)
+    }
+}
+
+impl<'a, 't, J, S> ParJoin for ChangedSince<'t, J>
+where
+    J: ParJoin<Value = &'a S>,
+    S: Tracked + ?Sized + 'a,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use asparit::{Driver, ParallelIterator};
+
+    use crate::{
+        component::Component,
+        entity::Builder,
+        storage::{FlaggedStorage, VecStorage},
+        world::World,
+    };
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Vel(u32);
+
+    impl Component for Vel {
+        type Storage = VecStorage<Self>;
+    }
+
+    fn setup() -> World {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Vel>();
+        world
+    }
+
+    #[test]
+    fn insert_counts_as_changed() {
+        let mut world = setup();
+        let mut tracker = ChangeTracker::new();
+
+        world.create_entity().with(Pos(1)).build();
+
+        let pos = world.component::<Pos>();
+        assert_eq!(pos.changed_since(&mut tracker).join().count(), 1);
+        assert_eq!(pos.changed_since(&mut tracker).join().count(), 0);
+        drop(pos);
+
+        let e = world.create_entity().with(Pos(2)).build();
+
+        let pos = world.component::<Pos>();
+        let changed: Vec<_> = (&world.entities(), pos.changed_since(&mut tracker))
+            .join()
+            .collect();
+        assert_eq!(changed, vec![(e, &Pos(2))]);
+    }
+
+    #[test]
+    fn removed_components_are_not_reported() {
+        let mut world = setup();
+        let mut tracker = ChangeTracker::new();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+
+        {
+            let mut pos = world.component_mut::<Pos>();
+            pos.get_mut(e1).unwrap().0 = 3;
+            pos.remove(e1);
+        }
+
+        let pos = world.component::<Pos>();
+        let changed: Vec<_> = (&world.entities(), pos.changed_since(&mut tracker))
+            .join()
+            .collect();
+        assert_eq!(changed, vec![(e2, &Pos(2))]);
+    }
+
+    #[test]
+    fn multiple_independent_trackers() {
+        let mut world = setup();
+        let mut a = ChangeTracker::new();
+        let mut b = ChangeTracker::new();
+
+        world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+
+        assert_eq!(
+            world
+                .component::<Pos>()
+                .changed_since(&mut a)
+                .join()
+                .count(),
+            2
+        );
+
+        world.component_mut::<Pos>().get_mut(e2).unwrap().0 = 4;
+
+        let pos = world.component::<Pos>();
+        assert_eq!(
+            pos.changed_since(&mut a).join().collect::<Vec<_>>(),
+            vec![&Pos(4)]
+        );
+        assert_eq!(pos.changed_since(&mut b).join().count(), 2);
+        assert_eq!(pos.changed_since(&mut a).join().count(), 0);
+        assert_eq!(pos.changed_since(&mut b).join().count(), 0);
+    }
+
+    #[test]
+    fn changes_are_kept_until_joined() {
+        let mut world = setup();
+        let mut tracker = ChangeTracker::new();
+
+        world.create_entity().with(Pos(1)).build();
+
+        {
+            let pos = world.component::<Pos>();
+            let _unused = pos.changed_since(&mut tracker);
+        }
+
+        assert_eq!(
+            world
+                .component::<Pos>()
+                .changed_since(&mut tracker)
+                .join()
+                .count(),
+            1
+        );
+
+        world.create_entity().with(Pos(2)).build();
+        tracker.mark_seen();
+
+        assert_eq!(
+            world
+                .component::<Pos>()
+                .changed_since(&mut tracker)
+                .join()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn compose_with_other_storages() {
+        let mut world = setup();
+        let mut tracker = ChangeTracker::new();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).with(Vel(1)).build();
+
+        let mut pos = world.component_mut::<Pos>();
+        let vel = world.component::<Vel>();
+
+        (pos.changed_since_mut(&mut tracker), &vel)
+            .par_join()
+            .for_each(|(pos, vel)| pos.0 += vel.0)
+            .exec();
+
+        assert_eq!(pos.get(e1), Some(&Pos(1)));
+        assert_eq!(pos.get(e2), Some(&Pos(3)));
+    }
+}
@@ -0,0 +1,99 @@
+use asparit::{DefaultExecutor, Driver, Executor};
+use futures::future::{BoxFuture, FutureExt};
+use tokio::task::block_in_place;
+
+/// Extension of the asparit `Driver` to execute a parallel iterator (like
+/// the one returned by `ParJoin::par_join`) from inside an `AsyncSystem`.
+///
+/// The executor is run inside `tokio::task::block_in_place`, so the runtime
+/// hands over the other tasks of the current worker thread to a new worker,
+/// instead of blocking them until the iteration is finished. This requires
+/// the multi-threaded tokio runtime and the `multi-thread` feature of this
+/// crate.
+///
+/// The executor that is used by `exec_async` is asparit's `DefaultExecutor`.
+/// Enable the `rayon` feature of this crate to execute the iteration on the
+/// global rayon pool, or use `exec_async_with` to pass a custom executor.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::*;
+/// # use async_ecs::asparit::ParallelIterator;
+/// # use futures::future::{BoxFuture, FutureExt};
+/// #
+/// struct Pos(u32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Move;
+///
+/// impl<'a> AsyncSystem<'a> for Move {
+///     type SystemData = WriteStorage<'a, Pos>;
+///
+///     fn run_async(&mut self, mut pos: Self::SystemData) -> BoxFuture<'a, ()> {
+///         async move {
+///             (&mut pos)
+///                 .par_join()
+///                 .for_each(|pos| pos.0 += 1)
+///                 .exec_async()
+///                 .await;
+///         }
+///         .boxed()
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// world.register_component::<Pos>();
+///
+/// let entity = world.create_entity().with(Pos(1)).build();
+///
+/// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with_async(Move, "move", &[])
+///     .unwrap()
+///     .build();
+///
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// assert_eq!(world.component::<Pos>().get(entity).unwrap().0, 2);
+/// # }
+/// ```
+pub trait AsyncDriver<'a, T1, T2 = (), T3 = ()>: Driver<'a, T1, T2, T3>
+where
+    T1: Send + 'a,
+    T2: Send + 'a,
+    T3: Send + 'a,
+{
+    /// Executes the driver with the default executor, without blocking the
+    /// current worker thread of the tokio runtime.
+    fn exec_async(self) -> BoxFuture<'a, T1>
+    where
+        Self: Send + 'a,
+        DefaultExecutor: Executor<'a, T1, T2, T3, Result = T1>,
+    {
+        self.exec_async_with(DefaultExecutor::default())
+    }
+
+    /// Executes the driver with the passed executor, without blocking the
+    /// current worker thread of the tokio runtime.
+    fn exec_async_with<E>(self, executor: E) -> BoxFuture<'a, T1>
+    where
+        Self: Send + 'a,
+        E: Executor<'a, T1, T2, T3, Result = T1> + Send + 'a,
+    {
+        async move { block_in_place(move || self.exec_with(executor)) }.boxed()
+    }
+}
+
+impl<'a, D, T1, T2, T3> AsyncDriver<'a, T1, T2, T3> for D
+where
+    D: Driver<'a, T1, T2, T3>,
+    T1: Send + 'a,
+    T2: Send + 'a,
+    T3: Send + 'a,
+{
+}
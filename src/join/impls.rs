@@ -1,9 +1,14 @@
 use std::ops::{Deref, DerefMut};
 
+use hibitset::{
+    AtomicBitSet, BitSet, BitSetAll, BitSetAnd, BitSetLike, BitSetNot as HiBitSetNot, BitSetOr,
+    BitSetXor,
+};
+
 use crate::{
     access::{read::Read, write::Write},
     entity::Index,
-    misc::BitAnd,
+    misc::{BitAnd, BitSetNot},
     resource::{Ref, RefMut, Resource},
 };
 
@@ -144,3 +149,139 @@ macro_rules! define_immutable_join {
 
 define_immutable_join!(Read<'b, T>);
 define_immutable_join!(Ref<'b, T>);
+
+macro_rules! define_bit_join {
+    (impl<($($lifetime:tt)*)($($arg:ident),*)> for $bitset:ty) => {
+        impl<$($lifetime,)* $($arg),*> Join for $bitset
+        where
+            $($arg: BitSetLike),*
+        {
+            type Type = Index;
+            type Value = ();
+            type Mask = $bitset;
+
+            unsafe fn open(self) -> (Self::Mask, Self::Value) {
+                (self, ())
+            }
+
+            unsafe fn get(_: &mut Self::Value, index: Index) -> Self::Type {
+                index
+            }
+        }
+
+        impl<$($lifetime,)* $($arg),*> ParJoin for $bitset
+        where
+            $($arg: BitSetLike),*
+        {
+        }
+    };
+}
+
+define_bit_join!(impl<()()> for BitSet);
+define_bit_join!(impl<('a)()> for &'a BitSet);
+define_bit_join!(impl<()()> for AtomicBitSet);
+define_bit_join!(impl<('a)()> for &'a AtomicBitSet);
+define_bit_join!(impl<()()> for BitSetAll);
+define_bit_join!(impl<('a)()> for &'a BitSetAll);
+define_bit_join!(impl<('a)()> for &'a dyn BitSetLike);
+define_bit_join!(impl<()(A)> for BitSetNot<A>);
+define_bit_join!(impl<('a)(A)> for &'a BitSetNot<A>);
+define_bit_join!(impl<()(A)> for HiBitSetNot<A>);
+define_bit_join!(impl<('a)(A)> for &'a HiBitSetNot<A>);
+define_bit_join!(impl<()(A, B)> for BitSetAnd<A, B>);
+define_bit_join!(impl<('a)(A, B)> for &'a BitSetAnd<A, B>);
+define_bit_join!(impl<()(A, B)> for BitSetOr<A, B>);
+define_bit_join!(impl<('a)(A, B)> for &'a BitSetOr<A, B>);
+define_bit_join!(impl<()(A, B)> for BitSetXor<A, B>);
+define_bit_join!(impl<('a)(A, B)> for &'a BitSetXor<A, B>);
+
+#[cfg(test)]
+mod tests {
+    use asparit::{Driver, ParallelIterator};
+
+    use crate::{
+        access::ReadStorage, component::Component, entity::Builder, storage::VecStorage,
+        system::SystemData, world::World,
+    };
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn join_bit_sets() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let entities = (0..5)
+            .map(|i| world.create_entity().with(Pos(i)).build())
+            .collect::<Vec<_>>();
+
+        let mut filter = BitSet::new();
+        filter.add(entities[1].index());
+        filter.add(entities[3].index());
+
+        let mut other = BitSet::new();
+        other.add(entities[3].index());
+        other.add(entities[4].index());
+
+        let pos = ReadStorage::<Pos>::fetch(&world);
+
+        let found: Vec<_> = (&pos, &filter).join().map(|(pos, _)| pos.0).collect();
+        assert_eq!(found, vec![1, 3]);
+
+        let found: Vec<_> = (&pos, BitSetAnd(&filter, &other))
+            .join()
+            .map(|(pos, _)| pos.0)
+            .collect();
+        assert_eq!(found, vec![3]);
+
+        let found: Vec<_> = (&pos, BitSetOr(&filter, &other))
+            .join()
+            .map(|(pos, _)| pos.0)
+            .collect();
+        assert_eq!(found, vec![1, 3, 4]);
+
+        let found: Vec<_> = (&pos, BitSetNot(&filter))
+            .join()
+            .map(|(pos, _)| pos.0)
+            .collect();
+        assert_eq!(found, vec![0, 2, 4]);
+
+        let indices: Vec<_> = (&filter).join().collect();
+        assert_eq!(indices, vec![entities[1].index(), entities[3].index()]);
+
+        let sum = (&pos, &filter)
+            .par_join()
+            .map(|(pos, _)| pos.0)
+            .sum::<u32>()
+            .exec();
+        assert_eq!(sum, 4);
+    }
+
+    #[test]
+    fn filter_mask() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let entities = (0..5)
+            .map(|i| world.create_entity().with(Pos(i)).build())
+            .collect::<Vec<_>>();
+
+        let pos = ReadStorage::<Pos>::fetch(&world);
+        let entities_res = world.entities();
+
+        let even = (&pos).filter_mask(|pos| pos.0 % 2 == 0);
+        let found: Vec<_> = (&entities_res, &even).join().map(|(e, _)| e).collect();
+        assert_eq!(found, vec![entities[0], entities[2], entities[4]]);
+
+        let odd = (&pos, BitSetNot(&even)).filter_mask(|(pos, _)| pos.0 > 1);
+        let found: Vec<_> = (&pos, &odd).join().map(|(pos, _)| pos.0).collect();
+        assert_eq!(found, vec![3]);
+    }
+}
@@ -5,7 +5,7 @@ use log::warn;
 
 use crate::entity::{Entities, Entity};
 
-use super::Join;
+use super::{Join, WithId};
 
 /// `JoinIter` is an `Iterator` over a group of `Storages`.
 pub struct JoinIter<J: Join> {
@@ -85,6 +85,39 @@ impl<J: Join> JoinIter<J> {
             None
         }
     }
+
+    /// Yields the index of each joined entity together with the joined
+    /// values.
+    ///
+    /// In contrast to joining the `Entities`, this does not need to fetch
+    /// the entities resource and does not build the entity from its
+    /// generation.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// # struct Pos(u32);
+    /// # impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// #
+    /// let mut world = World::default();
+    /// world.register_component::<Pos>();
+    ///
+    /// let e1 = world.create_entity().with(Pos(1)).build();
+    /// let e2 = world.create_entity().with(Pos(2)).build();
+    ///
+    /// let pos = world.component::<Pos>();
+    /// let ids: Vec<_> = (&pos).join().with_id().map(|(id, _)| id).collect();
+    ///
+    /// assert_eq!(ids, vec![e1.index(), e2.index()]);
+    /// ```
+    pub fn with_id(self) -> JoinIter<WithId<J>> {
+        JoinIter {
+            keys: self.keys,
+            values: self.values,
+        }
+    }
 }
 
 impl<J: Join> Iterator for JoinIter<J> {
@@ -1,13 +1,32 @@
+mod cached_mask;
+mod changed;
+#[cfg(feature = "multi-thread")]
+mod exec;
 mod impls;
 mod iter;
 mod maybe;
+mod ordered;
 mod parallel;
+mod with_id;
 
+pub use cached_mask::{CachedMask, MaskGeneration};
+pub use changed::{ChangeTracker, ChangedSince};
+#[cfg(feature = "multi-thread")]
+pub use exec::AsyncDriver;
 pub use iter::JoinIter;
 pub use maybe::MaybeJoin;
+pub use ordered::OrderedJoin;
 pub use parallel::JoinParIter;
+pub use with_id::WithId;
 
-use hibitset::BitSetLike;
+#[cfg(feature = "multi-thread")]
+use asparit::{DefaultExecutor, Executor, FromParallelIterator, ParallelIterator};
+#[cfg(feature = "multi-thread")]
+use futures::future::BoxFuture;
+use std::cmp::Ordering;
+use std::vec::IntoIter;
+
+use hibitset::{BitSet, BitSetLike};
 
 use crate::entity::Index;
 
@@ -166,6 +185,162 @@ pub trait Join {
         MaybeJoin(self)
     }
 
+    /// Returns an iterator over the joined values, that yields them in
+    /// ascending order of the entity indices.
+    ///
+    /// `join` does currently yield the same order, but this is not
+    /// guaranteed. Use this method if the processing depends on the order.
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// # struct Pos(u32);
+    /// # impl Component for Pos { type Storage = HashMapStorage<Self>; }
+    /// #
+    /// let mut world = World::default();
+    /// world.register_component::<Pos>();
+    ///
+    /// let e1 = world.create_entity().build();
+    /// let e2 = world.create_entity().build();
+    ///
+    /// let mut pos = world.component_mut::<Pos>();
+    /// pos.insert(e2, Pos(2)).unwrap();
+    /// pos.insert(e1, Pos(1)).unwrap();
+    ///
+    /// let values: Vec<_> = (&pos).ordered().map(|pos| pos.0).collect();
+    /// assert_eq!(values, vec![1, 2]);
+    /// ```
+    fn ordered(self) -> OrderedJoin<Self>
+    where
+        Self: Sized,
+    {
+        OrderedJoin::new(self)
+    }
+
+    /// Collects the joined values and sorts them using the passed comparator.
+    ///
+    /// The sort is stable, so values that compare equal are yielded in
+    /// ascending order of the entity indices. This makes the order fully
+    /// deterministic.
+    fn sorted_by<F>(self, compare: F) -> IntoIter<Self::Type>
+    where
+        Self: Sized,
+        F: FnMut(&Self::Type, &Self::Type) -> Ordering,
+    {
+        let mut values = self.ordered().collect::<Vec<_>>();
+        values.sort_by(compare);
+
+        values.into_iter()
+    }
+
+    /// Collects the joined values and sorts them by the key that is
+    /// extracted by the passed function, usually from a key component that
+    /// is part of the join. See `sorted_by` for details.
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// # struct Name(&'static str);
+    /// # impl Component for Name { type Storage = VecStorage<Self>; }
+    /// #
+    /// # struct Layer(u32);
+    /// # impl Component for Layer { type Storage = VecStorage<Self>; }
+    /// #
+    /// let mut world = World::default();
+    /// world.register_component::<Name>();
+    /// world.register_component::<Layer>();
+    ///
+    /// world.create_entity().with(Name("top")).with(Layer(2)).build();
+    /// world.create_entity().with(Name("bottom")).with(Layer(0)).build();
+    /// world.create_entity().with(Name("middle")).with(Layer(1)).build();
+    ///
+    /// let names = world.component::<Name>();
+    /// let layers = world.component::<Layer>();
+    ///
+    /// let names: Vec<_> = (&names, &layers)
+    ///     .sorted_by_key(|(_, layer)| layer.0)
+    ///     .map(|(name, _)| name.0)
+    ///     .collect();
+    ///
+    /// assert_eq!(names, vec!["bottom", "middle", "top"]);
+    /// ```
+    fn sorted_by_key<K, F>(self, mut f: F) -> IntoIter<Self::Type>
+    where
+        Self: Sized,
+        K: Ord,
+        F: FnMut(&Self::Type) -> K,
+    {
+        self.sorted_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Computes the combined mask of this join and returns it as `BitSet`.
+    ///
+    /// Like the bit set of `filter_mask`, the returned bit set is
+    /// independent of the joined storages. Use `CachedMask` to only compute
+    /// the mask again if one of the storages was changed.
+    fn mask(self) -> BitSet
+    where
+        Self: Sized,
+    {
+        let (mask, _) = unsafe { self.open() };
+
+        mask.iter().collect()
+    }
+
+    /// Creates a bit set of all indices of this join, whose values match the
+    /// passed predicate.
+    ///
+    /// The returned bit set is independent of the joined storages, so it
+    /// can be passed to later joins (even of storages that are fetched
+    /// mutably) to restrict them to the matching entities. This is useful
+    /// for algorithms that need multiple passes over the same subset of
+    /// entities.
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// # struct Health(u32);
+    /// # impl Component for Health { type Storage = VecStorage<Self>; }
+    /// #
+    /// # struct Pos(u32);
+    /// # impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// #
+    /// let mut world = World::default();
+    /// world.register_component::<Health>();
+    /// world.register_component::<Pos>();
+    ///
+    /// let alive = world.create_entity().with(Health(10)).with(Pos(0)).build();
+    /// let dead = world.create_entity().with(Health(0)).with(Pos(0)).build();
+    ///
+    /// let health = world.component::<Health>();
+    /// let mut pos = world.component_mut::<Pos>();
+    ///
+    /// let mask = (&health).filter_mask(|health| health.0 > 0);
+    ///
+    /// for (pos, _) in (&mut pos, &mask).join() {
+    ///     pos.0 += 1;
+    /// }
+    ///
+    /// assert_eq!(pos.get(alive).unwrap().0, 1);
+    /// assert_eq!(pos.get(dead).unwrap().0, 0);
+    /// ```
+    fn filter_mask<P>(self, mut predicate: P) -> BitSet
+    where
+        Self: Sized,
+        P: FnMut(Self::Type) -> bool,
+    {
+        let (mask, mut value) = unsafe { self.open() };
+        let mut ret = BitSet::new();
+
+        for index in mask.iter() {
+            if predicate(unsafe { Self::get(&mut value, index) }) {
+                ret.add(index);
+            }
+        }
+
+        ret
+    }
+
     /// Open this join by returning the mask and the storages.
     ///
     /// # Safety
@@ -196,6 +371,43 @@ pub trait Join {
     }
 }
 
+/// The purpose of the `ParJoin` trait is to provide a way
+/// to access multiple storages in parallel at the same time with
+/// the merged bit set.
+///
+/// To get the entity of each joined item, join the `Entities` together with
+/// the storages. The mask of the entities is merged with the masks of the
+/// storages, so only alive entities are yielded, and the entity is built
+/// from the generation of the joined index. No additional lookups are
+/// needed. Storages provide `par_join_with_entities` as a shortcut for
+/// joining a single storage together with its entities.
+///
+/// ## Example
+///
+/// ```
+/// # use async_ecs::*;
+/// # use async_ecs::asparit::{Driver, ParallelIterator};
+/// #
+/// # struct Pos(u32);
+/// # impl Component for Pos { type Storage = VecStorage<Self>; }
+/// #
+/// let mut world = World::default();
+/// world.register_component::<Pos>();
+///
+/// let e1 = world.create_entity().with(Pos(1)).build();
+/// let e2 = world.create_entity().with(Pos(2)).build();
+///
+/// let entities = world.entities();
+/// let mut pos = world.component_mut::<Pos>();
+///
+/// (&entities, &mut pos)
+///     .par_join()
+///     .for_each(|(entity, pos)| pos.0 += entity.index())
+///     .exec();
+///
+/// assert_eq!(pos.get(e1).unwrap().0, 1 + e1.index());
+/// assert_eq!(pos.get(e2).unwrap().0, 2 + e2.index());
+/// ```
 // SAFETY: This is safe as long as `T` implements `ParJoin` safely. `MaybeJoin`
 // relies on `T as Join` for all storage access and safely wraps the inner
 // `Join` API, so it should also be able to implement `ParJoin`.
@@ -212,4 +424,68 @@ pub trait ParJoin: Join {
 
         JoinParIter::new(self)
     }
+
+    /// Collects the joined values in parallel into the collection `C`. This
+    /// is a shortcut for `par_join().collect::<C>().exec_async()`, see
+    /// `AsyncDriver` for details about the used executor.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// # use futures::future::{BoxFuture, FutureExt};
+    /// #
+    /// struct Pos(u32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct Sum(u32);
+    ///
+    /// struct SumPos;
+    ///
+    /// impl<'a> AsyncSystem<'a> for SumPos {
+    ///     type SystemData = (ReadStorage<'a, Pos>, Write<'a, Sum>);
+    ///
+    ///     fn run_async(&mut self, (pos, mut sum): Self::SystemData) -> BoxFuture<'a, ()> {
+    ///         async move {
+    ///             let pos: Vec<&Pos> = (&pos).par_join_async().await;
+    ///
+    ///             sum.0 = pos.iter().map(|pos| pos.0).sum();
+    ///         }
+    ///         .boxed()
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut world = World::default();
+    /// world.register_component::<Pos>();
+    /// world.create_entity().with(Pos(1)).build();
+    /// world.create_entity().with(Pos(2)).build();
+    ///
+    /// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+    ///     .with_async(SumPos, "sum_pos", &[])
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// dispatcher.dispatch(&world).await.unwrap();
+    ///
+    /// assert_eq!(world.resource::<Sum>().0, 3);
+    /// # }
+    /// ```
+    #[cfg(feature = "multi-thread")]
+    fn par_join_async<'a, C>(self) -> BoxFuture<'a, C>
+    where
+        Self: Sized + Send + 'a,
+        Self::Type: Send,
+        Self::Value: Copy + Send,
+        Self::Mask: Copy + Send + Sync,
+        C: FromParallelIterator<'a, Self::Type> + 'a,
+        DefaultExecutor: Executor<'a, C, C::ExecutorItem2, C::ExecutorItem3, Result = C>,
+    {
+        self.par_join().collect::<C>().exec_async()
+    }
 }
@@ -0,0 +1,90 @@
+use std::iter::FusedIterator;
+
+use hibitset::{BitIter, BitSetLike};
+
+use crate::entity::Index;
+
+use super::Join;
+
+/// Iterator over a `Join` that yields the joined values in ascending order
+/// of the entity indices.
+///
+/// The order of `JoinIter` is an implementation detail of the bit sets and
+/// should not be relied on. This iterator guarantees the ascending order,
+/// so it can be used for processing that needs to be deterministic (for
+/// example lockstep simulations or replays).
+///
+/// For usage see [`Join::ordered()`].
+///
+/// [`Join::ordered()`]: trait.Join.html#method.ordered
+pub struct OrderedJoin<J: Join> {
+    keys: BitIter<J::Mask>,
+    values: J::Value,
+    last: Option<Index>,
+}
+
+impl<J: Join> OrderedJoin<J> {
+    pub fn new(j: J) -> Self {
+        let (keys, values) = unsafe { j.open() };
+
+        Self {
+            keys: keys.iter(),
+            values,
+            last: None,
+        }
+    }
+}
+
+impl<J: Join> Iterator for OrderedJoin<J> {
+    type Item = J::Type;
+
+    fn next(&mut self) -> Option<J::Type> {
+        let index = self.keys.next()?;
+
+        // `BitIter` walks each layer from the lowest to the highest bit, so
+        // the indices are always ascending.
+        debug_assert!(self.last < Some(index));
+        self.last = Some(index);
+
+        Some(unsafe { J::get(&mut self.values, index) })
+    }
+}
+
+impl<J: Join> FusedIterator for OrderedJoin<J> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        component::Component, entity::Builder, join::Join, storage::HashMapStorage, world::World,
+    };
+
+    struct Key(u32);
+
+    impl Component for Key {
+        type Storage = HashMapStorage<Self>;
+    }
+
+    #[test]
+    fn ordered_across_layers() {
+        let mut world = World::default();
+        world.register_component::<Key>();
+
+        let entities = (0..5000)
+            .map(|_| world.create_entity().build())
+            .collect::<Vec<_>>();
+
+        let mut keys = world.component_mut::<Key>();
+        for &index in &[4097, 3, 64, 4095, 63, 0, 4096] {
+            keys.insert(entities[index], Key(index as u32)).unwrap();
+        }
+
+        let ordered = (&keys).ordered().map(|key| key.0).collect::<Vec<_>>();
+        assert_eq!(ordered, vec![0, 3, 63, 64, 4095, 4096, 4097]);
+
+        let sorted = (&keys)
+            .sorted_by_key(|key| key.0 % 7)
+            .map(|key| key.0)
+            .collect::<Vec<_>>();
+        assert_eq!(sorted, vec![0, 63, 4095, 64, 4096, 4097, 3]);
+    }
+}
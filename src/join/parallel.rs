@@ -1,19 +1,64 @@
 use std::iter::Iterator;
 
-use asparit::{Consumer, Executor, ParallelIterator, Producer, Reducer, WithSetup};
+use asparit::{Consumer, Executor, ParallelIterator, Producer, Reducer, Setup, WithSetup};
 
 use crate::misc::{BitIter, BitProducer};
 
-use super::Join;
+use super::{Join, WithId};
 
 /* JoinParIter */
 
 /// `JoinParIter` is a `ParallelIterator` over a group of `Storages`.
-pub struct JoinParIter<J>(J);
+///
+/// By default the set of entities is split until the executor has enough
+/// tasks for all of its threads, regardless of how many entities each task
+/// has to process. Use `batch()` and `splits()` to tune the splitting.
+pub struct JoinParIter<J> {
+    inner: J,
+    batch: Option<usize>,
+    splits: Option<usize>,
+}
 
 impl<J> JoinParIter<J> {
     pub fn new(inner: J) -> Self {
-        Self(inner)
+        Self {
+            inner,
+            batch: None,
+            splits: None,
+        }
+    }
+
+    /// Sets the minimum number of entities that are processed sequentially.
+    /// A part of the join is only split if it contains at least two batches
+    /// of entities.
+    ///
+    /// This avoids the overhead of many small tasks for small component sets
+    /// or skewed entity distributions.
+    pub fn batch(mut self, batch: usize) -> Self {
+        self.batch = Some(batch.max(1));
+
+        self
+    }
+
+    /// Sets the number of splits (and therefore the number of parallel
+    /// tasks) the executor should create at most.
+    pub fn splits(mut self, splits: usize) -> Self {
+        self.splits = Some(splits.max(1));
+
+        self
+    }
+
+    /// Yields the index of each joined entity together with the joined
+    /// values. See `JoinIter::with_id` for details.
+    pub fn with_id(self) -> JoinParIter<WithId<J>>
+    where
+        J: Join,
+    {
+        JoinParIter {
+            inner: WithId(self.inner),
+            batch: self.batch,
+            splits: self.splits,
+        }
     }
 }
 
@@ -33,12 +78,12 @@ where
         D: Send + 'a,
         R: Reducer<D> + Send + 'a,
     {
-        let (keys, values) = unsafe { self.0.open() };
+        let (keys, values) = unsafe { self.inner.open() };
 
         let keys = BitIter::new(keys);
 
         let producer = BitProducer::new(keys);
-        let producer = JoinProducer::<J>::new(producer, values);
+        let producer = JoinProducer::<J>::new(producer, values, self.batch, self.splits);
 
         executor.exec(producer, consumer)
     }
@@ -52,14 +97,26 @@ where
 {
     keys: BitProducer<J::Mask>,
     values: J::Value,
+    batch: Option<usize>,
+    splits: Option<usize>,
 }
 
 impl<J> JoinProducer<J>
 where
     J: Join,
 {
-    fn new(keys: BitProducer<J::Mask>, values: J::Value) -> Self {
-        JoinProducer { keys, values }
+    fn new(
+        keys: BitProducer<J::Mask>,
+        values: J::Value,
+        batch: Option<usize>,
+        splits: Option<usize>,
+    ) -> Self {
+        JoinProducer {
+            keys,
+            values,
+            batch,
+            splits,
+        }
     }
 }
 
@@ -72,7 +129,18 @@ where
 {
 }
 
-impl<J> WithSetup for JoinProducer<J> where J: Join {}
+impl<J> WithSetup for JoinProducer<J>
+where
+    J: Join,
+{
+    fn setup(&self) -> Setup {
+        Setup {
+            splits: self.splits,
+            min_len: self.batch,
+            max_len: None,
+        }
+    }
+}
 
 impl<J> Producer for JoinProducer<J>
 where
@@ -92,11 +160,30 @@ where
     }
 
     fn split(self) -> (Self, Option<Self>) {
-        let values = self.values;
-        let (left, right) = self.keys.split();
+        let Self {
+            keys,
+            values,
+            batch,
+            splits,
+        } = self;
+
+        // Both parts need at least `batch` entities, so we only split if
+        // there are at least two batches left.
+        if let Some(batch) = batch {
+            if keys
+                .iter
+                .clone()
+                .nth(batch.saturating_mul(2).saturating_sub(1))
+                .is_none()
+            {
+                return (Self::new(keys, values, Some(batch), splits), None);
+            }
+        }
+
+        let (left, right) = keys.split();
 
-        let left = JoinProducer::new(left, values);
-        let right = right.map(|right| JoinProducer::new(right, values));
+        let left = JoinProducer::new(left, values, batch, splits);
+        let right = right.map(|right| JoinProducer::new(right, values, batch, splits));
 
         (left, right)
     }
@@ -129,3 +216,37 @@ where
         self.keys.size_hint()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use asparit::{Driver, ParallelIterator};
+    use hibitset::BitSet;
+
+    use crate::join::ParJoin;
+
+    use super::*;
+
+    fn producer(set: &BitSet, batch: Option<usize>) -> JoinProducer<&BitSet> {
+        let (keys, values) = unsafe { set.open() };
+        let keys = BitProducer::new(BitIter::new(keys));
+
+        JoinProducer::new(keys, values, batch, None)
+    }
+
+    #[test]
+    fn split_respects_batch() {
+        let set = (0..100).collect::<BitSet>();
+
+        let (left, right) = producer(&set, Some(40)).split();
+        let right = right.unwrap();
+
+        assert!(left.split().1.is_none());
+        assert!(right.split().1.is_none());
+        assert!(producer(&set, Some(60)).split().1.is_none());
+        assert!(producer(&set, Some(usize::MAX)).split().1.is_none());
+        assert!(producer(&set, None).split().1.is_some());
+
+        let count = (&set).par_join().batch(10).splits(4).count().exec();
+        assert_eq!(count, 100);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::entity::Index;
+
+use super::{Join, ParJoin};
+
+/// A `Join`-able structure that yields the index of each joined element
+/// together with the joined values.
+///
+/// For usage see [`JoinIter::with_id()`] and [`JoinParIter::with_id()`].
+///
+/// [`JoinIter::with_id()`]: struct.JoinIter.html#method.with_id
+/// [`JoinParIter::with_id()`]: struct.JoinParIter.html#method.with_id
+pub struct WithId<J: Join>(pub J);
+
+impl<T> Join for WithId<T>
+where
+    T: Join,
+{
+    type Mask = <T as Join>::Mask;
+    type Type = (Index, <T as Join>::Type);
+    type Value = <T as Join>::Value;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        self.0.open()
+    }
+
+    unsafe fn get(value: &mut Self::Value, index: Index) -> Self::Type {
+        (index, <T as Join>::get(value, index))
+    }
+
+    fn is_unconstrained() -> bool {
+        <T as Join>::is_unconstrained()
+    }
+}
+
+impl<T> ParJoin for WithId<T> where T: ParJoin {}
+
+#[cfg(test)]
+mod tests {
+    use asparit::{Driver, ParallelIterator};
+
+    use crate::{
+        component::Component, entity::Builder, join::ParJoin, storage::VecStorage, world::World,
+    };
+
+    use super::*;
+
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn with_id() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().build();
+        let e3 = world.create_entity().with(Pos(3)).build();
+
+        let mut pos = world.component_mut::<Pos>();
+
+        let ids = (&pos)
+            .join()
+            .with_id()
+            .map(|(index, pos)| (index, pos.0))
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![(e1.index(), 1), (e3.index(), 3)]);
+
+        (&mut pos)
+            .par_join()
+            .with_id()
+            .for_each(|(index, pos)| pos.0 += 10 * index)
+            .exec();
+
+        assert_eq!(pos.get(e1).unwrap().0, 1 + 10 * e1.index());
+        assert_eq!(pos.get(e3).unwrap().0, 3 + 10 * e3.index());
+        assert!(pos.get(e2).is_none());
+    }
+}
@@ -1,28 +1,51 @@
 #![allow(dead_code)]
 
 pub mod access;
+pub mod assets;
 pub mod component;
 pub mod dispatcher;
 pub mod entity;
 pub mod error;
+pub mod event;
+pub mod hierarchy;
+#[cfg(feature = "specs")]
+pub mod interop;
 pub mod join;
 pub mod misc;
+pub mod prefab;
+#[cfg(feature = "serde")]
+pub mod replication;
 pub mod resource;
+#[cfg(feature = "serde")]
+pub mod saveload;
+#[cfg(feature = "spatial")]
+pub mod spatial;
 pub mod storage;
 pub mod system;
+pub mod util;
 pub mod world;
 
 pub use asparit;
 
-pub use access::{Read, ReadStorage, Write, WriteStorage};
+pub use access::{
+    LocalRead, LocalWrite, MaskRead, Query, Read, ReadExpect, ReadSingleton, ReadStorage, Write,
+    WriteExpect, WriteSingleton, WriteStorage,
+};
 pub use component::Component;
 pub use dispatcher::Dispatcher;
 pub use entity::Builder;
-pub use join::{Join, ParJoin};
+#[cfg(feature = "multi-thread")]
+pub use join::AsyncDriver;
+pub use join::{ChangeTracker, Join, OrderedJoin, ParJoin};
 pub use resource::{ResourceId, Resources};
-pub use storage::{DenseVecStorage, HashMapStorage, VecStorage};
+pub use storage::{
+    DefaultVecStorage, DenseVecStorage, FlaggedStorage, HashMapStorage, NullStorage, PagedStorage,
+    SparseSetStorage, VecStorage,
+};
 pub use system::{AsyncSystem, System};
-pub use world::{CastFrom, Lazy, MetaTable, World};
+pub use world::{
+    CastFrom, CommandBuffer, CommandLog, EntityMap, Lazy, MetaTable, Time, World, WorldSnapshot,
+};
 
 pub type Entities<'a> = Read<'a, entity::Entities>;
 
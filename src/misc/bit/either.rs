@@ -0,0 +1,55 @@
+use hibitset::BitSetLike;
+
+use crate::entity::Index;
+
+/* BitSetEither */
+
+/// Bit set that is one of two bit sets of different types. This is used if
+/// the type of a mask is only known at runtime.
+#[derive(Debug, Clone, Copy)]
+pub enum BitSetEither<A, B> {
+    Left(A),
+    Right(B),
+}
+
+impl<A, B> BitSetLike for BitSetEither<A, B>
+where
+    A: BitSetLike,
+    B: BitSetLike,
+{
+    #[inline]
+    fn layer3(&self) -> usize {
+        match self {
+            Self::Left(a) => a.layer3(),
+            Self::Right(b) => b.layer3(),
+        }
+    }
+    #[inline]
+    fn layer2(&self, i: usize) -> usize {
+        match self {
+            Self::Left(a) => a.layer2(i),
+            Self::Right(b) => b.layer2(i),
+        }
+    }
+    #[inline]
+    fn layer1(&self, i: usize) -> usize {
+        match self {
+            Self::Left(a) => a.layer1(i),
+            Self::Right(b) => b.layer1(i),
+        }
+    }
+    #[inline]
+    fn layer0(&self, i: usize) -> usize {
+        match self {
+            Self::Left(a) => a.layer0(i),
+            Self::Right(b) => b.layer0(i),
+        }
+    }
+    #[inline]
+    fn contains(&self, i: Index) -> bool {
+        match self {
+            Self::Left(a) => a.contains(i),
+            Self::Right(b) => b.contains(i),
+        }
+    }
+}
@@ -1,7 +1,11 @@
 mod and;
+mod either;
 mod iter;
+mod not;
 mod producer;
 
 pub use and::BitAnd;
+pub use either::BitSetEither;
 pub use iter::BitIter;
+pub use not::BitSetNot;
 pub use producer::BitProducer;
@@ -0,0 +1,36 @@
+use hibitset::BitSetLike;
+
+use crate::entity::Index;
+
+/* BitSetNot */
+
+/// Inverts the wrapped bit set. Other than `hibitset::BitSetNot` this is
+/// `Copy` if the wrapped bit set is, which is needed for parallel joins.
+#[derive(Debug, Clone, Copy)]
+pub struct BitSetNot<A: BitSetLike>(pub A);
+
+impl<A> BitSetLike for BitSetNot<A>
+where
+    A: BitSetLike,
+{
+    #[inline]
+    fn layer3(&self) -> usize {
+        !0
+    }
+    #[inline]
+    fn layer2(&self, _: usize) -> usize {
+        !0
+    }
+    #[inline]
+    fn layer1(&self, _: usize) -> usize {
+        !0
+    }
+    #[inline]
+    fn layer0(&self, i: usize) -> usize {
+        !self.0.layer0(i)
+    }
+    #[inline]
+    fn contains(&self, i: Index) -> bool {
+        !self.0.contains(i)
+    }
+}
@@ -2,6 +2,6 @@ pub mod bit;
 pub mod split;
 pub mod try_default;
 
-pub use bit::{BitAnd, BitIter, BitProducer};
+pub use bit::{BitAnd, BitIter, BitProducer, BitSetEither, BitSetNot};
 pub use split::Split;
 pub use try_default::TryDefault;
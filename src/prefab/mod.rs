@@ -0,0 +1,63 @@
+//! Templates of entities, that can be spawned many times.
+//!
+//! A `Prefab` describes an entity as data: a set of components and an
+//! optional list of child prefabs. Prefabs are registered by name in the
+//! `PrefabStore` resource using `World::register_prefab`, and can be
+//! spawned immediately using `Prefab::spawn`, or lazily (for example from
+//! inside a system) using `Prefab::spawn_lazy`.
+//!
+//! Each spawned child gets a `hierarchy::Parent` component, that refers to
+//! the entity spawned for its parent prefab.
+//!
+//! ## Examples
+//!
+//! ```
+//! use async_ecs::{
+//!     hierarchy::Parent,
+//!     prefab::{Prefab, PrefabStore},
+//!     *,
+//! };
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct Health(u32);
+//!
+//! impl Component for Health {
+//!     type Storage = VecStorage<Self>;
+//! }
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct Weapon(&'static str);
+//!
+//! impl Component for Weapon {
+//!     type Storage = HashMapStorage<Self>;
+//! }
+//!
+//! let mut world = World::default();
+//! world.register_component::<Health>();
+//! world.register_component::<Weapon>();
+//! world.register_prefab(
+//!     "goblin",
+//!     Prefab::new()
+//!         .with(Health(10))
+//!         .with_child(Prefab::new().with(Weapon("club"))),
+//! );
+//!
+//! // spawn an instance with a per-instance override
+//! let prefab = world.resource::<PrefabStore>().instance("goblin").unwrap();
+//! let goblin = prefab.with(Health(20)).spawn(&mut world);
+//!
+//! assert_eq!(world.component::<Health>().get(goblin), Some(&Health(20)));
+//!
+//! let parents = world.component::<Parent>();
+//! let weapons = world.component::<Weapon>();
+//! let (weapon, parent) = (&weapons, &parents).join().next().unwrap();
+//!
+//! assert_eq!(weapon, &Weapon("club"));
+//! assert_eq!(parent.0, goblin);
+//! ```
+
+mod store;
+mod template;
+
+pub use store::PrefabStore;
+pub use template::Prefab;
@@ -0,0 +1,52 @@
+use hashbrown::HashMap;
+
+use super::Prefab;
+
+/// Resource that stores prefabs by their name.
+///
+/// Prefabs are usually registered using `World::register_prefab`, which
+/// also registers the components needed to spawn them.
+#[derive(Default)]
+pub struct PrefabStore {
+    prefabs: HashMap<String, Prefab>,
+}
+
+impl PrefabStore {
+    /// Inserts a prefab with the passed name. Returns the prefab that was
+    /// previously stored with this name.
+    pub fn insert<S>(&mut self, name: S, prefab: Prefab) -> Option<Prefab>
+    where
+        S: Into<String>,
+    {
+        self.prefabs.insert(name.into(), prefab)
+    }
+
+    /// Removes the prefab with the passed name.
+    pub fn remove(&mut self, name: &str) -> Option<Prefab> {
+        self.prefabs.remove(name)
+    }
+
+    /// Returns `true` if a prefab with the passed name is stored.
+    pub fn contains(&self, name: &str) -> bool {
+        self.prefabs.contains_key(name)
+    }
+
+    /// Returns the prefab with the passed name.
+    pub fn get(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs.get(name)
+    }
+
+    /// Returns a copy of the prefab with the passed name, that can be
+    /// modified for a single instance before it is spawned.
+    ///
+    /// The components of the prefab are shared with the stored prefab, so
+    /// this is cheap.
+    pub fn instance(&self, name: &str) -> Option<Prefab> {
+        self.prefabs.get(name).cloned()
+    }
+
+    /// Returns an iterator over the names of all stored prefabs.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.prefabs.keys().map(String::as_str)
+    }
+}
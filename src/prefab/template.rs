@@ -0,0 +1,233 @@
+use std::any::TypeId;
+use std::sync::Arc;
+
+use crate::{
+    component::Component,
+    entity::{Builder, Entities, Entity, EntityBuilder},
+    hierarchy::Parent,
+    world::{Lazy, LazyBuilder, World},
+};
+
+/// Template of an entity, that consists of a set of components and a list
+/// of child prefabs.
+///
+/// Cloning a prefab is cheap, because the components are shared between
+/// the clones. The components are cloned for each spawned entity.
+#[derive(Default, Clone)]
+pub struct Prefab {
+    components: Vec<Arc<dyn PrefabComponent>>,
+    children: Vec<Prefab>,
+}
+
+impl Prefab {
+    /// Create a new empty prefab.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a component to the prefab.
+    ///
+    /// If the prefab already contains a component of the same type, it is
+    /// replaced. This can be used to override components of a single
+    /// instance (see `PrefabStore::instance`).
+    pub fn with<C>(mut self, component: C) -> Self
+    where
+        C: Component + Clone + Send + Sync,
+    {
+        let component: Arc<dyn PrefabComponent> = Arc::new(component);
+        let type_id = component.component_type();
+
+        match self
+            .components
+            .iter_mut()
+            .find(|c| c.component_type() == type_id)
+        {
+            Some(c) => *c = component,
+            None => self.components.push(component),
+        }
+
+        self
+    }
+
+    /// Removes the component `C` from the prefab.
+    pub fn without<C>(mut self) -> Self
+    where
+        C: Component,
+    {
+        let type_id = TypeId::of::<C>();
+
+        self.components.retain(|c| c.component_type() != type_id);
+
+        self
+    }
+
+    /// Adds a child prefab, that is spawned together with this prefab.
+    pub fn with_child(mut self, child: Prefab) -> Self {
+        self.children.push(child);
+
+        self
+    }
+
+    /// Returns the child prefabs of this prefab.
+    pub fn children(&self) -> &[Prefab] {
+        &self.children
+    }
+
+    /// Spawns the prefab and all of its children immediately. Returns the
+    /// entity spawned for this prefab.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the components (or `Parent` if the prefab has
+    /// children) is not registered.
+    pub fn spawn(&self, world: &mut World) -> Entity {
+        self.spawn_inner(world, None)
+    }
+
+    /// Spawns the prefab and all of its children lazily. The entities are
+    /// allocated immediately, but the components are only inserted on
+    /// `World::maintain`. Returns the entity spawned for this prefab.
+    pub fn spawn_lazy(&self, entities: &Entities, lazy: &Lazy) -> Entity {
+        self.spawn_lazy_inner(entities, lazy, None)
+    }
+
+    fn spawn_inner(&self, world: &mut World, parent: Option<Entity>) -> Entity {
+        let mut builder = world.create_entity();
+        if let Some(parent) = parent {
+            builder = builder.with(Parent(parent));
+        }
+
+        let entity = self
+            .components
+            .iter()
+            .fold(builder, |builder, c| c.apply(builder))
+            .build();
+
+        for child in &self.children {
+            child.spawn_inner(world, Some(entity));
+        }
+
+        entity
+    }
+
+    fn spawn_lazy_inner(&self, entities: &Entities, lazy: &Lazy, parent: Option<Entity>) -> Entity {
+        let mut builder = LazyBuilder {
+            entity: entities.create(),
+            lazy,
+        };
+        if let Some(parent) = parent {
+            builder = builder.with(Parent(parent));
+        }
+
+        let entity = self
+            .components
+            .iter()
+            .fold(builder, |builder, c| c.apply_lazy(builder))
+            .build();
+
+        for child in &self.children {
+            child.spawn_lazy_inner(entities, lazy, Some(entity));
+        }
+
+        entity
+    }
+}
+
+/// Type erased component of a `Prefab`.
+trait PrefabComponent: Send + Sync {
+    fn component_type(&self) -> TypeId;
+
+    fn apply<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a>;
+
+    fn apply_lazy<'a>(&self, builder: LazyBuilder<'a>) -> LazyBuilder<'a>;
+}
+
+impl<C> PrefabComponent for C
+where
+    C: Component + Clone + Send + Sync,
+{
+    fn component_type(&self) -> TypeId {
+        TypeId::of::<C>()
+    }
+
+    fn apply<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+
+    fn apply_lazy<'a>(&self, builder: LazyBuilder<'a>) -> LazyBuilder<'a> {
+        builder.with(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{join::Join, prefab::PrefabStore, storage::VecStorage, system::SystemData, Read};
+
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Name(&'static str);
+
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+    }
+
+    fn prefab() -> Prefab {
+        Prefab::new()
+            .with(Health(10))
+            .with(Name("root"))
+            .with_child(Prefab::new().with(Name("child")))
+    }
+
+    #[tokio::test]
+    async fn spawn_lazy() {
+        let mut world = World::default();
+        world.register_component::<Health>();
+        world.register_component::<Name>();
+        world.register_prefab("test", prefab());
+
+        let (entities, lazy) = <(Read<Entities>, Read<Lazy>)>::fetch(&world);
+        let prefab = world.resource::<PrefabStore>().instance("test").unwrap();
+        let root = prefab.with(Health(5)).spawn_lazy(&entities, &lazy);
+        drop((entities, lazy));
+
+        assert_eq!(world.component::<Health>().get(root), None);
+
+        world.maintain().await;
+
+        let health = world.component::<Health>();
+        let names = world.component::<Name>();
+        let parents = world.component::<Parent>();
+
+        assert_eq!(health.get(root), Some(&Health(5)));
+        assert_eq!(names.get(root), Some(&Name("root")));
+        assert_eq!(parents.get(root), None);
+
+        let children = (&names, &parents).join().collect::<Vec<_>>();
+        assert_eq!(children, vec![(&Name("child"), &Parent(root))]);
+    }
+
+    #[test]
+    fn overrides() {
+        let prefab = prefab().with(Health(1)).without::<Name>();
+
+        let mut world = World::default();
+        world.register_component::<Health>();
+        world.register_component::<Name>();
+        world.register_component::<Parent>();
+
+        let root = prefab.spawn(&mut world);
+
+        assert_eq!(prefab.components.len(), 1);
+        assert_eq!(world.component::<Health>().get(root), Some(&Health(1)));
+        assert_eq!(world.component::<Name>().get(root), None);
+        assert_eq!(world.component::<Name>().count(), 1);
+    }
+}
@@ -0,0 +1,132 @@
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer};
+
+use crate::{
+    access::WriteStorage,
+    component::Component,
+    entity::{Entities, Entity},
+    saveload::{ConvertSaveload, EntityData, Marker, MarkerAllocator},
+};
+
+use super::{ComponentDelta, Delta};
+
+/// Applies the deltas, that were produced by `ReplicateComponents`, to the
+/// world of a client.
+///
+/// This is implemented for tuples of `WriteStorage`s. The storages have to
+/// be in the same order as the storages that were used to produce the
+/// deltas.
+pub trait ApplyComponents<M>
+where
+    M: Marker,
+{
+    /// Serialized data of the changed components of a single entity.
+    type Data: DeserializeOwned;
+
+    /// Inserts the changed components of the passed entity into the storages
+    /// and removes the removed ones. `ids` is used to look up the entities
+    /// of referenced markers.
+    fn apply_entity<Err, Ids>(
+        &mut self,
+        entity: Entity,
+        components: Self::Data,
+        ids: Ids,
+    ) -> Result<(), Err>
+    where
+        Err: de::Error,
+        Ids: FnMut(M) -> Option<Entity>;
+
+    /// Deserializes a `Delta` and applies it. Entities that are not known by
+    /// the allocator are created atomically, removed entities are deleted,
+    /// so `World::maintain` has to be called afterwards. Returns the tick of
+    /// the applied delta.
+    fn apply_delta<'de, D>(
+        &mut self,
+        entities: &Entities,
+        markers: &mut WriteStorage<M>,
+        allocator: &mut M::Allocator,
+        deserializer: D,
+    ) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let delta = Delta::<M, Self::Data>::deserialize(deserializer)?;
+
+        for marker in delta.removed {
+            if let Some(entity) = allocator.retrieve_entity_internal(marker.id()) {
+                if markers.get(entity) == Some(&marker) {
+                    let _ = entities.delete(entity);
+                }
+            }
+        }
+
+        for EntityData { marker, components } in delta.changed {
+            let entity = allocator.retrieve_entity(marker, markers, entities);
+            let ids = |marker| Some(allocator.retrieve_entity(marker, markers, entities));
+
+            self.apply_entity(entity, components, ids)?;
+        }
+
+        Ok(delta.tick)
+    }
+}
+
+macro_rules! define_apply_components {
+    ($($from:ident $data:ident),*) => {
+        impl<'a, M, $($from,)*> ApplyComponents<M> for ($(WriteStorage<'a, $from>,)*)
+        where
+            M: Marker,
+            $($from: Component + ConvertSaveload<M>,)*
+        {
+            type Data = ($(ComponentDelta<<$from as ConvertSaveload<M>>::Data>,)*);
+
+            #[allow(non_snake_case)]
+            fn apply_entity<Err, Ids>(
+                &mut self,
+                entity: Entity,
+                components: Self::Data,
+                mut ids: Ids,
+            ) -> Result<(), Err>
+            where
+                Err: de::Error,
+                Ids: FnMut(M) -> Option<Entity>,
+            {
+                let ($(ref mut $from,)*) = *self;
+                let ($($data,)*) = components;
+
+                $(
+                    match $data {
+                        ComponentDelta::Changed(data) => {
+                            let component =
+                                <$from as ConvertSaveload<M>>::convert_from(data, &mut ids).map_err(Err::custom)?;
+
+                            $from.insert(entity, component).map_err(Err::custom)?;
+                        }
+                        ComponentDelta::Removed => {
+                            $from.remove(entity);
+                        }
+                        ComponentDelta::Unchanged => (),
+                    }
+                )*
+
+                Ok(())
+            }
+        }
+    };
+}
+
+define_apply_components! { A a }
+define_apply_components! { A a, B b }
+define_apply_components! { A a, B b, C c }
+define_apply_components! { A a, B b, C c, D d }
+define_apply_components! { A a, B b, C c, D d, E e }
+define_apply_components! { A a, B b, C c, D d, E e, F f }
+define_apply_components! { A a, B b, C c, D d, E e, F f, G g }
+define_apply_components! { A a, B b, C c, D d, E e, F f, G g, H h }
+define_apply_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i }
+define_apply_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j }
+define_apply_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k }
+define_apply_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l }
+define_apply_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l, N n }
+define_apply_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l, N n, O o }
+define_apply_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l, N n, O o, P p }
+define_apply_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l, N n, O o, P p, Q q }
@@ -0,0 +1,273 @@
+//! Replication of entities and their components over the network.
+//!
+//! The server marks the entities that should be replicated using a
+//! `saveload::Marker`, and produces one `Delta` per tick using
+//! `ReplicateComponents::serialize_delta`. A delta only contains the
+//! components that were inserted, modified or removed since the last delta,
+//! and the markers of the entities that were deleted (or unmarked). The
+//! changes are detected using the change ticks of the storages, so the
+//! replicated components need a tracking storage like the `FlaggedStorage`.
+//!
+//! The client applies the received deltas using
+//! `ApplyComponents::apply_delta`. Deltas build on each other, so they have
+//! to be delivered reliably and in order. If a client (re)connects, the
+//! `Replicator` of the server is reset, so it sends a full snapshot.
+//!
+//! ## Examples
+//!
+//! ```
+//! use async_ecs::{
+//!     replication::{ApplyComponents, ReplicateComponents, Replicator},
+//!     saveload::{MarkerAllocator, SimpleMarker, SimpleMarkerAllocator},
+//!     *,
+//! };
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+//! struct Pos(i32, i32);
+//!
+//! impl Component for Pos {
+//!     type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+//! }
+//!
+//! struct Net;
+//!
+//! fn world() -> World {
+//!     let mut world = World::default();
+//!     world.register_component::<Pos>();
+//!     world.register_component::<SimpleMarker<Net>>();
+//!     world.register_resource(SimpleMarkerAllocator::<Net>::default());
+//!     world
+//! }
+//!
+//! fn send(server: &World, replicator: &mut Replicator<SimpleMarker<Net>>) -> Vec<u8> {
+//!     let mut packet = Vec::new();
+//!     (server.component::<Pos>(),)
+//!         .serialize_delta(
+//!             replicator,
+//!             &server.entities(),
+//!             &server.component::<SimpleMarker<Net>>(),
+//!             &mut serde_json::Serializer::new(&mut packet),
+//!         )
+//!         .unwrap();
+//!     packet
+//! }
+//!
+//! fn receive(client: &World, packet: &[u8]) {
+//!     (client.component_mut::<Pos>(),)
+//!         .apply_delta(
+//!             &client.entities(),
+//!             &mut client.component_mut::<SimpleMarker<Net>>(),
+//!             &mut client.resource_mut::<SimpleMarkerAllocator<Net>>(),
+//!             &mut serde_json::Deserializer::from_slice(packet),
+//!         )
+//!         .unwrap();
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let mut server = world();
+//! let mut client = world();
+//! let mut replicator = Replicator::new();
+//!
+//! let player = server.create_entity().with(Pos(0, 0)).build();
+//! server
+//!     .resource_mut::<SimpleMarkerAllocator<Net>>()
+//!     .mark(player, &mut server.component_mut());
+//!
+//! receive(&client, &send(&server, &mut replicator));
+//! client.maintain().await;
+//!
+//! server.component_mut::<Pos>().get_mut(player).unwrap().0 = 5;
+//!
+//! let packet = send(&server, &mut replicator);
+//! receive(&client, &packet);
+//!
+//! let positions: Vec<_> = client.component::<Pos>().join().cloned().collect();
+//! assert_eq!(positions, vec![Pos(5, 0)]);
+//! # }
+//! ```
+
+mod apply;
+mod replicate;
+
+pub use apply::ApplyComponents;
+pub use replicate::{ReplicateComponents, Replicator};
+
+use serde::{Deserialize, Serialize};
+
+use crate::saveload::EntityData;
+
+/// Changes of the replicated entities since the previous delta.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Delta<M, D> {
+    /// Sequence number of the delta, starting at 1.
+    pub tick: u64,
+
+    /// Marked entities with at least one changed component.
+    pub changed: Vec<EntityData<M, D>>,
+
+    /// Markers of the entities that were deleted or unmarked.
+    pub removed: Vec<M>,
+}
+
+/// Change of a single component of an entity in a `Delta`.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ComponentDelta<D> {
+    /// The component was not changed.
+    Unchanged,
+
+    /// The component was inserted or modified.
+    Changed(D),
+
+    /// The component was removed from the entity.
+    Removed,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        component::Component,
+        entity::{Builder, Entity},
+        join::Join,
+        saveload::{MarkerAllocator, SimpleMarker, SimpleMarkerAllocator},
+        storage::{FlaggedStorage, VecStorage},
+        world::World,
+    };
+
+    use super::*;
+
+    struct Net;
+
+    type Marker = SimpleMarker<Net>;
+    type Allocator = SimpleMarkerAllocator<Net>;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    fn world() -> World {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Health>();
+        world.register_component::<Marker>();
+        world.register_resource(Allocator::default());
+
+        world
+    }
+
+    fn spawn(world: &mut World, pos: u32) -> Entity {
+        let entity = world.create_entity().with(Pos(pos)).build();
+
+        world
+            .resource_mut::<Allocator>()
+            .mark(entity, &mut world.component_mut());
+
+        entity
+    }
+
+    fn send(world: &World, replicator: &mut Replicator<Marker>) -> String {
+        let mut buffer = Vec::new();
+
+        (world.component::<Pos>(), world.component::<Health>())
+            .serialize_delta(
+                replicator,
+                &world.entities(),
+                &world.component::<Marker>(),
+                &mut serde_json::Serializer::new(&mut buffer),
+            )
+            .unwrap();
+
+        String::from_utf8(buffer).unwrap()
+    }
+
+    async fn receive(world: &mut World, data: &str) -> u64 {
+        let tick = (
+            world.component_mut::<Pos>(),
+            world.component_mut::<Health>(),
+        )
+            .apply_delta(
+                &world.entities(),
+                &mut world.component_mut::<Marker>(),
+                &mut world.resource_mut::<Allocator>(),
+                &mut serde_json::Deserializer::from_str(data),
+            )
+            .unwrap();
+
+        world.maintain().await;
+
+        tick
+    }
+
+    fn changed(data: &str) -> usize {
+        let delta: Delta<Marker, (ComponentDelta<Pos>, ComponentDelta<Health>)> =
+            serde_json::from_str(data).unwrap();
+
+        delta.changed.len()
+    }
+
+    fn state(world: &World) -> Vec<(Option<Pos>, Option<Health>)> {
+        let pos = world.component::<Pos>();
+        let health = world.component::<Health>();
+
+        (&world.entities(), &world.component::<Marker>())
+            .join()
+            .map(|(e, _)| (pos.get(e).cloned(), health.get(e).cloned()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn replicate_changes() {
+        let mut server = world();
+        let mut client = world();
+        let mut replicator = Replicator::new();
+
+        let a = spawn(&mut server, 1);
+        let b = spawn(&mut server, 2);
+        server.create_entity().with(Pos(3)).build();
+
+        let data = send(&server, &mut replicator);
+        assert_eq!(changed(&data), 2);
+        assert_eq!(receive(&mut client, &data).await, 1);
+        assert_eq!(state(&client), state(&server));
+
+        let data = send(&server, &mut replicator);
+        assert_eq!(changed(&data), 0);
+
+        server.component_mut::<Pos>().get_mut(a).unwrap().0 = 10;
+        server
+            .component_mut::<Health>()
+            .insert(b, Health(5))
+            .unwrap();
+        server.component_mut::<Pos>().remove(b);
+
+        let data = send(&server, &mut replicator);
+        assert_eq!(changed(&data), 2);
+        assert_eq!(receive(&mut client, &data).await, 3);
+        assert_eq!(state(&client), state(&server));
+
+        server.delete_entity(a).unwrap();
+        server.maintain().await;
+
+        let data = send(&server, &mut replicator);
+        receive(&mut client, &data).await;
+        assert_eq!(state(&client), vec![(None, Some(Health(5)))]);
+
+        replicator.reset();
+
+        let data = send(&server, &mut replicator);
+        assert_eq!(changed(&data), 1);
+    }
+}
@@ -0,0 +1,222 @@
+use hashbrown::HashMap;
+use hibitset::BitSet;
+use serde::ser::{self, Serialize, Serializer};
+
+use crate::{
+    access::ReadStorage,
+    component::Component,
+    entity::{Entities, Entity, Index},
+    join::Join,
+    saveload::{ConvertSaveload, EntityData, Marker},
+    storage::{advance_tick, Tick, Tracked},
+};
+
+use super::{ComponentDelta, Delta};
+
+/// Server side state of the replication, that remembers what was already
+/// sent to the clients.
+///
+/// Each call to `ReplicateComponents::serialize_delta` produces the delta
+/// to the previous call, so all deltas have to be delivered to the clients
+/// reliably and in order. Use `reset` to send a full snapshot instead (for
+/// example when a new client connects).
+pub struct Replicator<M> {
+    tick: u64,
+    cursor: Tick,
+    masks: Option<Vec<BitSet>>,
+    known: HashMap<Index, M>,
+}
+
+impl<M> Replicator<M> {
+    /// Create a new replicator. The first delta is a full snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the tick of the last delta that was produced.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Forgets the replicated state, so the next delta is a full snapshot
+    /// of all marked entities.
+    pub fn reset(&mut self) {
+        self.masks = None;
+        self.known.clear();
+    }
+}
+
+impl<M> Default for Replicator<M> {
+    fn default() -> Self {
+        Self {
+            tick: 0,
+            cursor: 0,
+            masks: None,
+            known: HashMap::new(),
+        }
+    }
+}
+
+/// Produces the delta of the changed components of all marked entities.
+///
+/// This is implemented for tuples of `ReadStorage`s, whose storages keep
+/// track of the changes of the components (like the `FlaggedStorage`). The
+/// components of each entity are serialized as tuple of `ComponentDelta`s.
+pub trait ReplicateComponents<M>
+where
+    M: Marker,
+{
+    /// Serializable data of the changed components of a single entity.
+    type Data: Serialize;
+
+    /// Returns the masks of the storages, in the order of the components.
+    fn masks(&self) -> Vec<BitSet>;
+
+    /// Converts the components of the passed entity, that were changed
+    /// since `since`, into their serializable representation. `previous`
+    /// contains the masks of the last delta, or `None` if all components
+    /// of the entity should be sent. Returns `None` if nothing was changed.
+    fn delta_entity<Err, Ids>(
+        &self,
+        entity: Entity,
+        since: Tick,
+        previous: Option<&[BitSet]>,
+        ids: Ids,
+    ) -> Result<Option<Self::Data>, Err>
+    where
+        Err: ser::Error,
+        Ids: FnMut(Entity) -> Option<M>;
+
+    /// Serializes the `Delta` of all entities that have a marker in
+    /// `markers`, compared to the last delta of the `replicator`.
+    fn serialize_delta<S>(
+        &self,
+        replicator: &mut Replicator<M>,
+        entities: &Entities,
+        markers: &ReadStorage<M>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut known = HashMap::new();
+        let mut changed = Vec::new();
+
+        for (entity, marker) in (entities, markers).join() {
+            let previous = match replicator.known.get(&entity.index()) {
+                Some(known) if known == marker => replicator.masks.as_deref(),
+                _ => None,
+            };
+
+            let ids = |entity| markers.get(entity).cloned();
+            if let Some(components) = self.delta_entity(entity, replicator.cursor, previous, ids)? {
+                changed.push(EntityData {
+                    marker: marker.clone(),
+                    components,
+                });
+            }
+
+            known.insert(entity.index(), marker.clone());
+        }
+
+        let removed = replicator
+            .known
+            .drain()
+            .filter(|(index, marker)| known.get(index) != Some(marker))
+            .map(|(_, marker)| marker)
+            .collect();
+
+        replicator.tick += 1;
+        replicator.cursor = advance_tick();
+        replicator.masks = Some(self.masks());
+        replicator.known = known;
+
+        Delta {
+            tick: replicator.tick,
+            changed,
+            removed,
+        }
+        .serialize(serializer)
+    }
+}
+
+macro_rules! define_replicate_components {
+    ($($from:ident),*) => {
+        impl<'a, M, $($from,)*> ReplicateComponents<M> for ($(ReadStorage<'a, $from>,)*)
+        where
+            M: Marker,
+            $($from: Component + ConvertSaveload<M>, $from::Storage: Tracked,)*
+        {
+            type Data = ($(ComponentDelta<<$from as ConvertSaveload<M>>::Data>,)*);
+
+            #[allow(non_snake_case)]
+            fn masks(&self) -> Vec<BitSet> {
+                let ($(ref $from,)*) = *self;
+
+                vec![$($from.mask().clone(),)*]
+            }
+
+            #[allow(non_snake_case)]
+            fn delta_entity<Err, Ids>(
+                &self,
+                entity: Entity,
+                since: Tick,
+                previous: Option<&[BitSet]>,
+                mut ids: Ids,
+            ) -> Result<Option<Self::Data>, Err>
+            where
+                Err: ser::Error,
+                Ids: FnMut(Entity) -> Option<M>,
+            {
+                let ($(ref $from,)*) = *self;
+                let index = entity.index();
+                let mut masks = previous.map(<[BitSet]>::iter);
+                let mut changed = false;
+
+                let data = ($({
+                    let existed = masks
+                        .as_mut()
+                        .map(|masks| matches!(masks.next(), Some(mask) if mask.contains(index)));
+
+                    match $from.get(entity) {
+                        Some(component)
+                            if existed != Some(true)
+                                || $from.unprotected_storage().last_changed(index) >= since =>
+                        {
+                            changed = true;
+
+                            ComponentDelta::Changed(
+                                component.convert_into(&mut ids).map_err(Err::custom)?,
+                            )
+                        }
+                        None if existed == Some(true) => {
+                            changed = true;
+
+                            ComponentDelta::Removed
+                        }
+                        _ => ComponentDelta::Unchanged,
+                    }
+                },)*);
+
+                Ok(if changed { Some(data) } else { None })
+            }
+        }
+    };
+}
+
+define_replicate_components! { A }
+define_replicate_components! { A, B }
+define_replicate_components! { A, B, C }
+define_replicate_components! { A, B, C, D }
+define_replicate_components! { A, B, C, D, E }
+define_replicate_components! { A, B, C, D, E, F }
+define_replicate_components! { A, B, C, D, E, F, G }
+define_replicate_components! { A, B, C, D, E, F, G, H }
+define_replicate_components! { A, B, C, D, E, F, G, H, I }
+define_replicate_components! { A, B, C, D, E, F, G, H, I, J }
+define_replicate_components! { A, B, C, D, E, F, G, H, I, J, K }
+define_replicate_components! { A, B, C, D, E, F, G, H, I, J, K, L }
+define_replicate_components! { A, B, C, D, E, F, G, H, I, J, K, L, N }
+define_replicate_components! { A, B, C, D, E, F, G, H, I, J, K, L, N, O }
+define_replicate_components! { A, B, C, D, E, F, G, H, I, J, K, L, N, O, P }
+define_replicate_components! { A, B, C, D, E, F, G, H, I, J, K, L, N, O, P, Q }
@@ -0,0 +1,86 @@
+use std::cell::Cell as CopyCell;
+use std::ptr::null;
+
+use crate::{access::Accessor, system::SystemData};
+
+use super::{Cell, Resource, ResourceId, Resources};
+
+/// Caches the cells of a fixed set of resources, so they can be fetched
+/// without looking them up in `Resources` each time.
+///
+/// The cells are looked up again as soon as resources were inserted into or
+/// removed from the container, which is detected using
+/// `Resources::generation`. As long as the structure of the container does
+/// not change, fetching the cached resources only checks the borrow flags.
+///
+/// The cache is also an `Accessor` that reads the cached resources, so it
+/// can be stored in a system and returned by `System::accessor` to fetch a
+/// `Cached` system data.
+pub struct ResourceCache {
+    ids: Vec<ResourceId>,
+    generation: CopyCell<u64>,
+    cells: Vec<CopyCell<*const Cell<Box<dyn Resource>>>>,
+}
+
+// SAFETY: The cached pointers are only dereferenced while the `Resources`
+// they belong to are borrowed. The cache is not `Sync`, so it is never
+// updated concurrently.
+unsafe impl Send for ResourceCache {}
+
+impl ResourceCache {
+    /// Creates a new cache for the resources with the passed ids.
+    pub fn new(ids: Vec<ResourceId>) -> Self {
+        let cells = ids.iter().map(|_| CopyCell::new(null())).collect();
+
+        Self {
+            ids,
+            generation: CopyCell::new(0),
+            cells,
+        }
+    }
+
+    /// Creates a new cache for the resources read by the system data `T`.
+    pub fn of<'a, T>() -> Self
+    where
+        T: SystemData<'a>,
+    {
+        Self::new(T::reads())
+    }
+
+    /// Returns the ids of the cached resources.
+    pub fn ids(&self) -> &[ResourceId] {
+        &self.ids
+    }
+
+    /// Returns the cells of the cached resources in the order of the ids,
+    /// or `None` for each resource that does not exist in `resources`.
+    pub fn cells<'a, 'b>(
+        &'b self,
+        resources: &'a Resources,
+    ) -> impl Iterator<Item = Option<&'a Cell<Box<dyn Resource>>>> + 'b
+    where
+        'a: 'b,
+    {
+        if self.generation.get() != resources.generation() {
+            for (id, cell) in self.ids.iter().zip(&self.cells) {
+                cell.set(
+                    resources
+                        .get_raw(id)
+                        .map_or(null(), |cell| cell as *const _),
+                );
+            }
+
+            self.generation.set(resources.generation());
+        }
+
+        // SAFETY: The generation of the resources did not change since the
+        // pointers were stored, so the cells were not moved or dropped.
+        self.cells.iter().map(|cell| unsafe { cell.get().as_ref() })
+    }
+}
+
+impl Accessor for ResourceCache {
+    fn reads(&self) -> Vec<ResourceId> {
+        self.ids.clone()
+    }
+}
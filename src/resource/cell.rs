@@ -1,7 +1,16 @@
 use std::cell::UnsafeCell;
 use std::mem::forget;
 use std::ops::{Deref, DerefMut};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::storage::{current_tick, Tick};
+
+/// Values of the borrow flag above this value indicate mutable borrows. The
+/// first mutable borrow sets the flag to `usize::MAX`, each additional
+/// mutable borrow created by `RefMut::map_split` decrements it by one.
+const MUTABLY_BORROWED: usize = usize::MAX / 2;
 
 macro_rules! borrow_panic {
     ($s:expr) => {{
@@ -14,9 +23,15 @@ macro_rules! borrow_panic {
 }
 
 /// A custom cell container that is a `RefCell` with thread-safety.
+///
+/// Additionally the cell remembers the tick of the global change clock (see
+/// `storage::current_tick`) its data was last modified at. The tick is
+/// updated each time a mutable borrow is released.
 #[derive(Debug)]
 pub struct Cell<T> {
     flag: AtomicUsize,
+    changed: AtomicU64,
+    lock: RwLock<()>,
     inner: UnsafeCell<T>,
 }
 
@@ -25,6 +40,8 @@ impl<T> Cell<T> {
     pub fn new(inner: T) -> Self {
         Cell {
             flag: AtomicUsize::new(0),
+            changed: AtomicU64::new(current_tick()),
+            lock: RwLock::new(()),
             inner: UnsafeCell::new(inner),
         }
     }
@@ -34,6 +51,11 @@ impl<T> Cell<T> {
         self.inner.into_inner()
     }
 
+    /// Returns the tick the data was last modified at.
+    pub fn last_changed(&self) -> Tick {
+        self.changed.load(Ordering::Acquire)
+    }
+
     /// Get an immutable reference to the inner data.
     ///
     /// Absence of write accesses is checked at run-time.
@@ -49,6 +71,7 @@ impl<T> Cell<T> {
 
         Ref {
             flag: &self.flag,
+            changed: &self.changed,
             value: unsafe { &*self.inner.get() },
         }
     }
@@ -61,6 +84,7 @@ impl<T> Cell<T> {
         if self.check_flag_read() {
             Some(Ref {
                 flag: &self.flag,
+                changed: &self.changed,
                 value: unsafe { &*self.inner.get() },
             })
         } else {
@@ -83,6 +107,7 @@ impl<T> Cell<T> {
 
         RefMut {
             flag: &self.flag,
+            changed: &self.changed,
             value: unsafe { &mut *self.inner.get() },
         }
     }
@@ -95,6 +120,7 @@ impl<T> Cell<T> {
         if self.check_flag_write() {
             Some(RefMut {
                 flag: &self.flag,
+                changed: &self.changed,
                 value: unsafe { &mut *self.inner.get() },
             })
         } else {
@@ -102,10 +128,28 @@ impl<T> Cell<T> {
         }
     }
 
+    /// Acquires the async lock of the cell for shared access.
+    ///
+    /// The lock is independent of the borrow flag: it does not borrow the
+    /// inner data, but allows async code to wait until no one else holds the
+    /// lock for exclusive access, before actually borrowing the data.
+    pub async fn lock(&self) -> RwLockReadGuard<'_, ()> {
+        self.lock.read().await
+    }
+
+    /// Acquires the async lock of the cell for exclusive access.
+    ///
+    /// See `lock` for details.
+    pub async fn lock_mut(&self) -> RwLockWriteGuard<'_, ()> {
+        self.lock.write().await
+    }
+
     /// Gets exclusive access to the inner value, bypassing the Cell.
     ///
     /// Exclusive access is checked at compile time.
     pub fn get_mut(&mut self) -> &mut T {
+        *self.changed.get_mut() = current_tick();
+
         unsafe { &mut *self.inner.get() }
     }
 
@@ -115,7 +159,7 @@ impl<T> Cell<T> {
         loop {
             let val = self.flag.load(Ordering::Acquire);
 
-            if val == usize::MAX {
+            if val > MUTABLY_BORROWED {
                 return false;
             }
 
@@ -149,6 +193,7 @@ where
     T: ?Sized + 'a,
 {
     flag: &'a AtomicUsize,
+    changed: &'a AtomicU64,
     value: &'a T,
 }
 
@@ -204,15 +249,74 @@ where
         U: ?Sized,
     {
         let flag = unsafe { &*(self.flag as *const _) };
+        let changed = unsafe { &*(self.changed as *const _) };
         let value = unsafe { &*(self.value as *const _) };
 
         forget(self);
 
         Ref {
             flag,
+            changed,
             value: f(value),
         }
     }
+
+    /// Splits a `Ref` into multiple `Ref`s for different components of the
+    /// borrowed data.
+    ///
+    /// The `Cell` is already immutably borrowed, so this cannot fail.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `Ref::map_split(...)`, for the same reasons as `Ref::map`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_ecs::resource::cell::*;
+    ///
+    /// let c = Cell::new((5, 'b'));
+    /// let b: Ref<'_, (u32, char)> = c.borrow();
+    /// let (b1, b2): (Ref<'_, u32>, Ref<'_, char>) = Ref::map_split(b, |t| (&t.0, &t.1));
+    /// assert_eq!(*b1, 5);
+    /// assert_eq!(*b2, 'b');
+    /// ```
+    pub fn map_split<U, V, F>(self, f: F) -> (Ref<'a, U>, Ref<'a, V>)
+    where
+        F: FnOnce(&T) -> (&U, &V),
+        U: ?Sized,
+        V: ?Sized,
+    {
+        self.flag.fetch_add(1, Ordering::Release);
+
+        let flag = unsafe { &*(self.flag as *const _) };
+        let changed = unsafe { &*(self.changed as *const _) };
+        let value = unsafe { &*(self.value as *const _) };
+
+        forget(self);
+
+        let (a, b) = f(value);
+
+        (
+            Ref {
+                flag,
+                changed,
+                value: a,
+            },
+            Ref {
+                flag,
+                changed,
+                value: b,
+            },
+        )
+    }
+
+    /// Returns the tick the borrowed data was last modified at.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `Ref::last_changed(...)`, for the same reasons as `Ref::map`.
+    pub fn last_changed(this: &Self) -> Tick {
+        this.changed.load(Ordering::Acquire)
+    }
 }
 
 impl<'a, T> Deref for Ref<'a, T>
@@ -244,6 +348,7 @@ where
 
         Ref {
             flag: self.flag,
+            changed: self.changed,
             value: self.value,
         }
     }
@@ -258,6 +363,7 @@ where
     T: ?Sized + 'a,
 {
     flag: &'a AtomicUsize,
+    changed: &'a AtomicU64,
     value: &'a mut T,
 }
 
@@ -315,15 +421,75 @@ where
         U: ?Sized,
     {
         let flag = unsafe { &*(self.flag as *const _) };
+        let changed = unsafe { &*(self.changed as *const _) };
         let value = unsafe { &mut *(self.value as *mut _) };
 
         forget(self);
 
         RefMut {
             flag,
+            changed,
             value: f(value),
         }
     }
+
+    /// Splits a `RefMut` into multiple `RefMut`s for different components of
+    /// the borrowed data.
+    ///
+    /// The `Cell` stays mutably borrowed until all of the returned `RefMut`s
+    /// are dropped. The closure has to return disjoint references, which is
+    /// ensured by the borrow checker.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RefMut::map_split(...)`, for the same reasons as `RefMut::map`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_ecs::resource::cell::*;
+    ///
+    /// let c = Cell::new((5, 'b'));
+    /// let b: RefMut<'_, (u32, char)> = c.borrow_mut();
+    /// let (mut b1, mut b2) = RefMut::map_split(b, |t| (&mut t.0, &mut t.1));
+    /// *b1 = 6;
+    /// *b2 = 'c';
+    /// drop(b1);
+    ///
+    /// assert!(c.try_borrow().is_none());
+    ///
+    /// drop(b2);
+    ///
+    /// assert_eq!(*c.borrow(), (6, 'c'));
+    /// ```
+    pub fn map_split<U, V, F>(self, f: F) -> (RefMut<'a, U>, RefMut<'a, V>)
+    where
+        F: FnOnce(&mut T) -> (&mut U, &mut V),
+        U: ?Sized,
+        V: ?Sized,
+    {
+        self.flag.fetch_sub(1, Ordering::Release);
+
+        let flag = unsafe { &*(self.flag as *const _) };
+        let changed = unsafe { &*(self.changed as *const _) };
+        let value = unsafe { &mut *(self.value as *mut _) };
+
+        forget(self);
+
+        let (a, b) = f(value);
+
+        (
+            RefMut {
+                flag,
+                changed,
+                value: a,
+            },
+            RefMut {
+                flag,
+                changed,
+                value: b,
+            },
+        )
+    }
 }
 
 impl<'a, T> Deref for RefMut<'a, T>
@@ -351,12 +517,17 @@ where
     T: ?Sized,
 {
     fn drop(&mut self) {
-        self.flag.store(0, Ordering::Release)
+        self.changed.store(current_tick(), Ordering::Release);
+
+        // Releases one mutable borrow, the last one wraps the flag to `0`.
+        self.flag.fetch_add(1, Ordering::Release);
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::storage::advance_tick;
+
     use super::*;
 
     #[test]
@@ -424,6 +595,19 @@ mod tests {
         assert_eq!(7, *cell.borrow_mut());
     }
 
+    #[test]
+    fn write_updates_tick() {
+        let cell = Cell::new(5);
+        let created = cell.last_changed();
+
+        let tick = advance_tick();
+        assert!(Ref::last_changed(&cell.borrow()) < tick);
+
+        *cell.borrow_mut() = 7;
+        assert!(cell.last_changed() >= tick);
+        assert!(cell.last_changed() > created);
+    }
+
     #[test]
     fn try_write_and_read() {
         let cell = Cell::new(5);
@@ -474,6 +658,7 @@ mod tests {
     fn ref_with_non_sized() {
         let r: Ref<'_, [i32]> = Ref {
             flag: &AtomicUsize::new(1),
+            changed: &AtomicU64::new(0),
             value: &[2, 3, 4, 5][..],
         };
 
@@ -484,6 +669,7 @@ mod tests {
     fn ref_with_non_sized_clone() {
         let r: Ref<'_, [i32]> = Ref {
             flag: &AtomicUsize::new(1),
+            changed: &AtomicU64::new(0),
             value: &[2, 3, 4, 5][..],
         };
         let rr = r.clone();
@@ -499,6 +685,7 @@ mod tests {
     fn ref_with_trait_obj() {
         let ra: Ref<'_, dyn std::any::Any> = Ref {
             flag: &AtomicUsize::new(1),
+            changed: &AtomicU64::new(0),
             value: &2i32,
         };
 
@@ -509,6 +696,7 @@ mod tests {
     fn ref_mut_with_non_sized() {
         let mut r: RefMut<'_, [i32]> = RefMut {
             flag: &AtomicUsize::new(1),
+            changed: &AtomicU64::new(0),
             value: &mut [2, 3, 4, 5][..],
         };
 
@@ -519,6 +707,7 @@ mod tests {
     fn ref_mut_with_trait_obj() {
         let mut ra: RefMut<'_, dyn std::any::Any> = RefMut {
             flag: &AtomicUsize::new(1),
+            changed: &AtomicU64::new(0),
             value: &mut 2i32,
         };
 
@@ -614,4 +803,21 @@ mod tests {
         drop(r);
         assert_eq!(cell.flag.load(Ordering::SeqCst), 0);
     }
+
+    #[test]
+    fn ref_mut_map_split_keeps_borrow() {
+        let cell = Cell::new((1, 2));
+
+        let (mut a, b) = RefMut::map_split(cell.borrow_mut(), |t| (&mut t.0, &mut t.1));
+        assert_eq!(cell.flag.load(Ordering::SeqCst), std::usize::MAX - 1);
+        *a += *b;
+
+        drop(b);
+        assert!(cell.try_borrow().is_none());
+        assert!(cell.try_borrow_mut().is_none());
+
+        drop(a);
+        assert_eq!(cell.flag.load(Ordering::SeqCst), 0);
+        assert_eq!(*cell.borrow(), (3, 2));
+    }
 }
@@ -0,0 +1,235 @@
+use std::any::{type_name, Any, TypeId};
+use std::mem::{forget, take};
+use std::thread::{self, ThreadId};
+
+use hashbrown::HashMap;
+use log::warn;
+
+use crate::error::Error;
+
+use super::cell::{Cell, Ref, RefMut};
+
+/// Store for resources that are not `Send` or `Sync`, like GPU contexts or
+/// window handles.
+///
+/// The store itself lives in the `World` like any other resource, but the
+/// contained resources can only be accessed from the thread that inserted
+/// the first of them. Each access from a different thread panics. Systems
+/// access the resources using `LocalRead` and `LocalWrite`, which makes the
+/// dispatcher execute them on the local task set (like `add_local` does).
+///
+/// If the store is dropped on a different thread, the contained resources
+/// are leaked instead of being dropped.
+#[derive(Default)]
+pub struct LocalResources {
+    owner: Option<ThreadId>,
+    resources: HashMap<TypeId, Cell<Box<dyn Any>>>,
+}
+
+impl LocalResources {
+    /// Returns the id of the thread the resources are bound to, or `None` if
+    /// no resource was inserted yet.
+    pub fn owner(&self) -> Option<ThreadId> {
+        self.owner
+    }
+
+    /// Returns the number of resources in the store.
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Returns `true` if the store does not contain any resources.
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Inserts a resource into the store. An existing resource of the same
+    /// type is replaced.
+    ///
+    /// The first insert binds the store to the current thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the store is bound to a different thread.
+    pub fn insert<R>(&mut self, r: R)
+    where
+        R: 'static,
+    {
+        let current = thread::current().id();
+        self.owner.get_or_insert(current);
+        self.assert_owner::<R>();
+
+        self.resources
+            .insert(TypeId::of::<R>(), Cell::new(Box::new(r)));
+    }
+
+    /// Removes the resource of type `R` from the store and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the store is bound to a different thread.
+    pub fn remove<R>(&mut self) -> Option<R>
+    where
+        R: 'static,
+    {
+        self.assert_owner::<R>();
+
+        self.resources
+            .remove(&TypeId::of::<R>())
+            .map(Cell::into_inner)
+            .map(|r| *r.downcast().unwrap())
+    }
+
+    /// Returns `true` if the store contains a resource of type `R`.
+    pub fn contains<R>(&self) -> bool
+    where
+        R: 'static,
+    {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    /// Borrows the resource of type `R` immutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist, is borrowed mutably or if the
+    /// store is bound to a different thread.
+    pub fn borrow<R>(&self) -> Ref<'_, R>
+    where
+        R: 'static,
+    {
+        self.fetch().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Borrows the resource of type `R` mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist, is already borrowed or if the
+    /// store is bound to a different thread.
+    pub fn borrow_mut<R>(&self) -> RefMut<'_, R>
+    where
+        R: 'static,
+    {
+        self.fetch_mut().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as `borrow`, but returns an error instead of panicking if the
+    /// resource does not exist or is borrowed mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the store is bound to a different thread.
+    pub fn fetch<R>(&self) -> Result<Ref<'_, R>, Error>
+    where
+        R: 'static,
+    {
+        let cell = self.cell::<R>()?;
+
+        match cell.try_borrow() {
+            Some(r) => Ok(r.map(|r| r.downcast_ref().unwrap())),
+            None => Err(Error::BorrowConflict {
+                resource: type_name::<R>(),
+                mutably: true,
+            }),
+        }
+    }
+
+    /// Same as `borrow_mut`, but returns an error instead of panicking if the
+    /// resource does not exist or is already borrowed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the store is bound to a different thread.
+    pub fn fetch_mut<R>(&self) -> Result<RefMut<'_, R>, Error>
+    where
+        R: 'static,
+    {
+        let cell = self.cell::<R>()?;
+
+        match cell.try_borrow_mut() {
+            Some(r) => Ok(r.map(|r| r.downcast_mut().unwrap())),
+            None => Err(Error::BorrowConflict {
+                resource: type_name::<R>(),
+                mutably: cell.try_borrow().is_none(),
+            }),
+        }
+    }
+
+    fn cell<R>(&self) -> Result<&Cell<Box<dyn Any>>, Error>
+    where
+        R: 'static,
+    {
+        self.assert_owner::<R>();
+
+        self.resources
+            .get(&TypeId::of::<R>())
+            .ok_or_else(|| Error::ResourceNotFound(type_name::<R>()))
+    }
+
+    fn is_owner(&self) -> bool {
+        self.owner.is_none() || self.owner == Some(thread::current().id())
+    }
+
+    fn assert_owner<R>(&self) {
+        assert!(
+            self.is_owner(),
+            "Local resource of type `{}` was accessed from a different thread than it was inserted on",
+            type_name::<R>(),
+        );
+    }
+}
+
+impl Drop for LocalResources {
+    fn drop(&mut self) {
+        if !self.is_owner() {
+            warn!(
+                "Local resources were dropped on a different thread, leaking {} resource(s).",
+                self.resources.len()
+            );
+
+            forget(take(&mut self.resources));
+        }
+    }
+}
+
+// SAFETY: The contained resources are only accessed from the owning thread,
+// which is checked at run-time by each method that touches them. The
+// resources are leaked if the store is dropped on any other thread.
+unsafe impl Send for LocalResources {}
+unsafe impl Sync for LocalResources {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    #[test]
+    fn bound_to_thread() {
+        let mut resources = LocalResources::default();
+        resources.insert(Rc::new(5u32));
+
+        assert_eq!(**resources.borrow::<Rc<u32>>(), 5);
+        assert!(resources.fetch::<Rc<bool>>().is_err());
+
+        {
+            let _r = resources.borrow_mut::<Rc<u32>>();
+            assert!(resources.fetch::<Rc<u32>>().is_err());
+        }
+
+        let resources = thread::spawn(move || {
+            let r = catch_unwind(AssertUnwindSafe(|| {
+                resources.borrow::<Rc<u32>>();
+            }));
+            assert!(r.is_err());
+
+            resources
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(**resources.borrow::<Rc<u32>>(), 5);
+    }
+}
@@ -1,11 +1,20 @@
+pub mod cache;
 pub mod cell;
 pub mod entry;
+pub mod local;
 pub mod resources;
 
+pub use cache::ResourceCache;
 pub use cell::Cell;
-pub use resources::{Ref, RefMut, Resources};
+pub use local::LocalResources;
+pub use resources::{BorrowConflict, Ref, RefMut, ResourceLocks, Resources};
 
-use std::any::TypeId;
+use std::any::{type_name, TypeId};
+use std::cmp::Ordering;
+use std::collections::{hash_map::DefaultHasher, BTreeMap};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{Hash, Hasher};
+use std::sync::RwLock;
 
 use mopa::Any;
 
@@ -22,8 +31,12 @@ pub trait Resource: Any + Send + Sync + 'static {}
 /// at run time, without having different static types.
 ///
 /// [`Resource`]: trait.Resource.html
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub struct ResourceId(TypeId);
+#[derive(Clone)]
+pub struct ResourceId {
+    type_id: TypeId,
+    dynamic_id: u64,
+    type_name: &'static str,
+}
 
 impl ResourceId {
     /// Creates a new resource id from a given type.
@@ -31,12 +44,157 @@ impl ResourceId {
     where
         R: Resource,
     {
-        Self(TypeId::of::<R>())
+        Self::new_with_dynamic_id::<R>(0)
+    }
+
+    /// Creates a new resource id from a given type and a dynamic id.
+    ///
+    /// Resources with the same type but different dynamic ids are distinct
+    /// resources, so many instances of the same type can be stored in the
+    /// `World`. Use the `*_by_id` methods of `Resources` to access them.
+    pub fn new_with_dynamic_id<R>(dynamic_id: u64) -> Self
+    where
+        R: Resource,
+    {
+        Self {
+            type_id: TypeId::of::<R>(),
+            dynamic_id,
+            type_name: type_name::<R>(),
+        }
+    }
+
+    /// Creates the id of a resource of type `R` that is stored in the
+    /// `LocalResources` of the `World`.
+    ///
+    /// The id is never used to access the resource, it is only reported as
+    /// dependency by `LocalRead` and `LocalWrite`, so the dispatcher is able
+    /// to order the systems that access the same local resource and to
+    /// execute them on the local task set.
+    pub fn new_local<R>() -> Self
+    where
+        R: 'static,
+    {
+        let mut hasher = DefaultHasher::new();
+        TypeId::of::<R>().hash(&mut hasher);
+
+        Self {
+            type_id: TypeId::of::<LocalResources>(),
+            dynamic_id: hasher.finish().max(1),
+            type_name: type_name::<R>(),
+        }
+    }
+
+    /// Returns `true` if the id was created by `ResourceId::new_local`.
+    pub fn is_local(&self) -> bool {
+        self.type_id == TypeId::of::<LocalResources>() && self.dynamic_id != 0
+    }
+
+    /// Returns the dynamic id of the resource.
+    pub fn dynamic_id(&self) -> u64 {
+        self.dynamic_id
+    }
+
+    /// Returns the name of the resource type. The name is only meant to be
+    /// used for diagnostic purposes.
+    ///
+    /// Ids that were created from a plain `TypeId` are named using the
+    /// registry of the resource names, see `ResourceId::register_name`.
+    pub fn name(&self) -> &'static str {
+        if self.type_name == UNKNOWN_NAME {
+            lookup_name(self.type_id).unwrap_or(UNKNOWN_NAME)
+        } else {
+            self.type_name
+        }
+    }
+
+    /// Adds the name of the resource type to the global registry of resource
+    /// names, so ids that are created from the plain `TypeId` of the resource
+    /// are named as well. This is done automatically for each resource that
+    /// is inserted into `Resources`.
+    pub fn register_name(&self) {
+        if self.type_name == UNKNOWN_NAME || lookup_name(self.type_id).is_some() {
+            return;
+        }
+
+        NAMES.write().unwrap().insert(self.type_id, self.type_name);
+    }
+
+    /// Panics if the id does not belong to a resource of type `R`.
+    fn assert_same_type_id<R>(&self)
+    where
+        R: Resource,
+    {
+        assert_eq!(
+            self.type_id,
+            TypeId::of::<R>(),
+            "Resource id of type `{}` does not belong to type `{}`",
+            self.type_name,
+            type_name::<R>(),
+        );
     }
 }
 
 impl From<TypeId> for ResourceId {
-    fn from(id: TypeId) -> Self {
-        Self(id)
+    fn from(type_id: TypeId) -> Self {
+        Self {
+            type_id,
+            dynamic_id: 0,
+            type_name: lookup_name(type_id).unwrap_or(UNKNOWN_NAME),
+        }
+    }
+}
+
+impl Debug for ResourceId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut s = f.debug_tuple("ResourceId");
+        s.field(&self.name());
+
+        if self.dynamic_id != 0 {
+            s.field(&self.dynamic_id);
+        }
+
+        s.finish()
+    }
+}
+
+impl PartialEq for ResourceId {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_id == other.type_id && self.dynamic_id == other.dynamic_id
+    }
+}
+
+impl Eq for ResourceId {}
+
+impl PartialOrd for ResourceId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ResourceId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.type_id
+            .cmp(&other.type_id)
+            .then(self.dynamic_id.cmp(&other.dynamic_id))
+    }
+}
+
+impl Hash for ResourceId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.type_id.hash(state);
+        self.dynamic_id.hash(state);
     }
 }
+
+/* Names */
+
+/// Name of resource types that are not known.
+const UNKNOWN_NAME: &str = "<unknown>";
+
+/// Names of the resource types, registered by `ResourceId::register_name`.
+static NAMES: RwLock<BTreeMap<TypeId, &'static str>> = RwLock::new(BTreeMap::new());
+
+/// Returns the registered name of the resource type with the passed id.
+pub fn lookup_name(type_id: TypeId) -> Option<&'static str> {
+    NAMES.read().unwrap().get(&type_id).copied()
+}
@@ -1,20 +1,30 @@
+use std::any::type_name;
+use std::cell::RefCell;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 use hashbrown::HashMap;
 use mopa::Any;
+use tokio::sync::{
+    OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
 
 pub use super::cell::Cell;
 
+use crate::{error::Error, storage::Tick};
+
 use super::{
     cell::{Ref as CellRef, RefMut as CellRefMut},
     entry::Entry,
     Resource, ResourceId,
 };
 
-#[derive(Default)]
 pub struct Resources {
     resources: HashMap<ResourceId, Cell<Box<dyn Resource>>>,
+    local_locks: Mutex<HashMap<ResourceId, Arc<RwLock<()>>>>,
+    generation: u64,
 }
 
 pub struct Ref<'a, R: 'a> {
@@ -40,6 +50,83 @@ macro_rules! fetch_panic {
             resource_name_full = std::any::type_name::<R>(),
         )
     }};
+    ($id:expr) => {{
+        panic!(
+            "\
+            Tried to fetch resource from the resources map, but the resource does not exist.\n\
+\n\
+            Resource: `{resource_name_full}` (dynamic id {dynamic_id})\n\
+\n\
+            You may ensure the resource exists!\
+            ",
+            resource_name_full = $id.name(),
+            dynamic_id = $id.dynamic_id(),
+        )
+    }};
+}
+
+macro_rules! borrow_panic {
+    ($id:expr, $s:expr) => {{
+        BORROW_CONFLICT.with(|conflict| {
+            *conflict.borrow_mut() = Some(BorrowConflict {
+                id: $id.clone(),
+                name: type_name::<R>(),
+                mutably: $s,
+            })
+        });
+
+        panic!(
+            "Tried to fetch data of type {:?}, but it was already borrowed{}.",
+            type_name::<R>(),
+            if $s { " mutably" } else { "" },
+        )
+    }};
+}
+
+/// Source of the generations of all `Resources` containers. Generations are
+/// never reused, so a generation identifies a container and its structure.
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static BORROW_CONFLICT: RefCell<Option<BorrowConflict>> = const { RefCell::new(None) };
+}
+
+/// Describes a failed attempt to borrow a resource, because it was already
+/// borrowed in an incompatible way.
+#[derive(Clone, Debug)]
+pub struct BorrowConflict {
+    /// Id of the resource that could not be borrowed.
+    pub id: ResourceId,
+
+    /// Type name of the resource that could not be borrowed.
+    pub name: &'static str,
+
+    /// `true` if the resource was already borrowed mutably, `false` if it
+    /// was already borrowed immutably.
+    pub mutably: bool,
+}
+
+impl BorrowConflict {
+    /// Takes the last borrow conflict that occurred in the current thread.
+    ///
+    /// Each failed borrow records the conflict before panicking, so this
+    /// can be used to get details about the conflict after catching the
+    /// panic.
+    pub fn take_last() -> Option<Self> {
+        BORROW_CONFLICT.with(|conflict| conflict.borrow_mut().take())
+    }
+
+    /// Returns `true` if a borrow conflict occurred in the current thread
+    /// that was not taken yet.
+    pub(crate) fn is_pending() -> bool {
+        BORROW_CONFLICT.with(|conflict| conflict.borrow().is_some())
+    }
+
+    /// Forgets the last borrow conflict of the current thread, so a stale
+    /// conflict is not reported for an unrelated panic later on.
+    pub(crate) fn reset() {
+        BORROW_CONFLICT.with(|conflict| conflict.borrow_mut().take());
+    }
 }
 
 /// A [Resource] container, which provides methods to insert, access and manage
@@ -52,14 +139,22 @@ macro_rules! fetch_panic {
 ///
 /// # Resource Ids
 ///
-/// Resources are identified by `ResourceId`s, which consist of a `TypeId`.
+/// Resources are identified by `ResourceId`s, which consist of a `TypeId`
+/// and a dynamic id. The methods that take a type parameter only use the
+/// dynamic id `0`, use the `*_by_id` methods to access the resources with
+/// other dynamic ids.
 impl Resources {
     /// Returns an entry for the resource with type `R`.
     pub fn entry<R>(&mut self) -> Entry<R>
     where
         R: Resource,
     {
-        Entry::new(self.resources.entry(ResourceId::new::<R>()))
+        self.touch();
+
+        let id = ResourceId::new::<R>();
+        id.register_name();
+
+        Entry::new(self.resources.entry(id))
     }
 
     /// Inserts a resource into this container. If the resource existed before,
@@ -88,8 +183,24 @@ impl Resources {
     where
         R: Resource,
     {
-        self.resources
-            .insert(ResourceId::new::<R>(), Cell::new(Box::new(r)));
+        self.insert_by_id(ResourceId::new::<R>(), r);
+    }
+
+    /// Inserts a resource with the passed id into this container. If the
+    /// resource existed before, it will be overwritten.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id does not belong to the type `R`.
+    pub fn insert_by_id<R>(&mut self, id: ResourceId, r: R)
+    where
+        R: Resource,
+    {
+        id.assert_same_type_id::<R>();
+        id.register_name();
+
+        self.touch();
+        self.resources.insert(id, Cell::new(Box::new(r)));
     }
 
     /// Removes a resource of type `R` from this container and returns its
@@ -104,20 +215,51 @@ impl Resources {
     where
         R: Resource,
     {
+        self.remove_by_id(&ResourceId::new::<R>())
+    }
+
+    /// Removes the resource with the passed id from this container and
+    /// returns its ownership to the caller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id does not belong to the type `R`.
+    pub fn remove_by_id<R>(&mut self, id: &ResourceId) -> Option<R>
+    where
+        R: Resource,
+    {
+        id.assert_same_type_id::<R>();
+
+        self.touch();
         self.resources
-            .remove(&ResourceId::new::<R>())
+            .remove(id)
             .map(Cell::into_inner)
             .map(|x: Box<dyn Resource>| x.downcast())
             .map(|x: Result<Box<R>, _>| x.ok().unwrap())
             .map(|x| *x)
     }
 
+    /// Moves all resources of `other` into this container, that do not
+    /// exist in this container yet.
+    pub fn merge(&mut self, other: Resources) {
+        self.touch();
+
+        for (id, resource) in other.resources {
+            self.resources.entry(id).or_insert(resource);
+        }
+    }
+
     /// Returns true if the specified resource type `R` exists in `self`.
     pub fn contains<R>(&self) -> bool
     where
         R: Resource,
     {
-        self.resources.contains_key(&ResourceId::new::<R>())
+        self.contains_by_id(&ResourceId::new::<R>())
+    }
+
+    /// Returns true if the resource with the passed id exists in `self`.
+    pub fn contains_by_id(&self, id: &ResourceId) -> bool {
+        self.resources.contains_key(id)
     }
 
     /// Fetches the resource with the specified type `T` or panics if it doesn't
@@ -140,8 +282,139 @@ impl Resources {
     where
         R: Resource,
     {
-        self.resources.get(&ResourceId::new::<R>()).map(|r| Ref {
-            inner: CellRef::map(r.borrow(), Box::as_ref),
+        self.try_borrow_by_id(&ResourceId::new::<R>())
+    }
+
+    /// Fetches the resource with the passed id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource doesn't exist.
+    /// Panics if the resource is being accessed mutably.
+    /// Panics if the id does not belong to the type `R`.
+    pub fn borrow_by_id<R>(&self, id: &ResourceId) -> Ref<'_, R>
+    where
+        R: Resource,
+    {
+        self.try_borrow_by_id(id).unwrap_or_else(|| fetch_panic!(id))
+    }
+
+    /// Like `borrow_by_id`, but returns `None` if the resource does not
+    /// exist.
+    pub fn try_borrow_by_id<R>(&self, id: &ResourceId) -> Option<Ref<'_, R>>
+    where
+        R: Resource,
+    {
+        id.assert_same_type_id::<R>();
+
+        Self::try_borrow_cell(id, self.resources.get(id))
+    }
+
+    /// Borrows the resource with the passed id from the passed cell. This
+    /// is used to fetch resources from the cells of a `ResourceCache`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell is `None`.
+    /// Panics if the resource is being accessed mutably.
+    pub(crate) fn borrow_cell<'a, R>(
+        id: &ResourceId,
+        cell: Option<&'a Cell<Box<dyn Resource>>>,
+    ) -> Ref<'a, R>
+    where
+        R: Resource,
+    {
+        Self::try_borrow_cell(id, cell).unwrap_or_else(|| fetch_panic!(id))
+    }
+
+    /// Like `borrow_cell`, but returns `None` if the cell is `None`.
+    pub(crate) fn try_borrow_cell<'a, R>(
+        id: &ResourceId,
+        cell: Option<&'a Cell<Box<dyn Resource>>>,
+    ) -> Option<Ref<'a, R>>
+    where
+        R: Resource,
+    {
+        cell.map(|r| Ref {
+            inner: CellRef::map(
+                r.try_borrow().unwrap_or_else(|| borrow_panic!(id, true)),
+                Box::as_ref,
+            ),
+            phantom: PhantomData,
+        })
+    }
+
+    /// Fetches the resource with the specified type `R`.
+    ///
+    /// In contrast to `borrow` this never panics. Instead an error is
+    /// returned if the resource does not exist or if it is already borrowed
+    /// mutably.
+    pub fn fetch<R>(&self) -> Result<Ref<'_, R>, Error>
+    where
+        R: Resource,
+    {
+        self.fetch_by_id(&ResourceId::new::<R>())
+    }
+
+    /// Same as `fetch`, but for the resource with the passed id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id does not belong to the type `R`.
+    pub fn fetch_by_id<R>(&self, id: &ResourceId) -> Result<Ref<'_, R>, Error>
+    where
+        R: Resource,
+    {
+        id.assert_same_type_id::<R>();
+
+        let cell = self
+            .resources
+            .get(id)
+            .ok_or_else(|| Error::ResourceNotFound(id.name()))?;
+        let inner = cell.try_borrow().ok_or(Error::BorrowConflict {
+            resource: id.name(),
+            mutably: true,
+        })?;
+
+        Ok(Ref {
+            inner: CellRef::map(inner, Box::as_ref),
+            phantom: PhantomData,
+        })
+    }
+
+    /// Fetches the resource with the specified type `R` mutably.
+    ///
+    /// In contrast to `borrow_mut` this never panics. Instead an error is
+    /// returned if the resource does not exist or if it is already borrowed.
+    pub fn fetch_mut<R>(&self) -> Result<RefMut<'_, R>, Error>
+    where
+        R: Resource,
+    {
+        self.fetch_mut_by_id(&ResourceId::new::<R>())
+    }
+
+    /// Same as `fetch_mut`, but for the resource with the passed id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id does not belong to the type `R`.
+    pub fn fetch_mut_by_id<R>(&self, id: &ResourceId) -> Result<RefMut<'_, R>, Error>
+    where
+        R: Resource,
+    {
+        id.assert_same_type_id::<R>();
+
+        let cell = self
+            .resources
+            .get(id)
+            .ok_or_else(|| Error::ResourceNotFound(id.name()))?;
+        let inner = cell.try_borrow_mut().ok_or_else(|| Error::BorrowConflict {
+            resource: id.name(),
+            mutably: cell.try_borrow().is_none(),
+        })?;
+
+        Ok(RefMut {
+            inner: inner.map(Box::as_mut),
             phantom: PhantomData,
         })
     }
@@ -167,8 +440,33 @@ impl Resources {
     where
         R: Resource,
     {
-        self.resources.get(&ResourceId::new::<R>()).map(|r| RefMut {
-            inner: r.borrow_mut().map(Box::as_mut),
+        self.try_borrow_mut_by_id(&ResourceId::new::<R>())
+    }
+
+    /// Fetches the resource with the passed id mutably.
+    ///
+    /// Please see `borrow_by_id` for details.
+    pub fn borrow_mut_by_id<R>(&self, id: &ResourceId) -> RefMut<'_, R>
+    where
+        R: Resource,
+    {
+        self.try_borrow_mut_by_id(id)
+            .unwrap_or_else(|| fetch_panic!(id))
+    }
+
+    /// Like `borrow_mut_by_id`, but returns `None` if the resource does not
+    /// exist.
+    pub fn try_borrow_mut_by_id<R>(&self, id: &ResourceId) -> Option<RefMut<'_, R>>
+    where
+        R: Resource,
+    {
+        id.assert_same_type_id::<R>();
+
+        self.resources.get(id).map(|r| RefMut {
+            inner: r
+                .try_borrow_mut()
+                .unwrap_or_else(|| borrow_panic!(id, r.try_borrow().is_none()))
+                .map(Box::as_mut),
             phantom: PhantomData,
         })
     }
@@ -189,10 +487,118 @@ impl Resources {
             .map(Box::as_mut)
     }
 
+    /// Acquires the async locks of the passed resources. Resources that are
+    /// contained in `writes` are locked exclusively, all others are shared.
+    ///
+    /// As long as all code that accesses the resources acquires the locks
+    /// first, borrowing the resources will never panic. Instead the code
+    /// waits until the conflicting locks are released. The locks are always
+    /// acquired in the order of the resource ids, so two callers can not
+    /// dead lock each other. Resources that do not exist are skipped.
+    ///
+    /// Ids of local resources (see `ResourceId::new_local`) do not belong to
+    /// a resource of this container, so a separate lock is created for each
+    /// of them on first use.
+    pub async fn lock(&self, reads: &[ResourceId], writes: &[ResourceId]) -> ResourceLocks<'_> {
+        let mut ids = reads.iter().chain(writes).collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+
+        let mut guards = Vec::with_capacity(ids.len());
+        for id in ids {
+            let write = writes.contains(id);
+
+            let guard = match self.resources.get(id) {
+                Some(cell) if write => LockGuard::Write(cell.lock_mut().await),
+                Some(cell) => LockGuard::Read(cell.lock().await),
+                None if id.is_local() => {
+                    let lock = self.local_lock(id);
+
+                    if write {
+                        LockGuard::LocalWrite(lock.write_owned().await)
+                    } else {
+                        LockGuard::LocalRead(lock.read_owned().await)
+                    }
+                }
+                None => continue,
+            };
+
+            guards.push(guard);
+        }
+
+        ResourceLocks(guards)
+    }
+
+    fn local_lock(&self, id: &ResourceId) -> Arc<RwLock<()>> {
+        self.local_locks
+            .lock()
+            .unwrap()
+            .entry(id.clone())
+            .or_default()
+            .clone()
+    }
+
     /// Get raw access to the underlying cell.
     pub fn get_raw(&self, id: &ResourceId) -> Option<&Cell<Box<dyn Resource>>> {
         self.resources.get(id)
     }
+
+    /// Returns the structural generation of this container.
+    ///
+    /// The generation changes each time a resource is inserted or removed.
+    /// Generations are never shared between two containers, so a cell that
+    /// was looked up at a specific generation can be reused as long as the
+    /// generation of the container did not change (see `ResourceCache`).
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn touch(&mut self) {
+        self.generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for Resources {
+    fn default() -> Self {
+        Self {
+            resources: HashMap::default(),
+            local_locks: Mutex::default(),
+            generation: NEXT_GENERATION.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+/* ResourceLocks */
+
+/// Async locks of a set of resources, acquired by `Resources::lock`. The
+/// locks are released as soon as this is dropped.
+pub struct ResourceLocks<'a>(Vec<LockGuard<'a>>);
+
+enum LockGuard<'a> {
+    Read(RwLockReadGuard<'a, ()>),
+    Write(RwLockWriteGuard<'a, ()>),
+    LocalRead(OwnedRwLockReadGuard<()>),
+    LocalWrite(OwnedRwLockWriteGuard<()>),
+}
+
+impl ResourceLocks<'_> {
+    /// Returns the number of resources that were locked.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if no resource was locked.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of resources that were locked exclusively.
+    pub fn exclusive(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|guard| matches!(guard, LockGuard::Write(_) | LockGuard::LocalWrite(_)))
+            .count()
+    }
 }
 
 /* Resource */
@@ -218,6 +624,12 @@ impl<'a, R> Ref<'a, R> {
             phantom: PhantomData,
         }
     }
+
+    /// Returns the tick the resource was last modified at. See
+    /// `Cell::last_changed` for details.
+    pub fn last_changed(this: &Self) -> Tick {
+        CellRef::last_changed(&this.inner)
+    }
 }
 
 impl<'a, R> Deref for Ref<'a, R>
@@ -273,6 +685,10 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use futures::future::FutureExt;
+
     use super::*;
 
     #[derive(Default)]
@@ -289,6 +705,62 @@ mod tests {
         assert!(!resources.contains::<Foo>());
     }
 
+    #[test]
+    fn dynamic_ids() {
+        let id1 = ResourceId::new_with_dynamic_id::<usize>(1);
+        let id2 = ResourceId::new_with_dynamic_id::<usize>(2);
+
+        let mut resources = Resources::default();
+        resources.insert(0usize);
+        resources.insert_by_id(id1.clone(), 1usize);
+        resources.insert_by_id(id2.clone(), 2usize);
+
+        *resources.borrow_mut_by_id::<usize>(&id2) += 1;
+
+        {
+            let _write = resources.borrow_mut_by_id::<usize>(&id1);
+
+            assert_eq!(*resources.borrow::<usize>(), 0);
+            assert_eq!(*resources.borrow_by_id::<usize>(&id2), 3);
+        }
+
+        assert_eq!(resources.remove_by_id::<usize>(&id2), Some(3));
+        assert!(!resources.contains_by_id(&id2));
+    }
+
+    #[test]
+    fn names_of_plain_type_ids() {
+        use std::any::TypeId;
+
+        struct Named;
+
+        assert_eq!(ResourceId::from(TypeId::of::<Named>()).name(), "<unknown>");
+
+        let mut resources = Resources::default();
+        resources.insert(Named);
+
+        let id = ResourceId::from(TypeId::of::<Named>());
+        assert_eq!(id.name(), type_name::<Named>());
+        assert_eq!(
+            format!("{:?}", ResourceId::new_with_dynamic_id::<Named>(2)),
+            format!("ResourceId({:?}, 2)", type_name::<Named>()),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "(dynamic id 3)")]
+    fn missing_dynamic_id() {
+        let resources = Resources::default();
+        resources.borrow_by_id::<Res>(&ResourceId::new_with_dynamic_id::<Res>(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "does not belong to type")]
+    fn dynamic_id_of_other_type() {
+        let mut resources = Resources::default();
+        resources.insert_by_id(ResourceId::new_with_dynamic_id::<Res>(1), 1usize);
+    }
+
     #[test]
     #[should_panic(expected = "but it was already borrowed")]
     fn read_write_fails() {
@@ -309,6 +781,53 @@ mod tests {
         let _read = resources.borrow::<Res>();
     }
 
+    #[test]
+    fn borrow_conflict_is_recorded() {
+        let mut resources = Resources::default();
+        resources.insert(Res);
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _read = resources.borrow::<Res>();
+            let _write = resources.borrow_mut::<Res>();
+        }));
+        assert!(result.is_err());
+
+        let conflict = BorrowConflict::take_last().unwrap();
+        assert_eq!(conflict.id, ResourceId::new::<Res>());
+        assert!(conflict.name.ends_with("Res"));
+        assert!(!conflict.mutably);
+        assert!(BorrowConflict::take_last().is_none());
+    }
+
+    #[tokio::test]
+    async fn locks_are_exclusive() {
+        struct Other;
+
+        let mut resources = Resources::default();
+        resources.insert(Res);
+        resources.insert(Other);
+
+        let reads = [ResourceId::new::<Res>()];
+        let writes = [ResourceId::new::<Other>(), ResourceId::new::<u32>()];
+
+        let locks = resources.lock(&reads, &writes).await;
+        assert_eq!(locks.len(), 2);
+        assert_eq!(locks.exclusive(), 1);
+
+        // shared locks do not block each other
+        let shared = resources.lock(&reads, &[]).now_or_never();
+        assert!(shared.is_some());
+
+        // exclusive locks wait until the other locks are released
+        assert!(resources.lock(&[], &reads).now_or_never().is_none());
+        assert!(resources.lock(&writes, &[]).now_or_never().is_none());
+
+        drop(shared);
+        drop(locks);
+
+        assert!(resources.lock(&[], &reads).now_or_never().is_some());
+    }
+
     #[test]
     fn remove_insert() {
         let mut resources = Resources::default();
@@ -0,0 +1,103 @@
+use std::convert::Infallible;
+use std::fmt::Display;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::entity::{Entity, WeakEntity};
+
+use super::{Error, Marker};
+
+/// Converts a component into its serializable form and back.
+///
+/// This is implemented for all types that are `Serialize` and
+/// `DeserializeOwned` already. Components that store references to other
+/// entities have to implement it manually and convert the referenced
+/// entities using the passed `ids` function, which maps entities to their
+/// markers (and back).
+pub trait ConvertSaveload<M>: Sized {
+    /// Serializable representation of the component.
+    type Data: Serialize + DeserializeOwned;
+
+    /// Error that may occur during the conversion.
+    type Error: Display;
+
+    /// Converts the component into its serializable representation.
+    fn convert_into<F>(&self, ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>;
+
+    /// Converts the serializable representation back into the component.
+    fn convert_from<F>(data: Self::Data, ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>;
+}
+
+impl<C, M> ConvertSaveload<M> for C
+where
+    C: Clone + Serialize + DeserializeOwned,
+{
+    type Data = Self;
+    type Error = Infallible;
+
+    fn convert_into<F>(&self, _: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        Ok(self.clone())
+    }
+
+    fn convert_from<F>(data: Self::Data, _: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        Ok(data)
+    }
+}
+
+impl<M> ConvertSaveload<M> for Entity
+where
+    M: Marker,
+{
+    type Data = M;
+    type Error = Error;
+
+    fn convert_into<F>(&self, mut ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        ids(*self).ok_or(Error::EntityNotMarked(*self))
+    }
+
+    fn convert_from<F>(data: Self::Data, mut ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        ids(data).ok_or(Error::MarkerNotFound)
+    }
+}
+
+/// Weak references are stored as optional markers. References to entities
+/// that are not marked (for example because they were deleted) are stored
+/// as `None` and loaded as dangling references, instead of failing the
+/// whole conversion.
+impl<M> ConvertSaveload<M> for WeakEntity
+where
+    M: Marker,
+{
+    type Data = Option<M>;
+    type Error = Infallible;
+
+    fn convert_into<F>(&self, ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        Ok(self.entity_unchecked().and_then(ids))
+    }
+
+    fn convert_from<F>(data: Self::Data, ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        Ok(data.and_then(ids).into())
+    }
+}
@@ -0,0 +1,120 @@
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer};
+
+use crate::{
+    access::WriteStorage,
+    component::Component,
+    entity::{Entities, Entity},
+};
+
+use super::{ConvertSaveload, EntityData, Marker, MarkerAllocator};
+
+/// Deserializes entities and their components, that were serialized using
+/// `SerializeComponents`.
+///
+/// This is implemented for tuples of `WriteStorage`s. The storages have to
+/// be in the same order as the storages that were used for serialization.
+pub trait DeserializeComponents<M>
+where
+    M: Marker,
+{
+    /// Serialized data of the components of a single entity.
+    type Data: DeserializeOwned;
+
+    /// Inserts the passed components into the storages. Components that are
+    /// missing in the data are removed from the entity. `ids` is used to
+    /// look up the entities of referenced markers.
+    fn deserialize_entity<Err, Ids>(
+        &mut self,
+        entity: Entity,
+        components: Self::Data,
+        ids: Ids,
+    ) -> Result<(), Err>
+    where
+        Err: de::Error,
+        Ids: FnMut(M) -> Option<Entity>;
+
+    /// Deserializes a sequence of `EntityData`. Entities that are already
+    /// known by the allocator are updated, all others are created
+    /// atomically, so `World::maintain` has to be called afterwards.
+    fn deserialize<'de, D>(
+        &mut self,
+        entities: &Entities,
+        markers: &mut WriteStorage<M>,
+        allocator: &mut M::Allocator,
+        deserializer: D,
+    ) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = Vec::<EntityData<M, Self::Data>>::deserialize(deserializer)?;
+
+        for EntityData { marker, components } in data {
+            let entity = allocator.retrieve_entity(marker, markers, entities);
+            let ids = |marker| Some(allocator.retrieve_entity(marker, markers, entities));
+
+            self.deserialize_entity(entity, components, ids)?;
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! define_deserialize_components {
+    ($($from:ident $data:ident),*) => {
+        impl<'a, M, $($from,)*> DeserializeComponents<M> for ($(WriteStorage<'a, $from>,)*)
+        where
+            M: Marker,
+            $($from: Component + ConvertSaveload<M>,)*
+        {
+            type Data = ($(Option<<$from as ConvertSaveload<M>>::Data>,)*);
+
+            #[allow(non_snake_case)]
+            fn deserialize_entity<Err, Ids>(
+                &mut self,
+                entity: Entity,
+                components: Self::Data,
+                mut ids: Ids,
+            ) -> Result<(), Err>
+            where
+                Err: de::Error,
+                Ids: FnMut(M) -> Option<Entity>,
+            {
+                let ($(ref mut $from,)*) = *self;
+                let ($($data,)*) = components;
+
+                $(
+                    match $data {
+                        Some(data) => {
+                            let component =
+                                <$from as ConvertSaveload<M>>::convert_from(data, &mut ids).map_err(Err::custom)?;
+
+                            $from.insert(entity, component).map_err(Err::custom)?;
+                        }
+                        None => {
+                            $from.remove(entity);
+                        }
+                    }
+                )*
+
+                Ok(())
+            }
+        }
+    };
+}
+
+define_deserialize_components! { A a }
+define_deserialize_components! { A a, B b }
+define_deserialize_components! { A a, B b, C c }
+define_deserialize_components! { A a, B b, C c, D d }
+define_deserialize_components! { A a, B b, C c, D d, E e }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f, G g }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f, G g, H h }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l, N n }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l, N n, O o }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l, N n, O o, P p }
+define_deserialize_components! { A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l, N n, O o, P p, Q q }
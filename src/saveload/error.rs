@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+use crate::entity::Entity;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Referenced entity has no marker: {0}!")]
+    EntityNotMarked(Entity),
+
+    #[error("No entity was found for the referenced marker!")]
+    MarkerNotFound,
+}
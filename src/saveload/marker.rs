@@ -0,0 +1,229 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use hashbrown::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    access::{ReadStorage, WriteStorage},
+    component::Component,
+    entity::{Entities, Entity},
+    join::Join,
+    resource::Resource,
+    storage::DenseVecStorage,
+};
+
+/// Component that identifies an entity in the serialized data.
+///
+/// Only entities with a marker are serialized, and references to other
+/// entities are replaced by the markers of the referenced entities.
+pub trait Marker: Clone + Component + Debug + Eq + Hash + Serialize + DeserializeOwned {
+    /// Identifier the marker is keyed by.
+    type Identifier;
+
+    /// Allocator that creates new markers of this type.
+    type Allocator: MarkerAllocator<Self>;
+
+    /// Returns the identifier of this marker.
+    fn id(&self) -> Self::Identifier;
+}
+
+/// Resource that allocates new markers and keeps track of the entities they
+/// are attached to.
+pub trait MarkerAllocator<M>: Resource
+where
+    M: Marker,
+{
+    /// Allocates a new marker for the passed entity. If `id` is passed the
+    /// marker will use this identifier, otherwise a new one is created.
+    fn allocate(&mut self, entity: Entity, id: Option<M::Identifier>) -> M;
+
+    /// Returns the entity the marker with the passed identifier is attached
+    /// to, if it is known by the allocator.
+    fn retrieve_entity_internal(&self, id: M::Identifier) -> Option<Entity>;
+
+    /// Returns the entity the passed marker is attached to. If no such
+    /// entity exists, a new one is created atomically and marked.
+    fn retrieve_entity(
+        &mut self,
+        marker: M,
+        storage: &mut WriteStorage<M>,
+        entities: &Entities,
+    ) -> Entity {
+        if let Some(entity) = self.retrieve_entity_internal(marker.id()) {
+            if storage.get(entity) == Some(&marker) {
+                return entity;
+            }
+        }
+
+        let entity = entities.create();
+        let marker = self.allocate(entity, Some(marker.id()));
+
+        storage
+            .insert(entity, marker)
+            .expect("Newly created entity is not alive");
+
+        entity
+    }
+
+    /// Marks the passed entity. Returns the marker of the entity and `true`
+    /// if the marker was newly allocated, or `None` if the entity is not
+    /// alive.
+    fn mark<'m>(
+        &mut self,
+        entity: Entity,
+        storage: &'m mut WriteStorage<M>,
+    ) -> Option<(&'m M, bool)> {
+        let added = if storage.contains(entity) {
+            false
+        } else {
+            let marker = self.allocate(entity, None);
+            storage.insert(entity, marker).ok()?;
+
+            true
+        };
+
+        storage.get(entity).map(|marker| (marker, added))
+    }
+
+    /// Rebuilds the internal state of the allocator from the markers that
+    /// are stored in the world. Call this after markers were removed or
+    /// modified without using the allocator.
+    fn maintain(&mut self, entities: &Entities, storage: &ReadStorage<M>);
+}
+
+/// Basic marker that is identified by a `u64`. `T` is used to distinguish
+/// different kinds of markers in the same world.
+pub struct SimpleMarker<T> {
+    id: u64,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> SimpleMarker<T> {
+    /// Create a new marker with the given identifier.
+    pub fn new(id: u64) -> Self {
+        Self {
+            id,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Marker for SimpleMarker<T>
+where
+    T: 'static,
+{
+    type Identifier = u64;
+    type Allocator = SimpleMarkerAllocator<T>;
+
+    fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<T> Component for SimpleMarker<T>
+where
+    T: 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<T> Clone for SimpleMarker<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SimpleMarker<T> {}
+
+impl<T> Debug for SimpleMarker<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("SimpleMarker").field(&self.id).finish()
+    }
+}
+
+impl<T> PartialEq for SimpleMarker<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for SimpleMarker<T> {}
+
+impl<T> Hash for SimpleMarker<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> Serialize for SimpleMarker<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u64(self.id)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for SimpleMarker<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        u64::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// Allocator for `SimpleMarker`s. The identifiers are allocated
+/// sequentially, starting at zero.
+pub struct SimpleMarkerAllocator<T> {
+    next: u64,
+    mapping: HashMap<u64, Entity>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for SimpleMarkerAllocator<T> {
+    fn default() -> Self {
+        Self {
+            next: 0,
+            mapping: HashMap::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> MarkerAllocator<SimpleMarker<T>> for SimpleMarkerAllocator<T>
+where
+    T: 'static,
+{
+    fn allocate(&mut self, entity: Entity, id: Option<u64>) -> SimpleMarker<T> {
+        let id = match id {
+            Some(id) => {
+                self.next = self.next.max(id + 1);
+
+                id
+            }
+            None => {
+                self.next += 1;
+
+                self.next - 1
+            }
+        };
+
+        self.mapping.insert(id, entity);
+
+        SimpleMarker::new(id)
+    }
+
+    fn retrieve_entity_internal(&self, id: u64) -> Option<Entity> {
+        self.mapping.get(&id).copied()
+    }
+
+    fn maintain(&mut self, entities: &Entities, storage: &ReadStorage<SimpleMarker<T>>) {
+        self.mapping = (entities, storage)
+            .join()
+            .map(|(entity, marker)| (marker.id(), entity))
+            .collect();
+    }
+}
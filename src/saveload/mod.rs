@@ -0,0 +1,334 @@
+//! Save and load entities and their components using `serde`.
+//!
+//! Entities can not be serialized directly, because the index and generation
+//! of an entity is not stable between different worlds. Instead all entities
+//! that should be saved get a `Marker` component, which is used to identify
+//! the entity in the serialized data. Components that reference other
+//! entities can implement `ConvertSaveload` to replace these references by
+//! the markers of the referenced entities.
+//!
+//! ## Examples
+//!
+//! ```
+//! use async_ecs::{
+//!     saveload::{DeserializeComponents, MarkerAllocator, SerializeComponents, SimpleMarker, SimpleMarkerAllocator},
+//!     *,
+//! };
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+//! struct Pos(f32, f32);
+//!
+//! impl Component for Pos {
+//!     type Storage = VecStorage<Self>;
+//! }
+//!
+//! struct Saved;
+//!
+//! let mut world = World::default();
+//! world.register_component::<Pos>();
+//! world.register_component::<SimpleMarker<Saved>>();
+//! world.register_resource(SimpleMarkerAllocator::<Saved>::default());
+//!
+//! let entity = world.create_entity().with(Pos(1.0, 2.0)).build();
+//! world
+//!     .resource_mut::<SimpleMarkerAllocator<Saved>>()
+//!     .mark(entity, &mut world.component_mut());
+//!
+//! let mut buffer = Vec::new();
+//! (world.component::<Pos>(),)
+//!     .serialize(
+//!         &world.entities(),
+//!         &world.component::<SimpleMarker<Saved>>(),
+//!         &mut serde_json::Serializer::new(&mut buffer),
+//!     )
+//!     .unwrap();
+//!
+//! let mut world = World::default();
+//! world.register_component::<Pos>();
+//! world.register_component::<SimpleMarker<Saved>>();
+//! world.register_resource(SimpleMarkerAllocator::<Saved>::default());
+//!
+//! (world.component_mut::<Pos>(),)
+//!     .deserialize(
+//!         &world.entities(),
+//!         &mut world.component_mut::<SimpleMarker<Saved>>(),
+//!         &mut world.resource_mut::<SimpleMarkerAllocator<Saved>>(),
+//!         &mut serde_json::Deserializer::from_slice(&buffer),
+//!     )
+//!     .unwrap();
+//! ```
+
+mod convert;
+mod de;
+mod error;
+mod marker;
+mod record;
+mod ser;
+#[cfg(feature = "uuid_entity")]
+mod uuid_marker;
+
+pub use convert::ConvertSaveload;
+pub use de::DeserializeComponents;
+pub use error::Error;
+pub use marker::{Marker, MarkerAllocator, SimpleMarker, SimpleMarkerAllocator};
+pub use record::{CommandData, RecordComponents, RecordData};
+pub use ser::SerializeComponents;
+#[cfg(feature = "uuid_entity")]
+pub use uuid_marker::{UuidMarker, UuidMarkerAllocator};
+
+use serde::{Deserialize, Serialize};
+
+/// Serialized representation of a single entity: the marker that identifies
+/// the entity and the data of its components.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntityData<M, D> {
+    /// Marker of the entity.
+    pub marker: M,
+
+    /// Serialized data of the components of the entity.
+    pub components: D,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        component::Component,
+        entity::{Builder, Entity, WeakEntity},
+        storage::VecStorage,
+        world::{Lazy, World},
+    };
+
+    use super::*;
+
+    struct Saved;
+
+    type Marker = SimpleMarker<Saved>;
+    type Allocator = SimpleMarkerAllocator<Saved>;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Name(String);
+
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+    }
+
+    /// Component that references another entity.
+    struct Parent(Entity);
+
+    impl Component for Parent {
+        type Storage = VecStorage<Self>;
+    }
+
+    impl ConvertSaveload<Marker> for Parent {
+        type Data = Marker;
+        type Error = Error;
+
+        fn convert_into<F>(&self, ids: F) -> Result<Self::Data, Self::Error>
+        where
+            F: FnMut(Entity) -> Option<Marker>,
+        {
+            self.0.convert_into(ids)
+        }
+
+        fn convert_from<F>(data: Self::Data, ids: F) -> Result<Self, Self::Error>
+        where
+            F: FnMut(Marker) -> Option<Entity>,
+        {
+            Entity::convert_from(data, ids).map(Parent)
+        }
+    }
+
+    fn world() -> World {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Name>();
+        world.register_component::<Parent>();
+        world.register_component::<Marker>();
+        world.register_resource(Allocator::default());
+
+        world
+    }
+
+    fn mark(world: &World, entity: Entity) {
+        world
+            .resource_mut::<Allocator>()
+            .mark(entity, &mut world.component_mut());
+    }
+
+    fn save(world: &World) -> String {
+        let mut buffer = Vec::new();
+
+        (
+            world.component::<Pos>(),
+            world.component::<Name>(),
+            world.component::<Parent>(),
+        )
+            .serialize(
+                &world.entities(),
+                &world.component::<Marker>(),
+                &mut serde_json::Serializer::new(&mut buffer),
+            )
+            .unwrap();
+
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn load(world: &World, data: &str) {
+        (
+            world.component_mut::<Pos>(),
+            world.component_mut::<Name>(),
+            world.component_mut::<Parent>(),
+        )
+            .deserialize(
+                &world.entities(),
+                &mut world.component_mut::<Marker>(),
+                &mut world.resource_mut::<Allocator>(),
+                &mut serde_json::Deserializer::from_str(data),
+            )
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let mut world = world();
+
+        let root = world
+            .create_entity()
+            .with(Pos(1))
+            .with(Name("root".into()))
+            .build();
+        let child = world
+            .create_entity()
+            .with(Pos(2))
+            .with(Parent(root))
+            .build();
+        let unmarked = world.create_entity().with(Pos(3)).build();
+
+        mark(&world, child);
+        mark(&world, root);
+
+        let data = save(&world);
+
+        let mut world = world;
+        world.entities_mut().kill(&[root, child, unmarked]).unwrap();
+        world.maintain().await;
+
+        let mut loaded = self::world();
+        load(&loaded, &data);
+        loaded.maintain().await;
+
+        let allocator = loaded.resource::<Allocator>();
+        let markers = loaded.component::<Marker>();
+        let positions = loaded.component::<Pos>();
+        let names = loaded.component::<Name>();
+        let parents = loaded.component::<Parent>();
+
+        let child = allocator.retrieve_entity_internal(0).unwrap();
+        let root = allocator.retrieve_entity_internal(1).unwrap();
+
+        assert_eq!(markers.count(), 2);
+        assert_eq!(positions.count(), 2);
+        assert_eq!(positions.get(root), Some(&Pos(1)));
+        assert_eq!(positions.get(child), Some(&Pos(2)));
+        assert_eq!(names.get(root), Some(&Name("root".into())));
+        assert_eq!(names.get(child), None);
+        assert_eq!(parents.get(child).map(|p| p.0), Some(root));
+    }
+
+    #[tokio::test]
+    async fn load_into_existing_entities() {
+        let mut world = world();
+
+        let entity = world.create_entity().with(Pos(1)).build();
+        mark(&world, entity);
+
+        let data = save(&world);
+
+        world.component_mut::<Pos>().insert(entity, Pos(5)).unwrap();
+        load(&world, &data);
+        world.maintain().await;
+
+        assert_eq!(world.component::<Pos>().count(), 1);
+        assert_eq!(world.component::<Pos>().get(entity), Some(&Pos(1)));
+    }
+
+    #[test]
+    fn reference_to_unmarked_entity() {
+        let mut world = world();
+
+        let target = world.create_entity().build();
+        let entity = world.create_entity().with(Parent(target)).build();
+        mark(&world, entity);
+
+        let mut buffer = Vec::new();
+        let result = (world.component::<Parent>(),).serialize(
+            &world.entities(),
+            &world.component::<Marker>(),
+            &mut serde_json::Serializer::new(&mut buffer),
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn weak_references() {
+        let mut world = world();
+
+        let marked = world.create_entity().build();
+        let unmarked = world.create_entity().build();
+        mark(&world, marked);
+
+        let markers = world.component::<Marker>();
+        let ids = |entity| markers.get(entity).cloned();
+
+        let data = marked.downgrade().convert_into(ids).unwrap();
+        assert!(data.is_some());
+        assert_eq!(unmarked.downgrade().convert_into(ids).unwrap(), None);
+
+        let allocator = world.resource::<Allocator>();
+        let ids = |marker: Marker| allocator.retrieve_entity_internal(super::Marker::id(&marker));
+
+        let weak = WeakEntity::convert_from(data, ids).unwrap();
+        assert_eq!(world.entities().upgrade(weak), Some(marked));
+        assert!(WeakEntity::convert_from(None, ids).unwrap().is_dangling());
+    }
+
+    #[tokio::test]
+    async fn marked_builders() {
+        let mut world = world();
+
+        let entity = world
+            .create_entity()
+            .with(Pos(1))
+            .marked::<Marker>()
+            .build();
+        assert!(world.component::<Marker>().contains(entity));
+
+        let lazy = Lazy::clone(&world.resource::<Lazy>());
+        let lazy_entity = lazy
+            .create_entity(&world)
+            .marked::<Marker>()
+            .with_fn(|entity, world| {
+                assert!(world.component::<Marker>().contains(entity));
+            })
+            .build();
+        assert!(!world.component::<Marker>().contains(lazy_entity));
+
+        world.maintain().await;
+
+        let allocator = world.resource::<Allocator>();
+        assert_eq!(world.component::<Marker>().count(), 2);
+        assert_eq!(allocator.retrieve_entity_internal(0), Some(entity));
+        assert_eq!(allocator.retrieve_entity_internal(1), Some(lazy_entity));
+    }
+}
@@ -0,0 +1,281 @@
+use std::any::TypeId;
+
+use serde::{
+    de::{self, DeserializeOwned, Deserializer},
+    ser::{self, SerializeSeq, Serializer},
+    Deserialize, Serialize,
+};
+
+use crate::{
+    component::Component,
+    entity::Entity,
+    world::{Command, CommandLog, ComponentType, ComponentValue, Record},
+};
+
+/// Serialized representation of a single record of the `CommandLog`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordData<D> {
+    /// Sequence number of the record.
+    pub sequence: u64,
+
+    /// The recorded change.
+    pub command: CommandData<D>,
+}
+
+/// Serialized representation of a recorded change. Entities are stored by
+/// their id (see `Entity::id`).
+#[derive(Debug, Serialize, Deserialize)]
+pub enum CommandData<D> {
+    /// The entity was created.
+    Create(u64),
+
+    /// The entity was deleted.
+    Delete(u64),
+
+    /// The component was inserted for the entity.
+    Insert(u64, D),
+
+    /// The component with the passed index was removed from the entity.
+    Remove(u64, usize),
+}
+
+/// Component types that are serialized with the records of a `CommandLog`.
+///
+/// This is implemented for tuples of components. The inserted component of
+/// a record is serialized as tuple of options, where only the option of the
+/// inserted component is set.
+pub trait RecordComponents {
+    /// Serializable data of an inserted component.
+    type Data: Serialize + DeserializeOwned;
+
+    /// Converts the passed value into its serializable representation.
+    /// Returns `None` if the value is not one of the components.
+    fn serialize_value(value: &ComponentValue) -> Option<Self::Data>;
+
+    /// Converts the serialized data back into a value. Returns `None` if
+    /// the data does not contain a component.
+    fn deserialize_value(data: Self::Data) -> Option<ComponentValue>;
+
+    /// Returns the index of the passed component type.
+    fn index_of(component: &ComponentType) -> Option<usize>;
+
+    /// Returns the component type with the passed index.
+    fn component_type(index: usize) -> Option<ComponentType>;
+}
+
+impl CommandLog {
+    /// Serializes all records of the log as a sequence of `RecordData`.
+    ///
+    /// The recorded components have to be part of `C`, otherwise an error
+    /// is returned.
+    pub fn serialize<C, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        C: RecordComponents,
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+
+        for record in self.records() {
+            let command = match record.command() {
+                Command::Create(entity) => CommandData::Create(entity.id()),
+                Command::Delete(entity) => CommandData::Delete(entity.id()),
+                Command::Insert(entity, value) => {
+                    let data = C::serialize_value(value).ok_or_else(|| {
+                        ser::Error::custom(not_serializable(&value.component_type()))
+                    })?;
+
+                    CommandData::Insert(entity.id(), data)
+                }
+                Command::Remove(entity, component) => {
+                    let index = C::index_of(component)
+                        .ok_or_else(|| ser::Error::custom(not_serializable(component)))?;
+
+                    CommandData::Remove(entity.id(), index)
+                }
+            };
+
+            seq.serialize_element(&RecordData {
+                sequence: record.sequence(),
+                command,
+            })?;
+        }
+
+        seq.end()
+    }
+
+    /// Deserializes a log that was serialized using `CommandLog::serialize`
+    /// with the same components `C`. Recording is disabled for the returned
+    /// log.
+    pub fn deserialize<'de, C, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        C: RecordComponents,
+        D: Deserializer<'de>,
+    {
+        let records = Vec::<RecordData<C::Data>>::deserialize(deserializer)?
+            .into_iter()
+            .map(|record| {
+                let command = match record.command {
+                    CommandData::Create(id) => Command::Create(Entity::from_id(id)),
+                    CommandData::Delete(id) => Command::Delete(Entity::from_id(id)),
+                    CommandData::Insert(id, data) => {
+                        let value = C::deserialize_value(data)
+                            .ok_or_else(|| de::Error::custom("Record contains no component!"))?;
+
+                        Command::Insert(Entity::from_id(id), value)
+                    }
+                    CommandData::Remove(id, index) => {
+                        let component = C::component_type(index).ok_or_else(|| {
+                            de::Error::custom(format!("Unknown component index: {}!", index))
+                        })?;
+
+                        Command::Remove(Entity::from_id(id), component)
+                    }
+                };
+
+                Ok(Record::new(record.sequence, command))
+            })
+            .collect::<Result<Vec<_>, D::Error>>()?;
+
+        Ok(Self::from_records(records))
+    }
+}
+
+fn not_serializable(component: &ComponentType) -> String {
+    format!("Component is not serializable: {}!", component.name())
+}
+
+macro_rules! define_record_components {
+    ($($from:ident),*) => {
+        impl<$($from,)*> RecordComponents for ($($from,)*)
+        where
+            $($from: Component + Clone + Send + Sync + Serialize + DeserializeOwned,)*
+        {
+            type Data = ($(Option<$from>,)*);
+
+            fn serialize_value(value: &ComponentValue) -> Option<Self::Data> {
+                Self::index_of(&value.component_type())?;
+
+                Some(($(value.downcast_ref::<$from>().cloned(),)*))
+            }
+
+            #[allow(non_snake_case)]
+            fn deserialize_value(data: Self::Data) -> Option<ComponentValue> {
+                let ($($from,)*) = data;
+
+                $(
+                    if let Some(component) = $from {
+                        return Some(ComponentValue::new(component));
+                    }
+                )*
+
+                None
+            }
+
+            fn index_of(component: &ComponentType) -> Option<usize> {
+                [$(TypeId::of::<$from>(),)*]
+                    .iter()
+                    .position(|id| *id == component.id())
+            }
+
+            fn component_type(index: usize) -> Option<ComponentType> {
+                [$(ComponentType::of::<$from>(),)*].get(index).copied()
+            }
+        }
+    };
+}
+
+define_record_components! { A }
+define_record_components! { A, B }
+define_record_components! { A, B, C }
+define_record_components! { A, B, C, D }
+define_record_components! { A, B, C, D, E }
+define_record_components! { A, B, C, D, E, F }
+define_record_components! { A, B, C, D, E, F, G }
+define_record_components! { A, B, C, D, E, F, G, H }
+define_record_components! { A, B, C, D, E, F, G, H, I }
+define_record_components! { A, B, C, D, E, F, G, H, I, J }
+define_record_components! { A, B, C, D, E, F, G, H, I, J, K }
+define_record_components! { A, B, C, D, E, F, G, H, I, J, K, L }
+define_record_components! { A, B, C, D, E, F, G, H, I, J, K, L, N }
+define_record_components! { A, B, C, D, E, F, G, H, I, J, K, L, N, O }
+define_record_components! { A, B, C, D, E, F, G, H, I, J, K, L, N, O, P }
+define_record_components! { A, B, C, D, E, F, G, H, I, J, K, L, N, O, P, Q }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{entity::Builder, storage::VecStorage, world::World};
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+
+        fn setup(world: &mut World) {
+            world.register_record::<Self>();
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Name(String);
+
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+
+        fn setup(world: &mut World) {
+            world.register_record::<Self>();
+        }
+    }
+
+    fn world() -> World {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Name>();
+
+        world
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let mut world = world();
+        world.resource_mut::<CommandLog>().set_recording(true);
+
+        let a = world.create_entity().with(Pos(1)).build();
+        let b = world.create_entity().with(Name("b".into())).build();
+        world.lazy().remove::<Pos>(a);
+        world.lazy().insert(b, Pos(2));
+        world.maintain().await;
+        world.delete_entity(a).unwrap();
+
+        let mut buffer = Vec::new();
+        world
+            .resource::<CommandLog>()
+            .serialize::<(Pos, Name), _>(&mut serde_json::Serializer::new(&mut buffer))
+            .unwrap();
+
+        let mut buffer_pos = Vec::new();
+        assert!(world
+            .resource::<CommandLog>()
+            .serialize::<(Pos,), _>(&mut serde_json::Serializer::new(&mut buffer_pos))
+            .is_err());
+
+        let log = CommandLog::deserialize::<(Pos, Name), _>(
+            &mut serde_json::Deserializer::from_slice(&buffer),
+        )
+        .unwrap();
+
+        assert_eq!(log.len(), world.resource::<CommandLog>().len());
+        assert!(!log.is_recording());
+
+        let mut replay = self::world();
+        let entities = log.replay(&mut replay);
+
+        let b = entities.get(b).unwrap();
+        assert!(!replay.is_alive(entities.get(a).unwrap()));
+        assert_eq!(replay.component::<Pos>().get(b), Some(&Pos(2)));
+        assert_eq!(replay.component::<Name>().get(b), Some(&Name("b".into())));
+        assert_eq!(replay.component::<Pos>().count(), 1);
+    }
+}
@@ -0,0 +1,100 @@
+use serde::ser::{self, Serialize, SerializeSeq, Serializer};
+
+use crate::{
+    access::ReadStorage,
+    component::Component,
+    entity::{Entities, Entity},
+    join::Join,
+};
+
+use super::{ConvertSaveload, EntityData, Marker};
+
+/// Serializes the components of all marked entities.
+///
+/// This is implemented for tuples of `ReadStorage`s. The components of each
+/// entity are serialized as tuple of options, so entities that do not have
+/// all of the components are supported.
+pub trait SerializeComponents<M>
+where
+    M: Marker,
+{
+    /// Serializable data of the components of a single entity.
+    type Data: Serialize;
+
+    /// Converts the components of the passed entity into their serializable
+    /// representation. `ids` is used to look up the markers of referenced
+    /// entities.
+    fn serialize_entity<Err, Ids>(&self, entity: Entity, ids: Ids) -> Result<Self::Data, Err>
+    where
+        Err: ser::Error,
+        Ids: FnMut(Entity) -> Option<M>;
+
+    /// Serializes all entities that have a marker in `markers` as a sequence
+    /// of `EntityData`.
+    fn serialize<S>(
+        &self,
+        entities: &Entities,
+        markers: &ReadStorage<M>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let marked = (entities, markers).join().collect::<Vec<_>>();
+        let mut seq = serializer.serialize_seq(Some(marked.len()))?;
+
+        for (entity, marker) in marked {
+            let ids = |entity| markers.get(entity).cloned();
+            let components = self.serialize_entity(entity, ids)?;
+
+            seq.serialize_element(&EntityData { marker, components })?;
+        }
+
+        seq.end()
+    }
+}
+
+macro_rules! define_serialize_components {
+    ($($from:ident),*) => {
+        impl<'a, M, $($from,)*> SerializeComponents<M> for ($(ReadStorage<'a, $from>,)*)
+        where
+            M: Marker,
+            $($from: Component + ConvertSaveload<M>,)*
+        {
+            type Data = ($(Option<<$from as ConvertSaveload<M>>::Data>,)*);
+
+            #[allow(non_snake_case)]
+            fn serialize_entity<Err, Ids>(&self, entity: Entity, mut ids: Ids) -> Result<Self::Data, Err>
+            where
+                Err: ser::Error,
+                Ids: FnMut(Entity) -> Option<M>,
+            {
+                let ($(ref $from,)*) = *self;
+
+                Ok(($(
+                    $from
+                        .get(entity)
+                        .map(|c| c.convert_into(&mut ids).map_err(Err::custom))
+                        .transpose()?,
+                )*))
+            }
+        }
+    };
+}
+
+define_serialize_components! { A }
+define_serialize_components! { A, B }
+define_serialize_components! { A, B, C }
+define_serialize_components! { A, B, C, D }
+define_serialize_components! { A, B, C, D, E }
+define_serialize_components! { A, B, C, D, E, F }
+define_serialize_components! { A, B, C, D, E, F, G }
+define_serialize_components! { A, B, C, D, E, F, G, H }
+define_serialize_components! { A, B, C, D, E, F, G, H, I }
+define_serialize_components! { A, B, C, D, E, F, G, H, I, J }
+define_serialize_components! { A, B, C, D, E, F, G, H, I, J, K }
+define_serialize_components! { A, B, C, D, E, F, G, H, I, J, K, L }
+define_serialize_components! { A, B, C, D, E, F, G, H, I, J, K, L, N }
+define_serialize_components! { A, B, C, D, E, F, G, H, I, J, K, L, N, O }
+define_serialize_components! { A, B, C, D, E, F, G, H, I, J, K, L, N, O, P }
+define_serialize_components! { A, B, C, D, E, F, G, H, I, J, K, L, N, O, P, Q }
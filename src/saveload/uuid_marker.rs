@@ -0,0 +1,158 @@
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    access::ReadStorage,
+    component::Component,
+    entity::{Entities, Entity},
+    join::Join,
+    storage::DenseVecStorage,
+};
+
+use super::{Marker, MarkerAllocator};
+
+/// Marker that identifies an entity by a random `Uuid`.
+///
+/// In contrast to the index and generation of an entity, the uuid is stable
+/// between different runs and machines, so it can be used to reference
+/// entities in saved games or over the network.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::{
+///     saveload::{MarkerAllocator, UuidMarker, UuidMarkerAllocator},
+///     *,
+/// };
+///
+/// let mut world = World::default();
+/// world.register_component::<UuidMarker>();
+/// world.register_resource(UuidMarkerAllocator::default());
+///
+/// let entity = world.create_entity().build();
+/// let uuid = world
+///     .resource_mut::<UuidMarkerAllocator>()
+///     .mark(entity, &mut world.component_mut())
+///     .map(|(marker, _)| marker.uuid())
+///     .unwrap();
+///
+/// assert_eq!(world.resource::<UuidMarkerAllocator>().get(uuid), Some(entity));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UuidMarker {
+    uuid: Uuid,
+}
+
+impl UuidMarker {
+    /// Create a new marker with the given uuid.
+    pub fn new(uuid: Uuid) -> Self {
+        Self { uuid }
+    }
+
+    /// Create a new marker with a random uuid.
+    pub fn new_random() -> Self {
+        Self::new(Uuid::new_v4())
+    }
+
+    /// Returns the uuid of the marker.
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
+
+impl Marker for UuidMarker {
+    type Identifier = Uuid;
+    type Allocator = UuidMarkerAllocator;
+
+    fn id(&self) -> Uuid {
+        self.uuid
+    }
+}
+
+impl Component for UuidMarker {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Allocator for `UuidMarker`s. New markers get a random uuid.
+///
+/// The allocator keeps an index of all known uuids, so the entity of a uuid
+/// can be looked up using `get`.
+#[derive(Default)]
+pub struct UuidMarkerAllocator {
+    mapping: HashMap<Uuid, Entity>,
+}
+
+impl UuidMarkerAllocator {
+    /// Returns the entity that is marked with the passed uuid.
+    pub fn get(&self, uuid: Uuid) -> Option<Entity> {
+        self.mapping.get(&uuid).copied()
+    }
+}
+
+impl MarkerAllocator<UuidMarker> for UuidMarkerAllocator {
+    fn allocate(&mut self, entity: Entity, id: Option<Uuid>) -> UuidMarker {
+        let marker = match id {
+            Some(id) => UuidMarker::new(id),
+            None => UuidMarker::new_random(),
+        };
+
+        self.mapping.insert(marker.uuid(), entity);
+
+        marker
+    }
+
+    fn retrieve_entity_internal(&self, id: Uuid) -> Option<Entity> {
+        self.get(id)
+    }
+
+    fn maintain(&mut self, entities: &Entities, storage: &ReadStorage<UuidMarker>) {
+        self.mapping = (entities, storage)
+            .join()
+            .map(|(entity, marker)| (marker.uuid(), entity))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{entity::Builder, world::World};
+
+    use super::*;
+
+    #[test]
+    fn lookup_by_uuid() {
+        let mut world = World::default();
+        world.register_component::<UuidMarker>();
+        world.register_resource(UuidMarkerAllocator::default());
+
+        let uuid = Uuid::new_v4();
+        let e1 = world.create_entity().build();
+        let e2 = world.create_entity().build();
+
+        {
+            let mut allocator = world.resource_mut::<UuidMarkerAllocator>();
+            let mut storage = world.component_mut::<UuidMarker>();
+
+            let marker = allocator.allocate(e1, Some(uuid));
+            storage.insert(e1, marker).unwrap();
+
+            let (marker, added) = allocator.mark(e2, &mut storage).unwrap();
+            assert!(added);
+            assert_ne!(marker.uuid(), uuid);
+        }
+
+        assert_eq!(world.resource::<UuidMarkerAllocator>().get(uuid), Some(e1));
+
+        world.delete_entity(e1).unwrap();
+        world
+            .resource_mut::<UuidMarkerAllocator>()
+            .maintain(&world.entities(), &world.component());
+
+        let allocator = world.resource::<UuidMarkerAllocator>();
+        let e2_uuid = world.component::<UuidMarker>().get(e2).unwrap().uuid();
+
+        assert_eq!(allocator.get(uuid), None);
+        assert_eq!(allocator.get(e2_uuid), Some(e2));
+    }
+}
@@ -0,0 +1,453 @@
+use std::marker::PhantomData;
+
+use hashbrown::HashMap;
+use hibitset::BitSet;
+
+use crate::{
+    access::ReadStorage,
+    entity::{Entities, Entity, Index},
+    event::ReaderId,
+    join::Join,
+    storage::{ComponentEvent, Tracked},
+};
+
+use super::{Point, Position};
+
+type Cell = (i32, i32);
+
+/// Resource that stores the position of entities in a uniform grid, to
+/// query entities within a certain area or near a certain point.
+///
+/// The index can be updated manually using `insert` and `remove`, rebuilt
+/// from a storage using `rebuild`, or updated from the change events of a
+/// tracked storage using `maintain`. The `SpatialIndexSystem` calls
+/// `maintain` on each run.
+///
+/// The size of the cells should be chosen close to the typical radius of
+/// the queries. Small cells increase the number of cells that need to be
+/// visited, large cells increase the number of entities that need to be
+/// checked.
+pub struct SpatialIndex<T> {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<Index>>,
+    entries: HashMap<Index, Entry>,
+    reader: Option<ReaderId<ComponentEvent>>,
+    marker: PhantomData<fn() -> T>,
+}
+
+struct Entry {
+    entity: Entity,
+    point: Point,
+    cell: Cell,
+}
+
+impl<T> SpatialIndex<T> {
+    /// Create a new empty index with the passed size of the cells.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_size` is not positive.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0,
+            "Cell size of a spatial index must be positive"
+        );
+
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            entries: HashMap::new(),
+            reader: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the size of the cells of the grid.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Returns the number of indexed entities.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no entity is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns `true` if the passed entity is indexed.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.position(entity).is_some()
+    }
+
+    /// Returns the indexed position of the passed entity.
+    pub fn position(&self, entity: Entity) -> Option<Point> {
+        self.entries
+            .get(&entity.index())
+            .filter(|entry| entry.entity == entity)
+            .map(|entry| entry.point)
+    }
+
+    /// Inserts the passed entity at the passed position, or moves it if it
+    /// is already indexed.
+    pub fn insert(&mut self, entity: Entity, point: Point) {
+        let index = entity.index();
+        let cell = self.cell(point);
+
+        if let Some(entry) = self.entries.get_mut(&index) {
+            let old = entry.cell;
+
+            entry.entity = entity;
+            entry.point = point;
+            entry.cell = cell;
+
+            if old == cell {
+                return;
+            }
+
+            self.remove_from_cell(old, index);
+        } else {
+            self.entries.insert(
+                index,
+                Entry {
+                    entity,
+                    point,
+                    cell,
+                },
+            );
+        }
+
+        self.cells.entry(cell).or_default().push(index);
+    }
+
+    /// Removes the passed entity from the index and returns its position.
+    pub fn remove(&mut self, entity: Entity) -> Option<Point> {
+        self.position(entity)?;
+
+        self.remove_index(entity.index())
+    }
+
+    /// Removes all entities from the index.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.entries.clear();
+    }
+
+    /// Returns all entities within the circle with the passed `center` and
+    /// `radius`. The order of the entities is unspecified.
+    pub fn in_radius(&self, center: Point, radius: f32) -> Vec<Entity> {
+        let min = [center[0] - radius, center[1] - radius];
+        let max = [center[0] + radius, center[1] + radius];
+        let radius = radius * radius;
+
+        self.query(min, max, |point| distance_squared(point, center) <= radius)
+    }
+
+    /// Returns all entities within the rectangle spanned by `min` and `max`
+    /// (both inclusive). The order of the entities is unspecified.
+    pub fn in_rect(&self, min: Point, max: Point) -> Vec<Entity> {
+        self.query(min, max, |point| {
+            point[0] >= min[0] && point[0] <= max[0] && point[1] >= min[1] && point[1] <= max[1]
+        })
+    }
+
+    /// Returns the entity that is nearest to the passed point, or `None` if
+    /// the index is empty.
+    pub fn nearest(&self, point: Point) -> Option<Entity> {
+        let center = self.cell(point);
+        let mut best: Option<(f32, Entity)> = None;
+        let mut ring = 0;
+
+        loop {
+            if self.entries.is_empty() || 8 * ring as usize > self.cells.len() {
+                return self.nearest_linear(point);
+            }
+
+            for cell in ring_cells(center, ring) {
+                for entry in self.cell_entries(cell) {
+                    let d = distance_squared(entry.point, point);
+                    let closer = match best {
+                        Some((best, _)) => d < best,
+                        None => true,
+                    };
+
+                    if closer {
+                        best = Some((d, entry.entity));
+                    }
+                }
+            }
+
+            // All entities in the next rings are at least `ring` cells away.
+            let bound = ring as f32 * self.cell_size;
+            if let Some((d, entity)) = best {
+                if d <= bound * bound {
+                    return Some(entity);
+                }
+            }
+
+            ring += 1;
+        }
+    }
+
+    fn nearest_linear(&self, point: Point) -> Option<Entity> {
+        self.entries
+            .values()
+            .map(|entry| (distance_squared(entry.point, point), entry.entity))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, entity)| entity)
+    }
+
+    fn query<F>(&self, min: Point, max: Point, f: F) -> Vec<Entity>
+    where
+        F: Fn(Point) -> bool,
+    {
+        let (x0, y0) = self.cell(min);
+        let (x1, y1) = self.cell(max);
+        let mut ret = Vec::new();
+
+        let count = (x1 as i64 - x0 as i64 + 1) * (y1 as i64 - y0 as i64 + 1);
+
+        if count > self.cells.len() as i64 {
+            for cell in self.cells.keys() {
+                if cell.0 >= x0 && cell.0 <= x1 && cell.1 >= y0 && cell.1 <= y1 {
+                    ret.extend(self.matching(*cell, &f));
+                }
+            }
+        } else {
+            for x in x0..=x1 {
+                for y in y0..=y1 {
+                    ret.extend(self.matching((x, y), &f));
+                }
+            }
+        }
+
+        ret
+    }
+
+    fn matching<'a, F>(&'a self, cell: Cell, f: &'a F) -> impl Iterator<Item = Entity> + 'a
+    where
+        F: Fn(Point) -> bool,
+    {
+        self.cell_entries(cell)
+            .filter(move |entry| f(entry.point))
+            .map(|entry| entry.entity)
+    }
+
+    fn cell_entries(&self, cell: Cell) -> impl Iterator<Item = &Entry> {
+        self.cells
+            .get(&cell)
+            .into_iter()
+            .flatten()
+            .map(move |index| &self.entries[index])
+    }
+
+    fn cell(&self, point: Point) -> Cell {
+        (
+            (point[0] / self.cell_size).floor() as i32,
+            (point[1] / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn remove_index(&mut self, index: Index) -> Option<Point> {
+        let entry = self.entries.remove(&index)?;
+
+        self.remove_from_cell(entry.cell, index);
+
+        Some(entry.point)
+    }
+
+    fn remove_from_cell(&mut self, cell: Cell, index: Index) {
+        if let Some(indices) = self.cells.get_mut(&cell) {
+            if let Some(pos) = indices.iter().position(|i| *i == index) {
+                indices.swap_remove(pos);
+            }
+
+            if indices.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+}
+
+impl<T> SpatialIndex<T>
+where
+    T: Position,
+{
+    /// Removes all entities from the index and inserts all entities that
+    /// have a component of type `T`.
+    pub fn rebuild(&mut self, entities: &Entities, positions: &ReadStorage<T>) {
+        self.clear();
+
+        for (entity, position) in (entities, positions).join() {
+            self.insert(entity, position.position());
+        }
+    }
+
+    /// Applies all changes of the components of type `T` since the last
+    /// call to this method.
+    ///
+    /// The index registers itself as a reader of the storage on the first
+    /// call and rebuilds the index, so make sure to always pass the same
+    /// storage.
+    pub fn maintain(&mut self, entities: &Entities, positions: &ReadStorage<T>)
+    where
+        T::Storage: Tracked,
+    {
+        let mut changed = BitSet::new();
+
+        match &mut self.reader {
+            Some(reader) => {
+                for event in positions.channel().read(reader) {
+                    match *event {
+                        ComponentEvent::Inserted(index) | ComponentEvent::Modified(index) => {
+                            changed.add(index);
+                        }
+                        ComponentEvent::Removed(index) => {
+                            changed.remove(index);
+                            self.remove_index(index);
+                        }
+                    }
+                }
+            }
+            None => {
+                self.reader = Some(positions.register_reader());
+                self.rebuild(entities, positions);
+
+                return;
+            }
+        }
+
+        for (entity, position, _) in (entities, positions, &changed).join() {
+            self.insert(entity, position.position());
+        }
+    }
+}
+
+fn distance_squared(a: Point, b: Point) -> f32 {
+    let x = a[0] - b[0];
+    let y = a[1] - b[1];
+
+    x * x + y * y
+}
+
+fn ring_cells(center: Cell, ring: i32) -> impl Iterator<Item = Cell> {
+    let (cx, cy) = center;
+    let horizontal = (-ring..=ring).flat_map(move |x| {
+        let ys = if ring == 0 {
+            vec![0]
+        } else {
+            vec![-ring, ring]
+        };
+
+        ys.into_iter().map(move |y| (cx + x, cy + y))
+    });
+    let vertical =
+        (1 - ring..ring).flat_map(move |y| vec![(cx - ring, cy + y), (cx + ring, cy + y)]);
+
+    horizontal.chain(vertical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        component::Component,
+        entity::Builder,
+        storage::{FlaggedStorage, VecStorage},
+        world::World,
+    };
+
+    struct Pos(f32, f32);
+
+    impl Component for Pos {
+        type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    impl Position for Pos {
+        fn position(&self) -> Point {
+            [self.0, self.1]
+        }
+    }
+
+    fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+        entities.sort();
+
+        entities
+    }
+
+    #[test]
+    fn queries() {
+        let mut world = World::default();
+        let mut index = SpatialIndex::<Pos>::new(2.0);
+
+        let e1 = world.create_entity().build();
+        let e2 = world.create_entity().build();
+        let e3 = world.create_entity().build();
+
+        index.insert(e1, [0.5, 0.5]);
+        index.insert(e2, [-3.0, 1.0]);
+        index.insert(e3, [20.0, -20.0]);
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(sorted(index.in_radius([0.0, 0.0], 4.0)), vec![e1, e2]);
+        assert_eq!(index.in_rect([-1.0, -1.0], [1.0, 1.0]), vec![e1]);
+        assert_eq!(index.in_rect([-100.0, -100.0], [100.0, 100.0]).len(), 3);
+        assert_eq!(index.nearest([-10.0, 0.0]), Some(e2));
+        assert_eq!(index.nearest([15.0, -15.0]), Some(e3));
+
+        index.insert(e3, [1.0, 1.0]);
+        assert_eq!(sorted(index.in_radius([0.0, 0.0], 2.0)), vec![e1, e3]);
+
+        assert_eq!(index.remove(e1), Some([0.5, 0.5]));
+        assert_eq!(index.remove(e1), None);
+        assert_eq!(index.nearest([0.0, 0.0]), Some(e3));
+    }
+
+    #[test]
+    fn nearest_is_exact() {
+        let mut world = World::default();
+        let mut index = SpatialIndex::<Pos>::new(1.0);
+
+        // `e1` is in the cell of the query point, but `e2` is nearer
+        let e1 = world.create_entity().build();
+        let e2 = world.create_entity().build();
+
+        index.insert(e1, [0.9, 0.9]);
+        index.insert(e2, [-0.1, 0.1]);
+
+        assert_eq!(index.nearest([0.1, 0.1]), Some(e2));
+        assert_eq!(SpatialIndex::<Pos>::new(1.0).nearest([0.0, 0.0]), None);
+    }
+
+    #[tokio::test]
+    async fn maintain_from_events() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let mut index = SpatialIndex::<Pos>::new(1.0);
+
+        let e1 = world.create_entity().with(Pos(0.0, 0.0)).build();
+        index.maintain(&world.entities(), &world.component());
+        assert_eq!(index.position(e1), Some([0.0, 0.0]));
+
+        let e2 = world.create_entity().with(Pos(5.0, 5.0)).build();
+        world.component_mut::<Pos>().get_mut(e1).unwrap().0 = 3.0;
+        index.maintain(&world.entities(), &world.component());
+        assert_eq!(index.position(e1), Some([3.0, 0.0]));
+        assert_eq!(index.position(e2), Some([5.0, 5.0]));
+
+        world.entities().delete(e1).unwrap();
+        world.maintain().await;
+
+        let e3 = world.create_entity().with(Pos(1.0, 1.0)).build();
+        index.maintain(&world.entities(), &world.component());
+
+        assert_eq!(e1.index(), e3.index());
+        assert!(!index.contains(e1));
+        assert_eq!(index.position(e3), Some([1.0, 1.0]));
+        assert_eq!(sorted(index.in_radius([0.0, 0.0], 10.0)), vec![e2, e3]);
+    }
+}
@@ -0,0 +1,65 @@
+//! Spatial index of entities, that allows to query entities by their
+//! position.
+//!
+//! The position of an entity is taken from a component that implements the
+//! `Position` trait. The `SpatialIndex` resource stores the entities in a
+//! uniform grid and is kept up to date by the `SpatialIndexSystem`, that
+//! reads the change events of a component stored in a `FlaggedStorage`.
+//!
+//! ## Examples
+//!
+//! ```
+//! use async_ecs::{
+//!     spatial::{Point, Position, SpatialIndex, SpatialIndexSystem},
+//!     *,
+//! };
+//!
+//! struct Pos(f32, f32);
+//!
+//! impl Component for Pos {
+//!     type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+//! }
+//!
+//! impl Position for Pos {
+//!     fn position(&self) -> Point {
+//!         [self.0, self.1]
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let mut world = World::default();
+//! world.register_component::<Pos>();
+//!
+//! let mut dispatcher = Dispatcher::setup_builder(&mut world)
+//!     .with(SpatialIndexSystem::<Pos>::new(10.0), "spatial_index", &[])
+//!     .unwrap()
+//!     .build();
+//!
+//! let near = world.create_entity().with(Pos(1.0, 1.0)).build();
+//! let far = world.create_entity().with(Pos(50.0, 50.0)).build();
+//!
+//! dispatcher.dispatch(&world).await.unwrap();
+//!
+//! let index = world.resource::<SpatialIndex<Pos>>();
+//! assert_eq!(index.in_radius([0.0, 0.0], 5.0), vec![near]);
+//! assert_eq!(index.nearest([40.0, 40.0]), Some(far));
+//! # }
+//! ```
+
+mod index;
+mod system;
+
+pub use index::SpatialIndex;
+pub use system::SpatialIndexSystem;
+
+use crate::component::Component;
+
+/// Point in the two dimensional space of a `SpatialIndex`.
+pub type Point = [f32; 2];
+
+/// Component that describes the position of an entity.
+pub trait Position: Component {
+    /// Returns the position of the entity.
+    fn position(&self) -> Point;
+}
@@ -0,0 +1,67 @@
+use std::marker::PhantomData;
+
+use crate::{
+    access::{ReadStorage, Write},
+    storage::Tracked,
+    system::{DynamicSystemData, System},
+    world::{PanicHandler, World},
+    Entities,
+};
+
+use super::{Position, SpatialIndex};
+
+/// System that keeps the `SpatialIndex` of the component `T` up to date.
+///
+/// The index is inserted into the world during the setup of the system, if
+/// it does not exist yet. Each run applies the changes of the components
+/// since the last run, so the storage of `T` has to be tracked (for example
+/// using a `FlaggedStorage`).
+pub struct SpatialIndexSystem<T> {
+    cell_size: f32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> SpatialIndexSystem<T> {
+    /// Create a new system, that creates the index with the passed size of
+    /// the cells of the grid.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> System<'a> for SpatialIndexSystem<T>
+where
+    T: Position,
+    T::Storage: Tracked,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, T>,
+        Write<'a, SpatialIndex<T>, PanicHandler>,
+    );
+
+    fn run(&mut self, (entities, positions, mut index): Self::SystemData) {
+        index.maintain(&entities, &positions);
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        self.init();
+
+        let cell_size = self.cell_size;
+
+        world
+            .entry::<SpatialIndex<T>>()
+            .or_insert_with(|| SpatialIndex::new(cell_size));
+
+        <Self::SystemData as DynamicSystemData>::setup(&self.accessor(), world);
+    }
+}
+
+impl<T> Default for SpatialIndexSystem<T> {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
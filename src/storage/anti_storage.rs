@@ -1,8 +1,9 @@
-use hibitset::{BitSet, BitSetNot};
+use hibitset::BitSet;
 
 use crate::{
     entity::Index,
     join::{Join, ParJoin},
+    misc::BitSetNot,
 };
 
 use super::DistinctStorage;
@@ -26,3 +27,52 @@ impl<'a> Join for AntiStorage<'a> {
 }
 
 impl<'a> ParJoin for AntiStorage<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use asparit::{Driver, ParallelIterator};
+
+    use crate::{
+        component::Component,
+        entity::Builder,
+        join::{Join, ParJoin},
+        storage::VecStorage,
+        world::World,
+    };
+
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    struct Vel(u32);
+
+    impl Component for Vel {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn par_join_without_component() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Vel>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).with(Vel(1)).build();
+        let e3 = world.create_entity().with(Pos(3)).build();
+
+        let mut pos = world.component_mut::<Pos>();
+        let vel = world.component::<Vel>();
+
+        (&mut pos, !&vel)
+            .par_join()
+            .for_each(|(pos, ())| pos.0 *= 10)
+            .exec();
+
+        assert_eq!(pos.get(e1).unwrap().0, 10);
+        assert_eq!(pos.get(e2).unwrap().0, 2);
+        assert_eq!(pos.get(e3).unwrap().0, 30);
+        assert_eq!((&pos, !&vel).join().count(), 2);
+    }
+}
@@ -1,11 +1,15 @@
 use std::collections::BTreeMap;
+use std::mem::size_of;
 
 use hibitset::BitSetLike;
 
 use crate::entity::Index;
 
-use super::{DistinctStorage, Storage};
+use super::{DistinctStorage, Storage, StorageStats};
 
+/// BTree storage. Stores the components in a `BTreeMap` keyed by the index
+/// of the entity. Use the `PagedStorage` for components that are sparse but
+/// clustered and are joined frequently.
 pub struct BTreeStorage<T>(BTreeMap<Index, T>);
 
 impl<T> Default for BTreeStorage<T> {
@@ -14,6 +18,18 @@ impl<T> Default for BTreeStorage<T> {
     }
 }
 
+impl<T> StorageStats for BTreeStorage<T> {
+    fn capacity(&self) -> usize {
+        self.0.len()
+    }
+
+    fn heap_bytes(&self) -> usize {
+        // The nodes of the tree are not exposed, so only the entries are
+        // counted.
+        self.0.len() * size_of::<(Index, T)>()
+    }
+}
+
 impl<T> Storage<T> for BTreeStorage<T> {
     unsafe fn get(&self, index: Index) -> &T {
         &self.0[&index]
@@ -0,0 +1,138 @@
+use std::mem::{size_of, take};
+
+use hibitset::BitSetLike;
+
+use crate::entity::Index;
+
+use super::{mask_len, DistinctStorage, SliceAccess, Storage, StorageStats};
+
+/// Vector storage, like `VecStorage`, but allows safe access to the
+/// interior slices because unused slots are always initialized.
+///
+/// Requires the component to implement `Default`. Removed components are
+/// replaced by `T::default()`.
+///
+/// `as_slice()` and `as_mut_slice()` indices correspond to entity IDs.
+/// These can be compared to other `DefaultVecStorage`s, to other
+/// `VecStorage`s, and to `Entity::id()`s for live entities.
+pub struct DefaultVecStorage<T>(Vec<T>);
+
+impl<T> Storage<T> for DefaultVecStorage<T>
+where
+    T: Default,
+{
+    unsafe fn get(&self, index: Index) -> &T {
+        self.0.get_unchecked(index as usize)
+    }
+
+    unsafe fn get_mut(&mut self, index: Index) -> &mut T {
+        self.0.get_unchecked_mut(index as usize)
+    }
+
+    unsafe fn insert(&mut self, index: Index, value: T) {
+        let index = index as usize;
+
+        if self.0.len() <= index {
+            self.0.resize_with(index, Default::default);
+            self.0.push(value);
+        } else {
+            *self.0.get_unchecked_mut(index) = value;
+        }
+    }
+
+    unsafe fn remove(&mut self, index: Index) -> T {
+        take(self.0.get_unchecked_mut(index as usize))
+    }
+
+    unsafe fn clean<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        self.0.clear();
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.0.truncate(mask_len(has));
+        self.0.shrink_to_fit();
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+}
+
+impl<T> StorageStats for DefaultVecStorage<T> {
+    fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.0.capacity() * size_of::<T>()
+    }
+}
+
+impl<T> SliceAccess<T> for DefaultVecStorage<T> {
+    type Element = T;
+
+    /// Returns the stored components as slice. Slots of entities that do
+    /// not own the component contain `T::default()`.
+    fn as_slice(&self) -> &[T] {
+        self.0.as_slice()
+    }
+
+    /// Returns the stored components as mutable slice. Slots of entities
+    /// that do not own the component contain `T::default()`.
+    unsafe fn as_mut_slice(&mut self) -> &mut [T] {
+        self.0.as_mut_slice()
+    }
+}
+
+impl<T> DistinctStorage for DefaultVecStorage<T> {}
+
+impl<T> Default for DefaultVecStorage<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{component::Component, entity::Builder, world::World};
+
+    #[derive(Default, Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = DefaultVecStorage<Self>;
+    }
+
+    #[test]
+    fn slots_are_initialized() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+        let e3 = world.create_entity().with(Pos(3)).build();
+
+        {
+            let mut pos = world.component_mut::<Pos>();
+            assert_eq!(pos.get(e1), None);
+            assert_eq!(pos.remove(e2), Some(Pos(2)));
+            assert_eq!(pos.get(e2), None);
+            assert_eq!(pos.get(e3), Some(&Pos(3)));
+        }
+
+        let pos = world.component::<Pos>();
+        let slice = pos.as_slice();
+
+        assert_eq!(slice[e1.index() as usize], Pos(0));
+        assert_eq!(slice[e2.index() as usize], Pos(0));
+        assert_eq!(slice[e3.index() as usize], Pos(3));
+    }
+}
@@ -1,10 +1,10 @@
-use std::mem::MaybeUninit;
+use std::mem::{size_of, MaybeUninit};
 
 use hibitset::BitSetLike;
 
 use crate::{entity::Index, storage::Storage};
 
-use super::DistinctStorage;
+use super::{mask_len, DistinctStorage, SliceAccess, StorageStats};
 
 /// Dense vector storage. Has a redirection 2-way table
 /// between entities and components, allowing to leave
@@ -81,6 +81,50 @@ impl<T> Storage<T> for DenseVecStorage<T> {
     {
         // No Op
     }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.data_id.set_len(self.data_id.len().min(mask_len(has)));
+        self.data_id.shrink_to_fit();
+        self.entity_id.shrink_to_fit();
+        self.data.shrink_to_fit();
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.entity_id.reserve(additional);
+        self.data.reserve(additional);
+    }
+}
+
+impl<T> StorageStats for DenseVecStorage<T> {
+    fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.data.capacity() * size_of::<T>()
+            + (self.entity_id.capacity() + self.data_id.capacity()) * size_of::<Index>()
+    }
+}
+
+impl<T> SliceAccess<T> for DenseVecStorage<T> {
+    type Element = T;
+
+    /// Returns the densely packed components as slice. The position of the
+    /// component of a specific entity may change with each removal.
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        self.data.as_slice()
+    }
+
+    /// Returns the densely packed components as mutable slice. The position
+    /// of the component of a specific entity may change with each removal.
+    #[inline]
+    unsafe fn as_mut_slice(&mut self) -> &mut [T] {
+        self.data.as_mut_slice()
+    }
 }
 
 impl<T> DistinctStorage for DenseVecStorage<T> {}
@@ -1,6 +1,10 @@
 use hibitset::BitSet;
 
-use crate::{component::Component, entity::Index, join::Join};
+use crate::{
+    component::Component,
+    entity::{Entities, Entity, Index},
+    join::Join,
+};
 
 use super::MaskedStorage;
 
@@ -29,3 +33,37 @@ where
         value.remove(id).expect("Tried to access same index twice")
     }
 }
+
+/* DrainEntities */
+
+/// A draining storage wrapper which has a `Join` implementation that removes
+/// the components and yields them together with their entities.
+pub struct DrainEntities<'a, T: Component> {
+    /// The masked storage
+    pub data: &'a mut MaskedStorage<T>,
+
+    /// The entities the components belong to
+    pub entities: &'a Entities,
+}
+
+impl<'a, T> Join for DrainEntities<'a, T>
+where
+    T: Component,
+{
+    type Mask = BitSet;
+    type Type = (Entity, T);
+    type Value = (&'a mut MaskedStorage<T>, &'a Entities);
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let mask = self.data.mask().clone();
+
+        (mask, (self.data, self.entities))
+    }
+
+    unsafe fn get((data, entities): &mut Self::Value, id: Index) -> (Entity, T) {
+        let entity = <&Entities as Join>::get(entities, id);
+        let component = data.remove(id).expect("Tried to access same index twice");
+
+        (entity, component)
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{
+    component::Component,
+    entity::{Entity, Index},
+};
+
+use super::{MaskedStorage, Storage};
+
+/// An entry of a single component of a storage, which is either occupied
+/// or vacant. Returned by `StorageWrapper::entry`.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::*;
+/// #[derive(Debug, PartialEq)]
+/// struct Counter(u32);
+///
+/// impl Component for Counter {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::default();
+/// world.register_component::<Counter>();
+///
+/// let entity = world.create_entity().build();
+/// let mut counters = world.component_mut::<Counter>();
+///
+/// counters.entry(entity).unwrap().or_insert(Counter(0)).0 += 1;
+/// counters.entry(entity).unwrap().or_insert(Counter(0)).0 += 1;
+///
+/// assert_eq!(counters.get(entity), Some(&Counter(2)));
+/// ```
+pub enum StorageEntry<'a, T: Component> {
+    /// Entry of a component that already exists.
+    Occupied(OccupiedEntry<'a, T>),
+
+    /// Entry of a component that does not exist yet.
+    Vacant(VacantEntry<'a, T>),
+}
+
+impl<'a, T: Component> StorageEntry<'a, T> {
+    pub(crate) fn new(entity: Entity, data: &'a mut MaskedStorage<T>) -> Self {
+        if data.mask().contains(entity.index()) {
+            Self::Occupied(OccupiedEntry { entity, data })
+        } else {
+            Self::Vacant(VacantEntry { entity, data })
+        }
+    }
+
+    /// Returns the entity of this entry.
+    pub fn entity(&self) -> Entity {
+        match self {
+            Self::Occupied(e) => e.entity,
+            Self::Vacant(e) => e.entity,
+        }
+    }
+
+    /// Inserts `component` if the entry is vacant and returns a mutable
+    /// reference to the component of the entry.
+    pub fn or_insert(self, component: T) -> &'a mut T {
+        self.or_insert_with(|| component)
+    }
+
+    /// Inserts the component returned by `f` if the entry is vacant and
+    /// returns a mutable reference to the component of the entry.
+    pub fn or_insert_with<F>(self, f: F) -> &'a mut T
+    where
+        F: FnOnce() -> T,
+    {
+        match self {
+            Self::Occupied(e) => e.into_mut(),
+            Self::Vacant(e) => e.insert(f()),
+        }
+    }
+
+    /// Inserts the default component if the entry is vacant and returns a
+    /// mutable reference to the component of the entry.
+    pub fn or_default(self) -> &'a mut T
+    where
+        T: Default,
+    {
+        self.or_insert_with(Default::default)
+    }
+
+    /// Calls `f` with the component if the entry is occupied.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut T),
+    {
+        if let Self::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+
+        self
+    }
+}
+
+/// Entry of a component that already exists.
+pub struct OccupiedEntry<'a, T: Component> {
+    entity: Entity,
+    data: &'a mut MaskedStorage<T>,
+}
+
+impl<'a, T: Component> OccupiedEntry<'a, T> {
+    /// Returns the entity of this entry.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// Returns a reference to the component.
+    pub fn get(&self) -> &T {
+        unsafe { self.data.storage().get(self.index()) }
+    }
+
+    /// Returns a mutable reference to the component.
+    pub fn get_mut(&mut self) -> &mut T {
+        let index = self.index();
+
+        unsafe { self.data.storage_mut().get_mut(index) }
+    }
+
+    /// Converts the entry into a mutable reference to the component, that
+    /// lives as long as the storage is borrowed.
+    pub fn into_mut(self) -> &'a mut T {
+        let index = self.index();
+
+        unsafe { self.data.storage_mut().get_mut(index) }
+    }
+
+    /// Replaces the component and returns the old one.
+    pub fn insert(&mut self, component: T) -> T {
+        self.data
+            .insert(self.entity, component)
+            .expect("Occupied entry without component")
+    }
+
+    /// Removes the component from the storage and returns it.
+    pub fn remove(self) -> T {
+        self.data
+            .remove(self.index())
+            .expect("Occupied entry without component")
+    }
+
+    fn index(&self) -> Index {
+        self.entity.index()
+    }
+}
+
+/// Entry of a component that does not exist yet.
+pub struct VacantEntry<'a, T: Component> {
+    entity: Entity,
+    data: &'a mut MaskedStorage<T>,
+}
+
+impl<'a, T: Component> VacantEntry<'a, T> {
+    /// Returns the entity of this entry.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// Inserts the component and returns a mutable reference to it.
+    pub fn insert(self, component: T) -> &'a mut T {
+        let index = self.entity.index();
+
+        self.data.insert(self.entity, component);
+
+        unsafe { self.data.storage_mut().get_mut(index) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{entity::Builder, storage::VecStorage, world::World};
+
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn occupied_and_vacant() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().build();
+
+        let mut storage = world.component_mut::<Pos>();
+
+        match storage.entry(e1).unwrap() {
+            StorageEntry::Occupied(mut e) => assert_eq!(e.insert(Pos(5)), Pos(1)),
+            StorageEntry::Vacant(_) => panic!("Expected occupied entry"),
+        }
+
+        match storage.entry(e2).unwrap() {
+            StorageEntry::Occupied(_) => panic!("Expected vacant entry"),
+            StorageEntry::Vacant(e) => e.insert(Pos(2)).0 += 1,
+        }
+
+        storage
+            .entry(e1)
+            .unwrap()
+            .and_modify(|p| p.0 += 1)
+            .or_default();
+
+        assert_eq!(storage.get(e1), Some(&Pos(6)));
+        assert_eq!(storage.get(e2), Some(&Pos(3)));
+
+        match storage.entry(e2).unwrap() {
+            StorageEntry::Occupied(e) => assert_eq!(e.remove(), Pos(3)),
+            StorageEntry::Vacant(_) => panic!("Expected occupied entry"),
+        }
+
+        assert!(!storage.contains(e2));
+    }
+
+    #[test]
+    fn dead_entity() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let entity = world.create_entity().build();
+        world.entities_mut().kill(&[entity]).unwrap();
+
+        assert!(world.component_mut::<Pos>().entry(entity).is_err());
+    }
+}
@@ -0,0 +1,394 @@
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crossbeam_queue::SegQueue;
+use hibitset::BitSetLike;
+
+use crate::{
+    entity::Index,
+    event::{EventChannel, ReaderId},
+};
+
+use super::{mask_len, DenseVecStorage, DistinctStorage, Storage, StorageStats};
+
+/// Tick of the global change clock.
+pub type Tick = u64;
+
+/// Global clock that is used to stamp modifications of flagged components.
+static CLOCK: AtomicU64 = AtomicU64::new(0);
+
+/// Get the current tick of the global change clock.
+pub fn current_tick() -> Tick {
+    CLOCK.load(Ordering::Acquire)
+}
+
+/// Advances the global change clock and returns the new tick.
+pub fn advance_tick() -> Tick {
+    CLOCK.fetch_add(1, Ordering::AcqRel) + 1
+}
+
+/// Event that is emitted by a `FlaggedStorage` for each change of a
+/// component.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ComponentEvent {
+    /// A component was inserted for the entity with the given index.
+    Inserted(Index),
+
+    /// A component of the entity with the given index was modified.
+    Modified(Index),
+
+    /// The component of the entity with the given index was removed.
+    Removed(Index),
+}
+
+/// Storage that keeps track of the changes of its components. The actual
+/// data is stored in the wrapped storage `T`.
+///
+/// Each insertion, modification and removal is written as `ComponentEvent`
+/// into an event channel, that systems can subscribe to using
+/// `StorageWrapper::register_reader`. Additionally the tick a component was
+/// last inserted or modified at is stored, which allows to join over the
+/// changed components using `StorageWrapper::changed_since`.
+///
+/// Please note that every mutable access is treated as a modification, so
+/// joining over `&mut storage` flags all joined components, even if they
+/// are not actually modified.
+///
+/// The events are first pushed to a lock-free queue, so parallel joins over
+/// `&mut storage` do not contend on the event channel. They are moved into
+/// the channel as soon as it is accessed, and on each `World::maintain`.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::{storage::ComponentEvent, *};
+///
+/// pub struct Transform(f32, f32);
+///
+/// impl Component for Transform {
+///     type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+/// }
+///
+/// let mut world = World::default();
+/// world.register_component::<Transform>();
+///
+/// let mut reader = world.component::<Transform>().register_reader();
+///
+/// let entity = world.create_entity().with(Transform(0.0, 0.0)).build();
+/// world.component_mut::<Transform>().get_mut(entity).unwrap().0 = 1.0;
+/// world.component_mut::<Transform>().remove(entity);
+///
+/// let transforms = world.component::<Transform>();
+/// let events = transforms.channel().read(&mut reader).copied().collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     events,
+///     vec![
+///         ComponentEvent::Inserted(entity.index()),
+///         ComponentEvent::Modified(entity.index()),
+///         ComponentEvent::Removed(entity.index()),
+///     ]
+/// );
+/// ```
+pub struct FlaggedStorage<C, T = DenseVecStorage<C>> {
+    inner: T,
+    ticks: Vec<Tick>,
+    events: Mutex<EventChannel<ComponentEvent>>,
+    pending: SegQueue<ComponentEvent>,
+    marker: PhantomData<C>,
+}
+
+impl<C, T> FlaggedStorage<C, T> {
+    /// Get a reference to the wrapped storage.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn flag(&mut self, index: Index) {
+        let index = index as usize;
+
+        if self.ticks.len() <= index {
+            self.ticks.resize(index + 1, 0);
+        }
+
+        self.ticks[index] = current_tick();
+    }
+
+    fn emit(&self, event: ComponentEvent) {
+        self.pending.push(event);
+    }
+}
+
+/// Moves the pending events into the event channel.
+fn flush(pending: &SegQueue<ComponentEvent>, events: &mut EventChannel<ComponentEvent>) {
+    while let Some(event) = pending.pop() {
+        events.single_write(event);
+    }
+}
+
+impl<C, T> Default for FlaggedStorage<C, T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+            ticks: Default::default(),
+            events: Default::default(),
+            pending: SegQueue::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<C, T> Storage<C> for FlaggedStorage<C, T>
+where
+    T: Storage<C> + Default,
+{
+    unsafe fn get(&self, index: Index) -> &C {
+        self.inner.get(index)
+    }
+
+    unsafe fn get_mut(&mut self, index: Index) -> &mut C {
+        *self.ticks.get_unchecked_mut(index as usize) = current_tick();
+
+        self.emit(ComponentEvent::Modified(index));
+
+        self.inner.get_mut(index)
+    }
+
+    unsafe fn insert(&mut self, index: Index, value: C) {
+        self.flag(index);
+
+        self.emit(ComponentEvent::Inserted(index));
+
+        self.inner.insert(index, value);
+    }
+
+    unsafe fn remove(&mut self, index: Index) -> C {
+        self.emit(ComponentEvent::Removed(index));
+
+        self.inner.remove(index)
+    }
+
+    unsafe fn clean<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        let events = self.events.get_mut().unwrap();
+
+        flush(&self.pending, events);
+        events.iter_write((&has).iter().map(ComponentEvent::Removed));
+
+        self.inner.clean(has);
+    }
+
+    unsafe fn drop(&mut self, index: Index) {
+        self.emit(ComponentEvent::Removed(index));
+
+        self.inner.drop(index);
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.ticks.truncate(mask_len(&has));
+        self.ticks.shrink_to_fit();
+
+        self.inner.shrink_to_fit(has);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
+    }
+
+    fn maintain(&mut self) {
+        flush(&self.pending, self.events.get_mut().unwrap());
+
+        self.inner.maintain();
+    }
+}
+
+impl<C, T> StorageStats for FlaggedStorage<C, T>
+where
+    T: StorageStats,
+{
+    fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.inner.heap_bytes() + self.ticks.capacity() * size_of::<Tick>()
+    }
+}
+
+impl<C, T> DistinctStorage for FlaggedStorage<C, T> where T: DistinctStorage {}
+
+/* Tracked */
+
+/// Storage that keeps track of the changes of its components.
+pub trait Tracked {
+    /// Returns the tick the component with the given `index` was last
+    /// changed at.
+    fn last_changed(&self, index: Index) -> Tick;
+
+    /// Returns the event channel the changes of the components are written
+    /// to.
+    fn channel(&self) -> MutexGuard<'_, EventChannel<ComponentEvent>>;
+
+    /// Registers a new reader for the events of this storage.
+    fn register_reader(&self) -> ReaderId<ComponentEvent> {
+        self.channel().register_reader()
+    }
+}
+
+impl<C, T> Tracked for FlaggedStorage<C, T> {
+    fn last_changed(&self, index: Index) -> Tick {
+        self.ticks.get(index as usize).copied().unwrap_or_default()
+    }
+
+    fn channel(&self) -> MutexGuard<'_, EventChannel<ComponentEvent>> {
+        let mut events = self.events.lock().unwrap();
+
+        flush(&self.pending, &mut events);
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        component::Component,
+        entity::Builder,
+        join::{Join, ParJoin},
+        storage::VecStorage,
+        world::World,
+    };
+
+    use asparit::{Driver, ParallelIterator};
+
+    use super::*;
+
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    fn events(world: &World, reader: &mut ReaderId<ComponentEvent>) -> Vec<ComponentEvent> {
+        world
+            .component::<Pos>()
+            .channel()
+            .read(reader)
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn insert_modify_remove() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let mut reader = world.component::<Pos>().register_reader();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+
+        assert_eq!(
+            events(&world, &mut reader),
+            vec![
+                ComponentEvent::Inserted(e1.index()),
+                ComponentEvent::Inserted(e2.index())
+            ]
+        );
+
+        for pos in (&mut world.component_mut::<Pos>()).join() {
+            pos.0 += 1;
+        }
+        world.component_mut::<Pos>().insert(e1, Pos(5)).unwrap();
+
+        assert_eq!(
+            events(&world, &mut reader),
+            vec![
+                ComponentEvent::Modified(e1.index()),
+                ComponentEvent::Modified(e2.index()),
+                ComponentEvent::Modified(e1.index())
+            ]
+        );
+
+        world.component_mut::<Pos>().remove(e2);
+        world.component_mut::<Pos>().clear();
+
+        assert_eq!(
+            events(&world, &mut reader),
+            vec![
+                ComponentEvent::Removed(e2.index()),
+                ComponentEvent::Removed(e1.index())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn deleted_entities_are_removed() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e = world.create_entity().with(Pos(1)).build();
+        let mut reader = world.component::<Pos>().register_reader();
+
+        world.entities().delete(e).unwrap();
+        world.maintain().await;
+
+        assert_eq!(
+            events(&world, &mut reader),
+            vec![ComponentEvent::Removed(e.index())]
+        );
+    }
+
+    #[test]
+    fn par_join_emits_events() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        for i in 0..100 {
+            world.create_entity().with(Pos(i)).build();
+        }
+
+        let mut reader = world.component::<Pos>().register_reader();
+
+        (&mut world.component_mut::<Pos>())
+            .par_join()
+            .for_each(|pos| pos.0 += 1)
+            .exec();
+
+        let mut modified = events(&world, &mut reader)
+            .into_iter()
+            .map(|event| match event {
+                ComponentEvent::Modified(index) => index,
+                event => panic!("Unexpected event: {:?}", event),
+            })
+            .collect::<Vec<_>>();
+        modified.sort_unstable();
+
+        let entities = world.entities();
+        let expected = (&entities).join().map(|e| e.index()).collect::<Vec<_>>();
+
+        assert_eq!(modified, expected);
+    }
+
+    #[test]
+    fn events_without_reader_are_dropped() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        for i in 0..10 {
+            world.create_entity().with(Pos(i)).build();
+        }
+
+        assert_eq!(world.component::<Pos>().channel().len(), 1);
+    }
+}
@@ -0,0 +1,646 @@
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    component::Component,
+    entity::Index,
+    resource::ResourceId,
+    world::{CastFrom, World},
+};
+
+use super::{MaskedStorage, SparseSetStorage, StorageWrapper};
+
+/// A group of components, whose storages are packed together.
+///
+/// The storages of all grouped components are sorted, so the components of
+/// the entities that own every component of the group are stored at the
+/// beginning of the dense arrays, in the same order for all storages.
+/// Joining the storages using `Group::join` is then a linear scan over these
+/// arrays, without any bit set operations or index lookups.
+///
+/// Groups are registered using `World::register_group`, and only support
+/// components that are stored in a `SparseSetStorage`. Each component can
+/// only be part of one group.
+///
+/// Systems borrow the storages independently of each other, so a group can
+/// not be kept packed while components are inserted or removed. The storages
+/// are packed again on `World::maintain` (or `World::pack_groups`). Until
+/// then `Group::join` falls back to looking up the components of each entity
+/// of the first storage.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{storage::Group, *};
+/// #
+/// # #[derive(Debug, PartialEq)]
+/// # struct Pos(u32);
+/// # impl Component for Pos { type Storage = SparseSetStorage<Self>; }
+/// #
+/// # #[derive(Debug, PartialEq)]
+/// # struct Vel(u32);
+/// # impl Component for Vel { type Storage = SparseSetStorage<Self>; }
+/// #
+/// let mut world = World::default();
+/// world.register_group::<(Pos, Vel)>();
+///
+/// world.create_entity().with(Pos(1)).build();
+/// world.create_entity().with(Pos(2)).with(Vel(1)).build();
+/// world.create_entity().with(Vel(2)).build();
+/// world.create_entity().with(Pos(3)).with(Vel(3)).build();
+///
+/// // done by `World::maintain` as well
+/// world.pack_groups();
+///
+/// let group = world.resource::<Group<(Pos, Vel)>>();
+/// let mut pos = world.component_mut::<Pos>();
+/// let vel = world.component::<Vel>();
+///
+/// assert_eq!(group.len(), 2);
+///
+/// for (pos, vel) in group.join((&mut pos, &vel)) {
+///     pos.0 += vel.0;
+/// }
+///
+/// assert_eq!(&pos.as_slice()[..2], &[Pos(3), Pos(6)]);
+/// ```
+pub struct Group<T> {
+    len: usize,
+    versions: Vec<u64>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Group<T>
+where
+    T: GroupComponents,
+{
+    /// Returns the number of entities that own all components of the group,
+    /// as counted by the last packing of the storages.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no entity owned all components of the group, when
+    /// the storages were packed the last time.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Joins the passed storages of the grouped components. The storages
+    /// need to be passed in the same order as the components of the group.
+    ///
+    /// If the storages were not modified since they were packed, this is a
+    /// linear scan over the packed components.
+    pub fn join<J>(&self, storages: J) -> GroupIter<T, J>
+    where
+        J: GroupJoin<T>,
+    {
+        let values = storages.open();
+        let packed = J::versions(&values) == self.versions;
+        let end = if packed {
+            // Equal versions do not prove that the storages were packed by
+            // this group (they may belong to another world), so the scan is
+            // clamped to the storages to stay in bounds.
+            self.len.min(J::min_len(&values))
+        } else {
+            J::len(&values)
+        };
+
+        GroupIter {
+            values,
+            packed,
+            pos: 0,
+            end,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for Group<T> {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            versions: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+/* GroupComponents */
+
+/// Tuple of components that can be grouped by `World::register_group`.
+///
+/// This is implemented for tuples of two to four components, that are
+/// stored in a `SparseSetStorage`.
+pub trait GroupComponents: 'static {
+    /// Registers the storages of the grouped components.
+    fn register(world: &mut World);
+
+    /// Returns the resource ids of the storages of the grouped components.
+    fn components() -> Vec<ResourceId>;
+
+    /// Packs the storages of the grouped components. Returns the number of
+    /// entities that own all components, and the versions of the storages
+    /// after they were packed.
+    fn pack(world: &World) -> (usize, Vec<u64>);
+}
+
+/// Storage that can be joined by a `Group`. This is implemented for
+/// references to storages of components that are stored in a
+/// `SparseSetStorage`.
+pub trait GroupAccess {
+    /// Component of the storage.
+    type Component: Component;
+
+    /// Type of the joined component.
+    type Type;
+
+    /// Type of the opened storage.
+    type Value;
+
+    /// Opens the storage.
+    fn open(self) -> Self::Value;
+
+    /// Returns the inner storage of the opened storage.
+    fn storage(value: &Self::Value) -> &SparseSetStorage<Self::Component>;
+
+    /// Returns the component at the passed position of the dense arrays.
+    ///
+    /// # Safety
+    ///
+    /// The position must be in bounds and may only be accessed once.
+    unsafe fn get(value: &mut Self::Value, pos: usize) -> Self::Type;
+}
+
+impl<'a, 'e, C, D> GroupAccess for &'a StorageWrapper<'e, C, D>
+where
+    C: Component<Storage = SparseSetStorage<C>>,
+    D: Deref<Target = MaskedStorage<C>>,
+{
+    type Component = C;
+    type Type = &'a C;
+    type Value = &'a SparseSetStorage<C>;
+
+    fn open(self) -> Self::Value {
+        self.unprotected_storage()
+    }
+
+    fn storage(value: &Self::Value) -> &SparseSetStorage<C> {
+        value
+    }
+
+    unsafe fn get(value: &mut Self::Value, pos: usize) -> &'a C {
+        let value: &'a SparseSetStorage<C> = value;
+
+        value.get_dense(pos)
+    }
+}
+
+impl<'a, 'e, C, D> GroupAccess for &'a mut StorageWrapper<'e, C, D>
+where
+    C: Component<Storage = SparseSetStorage<C>>,
+    D: DerefMut<Target = MaskedStorage<C>>,
+{
+    type Component = C;
+    type Type = &'a mut C;
+    type Value = &'a mut SparseSetStorage<C>;
+
+    fn open(self) -> Self::Value {
+        self.unprotected_storage_mut()
+    }
+
+    fn storage(value: &Self::Value) -> &SparseSetStorage<C> {
+        value
+    }
+
+    unsafe fn get(value: &mut Self::Value, pos: usize) -> &'a mut C {
+        // SAFETY: Each position is only accessed once, so the returned
+        // references do not alias.
+        &mut *value.get_dense_mut(pos)
+    }
+}
+
+/// Tuple of storages that can be joined by a `Group` of the components `T`.
+pub trait GroupJoin<T> {
+    /// Type of the joined components.
+    type Type;
+
+    /// Type of the opened storages.
+    type Values;
+
+    /// Opens the storages.
+    fn open(self) -> Self::Values;
+
+    /// Returns the versions of the opened storages.
+    fn versions(values: &Self::Values) -> Vec<u64>;
+
+    /// Returns the number of components of the first storage.
+    fn len(values: &Self::Values) -> usize;
+
+    /// Returns the number of components of the smallest storage.
+    fn min_len(values: &Self::Values) -> usize;
+
+    /// Returns the components at the passed position of the packed storages.
+    ///
+    /// # Safety
+    ///
+    /// The storages must be packed, and each position may only be accessed
+    /// once.
+    unsafe fn get(values: &mut Self::Values, pos: usize) -> Self::Type;
+
+    /// Returns the components of the entity at the passed position of the
+    /// first storage, if the entity owns all components.
+    ///
+    /// # Safety
+    ///
+    /// The position must be in bounds and may only be accessed once.
+    unsafe fn lookup(values: &mut Self::Values, pos: usize) -> Option<Self::Type>;
+}
+
+/* GroupIter */
+
+/// Iterator over the components of a `Group`, returned by `Group::join`.
+pub struct GroupIter<T, J>
+where
+    J: GroupJoin<T>,
+{
+    values: J::Values,
+    packed: bool,
+    pos: usize,
+    end: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T, J> GroupIter<T, J>
+where
+    J: GroupJoin<T>,
+{
+    /// Returns `true` if the joined storages are packed, so the iterator
+    /// is a linear scan over the packed components.
+    pub fn is_packed(&self) -> bool {
+        self.packed
+    }
+}
+
+impl<T, J> Iterator for GroupIter<T, J>
+where
+    J: GroupJoin<T>,
+{
+    type Item = J::Type;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.end {
+            let pos = self.pos;
+
+            self.pos += 1;
+
+            if self.packed {
+                return Some(unsafe { J::get(&mut self.values, pos) });
+            }
+
+            if let Some(item) = unsafe { J::lookup(&mut self.values, pos) } {
+                return Some(item);
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.pos;
+
+        if self.packed {
+            (remaining, Some(remaining))
+        } else {
+            (0, Some(remaining))
+        }
+    }
+}
+
+/* AnyGroup */
+
+/// Type erased `Group`, used to pack all registered groups.
+pub trait AnyGroup {
+    /// Packs the storages of the group.
+    fn pack(&mut self, world: &World);
+
+    /// Returns the resource ids of the storages of the grouped components.
+    fn components(&self) -> Vec<ResourceId>;
+}
+
+unsafe impl<T> CastFrom<T> for dyn AnyGroup
+where
+    T: AnyGroup + 'static,
+{
+    fn cast(t: &T) -> &Self {
+        t
+    }
+
+    fn cast_mut(t: &mut T) -> &mut Self {
+        t
+    }
+}
+
+impl<T> AnyGroup for Group<T>
+where
+    T: GroupComponents,
+{
+    fn pack(&mut self, world: &World) {
+        let (len, versions) = T::pack(world);
+
+        self.len = len;
+        self.versions = versions;
+    }
+
+    fn components(&self) -> Vec<ResourceId> {
+        T::components()
+    }
+}
+
+/* Packing */
+
+/// Storage that can be packed by a group.
+trait Pack {
+    fn indices(&self) -> &[Index];
+
+    fn position(&self, index: Index) -> Option<usize>;
+
+    fn swap(&mut self, a: usize, b: usize);
+
+    fn version(&self) -> u64;
+}
+
+impl<T> Pack for SparseSetStorage<T> {
+    fn indices(&self) -> &[Index] {
+        SparseSetStorage::indices(self)
+    }
+
+    fn position(&self, index: Index) -> Option<usize> {
+        SparseSetStorage::position(self, index)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        SparseSetStorage::swap(self, a, b)
+    }
+
+    fn version(&self) -> u64 {
+        SparseSetStorage::version(self)
+    }
+}
+
+/// Moves the components of all entities that own a component in each of the
+/// passed storages to the beginning of the storages.
+fn pack(storages: &mut [&mut dyn Pack]) -> (usize, Vec<u64>) {
+    // Iterate over the smallest storage, the group can not contain more
+    // entities anyway.
+    let driver = (0..storages.len())
+        .min_by_key(|i| storages[*i].indices().len())
+        .unwrap();
+
+    let mut len = 0;
+    let mut positions = Vec::with_capacity(storages.len());
+
+    for pos in 0..storages[driver].indices().len() {
+        let index = storages[driver].indices()[pos];
+
+        positions.clear();
+        for storage in storages.iter() {
+            match storage.position(index) {
+                Some(pos) => positions.push(pos),
+                None => break,
+            }
+        }
+
+        if positions.len() != storages.len() {
+            continue;
+        }
+
+        for (storage, pos) in storages.iter_mut().zip(&positions) {
+            storage.swap(*pos, len);
+        }
+
+        len += 1;
+    }
+
+    let versions = storages.iter().map(|storage| storage.version()).collect();
+
+    (len, versions)
+}
+
+macro_rules! define_group {
+    ($ta:ident $sa:ident $va:ident, $($t:ident $s:ident $v:ident),+) => {
+        impl<$ta, $($t),+> GroupComponents for ($ta, $($t),+)
+        where
+            $ta: Component<Storage = SparseSetStorage<$ta>> + Send + Sync,
+            $($t: Component<Storage = SparseSetStorage<$t>> + Send + Sync,)+
+        {
+            fn register(world: &mut World) {
+                world.register_component::<$ta>();
+                $(world.register_component::<$t>();)+
+            }
+
+            fn components() -> Vec<ResourceId> {
+                vec![
+                    ResourceId::new::<MaskedStorage<$ta>>(),
+                    $(ResourceId::new::<MaskedStorage<$t>>(),)+
+                ]
+            }
+
+            #[allow(non_snake_case)]
+            fn pack(world: &World) -> (usize, Vec<u64>) {
+                let mut $ta = world.resource_mut::<MaskedStorage<$ta>>();
+                $(let mut $t = world.resource_mut::<MaskedStorage<$t>>();)+
+
+                pack(&mut [
+                    $ta.storage_mut() as &mut dyn Pack,
+                    $($t.storage_mut() as &mut dyn Pack,)+
+                ])
+            }
+        }
+
+        impl<$ta, $($t,)+ $sa, $($s),+> GroupJoin<($ta, $($t),+)> for ($sa, $($s),+)
+        where
+            $sa: GroupAccess<Component = $ta>,
+            $($s: GroupAccess<Component = $t>,)+
+        {
+            type Type = (<$sa as GroupAccess>::Type, $(<$s as GroupAccess>::Type),+);
+            type Values = (<$sa as GroupAccess>::Value, $(<$s as GroupAccess>::Value),+);
+
+            fn open(self) -> Self::Values {
+                let ($va, $($v),+) = self;
+
+                ($va.open(), $($v.open()),+)
+            }
+
+            fn versions(($va, $($v),+): &Self::Values) -> Vec<u64> {
+                vec![$sa::storage($va).version(), $($s::storage($v).version()),+]
+            }
+
+            fn len(($va, ..): &Self::Values) -> usize {
+                $sa::storage($va).indices().len()
+            }
+
+            fn min_len(($va, $($v),+): &Self::Values) -> usize {
+                let len = $sa::storage($va).indices().len();
+                $(let len = len.min($s::storage($v).indices().len());)+
+
+                len
+            }
+
+            unsafe fn get(($va, $($v),+): &mut Self::Values, pos: usize) -> Self::Type {
+                ($sa::get($va, pos), $($s::get($v, pos)),+)
+            }
+
+            unsafe fn lookup(
+                ($va, $($v),+): &mut Self::Values,
+                pos: usize,
+            ) -> Option<Self::Type> {
+                let index = $sa::storage($va).indices()[pos];
+
+                Some((
+                    $sa::get($va, pos),
+                    $($s::get($v, $s::storage($v).position(index)?)),+
+                ))
+            }
+        }
+    };
+}
+
+define_group!(A SA a, B SB b);
+define_group!(A SA a, B SB b, C SC c);
+define_group!(A SA a, B SB b, C SC c, D SD d);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::entity::Builder;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = SparseSetStorage<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Vel(u32);
+
+    impl Component for Vel {
+        type Storage = SparseSetStorage<Self>;
+    }
+
+    fn sorted(mut items: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+        items.sort_unstable();
+
+        items
+    }
+
+    #[tokio::test]
+    async fn pack_and_join() {
+        let mut world = World::default();
+        world.register_group::<(Pos, Vel)>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        world.create_entity().with(Pos(2)).with(Vel(2)).build();
+        world.create_entity().with(Vel(3)).build();
+        world.create_entity().with(Pos(4)).with(Vel(4)).build();
+
+        world.pack_groups();
+
+        {
+            let group = world.resource::<Group<(Pos, Vel)>>();
+            let pos = world.component::<Pos>();
+            let vel = world.component::<Vel>();
+
+            assert_eq!(group.len(), 2);
+            assert_eq!(&pos.as_slice()[..2], &[Pos(2), Pos(4)]);
+            assert_eq!(&vel.as_slice()[..2], &[Vel(2), Vel(4)]);
+
+            let iter = group.join((&pos, &vel));
+            assert!(iter.is_packed());
+            assert_eq!(
+                iter.map(|(p, v)| (p.0, v.0)).collect::<Vec<_>>(),
+                vec![(2, 2), (4, 4)]
+            );
+        }
+
+        world.component_mut::<Vel>().insert(e1, Vel(1)).unwrap();
+        world.create_entity().with(Pos(5)).with(Vel(5)).build();
+
+        {
+            let group = world.resource::<Group<(Pos, Vel)>>();
+            let mut pos = world.component_mut::<Pos>();
+            let vel = world.component::<Vel>();
+
+            let iter = group.join((&mut pos, &vel));
+            assert!(!iter.is_packed());
+            assert_eq!(
+                sorted(iter.map(|(p, v)| (p.0, v.0)).collect()),
+                vec![(1, 1), (2, 2), (4, 4), (5, 5)]
+            );
+        }
+
+        world.maintain().await;
+
+        let group = world.resource::<Group<(Pos, Vel)>>();
+        let pos = world.component::<Pos>();
+        let vel = world.component::<Vel>();
+
+        let iter = group.join((&pos, &vel));
+        assert_eq!(group.len(), 4);
+        assert!(iter.is_packed());
+        assert_eq!(
+            sorted(iter.map(|(p, v)| (p.0, v.0)).collect()),
+            vec![(1, 1), (2, 2), (4, 4), (5, 5)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is already part of another group")]
+    fn overlapping_groups() {
+        #[derive(Debug, PartialEq)]
+        struct Acc(u32);
+
+        impl Component for Acc {
+            type Storage = SparseSetStorage<Self>;
+        }
+
+        let mut world = World::default();
+        world.register_group::<(Pos, Vel)>();
+        world.register_group::<(Acc, Vel)>();
+    }
+
+    #[test]
+    #[should_panic(expected = "contains a component more than once")]
+    fn duplicate_components() {
+        let mut world = World::default();
+        world.register_group::<(Pos, Vel, Pos)>();
+    }
+
+    #[test]
+    fn join_storages_of_other_world() {
+        let mut world = World::default();
+        world.register_group::<(Pos, Vel)>();
+        world.create_entity().with(Pos(1)).with(Vel(1)).build();
+        world.create_entity().with(Pos(2)).with(Vel(2)).build();
+        world.pack_groups();
+
+        let mut other = World::default();
+        other.register_component::<Pos>();
+        other.register_component::<Vel>();
+        other.create_entity().with(Pos(1)).build();
+        let e = other.create_entity().with(Pos(2)).with(Vel(2)).build();
+        other.component_mut::<Vel>().remove(e);
+
+        let group = world.resource::<Group<(Pos, Vel)>>();
+        let pos = other.component::<Pos>();
+        let vel = other.component::<Vel>();
+
+        // The versions of the storages match the versions of the group, but
+        // the scan is still limited to the existing components.
+        let iter = group.join((&pos, &vel));
+        assert!(iter.is_packed());
+        assert_eq!(iter.count(), 0);
+    }
+}
@@ -1,9 +1,11 @@
+use std::mem::size_of;
+
 use hashbrown::HashMap;
 use hibitset::BitSetLike;
 
 use crate::entity::Index;
 
-use super::{DistinctStorage, Storage};
+use super::{DistinctStorage, Storage, StorageStats};
 
 /// `HashMap`-based storage. Best suited for rare components.
 ///
@@ -39,6 +41,28 @@ impl<T> Storage<T> for HashMapStorage<T> {
     {
         // No Op
     }
+
+    unsafe fn shrink_to_fit<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        self.0.shrink_to_fit();
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+}
+
+impl<T> StorageStats for HashMapStorage<T> {
+    fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    fn heap_bytes(&self) -> usize {
+        // Each bucket stores the entry and one control byte.
+        self.0.capacity() * (size_of::<(Index, T)>() + 1)
+    }
 }
 
 impl<T> DistinctStorage for HashMapStorage<T> {}
@@ -1,18 +1,26 @@
+use std::any::type_name;
 use std::mem::swap;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use hibitset::BitSet;
+use hibitset::{BitSet, BitSetAnd, BitSetLike};
 
 use crate::{
-    component::Component,
+    component::{Component, ComponentHooks},
     entity::{Entity, Index},
-    storage::Storage,
+    storage::{ComponentStats, Storage, StorageStats},
 };
 
+/// Source of the mask generations of all storages. Generations are never
+/// reused, so a generation identifies a storage and the state of its mask.
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
 /// The `Storage` together with the `BitSet` that knows
 /// about which elements are stored, and which are not.
 pub struct MaskedStorage<T: Component> {
     mask: BitSet,
     inner: T::Storage,
+    hooks: ComponentHooks<T>,
+    generation: u64,
 }
 
 impl<T: Component> MaskedStorage<T> {
@@ -21,14 +29,39 @@ impl<T: Component> MaskedStorage<T> {
         Self {
             mask: BitSet::new(),
             inner,
+            hooks: ComponentHooks::default(),
+            generation: next_generation(),
         }
     }
 
+    /// Get the hooks that are invoked when components are inserted or removed.
+    pub fn hooks(&self) -> &ComponentHooks<T> {
+        &self.hooks
+    }
+
+    /// Set the hooks that are invoked when components are inserted or removed.
+    pub fn set_hooks(&mut self, hooks: ComponentHooks<T>) {
+        self.hooks = hooks;
+    }
+
+    pub(crate) fn hooks_mut(&mut self) -> &mut ComponentHooks<T> {
+        &mut self.hooks
+    }
+
     /// Get the mask of living elements.
     pub fn mask(&self) -> &BitSet {
         &self.mask
     }
 
+    /// Returns the generation of the mask. A new generation is assigned each
+    /// time an element is added to or removed from the mask. Generations are
+    /// unique across all storages and always greater than the previous ones,
+    /// so they can be used to check cheaply if a mask that was computed from
+    /// this storage is still valid (see `CachedMask`).
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     /// Get areference to the inner storage.
     pub fn storage(&self) -> &T::Storage {
         &self.inner
@@ -39,43 +72,131 @@ impl<T: Component> MaskedStorage<T> {
         &mut self.inner
     }
 
+    /// Get the mask of living elements and a mutable reference to the inner
+    /// storage at the same time.
+    pub fn open_mut(&mut self) -> (&BitSet, &mut T::Storage) {
+        (&self.mask, &mut self.inner)
+    }
+
     /// Insert new element
     pub fn insert(&mut self, entity: Entity, mut component: T) -> Option<T> {
         let index = entity.index();
 
-        if self.mask.contains(index) {
-            swap(&mut component, unsafe { self.inner.get_mut(index) });
+        // The insert hook of a new component is executed before the index is
+        // added to the mask, otherwise a panicking hook would leave the mask
+        // pointing to a component that was never stored.
+        if self.hooks.has_insert() && !self.mask.contains(index) {
+            self.hooks.inserted(index, &mut component);
+        }
+
+        // `BitSet::add` returns `true` if the index was already part of the
+        // mask.
+        if self.mask.add(index) {
+            let current = unsafe { self.inner.get_mut(index) };
+
+            self.hooks.removed(index, current);
+            self.hooks.inserted(index, &mut component);
+
+            swap(&mut component, current);
 
             Some(component)
         } else {
-            self.mask.add(index);
-
             unsafe { self.inner.insert(index, component) };
 
+            self.generation = next_generation();
+
             None
         }
     }
 
     /// Clear the contents of this storage.
     pub fn clear(&mut self) {
+        if self.hooks.has_drop() {
+            for index in (&self.mask).iter() {
+                self.hooks.dropped(index, unsafe { self.inner.get(index) });
+            }
+        }
+
         unsafe { self.inner.clean(&self.mask) };
 
         self.mask.clear();
+        self.generation = next_generation();
+    }
+
+    /// Shrinks the memory that is allocated by the storage as much as
+    /// possible.
+    pub fn shrink_to_fit(&mut self) {
+        unsafe { self.inner.shrink_to_fit(&self.mask) };
+    }
+
+    /// Reserves memory for at least `additional` more components. See the
+    /// documentation of the storage for details.
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
+    }
+
+    /// Returns the memory usage of the storage.
+    pub fn stats(&self) -> ComponentStats {
+        ComponentStats {
+            name: type_name::<T>(),
+            len: (&self.mask).iter().count(),
+            capacity: self.inner.capacity(),
+            heap_bytes: self.inner.heap_bytes(),
+        }
     }
 
     /// Remove an element by a given index.
     pub fn remove(&mut self, index: Index) -> Option<T> {
-        if self.mask.remove(index) {
-            Some(unsafe { self.inner.remove(index) })
-        } else {
-            None
+        if !self.mask.remove(index) {
+            return None;
+        }
+
+        let component = unsafe { self.inner.remove(index) };
+
+        self.generation = next_generation();
+        self.hooks.removed(index, &component);
+
+        Some(component)
+    }
+
+    /// Drops the elements of all indices that are contained in the passed
+    /// mask. Indices without an element are skipped without looking at them
+    /// one by one.
+    pub fn drop_many<B>(&mut self, indices: B)
+    where
+        B: BitSetLike,
+    {
+        let dropped = BitSetAnd(&self.mask, indices).iter().collect::<Vec<_>>();
+        if !dropped.is_empty() {
+            self.generation = next_generation();
+        }
+
+        for index in dropped {
+            self.hooks.dropped(index, unsafe { self.inner.get(index) });
+            self.mask.remove(index);
+
+            unsafe { self.inner.drop(index) };
         }
     }
 
     /// Drop an element by a given index.
     pub fn drop(&mut self, index: Index) {
-        if self.mask.remove(index) {
+        if !self.mask.remove(index) {
+            return;
+        }
+
+        self.generation = next_generation();
+
+        if self.hooks.has_drop() {
+            let component = unsafe { self.inner.remove(index) };
+
+            self.hooks.dropped(index, &component);
+        } else {
             unsafe { self.inner.drop(index) };
         }
     }
 }
+
+fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
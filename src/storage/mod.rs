@@ -1,19 +1,43 @@
 mod anti_storage;
 mod btree_storage;
+mod default_vec_storage;
 mod dense_vec_storage;
 mod drain;
+mod entry;
+mod flagged_storage;
+mod group;
 mod hash_map_storage;
 mod masked_storage;
+mod null_storage;
+mod paged_storage;
+mod restrict;
+mod sparse_set_storage;
+mod stats;
 mod storage_wrapper;
+mod tracked_storage;
 mod vec_storage;
 
 pub use anti_storage::AntiStorage;
 pub use btree_storage::BTreeStorage;
+pub use default_vec_storage::DefaultVecStorage;
 pub use dense_vec_storage::DenseVecStorage;
-pub use drain::Drain;
+pub use drain::{Drain, DrainEntities};
+pub use entry::{OccupiedEntry, StorageEntry, VacantEntry};
+pub use flagged_storage::{
+    advance_tick, current_tick, ComponentEvent, FlaggedStorage, Tick, Tracked,
+};
+pub use group::{AnyGroup, Group, GroupAccess, GroupComponents, GroupIter, GroupJoin};
 pub use hash_map_storage::HashMapStorage;
 pub use masked_storage::MaskedStorage;
+pub use null_storage::NullStorage;
+pub use paged_storage::PagedStorage;
+pub use restrict::{
+    ImmutableRestriction, MutableParallelRestriction, PairedStorage, RestrictedStorage,
+};
+pub use sparse_set_storage::SparseSetStorage;
+pub use stats::{ComponentStats, StorageStats};
 pub use storage_wrapper::StorageWrapper;
+pub use tracked_storage::{ChangeMasks, TrackedStorage};
 pub use vec_storage::VecStorage;
 
 use hibitset::BitSetLike;
@@ -21,7 +45,7 @@ use hibitset::BitSetLike;
 use crate::{entity::Index, misc::TryDefault};
 
 /// Used by the framework to quickly join components.
-pub trait Storage<T>: TryDefault {
+pub trait Storage<T>: TryDefault + StorageStats {
     /// Tries reading the data associated with an `Index`.
     /// This is unsafe because the external set used
     /// to protect this storage is absent.
@@ -90,6 +114,40 @@ pub trait Storage<T>: TryDefault {
     unsafe fn drop(&mut self, index: Index) {
         self.remove(index);
     }
+
+    /// Shrinks the memory that is allocated by the storage as much as
+    /// possible. Defaults to doing nothing.
+    ///
+    /// # Safety
+    ///
+    /// May only be called with the mask which keeps track of the elements
+    /// existing in this storage.
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        let _has = has;
+    }
+
+    /// Reserves memory for at least `additional` more components. Defaults
+    /// to doing nothing.
+    fn reserve(&mut self, additional: usize) {
+        let _additional = additional;
+    }
+
+    /// Called by `World::maintain` before the lazy updates are applied.
+    /// Storages can use this to reset per frame state. Defaults to doing
+    /// nothing.
+    fn maintain(&mut self) {}
+}
+
+/// Returns the length a vector indexed by the indices of the passed mask
+/// needs, to contain all of the indices.
+pub(crate) fn mask_len<B>(mask: B) -> usize
+where
+    B: BitSetLike,
+{
+    mask.iter().last().map_or(0, |index| index as usize + 1)
 }
 
 /// This is a marker trait which requires you to uphold the following guarantee:
@@ -113,3 +171,25 @@ pub trait Storage<T>: TryDefault {
 /// Implementing this trait marks the storage safe for concurrent mutation (of
 /// distinct elements), thus allows `join_par()`.
 pub trait DistinctStorage {}
+
+/// Allows direct access to the inner data of a storage as slice.
+///
+/// The indices of the slice are storage specific, please refer to the
+/// documentation of the storage to see how they map to entities.
+pub trait SliceAccess<T> {
+    /// Type of the elements of the slice.
+    type Element;
+
+    /// Returns the inner data of the storage as slice.
+    fn as_slice(&self) -> &[Self::Element];
+
+    /// Returns the inner data of the storage as mutable slice.
+    ///
+    /// ## Safety
+    ///
+    /// The elements of entities that own the component must stay valid
+    /// components. This is only a concern for storages whose elements may
+    /// be uninitialized (like `VecStorage`), storages with `Element = T`
+    /// can not be corrupted by writing to the slice.
+    unsafe fn as_mut_slice(&mut self) -> &mut [Self::Element];
+}
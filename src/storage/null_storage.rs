@@ -0,0 +1,175 @@
+use std::mem::size_of;
+use std::ptr::{read, NonNull};
+
+use hibitset::BitSetLike;
+
+use crate::entity::Index;
+
+use super::{DistinctStorage, Storage, StorageStats};
+
+/// A null storage type, used for cases where the component
+/// doesn't contain any data and instead works as a simple flag.
+///
+/// Only the mask of the storage is used to track which entities own the
+/// component, so no memory is allocated per entity.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::*;
+///
+/// #[derive(Default)]
+/// struct Tag;
+///
+/// impl Component for Tag {
+///     type Storage = NullStorage<Self>;
+/// }
+///
+/// let mut world = World::default();
+/// world.register_component::<Tag>();
+///
+/// let tagged = world.create_entity().with(Tag).build();
+/// let _untagged = world.create_entity().build();
+///
+/// let entities = world.entities();
+/// let tags = world.component::<Tag>();
+///
+/// let found: Vec<_> = (&entities, &tags).join().map(|(e, _)| e).collect();
+/// assert_eq!(found, vec![tagged]);
+/// ```
+pub struct NullStorage<T>(T);
+
+impl<T> Default for NullStorage<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        assert_eq!(
+            size_of::<T>(),
+            0,
+            "NullStorage can only be used with zero-sized types"
+        );
+
+        Self(Default::default())
+    }
+}
+
+// zero-sized components never need any memory, so the defaults are used
+impl<T> StorageStats for NullStorage<T> {}
+
+impl<T> Storage<T> for NullStorage<T>
+where
+    T: Default,
+{
+    unsafe fn get(&self, _index: Index) -> &T {
+        &self.0
+    }
+
+    unsafe fn get_mut(&mut self, _index: Index) -> &mut T {
+        // zero-sized types do not need a valid address, so every entity can
+        // get its own mutable reference (even from different threads)
+        &mut *NonNull::dangling().as_ptr()
+    }
+
+    unsafe fn insert(&mut self, _index: Index, _value: T) {}
+
+    unsafe fn remove(&mut self, _index: Index) -> T {
+        read(&self.0)
+    }
+
+    unsafe fn clean<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+    }
+}
+
+impl<T> DistinctStorage for NullStorage<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        access::WriteStorage,
+        component::Component,
+        entity::Builder,
+        join::{ChangeTracker, Join, ParJoin},
+        storage::FlaggedStorage,
+        system::SystemData,
+        world::World,
+    };
+
+    use asparit::{Driver, ParallelIterator};
+
+    #[derive(Default, Debug, PartialEq)]
+    struct Tag;
+
+    impl Component for Tag {
+        type Storage = NullStorage<Self>;
+    }
+
+    #[derive(Default)]
+    struct FlaggedTag;
+
+    impl Component for FlaggedTag {
+        type Storage = FlaggedStorage<Self, NullStorage<Self>>;
+    }
+
+    #[test]
+    #[should_panic]
+    fn non_zero_sized() {
+        let _storage = NullStorage::<u32>::default();
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut world = World::default();
+        world.register_component::<Tag>();
+
+        let e1 = world.create_entity().with(Tag).build();
+        let e2 = world.create_entity().build();
+        let e3 = world.create_entity().with(Tag).build();
+
+        let mut tags = WriteStorage::<Tag>::fetch(&world);
+        assert_eq!(tags.get(e1), Some(&Tag));
+        assert_eq!(tags.get(e2), None);
+        assert_eq!(tags.count(), 2);
+
+        assert_eq!(tags.remove(e1), Some(Tag));
+        assert_eq!(tags.insert(e2, Tag).unwrap(), None);
+
+        let entities = world.entities();
+        let tagged: Vec<_> = (&entities, &tags).join().map(|(e, _)| e).collect();
+        assert_eq!(tagged, vec![e2, e3]);
+
+        (&mut tags).par_join().for_each(|_tag| ()).exec();
+    }
+
+    #[test]
+    fn flagged() {
+        let mut world = World::default();
+        world.register_component::<FlaggedTag>();
+
+        let mut tracker = ChangeTracker::new();
+
+        let e1 = world.create_entity().with(FlaggedTag).build();
+        let e2 = world.create_entity().with(FlaggedTag).build();
+
+        let mut changed = |world: &World| {
+            let entities = world.entities();
+            let tags = world.component::<FlaggedTag>();
+
+            (&entities, tags.changed_since(&mut tracker))
+                .join()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(changed(&world), vec![e1, e2]);
+
+        world.component_mut::<FlaggedTag>().get_mut(e2).unwrap();
+
+        assert_eq!(changed(&world), vec![e2]);
+    }
+}
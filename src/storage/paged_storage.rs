@@ -0,0 +1,275 @@
+use std::mem::{size_of, MaybeUninit};
+use std::ptr::{drop_in_place, read};
+
+use hibitset::BitSetLike;
+
+use crate::entity::Index;
+
+use super::{DistinctStorage, Storage, StorageStats};
+
+/// Number of bits of the index that select the slot within a page.
+const PAGE_BITS: u32 = 6;
+
+/// Number of components that are stored in a single page.
+const PAGE_SIZE: usize = 1 << PAGE_BITS;
+
+/// Paged storage. Splits the index space into fixed-size blocks of
+/// `PAGE_SIZE` entities and only allocates a page for blocks that contain
+/// at least one component.
+///
+/// Components of entities with neighbouring indices are stored next to each
+/// other, so joins over components that are sparse but clustered (like
+/// components that are added to groups of entities created together) have
+/// a much better iteration locality than with the `BTreeStorage` or the
+/// `HashMapStorage`, without wasting the memory of a `VecStorage` for the
+/// unused index ranges. Pages that become empty are freed again.
+pub struct PagedStorage<T> {
+    pages: Vec<Option<Box<Page<T>>>>,
+    page_count: usize,
+}
+
+impl<T> PagedStorage<T> {
+    /// Returns the number of pages that are currently allocated.
+    pub fn page_count(&self) -> usize {
+        self.page_count
+    }
+
+    /// Returns the number of components a single page can store.
+    pub fn page_size(&self) -> usize {
+        PAGE_SIZE
+    }
+
+    unsafe fn page(&self, index: Index) -> &Page<T> {
+        match self.pages.get_unchecked(page_index(index)) {
+            Some(page) => page,
+            None => std::hint::unreachable_unchecked(),
+        }
+    }
+
+    unsafe fn page_mut(&mut self, index: Index) -> &mut Page<T> {
+        match self.pages.get_unchecked_mut(page_index(index)) {
+            Some(page) => page,
+            None => std::hint::unreachable_unchecked(),
+        }
+    }
+}
+
+impl<T> Default for PagedStorage<T> {
+    fn default() -> Self {
+        Self {
+            pages: Vec::new(),
+            page_count: 0,
+        }
+    }
+}
+
+impl<T> Storage<T> for PagedStorage<T> {
+    unsafe fn get(&self, index: Index) -> &T {
+        self.page(index).get(slot_index(index))
+    }
+
+    unsafe fn get_mut(&mut self, index: Index) -> &mut T {
+        self.page_mut(index).get_mut(slot_index(index))
+    }
+
+    unsafe fn insert(&mut self, index: Index, value: T) {
+        let page = page_index(index);
+
+        if self.pages.len() <= page {
+            self.pages.resize_with(page + 1, || None);
+        }
+
+        let page = self.pages.get_unchecked_mut(page);
+        let page = match page {
+            Some(page) => page,
+            None => {
+                self.page_count += 1;
+
+                page.insert(Page::new())
+            }
+        };
+
+        page.insert(slot_index(index), value);
+    }
+
+    unsafe fn remove(&mut self, index: Index) -> T {
+        let page = self.pages.get_unchecked_mut(page_index(index));
+        let value = match page {
+            Some(page) => page.remove(slot_index(index)),
+            None => std::hint::unreachable_unchecked(),
+        };
+
+        if page.as_ref().is_some_and(|page| page.is_empty()) {
+            *page = None;
+
+            self.page_count -= 1;
+        }
+
+        value
+    }
+
+    unsafe fn clean<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        self.pages.clear();
+        self.page_count = 0;
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        // Empty pages are already freed, so only the trailing slots of the
+        // page table are left.
+        let len = self
+            .pages
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |page| page + 1);
+
+        self.pages.truncate(len);
+        self.pages.shrink_to_fit();
+    }
+}
+
+impl<T> StorageStats for PagedStorage<T> {
+    fn capacity(&self) -> usize {
+        self.page_count * PAGE_SIZE
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.page_count * size_of::<Page<T>>()
+            + self.pages.capacity() * size_of::<Option<Box<Page<T>>>>()
+    }
+}
+
+impl<T> DistinctStorage for PagedStorage<T> {}
+
+/* Page */
+
+/// Single page of the `PagedStorage`. Keeps track of the occupied slots, so
+/// the stored components can be dropped with the page.
+struct Page<T> {
+    occupied: u64,
+    data: [MaybeUninit<T>; PAGE_SIZE],
+}
+
+impl<T> Page<T> {
+    fn new() -> Box<Self> {
+        Box::new(Self {
+            occupied: 0,
+            data: [(); PAGE_SIZE].map(|_| MaybeUninit::uninit()),
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.occupied == 0
+    }
+
+    unsafe fn get(&self, slot: usize) -> &T {
+        &*self.data.get_unchecked(slot).as_ptr()
+    }
+
+    unsafe fn get_mut(&mut self, slot: usize) -> &mut T {
+        &mut *self.data.get_unchecked_mut(slot).as_mut_ptr()
+    }
+
+    unsafe fn insert(&mut self, slot: usize, value: T) {
+        self.occupied |= 1 << slot;
+
+        *self.data.get_unchecked_mut(slot) = MaybeUninit::new(value);
+    }
+
+    unsafe fn remove(&mut self, slot: usize) -> T {
+        self.occupied &= !(1 << slot);
+
+        read(self.get(slot))
+    }
+}
+
+impl<T> Drop for Page<T> {
+    fn drop(&mut self) {
+        let mut occupied = self.occupied;
+
+        while occupied != 0 {
+            let slot = occupied.trailing_zeros() as usize;
+            occupied &= occupied - 1;
+
+            unsafe { drop_in_place(self.get_mut(slot)) };
+        }
+    }
+}
+
+#[inline]
+fn page_index(index: Index) -> usize {
+    (index >> PAGE_BITS) as usize
+}
+
+#[inline]
+fn slot_index(index: Index) -> usize {
+    index as usize & (PAGE_SIZE - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::{component::Component, entity::Builder, join::Join, world::World};
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = PagedStorage<Self>;
+    }
+
+    #[test]
+    fn pages() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let entities = (0..100)
+            .map(|i| world.create_entity().with(Pos(i)).build())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            world.component::<Pos>().unprotected_storage().page_count(),
+            2
+        );
+
+        let mut pos = world.component_mut::<Pos>();
+        for entity in &entities[..PAGE_SIZE] {
+            pos.remove(*entity);
+        }
+
+        assert_eq!(pos.unprotected_storage().page_count(), 1);
+        assert_eq!(
+            (&pos).join().map(|p| p.0).collect::<Vec<_>>(),
+            (PAGE_SIZE as u32..100).collect::<Vec<_>>()
+        );
+        assert_eq!(pos.get(entities[99]), Some(&Pos(99)));
+        assert_eq!(pos.get(entities[0]), None);
+    }
+
+    #[test]
+    fn drop_components() {
+        let value = Arc::new(());
+
+        let mut storage = PagedStorage::default();
+        unsafe {
+            storage.insert(3, value.clone());
+            storage.insert(200, value.clone());
+            storage.insert(201, value.clone());
+            drop(storage.remove(201));
+        }
+
+        assert_eq!(Arc::strong_count(&value), 3);
+
+        drop(storage);
+
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,279 @@
+use std::marker::PhantomData;
+
+use hibitset::BitSet;
+
+use crate::{
+    component::Component,
+    entity::{Entities, Entity, Index},
+    join::{Join, ParJoin},
+};
+
+use super::{DistinctStorage, Storage};
+
+/// Restriction of a `RestrictedStorage` that only allows immutable access
+/// to the components. Can be used with `join` and `par_join`.
+pub struct ImmutableRestriction;
+
+/// Restriction of a `RestrictedStorage` that allows mutable access to the
+/// component of the current entity only. Can be used with `join` and
+/// `par_join`.
+pub struct MutableParallelRestriction;
+
+/// Storage that yields a `PairedStorage` for each joined entity, instead of
+/// the component itself. This allows to decide per entity whether and how
+/// the component is accessed.
+///
+/// Use `StorageWrapper::restrict` or `StorageWrapper::par_restrict_mut` to
+/// create a restricted storage.
+pub struct RestrictedStorage<'a, C: Component, R> {
+    mask: &'a BitSet,
+    storage: *mut C::Storage,
+    entities: &'a Entities,
+    marker: PhantomData<(&'a mut C::Storage, R)>,
+}
+
+impl<'a, C> RestrictedStorage<'a, C, ImmutableRestriction>
+where
+    C: Component,
+{
+    pub(crate) fn new(mask: &'a BitSet, storage: &'a C::Storage, entities: &'a Entities) -> Self {
+        Self {
+            mask,
+            // never written to, because of the immutable restriction
+            storage: storage as *const C::Storage as *mut C::Storage,
+            entities,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, C> RestrictedStorage<'a, C, MutableParallelRestriction>
+where
+    C: Component,
+{
+    pub(crate) fn new_mut(
+        mask: &'a BitSet,
+        storage: &'a mut C::Storage,
+        entities: &'a Entities,
+    ) -> Self {
+        Self {
+            mask,
+            storage,
+            entities,
+            marker: PhantomData,
+        }
+    }
+}
+
+unsafe impl<'a, C, R> Send for RestrictedStorage<'a, C, R>
+where
+    C: Component,
+    C::Storage: Send,
+{
+}
+
+unsafe impl<'a, C, R> Sync for RestrictedStorage<'a, C, R>
+where
+    C: Component,
+    C::Storage: Sync,
+{
+}
+
+impl<'a, 'j, C> Join for &'j RestrictedStorage<'a, C, ImmutableRestriction>
+where
+    C: Component,
+{
+    type Mask = &'j BitSet;
+    type Type = PairedStorage<'j, C, ImmutableRestriction>;
+    type Value = Self;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (self.mask, self)
+    }
+
+    unsafe fn get(v: &mut Self::Value, index: Index) -> Self::Type {
+        PairedStorage::new(index, v)
+    }
+}
+
+impl<'a, 'j, C> ParJoin for &'j RestrictedStorage<'a, C, ImmutableRestriction>
+where
+    C: Component,
+    C::Storage: Sync,
+{
+}
+
+impl<'a, 'j, C> Join for &'j mut RestrictedStorage<'a, C, MutableParallelRestriction>
+where
+    C: Component,
+{
+    type Mask = &'j BitSet;
+    type Type = PairedStorage<'j, C, MutableParallelRestriction>;
+    type Value = &'j RestrictedStorage<'a, C, MutableParallelRestriction>;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (self.mask, self)
+    }
+
+    unsafe fn get(v: &mut Self::Value, index: Index) -> Self::Type {
+        PairedStorage::new(index, v)
+    }
+}
+
+impl<'a, 'j, C> ParJoin for &'j mut RestrictedStorage<'a, C, MutableParallelRestriction>
+where
+    C: Component,
+    C::Storage: Sync + DistinctStorage,
+{
+}
+
+/* PairedStorage */
+
+/// Access to the component of a single entity of a `RestrictedStorage`.
+pub struct PairedStorage<'a, C: Component, R> {
+    index: Index,
+    storage: *mut C::Storage,
+    entities: &'a Entities,
+    mask: &'a BitSet,
+    marker: PhantomData<(&'a mut C, R)>,
+}
+
+impl<'a, C, R> PairedStorage<'a, C, R>
+where
+    C: Component,
+{
+    fn new<'b>(index: Index, storage: &RestrictedStorage<'b, C, R>) -> Self
+    where
+        'b: 'a,
+    {
+        Self {
+            index,
+            storage: storage.storage,
+            entities: storage.entities,
+            mask: storage.mask,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the entity this component belongs to.
+    pub fn entity(&self) -> Entity {
+        let mut entities = self.entities;
+
+        unsafe { <&Entities as Join>::get(&mut entities, self.index) }
+    }
+}
+
+impl<'a, C> PairedStorage<'a, C, ImmutableRestriction>
+where
+    C: Component,
+{
+    /// Returns the component of the current entity.
+    pub fn get_unchecked(&self) -> &'a C {
+        unsafe { (*self.storage).get(self.index) }
+    }
+
+    /// Returns the component of the passed entity, if it has one and is
+    /// alive.
+    pub fn get(&self, entity: Entity) -> Option<&'a C> {
+        if self.mask.contains(entity.index()) && self.entities.is_alive(entity) {
+            Some(unsafe { (*self.storage).get(entity.index()) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, C> PairedStorage<'a, C, MutableParallelRestriction>
+where
+    C: Component,
+{
+    /// Returns the component of the current entity.
+    pub fn get_unchecked(&self) -> &C {
+        unsafe { (*self.storage).get(self.index) }
+    }
+
+    /// Returns the component of the current entity mutably.
+    ///
+    /// Please note that storages like the `FlaggedStorage` treat this as
+    /// a modification, while `get_unchecked` is not.
+    pub fn get_mut_unchecked(&mut self) -> &mut C {
+        // Each paired storage of a mutable restriction has a distinct index,
+        // so the returned references never alias.
+        unsafe { (*self.storage).get_mut(self.index) }
+    }
+}
+
+unsafe impl<'a, C, R> Send for PairedStorage<'a, C, R>
+where
+    C: Component,
+    C::Storage: Sync,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{entity::Builder, storage::VecStorage, world::World};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    struct Target(Entity);
+
+    impl Component for Target {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn restrict() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Target>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).with(Target(e1)).build();
+        let e3 = world.create_entity().with(Target(e2)).build();
+
+        let pos = world.component::<Pos>();
+        let targets = world.component::<Target>();
+        let restricted = pos.restrict();
+
+        let found = (&restricted, &targets)
+            .join()
+            .map(|(pos, target)| (pos.entity(), pos.get_unchecked().0, pos.get(target.0)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(found, vec![(e2, 2, Some(&Pos(1)))]);
+        assert!((&restricted).join().all(|pos| pos.get(e3).is_none()));
+    }
+
+    #[test]
+    fn par_restrict_mut() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let entities = (0..4)
+            .map(|i| world.create_entity().with(Pos(i)).build())
+            .collect::<Vec<_>>();
+
+        let mut pos = world.component_mut::<Pos>();
+
+        for mut pos in (&mut pos.par_restrict_mut()).join() {
+            if pos.get_unchecked().0 % 2 == 1 {
+                pos.get_mut_unchecked().0 *= 10;
+            }
+        }
+
+        let values = entities
+            .iter()
+            .map(|e| pos.get(*e).unwrap().0)
+            .collect::<Vec<_>>();
+
+        assert_eq!(values, vec![0, 10, 2, 30]);
+    }
+}
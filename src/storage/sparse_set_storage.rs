@@ -0,0 +1,283 @@
+use std::mem::size_of;
+
+use hibitset::BitSetLike;
+
+use crate::{entity::Index, storage::Storage};
+
+use super::{mask_len, DistinctStorage, SliceAccess, StorageStats};
+
+/// Marks an index of the sparse array that has no component.
+const EMPTY: Index = Index::MAX;
+
+/// Sparse set storage. Stores the components and the indices of their
+/// entities in two densely packed arrays, and the position of each entity
+/// in a sparse array.
+///
+/// The dense arrays can be used to iterate over all components without
+/// consulting the bit set of the storage: `as_slice()` and `as_mut_slice()`
+/// return the components, `indices()` returns the index of the entity that
+/// owns the component at the same position.
+///
+/// Components are removed in constant time by moving the last component
+/// into the freed slot, so the position of a component within the slice
+/// may change with each removal.
+pub struct SparseSetStorage<T> {
+    sparse: Vec<Index>,
+    dense: Vec<Index>,
+    data: Vec<T>,
+    version: u64,
+}
+
+impl<T> SparseSetStorage<T> {
+    /// Returns the indices of the entities that own the components, in the
+    /// same order as the components returned by `as_slice()`.
+    pub fn indices(&self) -> &[Index] {
+        &self.dense
+    }
+
+    /// Returns an iterator over the indices of the entities and their
+    /// components.
+    pub fn iter(&self) -> impl Iterator<Item = (Index, &T)> {
+        self.dense.iter().copied().zip(self.data.iter())
+    }
+
+    /// Returns an iterator over the indices of the entities and their
+    /// components, which yields the components mutably.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Index, &mut T)> {
+        self.dense.iter().copied().zip(self.data.iter_mut())
+    }
+
+    /// Returns the position of the component of the entity with the passed
+    /// index within the dense arrays, or `None` if the entity has no
+    /// component.
+    pub fn position(&self, index: Index) -> Option<usize> {
+        match self.sparse.get(index as usize) {
+            Some(&pos) if pos != EMPTY => Some(pos as usize),
+            _ => None,
+        }
+    }
+
+    /// Swaps the components at the passed positions of the dense arrays.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the positions is out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+
+        self.dense.swap(a, b);
+        self.data.swap(a, b);
+
+        self.sparse[self.dense[a] as usize] = a as Index;
+        self.sparse[self.dense[b] as usize] = b as Index;
+
+        self.version += 1;
+    }
+
+    /// Returns a counter that is incremented whenever components are
+    /// inserted, removed or moved within the dense arrays.
+    ///
+    /// As long as the version did not change, the positions of all
+    /// components are the same.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the component at the passed position of the dense arrays.
+    ///
+    /// # Safety
+    ///
+    /// The position must be in bounds.
+    pub(crate) unsafe fn get_dense(&self, pos: usize) -> &T {
+        self.data.get_unchecked(pos)
+    }
+
+    /// Returns a pointer to the component at the passed position of the
+    /// dense arrays.
+    ///
+    /// # Safety
+    ///
+    /// The position must be in bounds.
+    pub(crate) unsafe fn get_dense_mut(&mut self, pos: usize) -> *mut T {
+        self.data.as_mut_ptr().add(pos)
+    }
+}
+
+impl<T> Default for SparseSetStorage<T> {
+    fn default() -> Self {
+        Self {
+            sparse: Default::default(),
+            dense: Default::default(),
+            data: Default::default(),
+            version: 0,
+        }
+    }
+}
+
+impl<T> Storage<T> for SparseSetStorage<T> {
+    unsafe fn get(&self, index: Index) -> &T {
+        let pos = *self.sparse.get_unchecked(index as usize);
+
+        self.data.get_unchecked(pos as usize)
+    }
+
+    unsafe fn get_mut(&mut self, index: Index) -> &mut T {
+        let pos = *self.sparse.get_unchecked(index as usize);
+
+        self.data.get_unchecked_mut(pos as usize)
+    }
+
+    unsafe fn insert(&mut self, index: Index, v: T) {
+        let index = index as usize;
+
+        if self.sparse.len() <= index {
+            self.sparse.resize(index + 1, EMPTY);
+        }
+
+        *self.sparse.get_unchecked_mut(index) = self.data.len() as Index;
+        self.dense.push(index as Index);
+        self.data.push(v);
+        self.version += 1;
+    }
+
+    unsafe fn remove(&mut self, index: Index) -> T {
+        let pos = *self.sparse.get_unchecked(index as usize);
+
+        *self.sparse.get_unchecked_mut(index as usize) = EMPTY;
+
+        self.dense.swap_remove(pos as usize);
+        if let Some(moved) = self.dense.get(pos as usize) {
+            *self.sparse.get_unchecked_mut(*moved as usize) = pos;
+        }
+
+        self.version += 1;
+
+        self.data.swap_remove(pos as usize)
+    }
+
+    unsafe fn clean<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        // All components are dropped, so the dense arrays do not keep
+        // components that are no longer part of the mask.
+        self.sparse.clear();
+        self.dense.clear();
+        self.data.clear();
+        self.version += 1;
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.sparse.truncate(mask_len(has));
+        self.sparse.shrink_to_fit();
+        self.dense.shrink_to_fit();
+        self.data.shrink_to_fit();
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.dense.reserve(additional);
+        self.data.reserve(additional);
+    }
+}
+
+impl<T> StorageStats for SparseSetStorage<T> {
+    fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.data.capacity() * size_of::<T>()
+            + (self.sparse.capacity() + self.dense.capacity()) * size_of::<Index>()
+    }
+}
+
+impl<T> SliceAccess<T> for SparseSetStorage<T> {
+    type Element = T;
+
+    /// Returns the densely packed components as slice. The position of the
+    /// component of a specific entity may change with each removal.
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        self.data.as_slice()
+    }
+
+    /// Returns the densely packed components as mutable slice. The position
+    /// of the component of a specific entity may change with each removal.
+    #[inline]
+    unsafe fn as_mut_slice(&mut self) -> &mut [T] {
+        self.data.as_mut_slice()
+    }
+}
+
+impl<T> DistinctStorage for SparseSetStorage<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{component::Component, entity::Builder, join::Join, world::World};
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = SparseSetStorage<Self>;
+    }
+
+    #[test]
+    fn dense_arrays() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+        let e3 = world.create_entity().with(Pos(3)).build();
+
+        assert_eq!(world.component_mut::<Pos>().remove(e1), Some(Pos(1)));
+
+        let pos = world.component::<Pos>();
+        let storage = pos.unprotected_storage();
+
+        assert_eq!(pos.as_slice(), &[Pos(3), Pos(2)]);
+        assert_eq!(storage.indices(), &[e3.index(), e2.index()]);
+        assert_eq!(pos.get(e1), None);
+        assert_eq!(pos.get(e2), Some(&Pos(2)));
+        assert_eq!(pos.get(e3), Some(&Pos(3)));
+
+        let mut items = storage.iter().collect::<Vec<_>>();
+        items.sort_by_key(|(index, _)| *index);
+
+        assert_eq!(
+            items,
+            (&world.entities(), &pos)
+                .join()
+                .map(|(e, p)| (e.index(), p))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn clean_drops_components() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        world.create_entity().with(Pos(2)).build();
+
+        let mut pos = world.component_mut::<Pos>();
+        pos.clear();
+
+        assert!(pos.as_slice().is_empty());
+        assert!(pos.unprotected_storage().indices().is_empty());
+
+        pos.insert(e1, Pos(3)).unwrap();
+
+        assert_eq!(pos.as_slice(), &[Pos(3)]);
+        assert_eq!(pos.get(e1), Some(&Pos(3)));
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Reports the memory usage of a storage. This is implemented by all
+/// storages, so the memory usage of all components can be inspected at
+/// runtime using `World::storage_stats`.
+///
+/// Both methods have default implementations that report no memory usage,
+/// so custom storages that do not want to report any statistics only need
+/// an empty `impl StorageStats for MyStorage {}`.
+pub trait StorageStats {
+    /// Returns the number of components the storage can hold without
+    /// allocating more memory.
+    fn capacity(&self) -> usize {
+        0
+    }
+
+    /// Returns the approximate number of bytes the storage has allocated on
+    /// the heap.
+    fn heap_bytes(&self) -> usize {
+        0
+    }
+}
+
+/// Memory usage of the storage of a single component type, returned by
+/// `MaskedStorage::stats` and `World::storage_stats`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComponentStats {
+    /// Type name of the component.
+    pub name: &'static str,
+
+    /// Number of stored components.
+    pub len: usize,
+
+    /// Number of components the storage can hold without allocating more
+    /// memory.
+    pub capacity: usize,
+
+    /// Approximate number of bytes the storage has allocated on the heap.
+    pub heap_bytes: usize,
+}
+
+impl Display for ComponentStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}: {} components, capacity {}, {} bytes",
+            self.name, self.len, self.capacity, self.heap_bytes
+        )
+    }
+}
@@ -1,18 +1,24 @@
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut, Not};
+use std::sync::MutexGuard;
 
-use hibitset::{BitSet, BitSetLike};
+use hibitset::{AtomicBitSet, BitSet, BitSetLike};
 
 use crate::{
     component::Component,
     entity::{Entities, Entity, Index},
     error::Error,
-    join::{Join, ParJoin},
+    event::{EventChannel, ReaderId},
+    join::{ChangeTracker, ChangedSince, Join, JoinIter, JoinParIter, MaskGeneration, ParJoin},
     resource::Ref,
     storage::MaskedStorage,
 };
 
-use super::{AntiStorage, DistinctStorage, Drain, Storage};
+use super::{
+    AntiStorage, ChangeMasks, ComponentEvent, DistinctStorage, Drain, DrainEntities,
+    ImmutableRestriction, MutableParallelRestriction, RestrictedStorage, SliceAccess, Storage,
+    StorageEntry, Tracked,
+};
 
 /// A wrapper around the masked storage and the generations vector.
 /// Can be used for safe lookup of components, insertions and removes.
@@ -46,6 +52,15 @@ where
         &self.entities
     }
 
+    /// Returns the inner storage of the components. This allows to use
+    /// methods of specific storages (like `SparseSetStorage::indices`).
+    ///
+    /// Please note that the storage does not check if the accessed indices
+    /// contain a component, use the mask of this storage to verify this.
+    pub fn unprotected_storage(&self) -> &T::Storage {
+        self.data.storage()
+    }
+
     /// Tries to read the data associated with an `Entity`.
     pub fn get(&self, e: Entity) -> Option<&T> {
         let index = e.index();
@@ -86,6 +101,160 @@ where
     pub fn not(&self) -> AntiStorage<'_> {
         AntiStorage(&self.data.mask())
     }
+
+    /// Returns an iterator over the components of this storage, that yields
+    /// the entity of each component together with the component.
+    ///
+    /// This is the same as joining the fetched entities together with the
+    /// storage: `(&entities, &storage).join()`.
+    pub fn join_with_entities(&self) -> JoinIter<(&Entities, &Self)> {
+        (self.fetched_entities(), self).join()
+    }
+
+    /// Returns a parallel iterator over the components of this storage, that
+    /// yields the entity of each component together with the component.
+    ///
+    /// This is the same as joining the fetched entities together with the
+    /// storage: `(&entities, &storage).par_join()`.
+    pub fn par_join_with_entities(&self) -> JoinParIter<(&Entities, &Self)>
+    where
+        T::Storage: Sync,
+    {
+        (self.fetched_entities(), self).par_join()
+    }
+
+    /// Returns the inner data of the storage as slice. This allows fast
+    /// linear passes over the component data.
+    ///
+    /// See the documentation of the used storage to find out how the
+    /// indices of the slice map to entities.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// # struct Mass(f32);
+    /// # impl Component for Mass { type Storage = DenseVecStorage<Self>; }
+    /// #
+    /// let mut world = World::default();
+    /// world.register_component::<Mass>();
+    ///
+    /// world.create_entity().with(Mass(1.0)).build();
+    /// world.create_entity().with(Mass(2.5)).build();
+    ///
+    /// let mass = world.component::<Mass>();
+    /// let total: f32 = mass.as_slice().iter().map(|m| m.0).sum();
+    /// assert_eq!(total, 3.5);
+    /// ```
+    pub fn as_slice(&self) -> &[<T::Storage as SliceAccess<T>>::Element]
+    where
+        T::Storage: SliceAccess<T>,
+    {
+        self.data.storage().as_slice()
+    }
+
+    /// Returns a `Join`-able structure that yields a `PairedStorage` for
+    /// each component, instead of the component itself. The paired storage
+    /// gives access to the component of the current entity and to the
+    /// components of all other entities.
+    ///
+    /// The restricted storage can be joined in parallel.
+    pub fn restrict(&self) -> RestrictedStorage<'_, T, ImmutableRestriction> {
+        RestrictedStorage::new(self.data.mask(), self.data.storage(), &self.entities)
+    }
+
+    /// Returns a `Join`-able structure that only yields the components that
+    /// were changed since the passed `tracker` has seen them the last time.
+    ///
+    /// The tracker is advanced as soon as the returned structure is joined.
+    /// Removed components are never yielded.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Pos(u32);
+    /// # impl Component for Pos { type Storage = FlaggedStorage<Self, VecStorage<Self>>; }
+    /// #
+    /// let mut world = World::default();
+    /// world.register_component::<Pos>();
+    ///
+    /// let mut tracker = ChangeTracker::new();
+    ///
+    /// world.create_entity().with(Pos(1)).build();
+    /// let e2 = world.create_entity().with(Pos(2)).build();
+    ///
+    /// {
+    ///     let pos = world.component::<Pos>();
+    ///     let changed: Vec<_> = pos.changed_since(&mut tracker).join().collect();
+    ///     assert_eq!(changed, vec![&Pos(1), &Pos(2)]);
+    /// }
+    ///
+    /// world.component_mut::<Pos>().get_mut(e2).unwrap().0 = 3;
+    ///
+    /// {
+    ///     let pos = world.component::<Pos>();
+    ///     let changed: Vec<_> = pos.changed_since(&mut tracker).join().collect();
+    ///     assert_eq!(changed, vec![&Pos(3)]);
+    /// }
+    /// ```
+    pub fn changed_since<'t>(&self, tracker: &'t mut ChangeTracker) -> ChangedSince<'t, &Self>
+    where
+        T::Storage: Tracked,
+    {
+        ChangedSince::new(self, tracker)
+    }
+
+    /// Registers a new reader for the `ComponentEvent`s of this storage.
+    pub fn register_reader(&self) -> ReaderId<ComponentEvent>
+    where
+        T::Storage: Tracked,
+    {
+        self.data.storage().register_reader()
+    }
+
+    /// Returns the channel the `ComponentEvent`s of this storage are written
+    /// to. Use `EventChannel::read` to read the events of a registered
+    /// reader.
+    ///
+    /// Please note that the channel is locked as long as the returned guard
+    /// is alive, so modifying the storage in the meantime will deadlock.
+    pub fn channel(&self) -> MutexGuard<'_, EventChannel<ComponentEvent>>
+    where
+        T::Storage: Tracked,
+    {
+        self.data.storage().channel()
+    }
+
+    /// Returns the indices of the components that were inserted since the
+    /// last `World::maintain`. See `TrackedStorage` for details.
+    pub fn inserted(&self) -> &BitSet
+    where
+        T::Storage: ChangeMasks,
+    {
+        self.data.storage().inserted()
+    }
+
+    /// Returns the indices of the components that were accessed mutably
+    /// since the last `World::maintain`. See `TrackedStorage` for details.
+    pub fn modified(&self) -> &AtomicBitSet
+    where
+        T::Storage: ChangeMasks,
+    {
+        self.data.storage().modified()
+    }
+
+    /// Returns the indices of the components that were removed since the
+    /// last `World::maintain`. See `TrackedStorage` for details.
+    pub fn removed(&self) -> &BitSet
+    where
+        T::Storage: ChangeMasks,
+    {
+        self.data.storage().removed()
+    }
 }
 
 impl<'a, T, D> StorageWrapper<'a, T, D>
@@ -104,6 +273,46 @@ where
         }
     }
 
+    /// Returns the component of the passed entity mutably. If the entity has
+    /// no component yet, the default value is inserted first.
+    ///
+    /// Returns `None` if the entity is not alive.
+    pub fn get_mut_or_default(&mut self, e: Entity) -> Option<&mut T>
+    where
+        T: Default,
+    {
+        self.entry(e).ok().map(StorageEntry::or_default)
+    }
+
+    /// Tries to mutate the data associated with multiple entities at once.
+    ///
+    /// Returns `None` if one of the entities is not alive, has no component
+    /// in this storage, or if the same entity is passed more than once.
+    ///
+    /// This is only available for storages that implement `DistinctStorage`,
+    /// because only these guarantee that the components of different
+    /// indices do not alias.
+    pub fn get_many_mut<const N: usize>(&mut self, entities: [Entity; N]) -> Option<[&mut T; N]>
+    where
+        T::Storage: DistinctStorage,
+    {
+        for (i, e) in entities.iter().enumerate() {
+            if !self.data.mask().contains(e.index()) || !self.entities.is_alive(*e) {
+                return None;
+            }
+
+            if entities[..i].iter().any(|other| other.index() == e.index()) {
+                return None;
+            }
+        }
+
+        let storage: *mut T::Storage = self.data.storage_mut();
+
+        // SAFETY: All indices are distinct and contained in the mask, and the
+        // storage is distinct, so the returned references do not alias.
+        Some(entities.map(|e| unsafe { (*storage).get_mut(e.index()) }))
+    }
+
     /// Inserts new data for a given `Entity`.
     /// Returns the result of the operation as a `InsertResult<T>`
     ///
@@ -118,6 +327,67 @@ where
         Ok(self.data.insert(entity, component))
     }
 
+    /// Inserts a copy of the component of `source` for `target`, replacing
+    /// the component `target` had before.
+    ///
+    /// Returns `false` if `source` has no component.
+    pub fn clone_from_entity(&mut self, source: Entity, target: Entity) -> Result<bool, Error>
+    where
+        T: Clone,
+    {
+        for entity in [source, target] {
+            if !self.entities.is_alive(entity) {
+                return Err(Error::EntityIsNotAlive(entity));
+            }
+        }
+
+        let component = match self.get(source) {
+            Some(component) => component.clone(),
+            None => return Ok(false),
+        };
+
+        self.data.insert(target, component);
+
+        Ok(true)
+    }
+
+    /// Returns the entry of the component of the passed entity, which allows
+    /// to conditionally insert or modify the component with a single lookup.
+    pub fn entry(&mut self, entity: Entity) -> Result<StorageEntry<'_, T>, Error> {
+        if !self.entities.is_alive(entity) {
+            return Err(Error::EntityIsNotAlive(entity));
+        }
+
+        Ok(StorageEntry::new(entity, &mut self.data))
+    }
+
+    /// Returns the inner storage of the components mutably. This allows to
+    /// use methods of specific storages (like `SparseSetStorage::swap`).
+    ///
+    /// Please note that the storage does not check if the accessed indices
+    /// contain a component, use the mask of this storage to verify this.
+    pub fn unprotected_storage_mut(&mut self) -> &mut T::Storage {
+        self.data.storage_mut()
+    }
+
+    /// Returns the inner data of the storage as mutable slice. This allows
+    /// fast linear passes over the component data.
+    ///
+    /// See the documentation of the used storage to find out how the
+    /// indices of the slice map to entities.
+    ///
+    /// This is only available for storages whose elements are always valid
+    /// components. For the `VecStorage` use `unprotected_storage_mut` and
+    /// the unsafe `SliceAccess::as_mut_slice` instead.
+    pub fn as_mut_slice(&mut self) -> &mut [T]
+    where
+        T::Storage: SliceAccess<T, Element = T>,
+    {
+        // All elements of the slice are valid components, so writing to the
+        // slice can not corrupt the storage.
+        unsafe { self.data.storage_mut().as_mut_slice() }
+    }
+
     /// Removes the data associated with an `Entity`.
     pub fn remove(&mut self, e: Entity) -> Option<T> {
         let index = e.index();
@@ -133,6 +403,56 @@ where
         self.data.clear();
     }
 
+    /// Shrinks the memory that is allocated by the storage as much as
+    /// possible. This is useful after many components were removed.
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+    }
+
+    /// Reserves memory for at least `additional` more components, to avoid
+    /// reallocations when many components are inserted at once.
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.reserve(additional);
+    }
+
+    /// Same as `join_with_entities`, but yields the components mutably.
+    pub fn join_with_entities_mut(&mut self) -> JoinIter<(&Entities, &mut Self)> {
+        self.split_entities().join()
+    }
+
+    /// Same as `par_join_with_entities`, but yields the components mutably.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::{entity::Entity, *};
+    /// # use async_ecs::asparit::{Driver, ParallelIterator};
+    /// #
+    /// # struct Owner(Option<Entity>);
+    /// # impl Component for Owner { type Storage = VecStorage<Self>; }
+    /// #
+    /// let mut world = World::default();
+    /// world.register_component::<Owner>();
+    ///
+    /// let e1 = world.create_entity().with(Owner(None)).build();
+    /// let e2 = world.create_entity().with(Owner(None)).build();
+    ///
+    /// let mut owner = world.component_mut::<Owner>();
+    /// owner
+    ///     .par_join_with_entities_mut()
+    ///     .for_each(|(entity, owner)| owner.0 = Some(entity))
+    ///     .exec();
+    ///
+    /// assert_eq!(owner.get(e1).unwrap().0, Some(e1));
+    /// assert_eq!(owner.get(e2).unwrap().0, Some(e2));
+    /// ```
+    pub fn par_join_with_entities_mut(&mut self) -> JoinParIter<(&Entities, &mut Self)>
+    where
+        T::Storage: Sync + DistinctStorage,
+    {
+        self.split_entities().par_join()
+    }
+
     /// Creates a draining storage wrapper which can be `.join`ed
     /// to get a draining iterator.
     pub fn drain(&mut self) -> Drain<T> {
@@ -140,6 +460,98 @@ where
             data: &mut self.data,
         }
     }
+
+    /// Creates a draining storage wrapper which can be `.join`ed to get a
+    /// draining iterator, that yields the entity of each removed component
+    /// together with the component.
+    pub fn drain_with_entities(&mut self) -> DrainEntities<'_, T> {
+        DrainEntities {
+            data: &mut self.data,
+            entities: &self.entities,
+        }
+    }
+
+    /// Returns a `Join`-able structure that yields a `PairedStorage` for
+    /// each component, instead of the component itself. The paired storage
+    /// gives immutable and mutable access to the component of the current
+    /// entity only.
+    ///
+    /// This is useful if only a few of the joined components are actually
+    /// modified: in contrast to joining `&mut storage`, storages like the
+    /// `FlaggedStorage` only flag the components that are accessed using
+    /// `PairedStorage::get_mut_unchecked`.
+    ///
+    /// The restricted storage can be joined in parallel, if the storage
+    /// implements `DistinctStorage`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// # use async_ecs::asparit::{Driver, ParallelIterator};
+    /// #
+    /// struct Health(u32);
+    ///
+    /// impl Component for Health {
+    ///     type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    /// }
+    ///
+    /// let mut world = World::default();
+    /// world.register_component::<Health>();
+    ///
+    /// let e1 = world.create_entity().with(Health(0)).build();
+    /// let e2 = world.create_entity().with(Health(10)).build();
+    ///
+    /// let mut tracker = ChangeTracker::new();
+    /// let mut health = world.component_mut::<Health>();
+    /// health.changed_since(&mut tracker).join().for_each(drop);
+    ///
+    /// (&mut health.par_restrict_mut())
+    ///     .par_join()
+    ///     .for_each(|mut health| {
+    ///         if health.get_unchecked().0 == 0 {
+    ///             health.get_mut_unchecked().0 = 100;
+    ///         }
+    ///     })
+    ///     .exec();
+    ///
+    /// let changed = (&world.entities(), health.changed_since(&mut tracker))
+    ///     .join()
+    ///     .map(|(entity, _)| entity)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(changed, vec![e1]);
+    /// assert_eq!(health.get(e1).unwrap().0, 100);
+    /// assert_eq!(health.get(e2).unwrap().0, 10);
+    /// ```
+    pub fn par_restrict_mut(&mut self) -> RestrictedStorage<'_, T, MutableParallelRestriction> {
+        let (mask, storage) = self.data.open_mut();
+
+        RestrictedStorage::new_mut(mask, storage, &self.entities)
+    }
+
+    fn split_entities(&mut self) -> (&Entities, &mut Self) {
+        let entities: *const Entities = &*self.entities;
+
+        // SAFETY: The entities are not owned by the storage wrapper, it only
+        // holds a shared borrow of them. The wrapper never accesses the
+        // entities mutably, so the returned references do not alias.
+        (unsafe { &*entities }, self)
+    }
+
+    /// Same as `changed_since`, but yields the changed components mutably.
+    ///
+    /// Please note that the mutable access flags the yielded components as
+    /// changed again, so they are reported again by the next join.
+    pub fn changed_since_mut<'t>(
+        &mut self,
+        tracker: &'t mut ChangeTracker,
+    ) -> ChangedSince<'t, &mut Self>
+    where
+        T::Storage: Tracked,
+    {
+        ChangedSince::new(self, tracker)
+    }
 }
 
 impl<'a, T: Component, D> DistinctStorage for StorageWrapper<'a, T, D> where
@@ -197,6 +609,26 @@ where
     }
 }
 
+impl<'a, 'e, T, D> MaskGeneration for &'a StorageWrapper<'e, T, D>
+where
+    T: Component,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    fn mask_generation(&self) -> u64 {
+        self.data.generation()
+    }
+}
+
+impl<'a, 'e, T, D> MaskGeneration for &'a mut StorageWrapper<'e, T, D>
+where
+    T: Component,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    fn mask_generation(&self) -> u64 {
+        self.data.generation()
+    }
+}
+
 impl<'a, 'e, T, D> ParJoin for &'a StorageWrapper<'e, T, D>
 where
     T: Component,
@@ -212,3 +644,152 @@ where
     T::Storage: Sync + DistinctStorage,
 {
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{entity::Builder, storage::VecStorage, world::World};
+
+    use super::*;
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn get_mut_or_default() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().build();
+        let e3 = world.create_entity().build();
+        world.delete_entity(e3).unwrap();
+
+        let mut storage = world.component_mut::<Pos>();
+
+        storage.get_mut_or_default(e1).unwrap().0 += 1;
+        storage.get_mut_or_default(e2).unwrap().0 += 1;
+
+        assert_eq!(storage.get(e1), Some(&Pos(2)));
+        assert_eq!(storage.get(e2), Some(&Pos(1)));
+        assert!(storage.get_mut_or_default(e3).is_none());
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+        let e3 = world.create_entity().build();
+
+        let mut storage = world.component_mut::<Pos>();
+
+        let [p1, p2] = storage.get_many_mut([e1, e2]).unwrap();
+        std::mem::swap(&mut p1.0, &mut p2.0);
+
+        assert_eq!(storage.get(e1), Some(&Pos(2)));
+        assert_eq!(storage.get(e2), Some(&Pos(1)));
+
+        assert!(storage.get_many_mut([e1, e1]).is_none());
+        assert!(storage.get_many_mut([e1, e3]).is_none());
+    }
+
+    #[test]
+    fn drain_with_entities() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().build();
+        let e3 = world.create_entity().with(Pos(3)).build();
+
+        let mut storage = world.component_mut::<Pos>();
+        let drained = storage.drain_with_entities().join().collect::<Vec<_>>();
+
+        assert_eq!(drained, vec![(e1, Pos(1)), (e3, Pos(3))]);
+        assert!(storage.is_empty());
+        assert_eq!(storage.get(e2), None);
+    }
+
+    #[test]
+    fn join_with_entities() {
+        use asparit::{Driver, ParallelIterator};
+
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().build();
+        let e3 = world.create_entity().with(Pos(3)).build();
+        let e4 = world.create_entity().with(Pos(4)).build();
+        world.delete_entity(e4).unwrap();
+
+        let mut storage = world.component_mut::<Pos>();
+        storage
+            .par_join_with_entities_mut()
+            .for_each(|(entity, pos)| pos.0 += entity.index())
+            .exec();
+
+        let joined = storage
+            .join_with_entities()
+            .map(|(entity, pos)| (entity, pos.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            joined,
+            vec![(e1, Pos(1 + e1.index())), (e3, Pos(3 + e3.index()))]
+        );
+        assert_eq!(storage.par_join_with_entities().count().exec(), 2);
+        assert_eq!(storage.get(e2), None);
+    }
+
+    #[test]
+    fn clone_from_entity() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+        let e3 = world.create_entity().build();
+
+        let mut storage = world.component_mut::<Pos>();
+
+        assert!(storage.clone_from_entity(e1, e2).unwrap());
+        assert!(!storage.clone_from_entity(e3, e1).unwrap());
+
+        assert_eq!(storage.get(e1), Some(&Pos(1)));
+        assert_eq!(storage.get(e2), Some(&Pos(1)));
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let entities = (0..100)
+            .map(|i| world.create_entity().with(Pos(i)).build())
+            .collect::<Vec<_>>();
+
+        let mut storage = world.component_mut::<Pos>();
+        for entity in &entities[10..] {
+            storage.remove(*entity);
+        }
+
+        let len = |entity: Entity| entity.index() as usize + 1;
+        assert_eq!(storage.as_slice().len(), len(entities[99]));
+
+        storage.shrink_to_fit();
+        assert_eq!(storage.as_slice().len(), len(entities[9]));
+        assert_eq!(storage.get(entities[9]), Some(&Pos(9)));
+
+        storage.reserve(100);
+        storage.insert(entities[50], Pos(50)).unwrap();
+        assert_eq!(storage.as_slice().len(), len(entities[50]));
+        assert_eq!(storage.get(entities[50]), Some(&Pos(50)));
+    }
+}
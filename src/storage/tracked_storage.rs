@@ -0,0 +1,269 @@
+use std::marker::PhantomData;
+
+use hibitset::{AtomicBitSet, BitSet, BitSetLike};
+
+use crate::entity::Index;
+
+use super::{DenseVecStorage, DistinctStorage, Storage, StorageStats};
+
+/// Storage that keeps track of the indices of the inserted, modified and
+/// removed components in bit sets. The actual data is stored in the wrapped
+/// storage `T`.
+///
+/// In contrast to the `FlaggedStorage` no events are written, the bit sets
+/// are simply cleared on each `World::maintain`. So they contain the changes
+/// since the last maintain, which makes it cheap to build reactive systems
+/// that join over the changed components.
+///
+/// Please note that every mutable access is treated as a modification, so
+/// joining over `&mut storage` flags all joined components, even if they
+/// are not actually modified.
+///
+/// The bit sets are cleared before the entities that were deleted using
+/// `Entities::delete` are removed by `World::maintain`. So the components of
+/// these entities show up in the removed bit set only after the maintain and
+/// stay there until the next one.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{storage::TrackedStorage, *};
+/// # use hibitset::BitSetLike;
+/// #
+/// # #[tokio::main]
+/// # async fn main() {
+/// #[derive(Debug, PartialEq)]
+/// pub struct Pos(u32);
+///
+/// impl Component for Pos {
+///     type Storage = TrackedStorage<Self, VecStorage<Self>>;
+/// }
+///
+/// let mut world = World::default();
+/// world.register_component::<Pos>();
+///
+/// let e1 = world.create_entity().with(Pos(1)).build();
+/// let e2 = world.create_entity().with(Pos(2)).build();
+///
+/// world.maintain().await;
+/// world.component_mut::<Pos>().get_mut(e2).unwrap().0 = 3;
+///
+/// let pos = world.component::<Pos>();
+/// let modified = (pos.modified(), &pos).join().collect::<Vec<_>>();
+///
+/// assert_eq!(modified, vec![(e2.index(), &Pos(3))]);
+/// assert!(pos.inserted().is_empty());
+///
+/// drop(pos);
+/// world.entities().delete(e1).unwrap();
+/// world.maintain().await;
+///
+/// let pos = world.component::<Pos>();
+/// assert!(pos.removed().contains(e1.index()));
+/// # }
+/// ```
+pub struct TrackedStorage<C, T = DenseVecStorage<C>> {
+    inner: T,
+    inserted: BitSet,
+    modified: AtomicBitSet,
+    removed: BitSet,
+    marker: PhantomData<C>,
+}
+
+impl<C, T> TrackedStorage<C, T> {
+    /// Get a reference to the wrapped storage.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<C, T> Default for TrackedStorage<C, T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+            inserted: Default::default(),
+            modified: Default::default(),
+            removed: Default::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<C, T> Storage<C> for TrackedStorage<C, T>
+where
+    T: Storage<C> + Default,
+{
+    unsafe fn get(&self, index: Index) -> &C {
+        self.inner.get(index)
+    }
+
+    unsafe fn get_mut(&mut self, index: Index) -> &mut C {
+        // distinct indices may be accessed in parallel
+        self.modified.add_atomic(index);
+
+        self.inner.get_mut(index)
+    }
+
+    unsafe fn insert(&mut self, index: Index, value: C) {
+        self.inserted.add(index);
+
+        self.inner.insert(index, value);
+    }
+
+    unsafe fn remove(&mut self, index: Index) -> C {
+        self.unflag(index);
+
+        self.inner.remove(index)
+    }
+
+    unsafe fn clean<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        for index in (&has).iter() {
+            self.unflag(index);
+        }
+
+        self.inner.clean(has);
+    }
+
+    unsafe fn drop(&mut self, index: Index) {
+        self.unflag(index);
+
+        self.inner.drop(index);
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.inner.shrink_to_fit(has);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
+    }
+
+    fn maintain(&mut self) {
+        self.inserted.clear();
+        self.modified.clear();
+        self.removed.clear();
+
+        self.inner.maintain();
+    }
+}
+
+impl<C, T> TrackedStorage<C, T> {
+    fn unflag(&mut self, index: Index) {
+        self.inserted.remove(index);
+        self.modified.remove(index);
+        self.removed.add(index);
+    }
+}
+
+impl<C, T> StorageStats for TrackedStorage<C, T>
+where
+    T: StorageStats,
+{
+    fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.inner.heap_bytes()
+    }
+}
+
+impl<C, T> DistinctStorage for TrackedStorage<C, T> where T: DistinctStorage {}
+
+/* ChangeMasks */
+
+/// Storage that keeps track of the changes since the last `World::maintain`
+/// in bit sets, that can be joined with other storages.
+pub trait ChangeMasks {
+    /// Returns the indices of the components that were inserted.
+    fn inserted(&self) -> &BitSet;
+
+    /// Returns the indices of the components that were accessed mutably.
+    fn modified(&self) -> &AtomicBitSet;
+
+    /// Returns the indices of the components that were removed.
+    fn removed(&self) -> &BitSet;
+}
+
+impl<C, T> ChangeMasks for TrackedStorage<C, T> {
+    fn inserted(&self) -> &BitSet {
+        &self.inserted
+    }
+
+    fn modified(&self) -> &AtomicBitSet {
+        &self.modified
+    }
+
+    fn removed(&self) -> &BitSet {
+        &self.removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        component::Component, entity::Builder, join::Join, storage::VecStorage, world::World,
+    };
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = TrackedStorage<Self, VecStorage<Self>>;
+    }
+
+    fn indices<B: BitSetLike>(mask: B) -> Vec<Index> {
+        mask.iter().collect()
+    }
+
+    #[tokio::test]
+    async fn masks_are_cleared_on_maintain() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+
+        {
+            let pos = world.component::<Pos>();
+
+            assert_eq!(indices(pos.inserted()), vec![e1.index(), e2.index()]);
+            assert!(pos.modified().is_empty());
+        }
+
+        world.maintain().await;
+
+        for pos in (&mut world.component_mut::<Pos>()).join() {
+            pos.0 += 1;
+        }
+        world.component_mut::<Pos>().remove(e1);
+        world.entities().delete(e2).unwrap();
+
+        {
+            let pos = world.component::<Pos>();
+
+            assert!(pos.inserted().is_empty());
+            assert_eq!(indices(pos.modified()), vec![e2.index()]);
+            assert_eq!(indices(pos.removed()), vec![e1.index()]);
+        }
+
+        world.maintain().await;
+
+        // removals of deleted entities are reported after the maintain
+        let pos = world.component::<Pos>();
+
+        assert!(pos.modified().is_empty());
+        assert_eq!(indices(pos.removed()), vec![e2.index()]);
+    }
+}
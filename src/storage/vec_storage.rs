@@ -1,11 +1,11 @@
-use std::mem::MaybeUninit;
+use std::mem::{size_of, MaybeUninit};
 use std::ptr::{drop_in_place, read};
 
 use hibitset::BitSetLike;
 
 use crate::entity::Index;
 
-use super::{DistinctStorage, Storage};
+use super::{mask_len, DistinctStorage, SliceAccess, Storage, StorageStats};
 
 /// Vector storage. Uses a simple `Vec`. Supposed to have maximum
 /// performance for the components mostly present in entities.
@@ -56,6 +56,55 @@ impl<T> Storage<T> for VecStorage<T> {
 
         self.0.set_len(0);
     }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        // Slots after the last component are uninitialized, so they can be
+        // cut off without dropping anything.
+        self.0.set_len(self.0.len().min(mask_len(has)));
+        self.0.shrink_to_fit();
+    }
+
+    /// Reserves memory for at least `additional` more slots. Slots are
+    /// indexed by the index of the entity, so this only guarantees that no
+    /// reallocation is needed for components of entities with an index below
+    /// the current length plus `additional`.
+    fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+}
+
+impl<T> StorageStats for VecStorage<T> {
+    fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.0.capacity() * size_of::<T>()
+    }
+}
+
+impl<T> SliceAccess<T> for VecStorage<T> {
+    type Element = MaybeUninit<T>;
+
+    /// Returns the stored components as slice. Slots of entities that do
+    /// not own the component are uninitialized, so use the mask of the
+    /// storage to find the valid elements.
+    #[inline]
+    fn as_slice(&self) -> &[MaybeUninit<T>] {
+        self.0.as_slice()
+    }
+
+    /// Returns the stored components as mutable slice. Slots of entities
+    /// that do not own the component are uninitialized, so use the mask of
+    /// the storage to find the valid elements. The slots of entities that
+    /// own the component must not be overwritten with uninitialized values.
+    #[inline]
+    unsafe fn as_mut_slice(&mut self) -> &mut [MaybeUninit<T>] {
+        self.0.as_mut_slice()
+    }
 }
 
 impl<T> DistinctStorage for VecStorage<T> {}
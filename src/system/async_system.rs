@@ -0,0 +1,186 @@
+/// Implements `AsyncSystem` for a type using an `async fn run` instead of
+/// `AsyncSystem::run_async`. The body of `run` is moved into a boxed future,
+/// so the `Box::pin(async move { ... })` boilerplate is not needed.
+///
+/// The impl may be generic and may contain a where clause. All other items
+/// of the impl (like `init`, `init_async`, `setup`, `accessor` or `dispose`)
+/// are passed through unchanged.
+///
+/// `run` only takes the system data, not `self`: the returned future lives
+/// as long as the system data, so it can not borrow the system. The
+/// parameter is used as it is, so the system data can be destructured like
+/// in any other function.
+///
+/// ```compile_fail
+/// # use async_ecs::*;
+/// struct Invalid;
+///
+/// async_system! {
+///     impl<'a> AsyncSystem<'a> for Invalid {
+///         type SystemData = ();
+///
+///         async fn run(&mut self, _: Self::SystemData) {}
+///     }
+/// }
+/// ```
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::*;
+/// # use tokio::task::yield_now;
+/// #
+/// #[derive(Default)]
+/// struct Counter(usize);
+///
+/// #[derive(Default)]
+/// struct Step(usize);
+///
+/// struct Increment;
+///
+/// async_system! {
+///     impl<'a> AsyncSystem<'a> for Increment {
+///         type SystemData = (Read<'a, Step>, Write<'a, Counter>);
+///
+///         async fn run((step, mut counter): Self::SystemData) {
+///             yield_now().await;
+///
+///             counter.0 += step.0;
+///         }
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// world.insert(Step(2));
+///
+/// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with_async(Increment, "increment", &[])
+///     .unwrap()
+///     .build();
+///
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// assert_eq!(world.resource::<Counter>().0, 2);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! async_system {
+    (impl $($rest:tt)*) => {
+        $crate::async_system!(@header [] [] $($rest)*);
+    };
+
+    // The header of the impl (generics, trait, type and where clause) is
+    // everything in front of the braces that contain the items. The lifetime
+    // of the `AsyncSystem` trait is remembered for the returned future.
+    (@header [$($lt:tt)*] [$($header:tt)*] { $($items:tt)* }) => {
+        $crate::async_system!(@items [$($lt)*] [$($header)*] [] $($items)*);
+    };
+    (@header [$($lt:tt)*] [$($header:tt)*] AsyncSystem<$a:tt> $($rest:tt)*) => {
+        $crate::async_system!(@header [$a] [$($header)* AsyncSystem<$a>] $($rest)*);
+    };
+    (@header [$($lt:tt)*] [$($header:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::async_system!(@header [$($lt)*] [$($header)* $next] $($rest)*);
+    };
+
+    // `run` does not take `self`, see the documentation above.
+    (
+        @items [$($lt:tt)*] [$($header:tt)*] [$($items:tt)*]
+        async fn run(&mut self $($args:tt)*) $($rest:tt)*
+    ) => {
+        compile_error!(
+            "`run` must not take `self`, because the returned future can not borrow the system"
+        );
+    };
+    (
+        @items [$a:tt] [$($header:tt)*] [$($items:tt)*]
+        async fn run($($args:tt)*) $body:block $($rest:tt)*
+    ) => {
+        $crate::async_system!(
+            @items
+            [$a]
+            [$($header)*]
+            [
+                $($items)*
+
+                fn run_async(
+                    &mut self,
+                    $($args)*
+                ) -> ::std::pin::Pin<
+                    ::std::boxed::Box<
+                        dyn ::std::future::Future<Output = ()> + ::std::marker::Send + $a,
+                    >,
+                > {
+                    ::std::boxed::Box::pin(async move $body)
+                }
+            ]
+            $($rest)*
+        );
+    };
+    (@items [$($lt:tt)*] [$($header:tt)*] [$($items:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::async_system!(@items [$($lt)*] [$($header)*] [$($items)* $next] $($rest)*);
+    };
+    (@items [$($lt:tt)*] [$($header:tt)*] [$($items:tt)*]) => {
+        impl $($header)* {
+            $($items)*
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use crate::{access::Write, dispatcher::Dispatcher, system::AsyncSystem, world::World};
+
+    #[derive(Default)]
+    struct Counter(usize);
+
+    struct Add<T> {
+        amount: usize,
+        marker: PhantomData<T>,
+    }
+
+    async_system! {
+        impl<'a, T> AsyncSystem<'a> for Add<T>
+        where
+            T: Send + 'static,
+        {
+            type SystemData = Write<'a, Counter>;
+
+            fn init(&mut self) {
+                self.amount += 10;
+            }
+
+            async fn run(mut counter: Self::SystemData) {
+                counter.0 += 1;
+            }
+
+            fn dispose(self, world: &mut World) {
+                world.resource_mut::<Counter>().0 += self.amount;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn extra_items() {
+        let mut world = World::default();
+        let system = Add::<u32> {
+            amount: 0,
+            marker: PhantomData,
+        };
+
+        let mut dispatcher = Dispatcher::setup_builder(&mut world)
+            .with_async(system, "add", &[])
+            .unwrap()
+            .build();
+
+        dispatcher.dispatch(&world).await.unwrap();
+        dispatcher.dispatch(&world).await.unwrap();
+        assert_eq!(world.resource::<Counter>().0, 2);
+
+        dispatcher.shutdown(&mut world).await.unwrap();
+        assert_eq!(world.resource::<Counter>().0, 12);
+    }
+}
@@ -0,0 +1,171 @@
+use std::marker::PhantomData;
+
+use crate::{error::Error, resource::ResourceId, world::World};
+
+use super::SystemData;
+
+/// System data that fetches the wrapped `SystemData` on demand.
+///
+/// The `SystemData` of an `AsyncSystem` is fetched before `run_async` is
+/// called, and the future usually holds the borrowed resources until it
+/// is finished. If the future awaits something that is not related to the
+/// world (like external I/O), the resources stay borrowed the whole time.
+/// Using a `DataFetcher` as `SystemData` the future can drop the fetched
+/// data before it awaits, and fetch it again afterwards.
+///
+/// The fetcher reports the reads and writes of the wrapped data, so the
+/// dispatcher orders the system exactly like a system that uses the
+/// wrapped data directly.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{system::DataFetcher, *};
+/// # use futures::future::{BoxFuture, FutureExt};
+/// #
+/// #[derive(Default)]
+/// struct Requests(Vec<u32>);
+///
+/// #[derive(Default)]
+/// struct Responses(Vec<u32>);
+///
+/// struct Client;
+///
+/// impl<'a> AsyncSystem<'a> for Client {
+///     type SystemData = DataFetcher<'a, (Write<'a, Requests>, Write<'a, Responses>)>;
+///
+///     fn run_async(&mut self, fetcher: Self::SystemData) -> BoxFuture<'a, ()> {
+///         async move {
+///             let requests = {
+///                 let (mut requests, _) = fetcher.fetch();
+///
+///                 std::mem::take(&mut requests.0)
+///             };
+///
+///             // The resources are not borrowed while the future is waiting.
+///             tokio::task::yield_now().await;
+///
+///             let (_, mut responses) = fetcher.fetch();
+///             responses.0.extend(requests.into_iter().map(|r| r * 2));
+///         }
+///         .boxed()
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// world.register_resource(Requests(vec![1, 2]));
+///
+/// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with_async(Client, "client", &[])
+///     .unwrap()
+///     .build();
+///
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// assert_eq!(world.resource::<Responses>().0, vec![2, 4]);
+/// # }
+/// ```
+pub struct DataFetcher<'a, T> {
+    world: &'a World,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> DataFetcher<'a, T>
+where
+    T: SystemData<'a>,
+{
+    /// Fetches the wrapped system data from the world.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the resources does not exist or is already borrowed
+    /// in a conflicting way, for example because the data that was fetched
+    /// before is still alive.
+    pub fn fetch(&self) -> T {
+        T::fetch(self.world)
+    }
+
+    /// Same as `fetch`, but returns an error instead of panicking.
+    pub fn try_fetch(&self) -> Result<T, Error> {
+        T::try_fetch(self.world)
+    }
+
+    /// Returns the world the data is fetched from.
+    pub fn world(&self) -> &'a World {
+        self.world
+    }
+}
+
+impl<'a, T> Clone for DataFetcher<'a, T> {
+    fn clone(&self) -> Self {
+        Self {
+            world: self.world,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> SystemData<'a> for DataFetcher<'a, T>
+where
+    T: SystemData<'a>,
+{
+    fn setup(world: &mut World) {
+        T::setup(world)
+    }
+
+    fn fetch(world: &'a World) -> Self {
+        Self {
+            world,
+            marker: PhantomData,
+        }
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        Ok(<Self as SystemData>::fetch(world))
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        T::reads()
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        T::writes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::access::{Read, Write};
+
+    #[derive(Default)]
+    struct Counter(usize);
+
+    #[test]
+    fn refetch() {
+        let mut world = World::default();
+        <DataFetcher<Write<Counter>> as SystemData>::setup(&mut world);
+
+        let fetcher = <DataFetcher<Write<Counter>> as SystemData>::fetch(&world);
+
+        let mut counter = fetcher.fetch();
+        counter.0 += 1;
+
+        assert!(matches!(
+            fetcher.try_fetch(),
+            Err(Error::BorrowConflict { mutably: true, .. })
+        ));
+
+        drop(counter);
+
+        assert_eq!(fetcher.try_fetch().unwrap().0, 1);
+        assert_eq!(
+            <DataFetcher<Write<Counter>> as SystemData>::writes(),
+            Write::<Counter>::writes()
+        );
+        assert!(<DataFetcher<Read<Counter>> as SystemData>::writes().is_empty());
+    }
+}
@@ -1,8 +1,15 @@
+mod async_system;
+mod data_fetcher;
+mod run_once;
 mod system_data;
+mod system_tick;
 
+pub use data_fetcher::DataFetcher;
+pub use run_once::RunOnce;
 pub use system_data::{DynamicSystemData, SystemData};
+pub use system_tick::SystemTick;
 
-use futures::future::BoxFuture;
+use futures::future::{ready, BoxFuture, FutureExt};
 
 use crate::{
     access::{Accessor, AccessorCow, AccessorType},
@@ -70,6 +77,15 @@ pub trait AsyncSystem<'a>: Sized {
     /// Initialize the systems.
     fn init(&mut self) {}
 
+    /// Initializes the system asynchronous before the first dispatch, see
+    /// `Dispatcher::initialize`. Use this to load assets or open connections
+    /// the system needs to run.
+    fn init_async<'b>(&'b mut self, world: &'b World) -> BoxFuture<'b, ()> {
+        let _ = world;
+
+        ready(()).boxed()
+    }
+
     /// Executes the system with the required system data asynchronous.
     fn run_async(&mut self, data: Self::SystemData) -> BoxFuture<'a, ()>;
 
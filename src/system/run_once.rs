@@ -0,0 +1,130 @@
+use futures::future::{ready, BoxFuture, FutureExt};
+
+use crate::{access::AccessorCow, world::World};
+
+use super::{AsyncSystem, System};
+
+/// Wrapper that executes the wrapped system only once, the first time it is
+/// dispatched. All following runs are skipped.
+///
+/// The system stays part of the dispatcher (so systems that depend on it are
+/// still ordered correctly) until it is removed using `Dispatcher::remove`.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{system::RunOnce, *};
+/// #[derive(Default)]
+/// struct Counter(usize);
+///
+/// struct Init;
+///
+/// impl<'a> System<'a> for Init {
+///     type SystemData = Write<'a, Counter>;
+///
+///     fn run(&mut self, mut counter: Self::SystemData) {
+///         counter.0 += 1;
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with(RunOnce::new(Init), "init", &[])
+///     .unwrap()
+///     .build();
+///
+/// dispatcher.dispatch(&world).await.unwrap();
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// assert_eq!(world.resource::<Counter>().0, 1);
+/// # }
+/// ```
+pub struct RunOnce<S> {
+    system: S,
+    done: bool,
+}
+
+impl<S> RunOnce<S> {
+    /// Wrap the passed system.
+    pub fn new(system: S) -> Self {
+        Self {
+            system,
+            done: false,
+        }
+    }
+
+    /// Returns `true` if the wrapped system was already executed.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+impl<'a, S> System<'a> for RunOnce<S>
+where
+    S: System<'a>,
+{
+    type SystemData = S::SystemData;
+
+    fn init(&mut self) {
+        self.system.init();
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        if !self.done {
+            self.done = true;
+
+            self.system.run(data);
+        }
+    }
+
+    fn accessor<'b>(&'b self) -> AccessorCow<'a, 'b, Self::SystemData> {
+        self.system.accessor()
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        self.system.setup(world);
+    }
+
+    fn dispose(self, world: &mut World) {
+        self.system.dispose(world);
+    }
+}
+
+impl<'a, S> AsyncSystem<'a> for RunOnce<S>
+where
+    S: AsyncSystem<'a>,
+{
+    type SystemData = S::SystemData;
+
+    fn init(&mut self) {
+        self.system.init();
+    }
+
+    fn init_async<'b>(&'b mut self, world: &'b World) -> BoxFuture<'b, ()> {
+        self.system.init_async(world)
+    }
+
+    fn run_async(&mut self, data: Self::SystemData) -> BoxFuture<'a, ()> {
+        if self.done {
+            ready(()).boxed()
+        } else {
+            self.done = true;
+
+            self.system.run_async(data)
+        }
+    }
+
+    fn accessor<'b>(&'b self) -> AccessorCow<'a, 'b, Self::SystemData> {
+        self.system.accessor()
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        self.system.setup(world);
+    }
+
+    fn dispose(self, world: &mut World) {
+        self.system.dispose(world);
+    }
+}
@@ -2,6 +2,7 @@ use std::marker::PhantomData;
 
 use crate::{
     access::{Accessor, StaticAccessor},
+    error::Error,
     resource::ResourceId,
     world::World,
 };
@@ -72,6 +73,44 @@ pub trait SystemData<'a> {
     /// `SystemData` trait for every possible lifetime.
     fn fetch(world: &'a World) -> Self;
 
+    /// Same as `fetch`, but returns an error instead of panicking if a
+    /// resource does not exist or is borrowed in a conflicting way.
+    ///
+    /// The default implementation checks the resources returned by `reads`
+    /// and `writes` before it calls `fetch`.
+    fn try_fetch(world: &'a World) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        for id in Self::reads() {
+            let cell = world
+                .resource_raw(&id)
+                .ok_or_else(|| Error::ResourceNotFound(id.name()))?;
+
+            if cell.try_borrow().is_none() {
+                return Err(Error::BorrowConflict {
+                    resource: id.name(),
+                    mutably: true,
+                });
+            }
+        }
+
+        for id in Self::writes() {
+            let cell = world
+                .resource_raw(&id)
+                .ok_or_else(|| Error::ResourceNotFound(id.name()))?;
+
+            if cell.try_borrow_mut().is_none() {
+                return Err(Error::BorrowConflict {
+                    resource: id.name(),
+                    mutably: cell.try_borrow().is_none(),
+                });
+            }
+        }
+
+        Ok(Self::fetch(world))
+    }
+
     /// Returns all read dependencies as fetched from `Self::fetch`.
     ///
     /// Please note that returning wrong dependencies can lead to a panic.
@@ -126,6 +165,10 @@ where
         PhantomData
     }
 
+    fn try_fetch(_: &World) -> Result<Self, Error> {
+        Ok(PhantomData)
+    }
+
     fn reads() -> Vec<ResourceId> {
         vec![]
     }
@@ -140,6 +183,10 @@ impl<'a> SystemData<'a> for () {
 
     fn fetch(_: &'a World) -> Self {}
 
+    fn try_fetch(_: &'a World) -> Result<Self, Error> {
+        Ok(())
+    }
+
     fn reads() -> Vec<ResourceId> {
         Vec::new()
     }
@@ -188,6 +235,12 @@ mod impl_system_data {
                         ( $( <$ty as SystemData<'a>>::fetch(world), )* )
                     }
 
+                    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+                        #![allow(unused_variables)]
+
+                        Ok(( $( <$ty as SystemData<'a>>::try_fetch(world)?, )* ))
+                    }
+
                     fn reads() -> Vec<ResourceId> {
                         #![allow(unused_mut)]
 
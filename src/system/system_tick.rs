@@ -0,0 +1,30 @@
+use std::mem::replace;
+
+use crate::storage::{advance_tick, Tick};
+
+/// Per-system state that remembers the tick of the last run of a system.
+///
+/// Together with `Read::is_changed_since` this allows a system to skip its
+/// work, if the resources it depends on were not modified since its last
+/// run. A new tick reports all resources as changed on first use.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct SystemTick(Tick);
+
+impl SystemTick {
+    /// Create a new tick.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the tick of the last run.
+    pub fn last_run(&self) -> Tick {
+        self.0
+    }
+
+    /// Marks the current run of the system and returns the tick of the
+    /// previous run. All modifications after this call are reported as
+    /// changed on the next call.
+    pub fn update(&mut self) -> Tick {
+        replace(&mut self.0, advance_tick())
+    }
+}
@@ -0,0 +1,5 @@
+pub mod instant;
+pub mod system_cache;
+
+pub use instant::Instant;
+pub use system_cache::SystemCache;
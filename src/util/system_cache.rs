@@ -0,0 +1,359 @@
+use std::collections::BTreeMap;
+
+use hashbrown::HashMap;
+
+use crate::{
+    entity::{Entities, Entity},
+    event::{EventChannel, ReaderId},
+};
+
+/// A map of values keyed by `Entity` that is meant to be stored inside a
+/// system. It is kept consistent with the entities of the world by calling
+/// `maintain` at the beginning of each run, which removes the values of all
+/// entities that were deleted since the last call.
+///
+/// Optionally the cache can be bounded to a maximum number of values. If the
+/// cache is full, the least recently used value is evicted. The entities are
+/// additionally ordered by their last usage, so eviction takes `O(log n)`.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::{util::SystemCache, *};
+///
+/// struct Mesh(String);
+///
+/// impl Component for Mesh {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Compiled(usize);
+///
+/// #[derive(Default)]
+/// struct RenderSystem {
+///     cache: SystemCache<Compiled>,
+/// }
+///
+/// impl<'a> System<'a> for RenderSystem {
+///     type SystemData = (Entities<'a>, ReadStorage<'a, Mesh>);
+///
+///     fn run(&mut self, (entities, meshes): Self::SystemData) {
+///         self.cache.maintain(entities.deleted());
+///
+///         for (entity, mesh) in (&entities, &meshes).join() {
+///             let compiled = self
+///                 .cache
+///                 .get_or_insert_with(entity, || Compiled(mesh.0.len()));
+///
+///             println!("Render {:?} using {} vertices", entity, compiled.0);
+///         }
+///     }
+/// }
+/// ```
+pub struct SystemCache<V> {
+    values: HashMap<Entity, Item<V>>,
+    usage: BTreeMap<u64, Entity>,
+    reader: Option<ReaderId<Entity>>,
+    capacity: Option<usize>,
+    tick: u64,
+}
+
+impl<V> SystemCache<V> {
+    /// Create a new unbounded cache.
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+            usage: BTreeMap::new(),
+            reader: None,
+            capacity: None,
+            tick: 0,
+        }
+    }
+
+    /// Create a new cache that stores at most `capacity` values. If the
+    /// cache is full, the least recently used value is evicted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn bounded(capacity: usize) -> Self {
+        assert!(capacity > 0, "Capacity of a bounded cache must not be zero");
+
+        Self {
+            values: HashMap::with_capacity(capacity),
+            usage: BTreeMap::new(),
+            reader: None,
+            capacity: Some(capacity),
+            tick: 0,
+        }
+    }
+
+    /// Returns the maximum number of values of a bounded cache.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the number of cached values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if no value is cached.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns `true` if a value is cached for the passed entity.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.values.contains_key(&entity)
+    }
+
+    /// Returns the value of the passed entity and marks it as recently used.
+    pub fn get(&mut self, entity: Entity) -> Option<&V> {
+        self.get_mut(entity).map(|value| &*value)
+    }
+
+    /// Returns the value of the passed entity mutably and marks it as
+    /// recently used.
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut V> {
+        let tick = self.next_tick();
+        let usage = &mut self.usage;
+
+        self.values.get_mut(&entity).map(|item| {
+            usage.remove(&item.last_used);
+            usage.insert(tick, entity);
+            item.last_used = tick;
+
+            &mut item.value
+        })
+    }
+
+    /// Inserts a value for the passed entity and returns the previous value
+    /// if there was any.
+    pub fn insert(&mut self, entity: Entity, value: V) -> Option<V> {
+        let tick = self.next_tick();
+
+        if !self.values.contains_key(&entity) {
+            self.evict();
+        }
+
+        self.usage.insert(tick, entity);
+        self.values
+            .insert(
+                entity,
+                Item {
+                    value,
+                    last_used: tick,
+                },
+            )
+            .map(|item| {
+                self.usage.remove(&item.last_used);
+
+                item.value
+            })
+    }
+
+    /// Returns the value of the passed entity. If no value is cached, the
+    /// value is created using `f` and inserted into the cache.
+    pub fn get_or_insert_with<F>(&mut self, entity: Entity, f: F) -> &mut V
+    where
+        F: FnOnce() -> V,
+    {
+        let tick = self.next_tick();
+
+        if !self.values.contains_key(&entity) {
+            self.evict();
+        }
+
+        let item = self.values.entry(entity).or_insert_with(|| Item {
+            value: f(),
+            last_used: tick,
+        });
+        self.usage.remove(&item.last_used);
+        self.usage.insert(tick, entity);
+        item.last_used = tick;
+
+        &mut item.value
+    }
+
+    /// Removes the value of the passed entity.
+    pub fn remove(&mut self, entity: Entity) -> Option<V> {
+        let item = self.values.remove(&entity)?;
+        self.usage.remove(&item.last_used);
+
+        Some(item.value)
+    }
+
+    /// Removes all cached values.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.usage.clear();
+    }
+
+    /// Removes the values of all entities that are no longer alive.
+    pub fn retain_alive(&mut self, entities: &Entities) {
+        let usage = &mut self.usage;
+
+        self.values.retain(|entity, item| {
+            let alive = entities.is_alive(*entity);
+            if !alive {
+                usage.remove(&item.last_used);
+            }
+
+            alive
+        });
+    }
+
+    /// Removes the values of all entities that were deleted since the last
+    /// call to this method. Pass the channel returned by `Entities::deleted`.
+    ///
+    /// The cache registers itself as a reader on the first call, so make
+    /// sure to always pass the same channel.
+    pub fn maintain(&mut self, deleted: &EventChannel<Entity>) {
+        let reader = self.reader.get_or_insert_with(|| deleted.register_reader());
+
+        for entity in deleted.read(reader) {
+            if let Some(item) = self.values.remove(entity) {
+                self.usage.remove(&item.last_used);
+            }
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+
+        self.tick
+    }
+
+    fn evict(&mut self) {
+        let capacity = match self.capacity {
+            Some(capacity) if self.values.len() >= capacity => capacity,
+            _ => return,
+        };
+
+        while self.values.len() >= capacity {
+            let oldest = match self.usage.keys().next() {
+                Some(tick) => *tick,
+                None => break,
+            };
+
+            if let Some(entity) = self.usage.remove(&oldest) {
+                self.values.remove(&entity);
+            }
+        }
+    }
+}
+
+impl<V> Default for SystemCache<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Item<V> {
+    value: V,
+    last_used: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{entity::Builder, world::World};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn recycled_index() {
+        let mut world = World::default();
+        let mut cache = SystemCache::new();
+
+        let e1 = world.create_entity().build();
+        cache.maintain(world.entities().deleted());
+        cache.insert(e1, "old");
+
+        world.entities().delete(e1).unwrap();
+        world.maintain().await;
+
+        let e2 = world.create_entity().build();
+        assert_eq!(e1.index(), e2.index());
+
+        cache.maintain(world.entities().deleted());
+
+        assert!(cache.get(e1).is_none());
+        assert!(cache.get(e2).is_none());
+        assert_eq!(*cache.get_or_insert_with(e2, || "new"), "new");
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn no_leak_over_many_cycles() {
+        let mut world = World::default();
+        let mut cache = SystemCache::new();
+
+        for i in 0..100 {
+            cache.maintain(world.entities().deleted());
+
+            let entities = (0..10)
+                .map(|_| world.create_entity().build())
+                .collect::<Vec<_>>();
+            for entity in &entities {
+                cache.insert(*entity, i);
+            }
+
+            for entity in entities {
+                world.entities().delete(entity).unwrap();
+            }
+            world.maintain().await;
+        }
+
+        cache.maintain(world.entities().deleted());
+
+        assert!(cache.is_empty());
+        assert!(world.entities().deleted().len() <= 10);
+    }
+
+    #[test]
+    fn retain_alive() {
+        let mut world = World::default();
+        let mut cache = SystemCache::new();
+
+        let e1 = world.create_entity().build();
+        let e2 = world.create_entity().build();
+        cache.insert(e1, 1);
+        cache.insert(e2, 2);
+
+        world.entities_mut().kill(&[e1]).unwrap();
+        cache.retain_alive(&world.entities());
+
+        assert!(!cache.contains(e1));
+        assert!(cache.contains(e2));
+    }
+
+    #[test]
+    fn bounded_evicts_least_recently_used() {
+        let mut world = World::default();
+        let mut cache = SystemCache::bounded(2);
+
+        let e1 = world.create_entity().build();
+        let e2 = world.create_entity().build();
+        let e3 = world.create_entity().build();
+
+        cache.insert(e1, 1);
+        cache.insert(e2, 2);
+        cache.get(e1);
+        cache.insert(e3, 3);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(e1));
+        assert!(!cache.contains(e2));
+        assert!(cache.contains(e3));
+
+        cache.remove(e1);
+        cache.insert(e2, 2);
+        cache.insert(e1, 1);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(e1));
+        assert!(cache.contains(e2));
+        assert!(!cache.contains(e3));
+    }
+}
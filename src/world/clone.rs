@@ -0,0 +1,206 @@
+use crate::{
+    component::Component,
+    entity::{entities::Error as EntitiesError, Entities, Entity, EntityBuilder},
+    join::Join,
+    storage::{MaskedStorage, Storage},
+};
+
+use super::{CastFrom, MetaTable, World, WorldSnapshot};
+
+impl World {
+    /// Marks the component `T` as cloneable, so it is copied to the new
+    /// entity by `World::clone_entity` and is part of the snapshots created
+    /// by `World::snapshot`.
+    ///
+    /// This is usually called in the `setup` of the component, so the
+    /// component is marked as soon as it is registered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component was not registered.
+    pub fn register_clone<T>(&mut self)
+    where
+        T: Component + Clone + Send + Sync,
+    {
+        self.entry::<MetaTable<dyn CloneStorage>>()
+            .or_insert_with(Default::default);
+        self.resource_mut::<MetaTable<dyn CloneStorage>>()
+            .register(&*self.resource::<MaskedStorage<T>>());
+    }
+
+    /// Creates a new entity with a copy of each component of the passed
+    /// entity that was marked as cloneable (see `World::register_clone`).
+    ///
+    /// The returned builder may be used to add more components or to
+    /// overwrite the copied ones.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Pos(u32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    ///
+    ///     fn setup(world: &mut World) {
+    ///         world.register_clone::<Self>();
+    ///     }
+    /// }
+    ///
+    /// let mut world = World::default();
+    /// world.register_component::<Pos>();
+    ///
+    /// let prefab = world.create_entity().with(Pos(1)).build();
+    /// let entity = world.clone_entity(prefab).unwrap().build();
+    ///
+    /// assert_eq!(world.component::<Pos>().get(entity), Some(&Pos(1)));
+    /// ```
+    pub fn clone_entity(&mut self, entity: Entity) -> Result<EntityBuilder<'_>, EntitiesError> {
+        if !self.is_alive(entity) {
+            return Err(EntitiesError::EntityIsDead {
+                id: entity.id(),
+                op: "clone_entity",
+            });
+        }
+
+        self.entry::<MetaTable<dyn CloneStorage>>()
+            .or_insert_with(Default::default);
+
+        let builder = EntityBuilder::new(self);
+
+        for storage in self
+            .resource_mut::<MetaTable<dyn CloneStorage>>()
+            .iter_mut(self)
+        {
+            storage.clone_component(entity, builder.entity());
+        }
+
+        Ok(builder)
+    }
+}
+
+/* CloneStorage */
+
+/// Storage of a component that implements `Clone`. Storages are registered
+/// using `World::register_clone`.
+pub trait CloneStorage {
+    /// Inserts a copy of the component of `source` for `target`. Does
+    /// nothing if `source` has no component.
+    fn clone_component(&mut self, source: Entity, target: Entity);
+
+    /// Adds a copy of all components of the storage to the passed snapshot.
+    fn snapshot(&self, entities: &Entities, snapshot: &mut WorldSnapshot);
+
+    /// Replaces all components of the storage with the copies of the passed
+    /// snapshot. The storage is cleared if the snapshot does not contain the
+    /// component.
+    fn restore(&mut self, snapshot: &WorldSnapshot);
+}
+
+unsafe impl<T> CastFrom<T> for dyn CloneStorage
+where
+    T: CloneStorage + 'static,
+{
+    fn cast(t: &T) -> &Self {
+        t
+    }
+
+    fn cast_mut(t: &mut T) -> &mut Self {
+        t
+    }
+}
+
+impl<T> CloneStorage for MaskedStorage<T>
+where
+    T: Component + Clone + Send + Sync,
+{
+    fn clone_component(&mut self, source: Entity, target: Entity) {
+        let index = source.index();
+
+        if self.mask().contains(index) {
+            let component = unsafe { self.storage().get(index) }.clone();
+
+            self.insert(target, component);
+        }
+    }
+
+    fn snapshot(&self, entities: &Entities, snapshot: &mut WorldSnapshot) {
+        let components = (entities, self.mask())
+            .join()
+            .map(|(entity, _)| {
+                let component = unsafe { self.storage().get(entity.index()) }.clone();
+
+                (entity, component)
+            })
+            .collect();
+
+        snapshot.insert_components::<T>(components);
+    }
+
+    fn restore(&mut self, snapshot: &WorldSnapshot) {
+        self.clear();
+
+        for (entity, component) in snapshot.components::<T>().unwrap_or_default() {
+            self.insert(*entity, component.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{entity::Builder, storage::VecStorage};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+
+        fn setup(world: &mut World) {
+            world.register_clone::<Self>();
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn clone_entity() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Name>();
+
+        let prefab = world
+            .create_entity()
+            .with(Pos(1))
+            .with(Name("prefab"))
+            .build();
+        let entity = world
+            .clone_entity(prefab)
+            .unwrap()
+            .with(Name("clone"))
+            .build();
+
+        assert_eq!(world.component::<Pos>().get(entity), Some(&Pos(1)));
+        assert_eq!(world.component::<Name>().get(entity), Some(&Name("clone")));
+        assert_eq!(world.component::<Pos>().get(prefab), Some(&Pos(1)));
+
+        let empty = world.create_entity().build();
+        let entity = world.clone_entity(empty).unwrap().build();
+
+        assert_eq!(world.component::<Pos>().get(entity), None);
+
+        world.delete_entity(empty).unwrap();
+
+        assert!(world.clone_entity(empty).is_err());
+    }
+}
@@ -0,0 +1,302 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+use log::warn;
+
+#[cfg(feature = "serde")]
+use crate::saveload::Marker;
+use crate::{
+    component::Component,
+    entity::{Builder, Entity},
+    resource::Resource,
+};
+
+use super::{lazy::Step, DeferredEntity, World};
+
+type BufferedCommand = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+/// Buffer of world updates that can be filled without any access to the
+/// `World` or the `Lazy` resource.
+///
+/// In contrast to `Lazy` the buffer is a plain value, so it can be filled on
+/// any thread or task (for example in a network or message handler) and is
+/// then passed to `World::apply`. The commands are applied in the order they
+/// were added to the buffer.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{world::CommandBuffer, *};
+/// #
+/// #[derive(Debug, PartialEq)]
+/// struct Pos(u32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// #[derive(Default)]
+/// struct Score(u32);
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// world.register_component::<Pos>();
+///
+/// let existing = world.create_entity().with(Pos(1)).build();
+///
+/// let buffer = tokio::spawn(async move {
+///     let mut buffer = CommandBuffer::new();
+///     buffer.remove::<Pos>(existing);
+///     buffer.insert_resource(Score(10));
+///
+///     let created = buffer.create_entity().with(Pos(2)).build();
+///
+///     (buffer, created)
+/// });
+///
+/// let (buffer, created) = buffer.await.unwrap();
+/// world.apply(buffer);
+///
+/// let created = created.get().unwrap();
+/// assert_eq!(world.component::<Pos>().get(existing), None);
+/// assert_eq!(world.component::<Pos>().get(created), Some(&Pos(2)));
+/// assert_eq!(world.resource::<Score>().0, 10);
+/// # }
+/// ```
+#[derive(Default)]
+pub struct CommandBuffer {
+    commands: Vec<BufferedCommand>,
+}
+
+impl CommandBuffer {
+    /// Create a new empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of buffered commands.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns `true` if no command is buffered.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Moves all commands of `other` to the end of this buffer.
+    pub fn append(&mut self, other: &mut CommandBuffer) {
+        self.commands.append(&mut other.commands);
+    }
+
+    /// Buffers a closure that is executed with world access.
+    pub fn exec<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut World) + Send + Sync + 'static,
+    {
+        self.commands.push(Box::new(f));
+    }
+
+    /// Creates a new `CommandBuilder` that creates an entity when the buffer
+    /// is applied. The returned `DeferredEntity` is resolved to the created
+    /// entity by `World::apply`.
+    pub fn create_entity(&mut self) -> CommandBuilder<'_> {
+        CommandBuilder {
+            buffer: self,
+            entity: DeferredEntity::default(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Buffers the deletion of an entity. The components of the entity are
+    /// removed immediately when the buffer is applied.
+    pub fn delete_entity(&mut self, entity: Entity) {
+        self.exec(move |world| {
+            if world.delete_entity(entity).is_err() {
+                warn!("Buffered delete failed because {:?} was dead.", entity);
+            }
+        });
+    }
+
+    /// Buffers the insertion of a component for an entity.
+    ///
+    /// If a component was already associated with the entity, it will
+    /// overwrite the previous component.
+    pub fn insert<C>(&mut self, entity: Entity, component: C)
+    where
+        C: Component + Send + Sync,
+    {
+        self.exec(move |world| {
+            if world.is_alive(entity) {
+                world.record(|log| log.insert(entity, &component));
+            }
+
+            let mut storage = world.component_mut::<C>();
+
+            if storage.insert(entity, component).is_err() {
+                warn!("Buffered insert failed because {:?} was dead.", entity);
+            }
+        });
+    }
+
+    /// Buffers the removal of a component from an entity.
+    pub fn remove<C>(&mut self, entity: Entity)
+    where
+        C: Component,
+    {
+        self.exec(move |world| {
+            world.record(|log| log.remove::<C>(entity));
+
+            world.component_mut::<C>().remove(entity);
+        });
+    }
+
+    /// Buffers the insertion of a resource. An existing resource of the
+    /// same type is replaced.
+    pub fn insert_resource<R>(&mut self, resource: R)
+    where
+        R: Resource,
+    {
+        self.exec(move |world| world.insert(resource));
+    }
+}
+
+impl Debug for CommandBuffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("CommandBuffer")
+            .field("len", &self.commands.len())
+            .finish()
+    }
+}
+
+impl World {
+    /// Applies all commands of the passed buffer in the order they were
+    /// added. See `CommandBuffer` for details.
+    pub fn apply(&mut self, buffer: CommandBuffer) {
+        for command in buffer.commands {
+            command(self);
+        }
+    }
+}
+
+/* CommandBuilder */
+
+/// Builder that creates an entity when the `CommandBuffer` it was created
+/// from is applied.
+///
+/// Like the `DeferredBuilder` of `Lazy`, `build` returns a `DeferredEntity`
+/// that is resolved to the created entity. Nothing is buffered until `build`
+/// is called.
+#[must_use = "the entity is only created if the builder is built"]
+pub struct CommandBuilder<'a> {
+    buffer: &'a mut CommandBuffer,
+    entity: DeferredEntity,
+    steps: Vec<Step>,
+}
+
+impl<'a> CommandBuilder<'a> {
+    /// Inserts a component for the entity.
+    ///
+    /// If a component was already associated with the entity, it will
+    /// overwrite the previous component.
+    pub fn with<C>(mut self, component: C) -> Self
+    where
+        C: Component + Send + Sync,
+    {
+        self.steps
+            .push(Box::new(move |builder| builder.with(component)));
+
+        self
+    }
+
+    /// Registers a function that is called with the entity right after it
+    /// was created.
+    pub fn with_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Entity, &World) + Send + Sync + 'static,
+    {
+        self.steps.push(Box::new(move |builder| builder.with_fn(f)));
+
+        self
+    }
+
+    /// Marks the entity with a new marker of type `M`.
+    #[cfg(feature = "serde")]
+    pub fn marked<M: Marker>(mut self) -> Self {
+        self.steps.push(Box::new(|builder| builder.marked::<M>()));
+
+        self
+    }
+
+    /// Finishes the building and returns the handle of the entity, that
+    /// is resolved when the buffer is applied.
+    pub fn build(self) -> DeferredEntity {
+        let Self {
+            buffer,
+            entity,
+            steps,
+        } = self;
+
+        let handle = entity.clone();
+
+        buffer.exec(move |world| {
+            let builder = steps
+                .into_iter()
+                .fold(world.create_entity(), |builder, step| step(builder));
+
+            handle.resolve(builder.build());
+        });
+
+        entity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::storage::VecStorage;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn commands_are_applied_in_order() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+
+        let mut buffer = CommandBuffer::new();
+        let created = buffer.create_entity().with(Pos(3)).build();
+        buffer.remove::<Pos>(e1);
+        buffer.insert(e1, Pos(4));
+        buffer.delete_entity(e2);
+
+        let handle = created.clone();
+        let mut other = CommandBuffer::new();
+        other.exec(move |world| {
+            let created = handle.get().unwrap();
+
+            world.component_mut::<Pos>().get_mut(created).unwrap().0 += 1;
+        });
+        buffer.append(&mut other);
+
+        assert_eq!(buffer.len(), 5);
+        assert!(other.is_empty());
+        assert!(!created.is_resolved());
+
+        world.apply(buffer);
+
+        let pos = world.component::<Pos>();
+        assert!(!world.is_alive(e2));
+        assert_eq!(pos.get(e1), Some(&Pos(4)));
+        assert_eq!(pos.get(created.get().unwrap()), Some(&Pos(4)));
+        assert_eq!(pos.count(), 2);
+    }
+}
@@ -0,0 +1,180 @@
+use hibitset::BitSet;
+
+use crate::{
+    access::{ReadStorage, WriteStorage},
+    component::{Component, DynamicId},
+    resource::ResourceId,
+    storage::MaskedStorage,
+};
+
+use super::World;
+
+impl World {
+    /// Registers a dynamic storage of the component `T` with the passed id.
+    ///
+    /// In contrast to `World::register_component`, many storages of the same
+    /// component type can be registered, as long as they use different ids.
+    /// Each storage is a separate resource (see `DynamicId::resource_id`),
+    /// so systems that access different dynamic storages may run in
+    /// parallel. Use `DynamicStorages` to access them in a system.
+    ///
+    /// Nothing happens if the storage was already registered.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// #[derive(Debug, PartialEq)]
+    /// struct Value(f32);
+    ///
+    /// impl Component for Value {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::default();
+    /// world.register_dynamic_component::<Value, _>("health");
+    /// world.register_dynamic_component::<Value, _>("mana");
+    ///
+    /// let entity = world.create_entity().build();
+    ///
+    /// world
+    ///     .dynamic_component_mut::<Value, _>("health")
+    ///     .insert(entity, Value(100.0))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     world.dynamic_component::<Value, _>("health").get(entity),
+    ///     Some(&Value(100.0))
+    /// );
+    /// assert_eq!(world.dynamic_component::<Value, _>("mana").get(entity), None);
+    /// ```
+    pub fn register_dynamic_component<T, I>(&mut self, id: I)
+    where
+        T: Component,
+        T::Storage: Default,
+        I: Into<DynamicId>,
+    {
+        let id = id.into().resource_id::<T>();
+        if self.contains_by_id(&id) {
+            return;
+        }
+
+        self.insert_by_id(id.clone(), MaskedStorage::<T>::new(Default::default()));
+        self.entry::<DynamicStorageRegistry>()
+            .or_insert_with(Default::default)
+            .0
+            .push((id, drop_components::<T>));
+    }
+
+    /// Fetches the dynamic storage of the component `T` with the passed id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the storage was not registered or is borrowed mutably.
+    pub fn dynamic_component<T, I>(&self, id: I) -> ReadStorage<'_, T>
+    where
+        T: Component,
+        I: Into<DynamicId>,
+    {
+        let id = id.into().resource_id::<T>();
+
+        ReadStorage::new(self.borrow_by_id(&id), self.borrow())
+    }
+
+    /// Fetches the dynamic storage of the component `T` with the passed id
+    /// mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the storage was not registered or is already borrowed.
+    pub fn dynamic_component_mut<T, I>(&self, id: I) -> WriteStorage<'_, T>
+    where
+        T: Component,
+        I: Into<DynamicId>,
+    {
+        let id = id.into().resource_id::<T>();
+
+        WriteStorage::new(self.borrow_mut_by_id(&id), self.borrow())
+    }
+
+    /// Removes the components of the passed entities from all dynamic
+    /// storages.
+    pub(super) fn drop_dynamic_components(&self, entities: &BitSet) {
+        if let Some(registry) = self.try_borrow::<DynamicStorageRegistry>() {
+            for (id, drop) in &registry.0 {
+                drop(self, id, entities);
+            }
+        }
+    }
+}
+
+/// Keeps track of the registered dynamic storages, so the components of
+/// deleted entities can be removed from them.
+#[derive(Default)]
+struct DynamicStorageRegistry(Vec<(ResourceId, DropFn)>);
+
+type DropFn = fn(&World, &ResourceId, &BitSet);
+
+fn drop_components<T>(world: &World, id: &ResourceId, entities: &BitSet)
+where
+    T: Component,
+{
+    world
+        .borrow_mut_by_id::<MaskedStorage<T>>(id)
+        .drop_many(entities);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{entity::Builder, storage::VecStorage};
+
+    #[derive(Debug, PartialEq)]
+    struct Value(u32);
+
+    impl Component for Value {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn delete_entities() {
+        let mut world = World::default();
+        world.register_component::<Value>();
+        world.register_dynamic_component::<Value, _>(1);
+        world.register_dynamic_component::<Value, _>("name");
+
+        let e1 = world.create_entity().with(Value(0)).build();
+        let e2 = world.create_entity().build();
+
+        world
+            .dynamic_component_mut::<Value, _>(1)
+            .insert(e1, Value(1))
+            .unwrap();
+        world
+            .dynamic_component_mut::<Value, _>("name")
+            .insert(e2, Value(2))
+            .unwrap();
+
+        assert_eq!(world.component::<Value>().count(), 1);
+        assert_eq!(
+            world.dynamic_component::<Value, _>(1).get(e1),
+            Some(&Value(1))
+        );
+
+        world.delete_entities(&[e1, e2]).unwrap();
+
+        assert!(world.component::<Value>().is_empty());
+        assert!(world.dynamic_component::<Value, _>(1).is_empty());
+        assert!(world.dynamic_component::<Value, _>("name").is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Dynamic component id must not be zero")]
+    fn zero_id() {
+        let mut world = World::default();
+        world.register_component::<Value>();
+        world.register_dynamic_component::<Value, _>(0);
+    }
+}
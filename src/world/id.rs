@@ -0,0 +1,92 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{error::Error, resource::ResourceId, system::SystemData};
+
+use super::World;
+
+/// Unique id of a `World` instance.
+///
+/// The id is assigned when the world is created and never reused. A
+/// dispatcher can be dispatched against different worlds, so systems that
+/// cache state related to the world (like event readers) can use the id to
+/// find out which world they are currently serving.
+///
+/// `WorldId` implements `SystemData`, it does not borrow any resource.
+///
+/// ## Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// #
+/// # use async_ecs::{world::WorldId, *};
+/// #
+/// #[derive(Default)]
+/// struct Runs(HashMap<WorldId, usize>);
+///
+/// impl<'a> System<'a> for Runs {
+///     type SystemData = WorldId;
+///
+///     fn run(&mut self, id: WorldId) {
+///         *self.0.entry(id).or_default() += 1;
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut a = World::default();
+/// let mut b = World::default();
+/// assert_ne!(a.id(), b.id());
+///
+/// let mut dispatcher = Dispatcher::setup_builder(&mut a)
+///     .with(Runs::default(), "runs", &[])
+///     .unwrap()
+///     .build();
+/// dispatcher.setup(&mut b).await.unwrap();
+///
+/// dispatcher.dispatch(&a).await.unwrap();
+/// dispatcher.dispatch(&b).await.unwrap();
+/// dispatcher.dispatch(&a).await.unwrap();
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct WorldId(u64);
+
+impl WorldId {
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Returns the raw value of the id.
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for WorldId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'a> SystemData<'a> for WorldId {
+    fn setup(_: &mut World) {}
+
+    fn fetch(world: &'a World) -> Self {
+        world.id()
+    }
+
+    fn try_fetch(world: &'a World) -> Result<Self, Error> {
+        Ok(world.id())
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        vec![]
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        vec![]
+    }
+}
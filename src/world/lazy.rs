@@ -1,13 +1,19 @@
-use std::sync::Arc;
+use std::any::{Any, TypeId};
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::sync::{Arc, Mutex};
 
 use crossbeam_queue::SegQueue;
 use futures::future::BoxFuture;
+use hashbrown::HashMap;
 use log::warn;
 
+#[cfg(feature = "serde")]
+use crate::saveload::{Marker, MarkerAllocator};
 use crate::{
     access::WriteStorage,
     component::Component,
-    entity::{Builder, Entity},
+    entity::{Builder, Entity, EntityBuilder},
     system::SystemData,
 };
 
@@ -20,13 +26,96 @@ use super::World;
 /// Lazy updates are dispatched in the order that they are requested. Multiple updates
 /// sent from one system may be overridden by updates sent from other systems.
 ///
+/// Inserts and removes of components are batched by the type of the component,
+/// so each storage is only fetched once for all queued updates between two
+/// calls to `exec` or `exec_async`.
+///
+/// Each `Lazy` queues its updates in a `LazyStage`. The `Lazy` resource of the
+/// world uses `LazyStage::DEFAULT`, use `Lazy::stage` to get a handle for
+/// another stage. On `maintain` the stages are executed in ascending order,
+/// the updates of a single stage are executed in the order they were
+/// requested. A single stage can be executed using `World::flush_lazy_stage`.
+///
 /// Please note that the provided methods take `&self` so there's no need to get
 /// `Lazy` mutably. This resource is added to the world by default.
 pub struct Lazy {
+    stage: LazyStage,
     queue: Arc<SegQueue<LazyUpdate>>,
+    stages: Arc<Mutex<BTreeMap<LazyStage, Arc<SegQueue<LazyUpdate>>>>>,
+}
+
+/// Stage of the lazy updates queued by a `Lazy`. Stages with a lower value are
+/// executed first.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::{world::LazyStage, *};
+/// #
+/// struct Pos(u32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// world.register_component::<Pos>();
+///
+/// let entity = world.create_entity().with(Pos(1)).build();
+///
+/// {
+///     let lazy = world.resource::<Lazy>();
+///
+///     // queued first, but executed after the removal
+///     lazy.insert(entity, Pos(2));
+///     lazy.stage(LazyStage::EARLY).remove::<Pos>(entity);
+/// }
+///
+/// world.maintain().await;
+///
+/// assert_eq!(world.component::<Pos>().get(entity).unwrap().0, 2);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LazyStage(pub i32);
+
+impl LazyStage {
+    /// Stage that is executed before the default stage, for example to apply
+    /// removals before inserts.
+    pub const EARLY: Self = Self(-100);
+
+    /// Stage of the `Lazy` resource of the world.
+    pub const DEFAULT: Self = Self(0);
+
+    /// Stage that is executed after the default stage.
+    pub const LATE: Self = Self(100);
 }
 
 impl Lazy {
+    /// Returns a handle that queues its updates in the passed stage.
+    pub fn stage(&self, stage: LazyStage) -> Lazy {
+        let queue = self
+            .stages
+            .lock()
+            .unwrap()
+            .entry(stage)
+            .or_default()
+            .clone();
+
+        Self {
+            stage,
+            queue,
+            stages: self.stages.clone(),
+        }
+    }
+
+    /// Returns the stage this handle queues its updates in.
+    pub fn current_stage(&self) -> LazyStage {
+        self.stage
+    }
+
     /// Lazily executes a closure with world access.
     ///
     /// ## Examples
@@ -99,13 +188,7 @@ impl Lazy {
     where
         C: Component + Send + Sync,
     {
-        self.exec(move |world| {
-            let mut storage: WriteStorage<C> = SystemData::fetch(world);
-
-            if storage.insert(e, c).is_err() {
-                warn!("Lazy insert of component failed because {:?} was dead.", e);
-            }
-        });
+        self.component(move |batches| batches.get::<C>().push(ComponentUpdate::Insert(e, c)));
     }
 
     /// Lazily inserts components for entities.
@@ -139,14 +222,10 @@ impl Lazy {
         C: Component + Send + Sync,
         I: IntoIterator<Item = (Entity, C)> + Send + Sync + 'static,
     {
-        self.exec(move |world| {
-            let mut storage: WriteStorage<C> = SystemData::fetch(world);
-
-            for (e, c) in iter {
-                if storage.insert(e, c).is_err() {
-                    log::warn!("Lazy insert of component failed because {:?} was dead.", e);
-                }
-            }
+        self.component(move |batches| {
+            batches
+                .get::<C>()
+                .extend(iter.into_iter().map(|(e, c)| ComponentUpdate::Insert(e, c)))
         });
     }
 
@@ -179,11 +258,7 @@ impl Lazy {
     where
         C: Component,
     {
-        self.exec(move |world| {
-            let mut storage: WriteStorage<C> = SystemData::fetch(world);
-
-            storage.remove(e);
-        });
+        self.component(move |batches| batches.get::<C>().push(ComponentUpdate::Remove(e)));
     }
 
     /// Lazily removes a component.
@@ -216,12 +291,10 @@ impl Lazy {
         C: Component,
         I: IntoIterator<Item = Entity> + Send + Sync + 'static,
     {
-        self.exec(move |world| {
-            let mut storage: WriteStorage<C> = SystemData::fetch(world);
-
-            for e in iter {
-                storage.remove(e);
-            }
+        self.component(move |batches| {
+            batches
+                .get::<C>()
+                .extend(iter.into_iter().map(ComponentUpdate::Remove))
         });
     }
 
@@ -250,21 +323,127 @@ impl Lazy {
         LazyBuilder { entity, lazy: self }
     }
 
-    /// Executes all stored lazy updates
+    /// Creates a new `DeferredBuilder` that creates the entity lazily.
+    ///
+    /// In contrast to `create_entity` the entity is not allocated until
+    /// `maintain` on `World` is performed, so no access to the `World` or
+    /// the `Entities` is needed. The returned `DeferredEntity` is resolved
+    /// to the created entity as soon as it was created.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::{system::SystemData, world::DeferredEntity, *};
+    /// #
+    /// struct Pos(f32, f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct Spawn(Vec<DeferredEntity>);
+    ///
+    /// impl<'a> System<'a> for Spawn {
+    ///     type SystemData = Read<'a, Lazy>;
+    ///
+    ///     fn run(&mut self, lazy: Self::SystemData) {
+    ///         let entity = lazy.create_entity_deferred().with(Pos(1.0, 3.0)).build();
+    ///
+    ///         self.0.push(entity);
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut world = World::default();
+    /// world.register_component::<Pos>();
+    ///
+    /// let mut spawn = Spawn::default();
+    /// spawn.run(SystemData::fetch(&world));
+    ///
+    /// let entity = &spawn.0[0];
+    /// assert_eq!(entity.get(), None);
+    ///
+    /// world.maintain().await;
+    ///
+    /// let entity = entity.get().unwrap();
+    /// assert!(world.is_alive(entity));
+    /// assert!(world.component::<Pos>().contains(entity));
+    /// # }
+    /// ```
+    pub fn create_entity_deferred(&self) -> DeferredBuilder<'_> {
+        DeferredBuilder {
+            lazy: self,
+            entity: DeferredEntity::default(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Executes all stored lazy updates, stage by stage.
     pub async fn maintain(&self, world: &mut World) {
-        while let Some(update) = self.queue.pop() {
+        let stages = self
+            .stages
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for queue in stages {
+            Self::execute(&queue, world).await;
+        }
+    }
+
+    /// Executes the stored lazy updates of the passed stage.
+    pub async fn maintain_stage(&self, stage: LazyStage, world: &mut World) {
+        let queue = self.stages.lock().unwrap().get(&stage).cloned();
+
+        if let Some(queue) = queue {
+            Self::execute(&queue, world).await;
+        }
+    }
+
+    async fn execute(queue: &SegQueue<LazyUpdate>, world: &mut World) {
+        let mut batches = Batches::default();
+
+        while let Some(update) = queue.pop() {
             match update {
-                LazyUpdate::Sync(update) => update(world),
-                LazyUpdate::Async(update) => update(world).await,
+                LazyUpdate::Sync(update) => {
+                    batches.apply(world);
+
+                    update(world);
+                }
+                LazyUpdate::Async(update) => {
+                    batches.apply(world);
+
+                    update(world).await;
+                }
+                LazyUpdate::Component(update) => update(&mut batches),
             }
         }
+
+        batches.apply(world);
+    }
+
+    fn component<F>(&self, f: F)
+    where
+        F: FnOnce(&mut Batches) + Send + Sync + 'static,
+    {
+        self.queue.push(LazyUpdate::Component(Box::new(f)));
     }
 }
 
 impl Default for Lazy {
     fn default() -> Self {
+        let queue = Arc::new(SegQueue::new());
+        let mut stages = BTreeMap::new();
+        stages.insert(LazyStage::DEFAULT, queue.clone());
+
         Self {
-            queue: Arc::new(SegQueue::new()),
+            stage: LazyStage::DEFAULT,
+            queue,
+            stages: Arc::new(Mutex::new(stages)),
         }
     }
 }
@@ -272,7 +451,9 @@ impl Default for Lazy {
 impl Clone for Lazy {
     fn clone(&self) -> Self {
         Self {
+            stage: self.stage,
             queue: self.queue.clone(),
+            stages: self.stages.clone(),
         }
     }
 }
@@ -280,6 +461,82 @@ impl Clone for Lazy {
 enum LazyUpdate {
     Sync(Box<dyn FnOnce(&mut World) + Send + Sync + 'static>),
     Async(Box<dyn FnOnce(&mut World) -> BoxFuture<'static, ()> + Send + Sync + 'static>),
+    Component(Box<dyn FnOnce(&mut Batches) + Send + Sync + 'static>),
+}
+
+/* Batches */
+
+/// Queued component updates, grouped by the type of the component.
+#[derive(Default)]
+struct Batches {
+    batches: Vec<Box<dyn Batch>>,
+    indices: HashMap<TypeId, usize>,
+}
+
+impl Batches {
+    /// Returns the batch of updates for the component `C`.
+    fn get<C: Component>(&mut self) -> &mut Vec<ComponentUpdate<C>> {
+        let batches = &mut self.batches;
+        let index = *self.indices.entry(TypeId::of::<C>()).or_insert_with(|| {
+            batches.push(Box::new(Vec::<ComponentUpdate<C>>::new()));
+
+            batches.len() - 1
+        });
+
+        self.batches[index].as_any_mut().downcast_mut().unwrap()
+    }
+
+    /// Applies all batches in the order they were created.
+    fn apply(&mut self, world: &World) {
+        self.indices.clear();
+
+        for batch in self.batches.drain(..) {
+            batch.apply(world);
+        }
+    }
+}
+
+trait Batch {
+    fn apply(self: Box<Self>, world: &World);
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+enum ComponentUpdate<C> {
+    Insert(Entity, C),
+    Remove(Entity),
+}
+
+impl<C> Batch for Vec<ComponentUpdate<C>>
+where
+    C: Component,
+{
+    fn apply(self: Box<Self>, world: &World) {
+        let mut storage: WriteStorage<C> = SystemData::fetch(world);
+
+        for update in *self {
+            match update {
+                ComponentUpdate::Insert(e, c) => {
+                    if world.is_alive(e) {
+                        world.record(|log| log.insert(e, &c));
+                    }
+
+                    if storage.insert(e, c).is_err() {
+                        warn!("Lazy insert of component failed because {:?} was dead.", e);
+                    }
+                }
+                ComponentUpdate::Remove(e) => {
+                    world.record(|log| log.remove::<C>(e));
+
+                    storage.remove(e);
+                }
+            }
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
 }
 
 /* LazyBuilder */
@@ -302,16 +559,34 @@ impl<'a> Builder for LazyBuilder<'a> {
     where
         C: Component + Send + Sync,
     {
+        self.lazy.insert(self.entity, component);
+
+        self
+    }
+
+    /// Registers a function using [Lazy], so it is called with the entity
+    /// on [`World::maintain`], after all previously queued updates.
+    fn with_fn<F>(self, f: F) -> Self
+    where
+        F: FnOnce(Entity, &World) + Send + Sync + 'static,
+    {
+        let entity = self.entity;
+
+        self.lazy.exec(move |world| f(entity, world));
+
+        self
+    }
+
+    /// Marks the entity with a new marker of type `M` using [Lazy].
+    #[cfg(feature = "serde")]
+    fn marked<M: Marker>(self) -> Self {
         let entity = self.entity;
+
         self.lazy.exec(move |world| {
-            let mut storage: WriteStorage<C> = SystemData::fetch(world);
+            let mut allocator = world.resource_mut::<M::Allocator>();
+            let mut storage = WriteStorage::<M>::fetch(world);
 
-            if storage.insert(entity, component).is_err() {
-                warn!(
-                    "Lazy insert of component failed because {:?} was dead.",
-                    entity
-                );
-            }
+            allocator.mark(entity, &mut storage);
         });
 
         self
@@ -324,3 +599,231 @@ impl<'a> Builder for LazyBuilder<'a> {
         self.entity
     }
 }
+
+/* DeferredBuilder */
+
+pub(super) type Step = Box<dyn for<'w> FnOnce(EntityBuilder<'w>) -> EntityBuilder<'w> + Send + Sync>;
+
+/// Builder that creates an entity lazily, meaning on `maintain`.
+///
+/// Other than `LazyBuilder` the entity itself is not allocated until
+/// the builder is applied, so `build` returns a `DeferredEntity` that is
+/// resolved to the created entity on `maintain`. Nothing is queued until
+/// `build` is called.
+#[must_use = "the entity is only created if the builder is built"]
+pub struct DeferredBuilder<'a> {
+    lazy: &'a Lazy,
+    entity: DeferredEntity,
+    steps: Vec<Step>,
+}
+
+impl<'a> DeferredBuilder<'a> {
+    /// Inserts a component for the entity.
+    ///
+    /// If a component was already associated with the entity, it will
+    /// overwrite the previous component.
+    pub fn with<C>(mut self, component: C) -> Self
+    where
+        C: Component + Send + Sync,
+    {
+        self.steps
+            .push(Box::new(move |builder| builder.with(component)));
+
+        self
+    }
+
+    /// Registers a function that is called with the entity right after it
+    /// was created on `maintain`.
+    pub fn with_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Entity, &World) + Send + Sync + 'static,
+    {
+        self.steps.push(Box::new(move |builder| builder.with_fn(f)));
+
+        self
+    }
+
+    /// Marks the entity with a new marker of type `M`.
+    #[cfg(feature = "serde")]
+    pub fn marked<M: Marker>(mut self) -> Self {
+        self.steps.push(Box::new(|builder| builder.marked::<M>()));
+
+        self
+    }
+
+    /// Finishes the building and returns the handle of the entity, that
+    /// is resolved on `maintain`.
+    pub fn build(self) -> DeferredEntity {
+        let Self {
+            lazy,
+            entity,
+            steps,
+        } = self;
+
+        let handle = entity.clone();
+
+        lazy.exec(move |world| {
+            let builder = steps
+                .into_iter()
+                .fold(world.create_entity(), |builder, step| step(builder));
+
+            handle.resolve(builder.build());
+        });
+
+        entity
+    }
+}
+
+/* DeferredEntity */
+
+/// Handle of an entity that is created by a `DeferredBuilder`.
+///
+/// The handle is resolved to the created entity on `maintain`, it can be
+/// cloned and shared with other systems or lazy updates.
+#[derive(Default, Clone)]
+pub struct DeferredEntity(Arc<Mutex<Option<Entity>>>);
+
+impl DeferredEntity {
+    /// Returns the created entity, or `None` if the entity was not created
+    /// yet.
+    pub fn get(&self) -> Option<Entity> {
+        *self.0.lock().unwrap()
+    }
+
+    /// Returns `true` if the entity was already created.
+    pub fn is_resolved(&self) -> bool {
+        self.get().is_some()
+    }
+
+    pub(super) fn resolve(&self, entity: Entity) {
+        *self.0.lock().unwrap() = Some(entity);
+    }
+}
+
+impl Debug for DeferredEntity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("DeferredEntity").field(&self.get()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::storage::VecStorage;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Vel(u32);
+
+    impl Component for Vel {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[tokio::test]
+    async fn batched_updates_keep_order() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Vel>();
+
+        let lazy = Lazy::clone(&world.resource::<Lazy>());
+        let entities = (0..10)
+            .map(|i| lazy.create_entity(&world).with(Pos(i)).with(Vel(i)).build())
+            .collect::<Vec<_>>();
+
+        let e0 = entities[0];
+        let e1 = entities[1];
+
+        lazy.remove::<Pos>(e0);
+        lazy.insert(e0, Pos(100));
+        lazy.remove_many::<Vel, _>(vec![e0, e1]);
+        lazy.exec(move |world| {
+            // all updates queued before are already applied
+            assert_eq!(world.component::<Pos>().get(e0), Some(&Pos(100)));
+            assert_eq!(world.component::<Vel>().get(e1), None);
+        });
+        lazy.insert_many(vec![(e1, Vel(101))]);
+        lazy.remove::<Pos>(e1);
+
+        world.maintain().await;
+
+        let pos = world.component::<Pos>();
+        let vel = world.component::<Vel>();
+
+        assert_eq!(pos.count(), 9);
+        assert_eq!(vel.count(), 9);
+        assert_eq!(pos.get(e0), Some(&Pos(100)));
+        assert_eq!(pos.get(e1), None);
+        assert_eq!(vel.get(e0), None);
+        assert_eq!(vel.get(e1), Some(&Vel(101)));
+        assert_eq!(pos.get(entities[9]), Some(&Pos(9)));
+    }
+
+    #[tokio::test]
+    async fn deferred_entity() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Vel>();
+
+        let lazy = Lazy::clone(&world.resource::<Lazy>());
+        let entity = lazy
+            .create_entity_deferred()
+            .with(Pos(1))
+            .with_fn(|entity, world| {
+                world.lazy().insert(entity, Vel(2));
+            })
+            .build();
+        let handle = entity.clone();
+        lazy.exec(move |world| {
+            let entity = handle.get().unwrap();
+
+            assert_eq!(world.component::<Pos>().get(entity), Some(&Pos(1)));
+        });
+
+        assert!(!entity.is_resolved());
+        assert_eq!(world.entities().len(), 0);
+
+        world.maintain().await;
+
+        let entity = entity.get().unwrap();
+
+        assert!(world.is_alive(entity));
+        assert_eq!(world.component::<Pos>().get(entity), Some(&Pos(1)));
+        assert_eq!(world.component::<Vel>().get(entity), Some(&Vel(2)));
+    }
+
+    #[tokio::test]
+    async fn stages() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let entity = world.create_entity().with(Pos(1)).build();
+
+        let lazy = Lazy::clone(&world.resource::<Lazy>());
+        let late = lazy.stage(LazyStage::LATE);
+
+        late.insert(entity, Pos(3));
+        lazy.insert(entity, Pos(2));
+        lazy.stage(LazyStage::EARLY).remove::<Pos>(entity);
+
+        world.flush_lazy_stage(LazyStage::EARLY).await;
+        assert_eq!(world.component::<Pos>().get(entity), None);
+
+        world.flush_lazy_stage(LazyStage::DEFAULT).await;
+        assert_eq!(world.component::<Pos>().get(entity), Some(&Pos(2)));
+
+        late.exec(move |world| {
+            assert_eq!(world.component::<Pos>().get(entity), Some(&Pos(3)));
+        });
+        lazy.remove::<Pos>(entity);
+
+        world.maintain().await;
+        assert_eq!(world.component::<Pos>().get(entity), Some(&Pos(3)));
+    }
+}
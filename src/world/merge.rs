@@ -0,0 +1,189 @@
+use hashbrown::HashMap;
+
+use crate::{entity::Entity, join::Join};
+
+use super::{AnyStorage, MetaTable, World};
+
+/// Maps the entities of a merged world to the entities that were created for
+/// them in the target world. Returned by `World::merge` and
+/// `World::merge_entities`.
+///
+/// Components that reference other entities are not updated during the
+/// merge, use this map to remap them afterwards.
+#[derive(Default, Debug, Clone)]
+pub struct EntityMap(HashMap<Entity, Entity>);
+
+impl EntityMap {
+    /// Returns the entity in the target world for the passed entity of the
+    /// merged world.
+    pub fn get(&self, source: Entity) -> Option<Entity> {
+        self.0.get(&source).copied()
+    }
+
+    /// Returns the number of merged entities.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if no entity was merged.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn insert(&mut self, source: Entity, target: Entity) {
+        self.0.insert(source, target);
+    }
+
+    /// Iterates over all pairs of entities, with the entity of the merged
+    /// world first.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.0.iter().map(|(source, target)| (*source, *target))
+    }
+}
+
+impl World {
+    /// Merges the passed world into this one.
+    ///
+    /// All entities of `other` are created in this world, together with
+    /// their components. Storages that are not registered in this world yet
+    /// are registered automatically. Resources of `other` are only moved if
+    /// this world does not contain a resource of the same type.
+    ///
+    /// Pending changes of `other` (atomically created or deleted entities
+    /// and lazy updates) are not merged, call `World::maintain` on `other`
+    /// before merging it.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Pos(u32);
+    /// # impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// #
+    /// let mut world = World::default();
+    /// world.register_component::<Pos>();
+    /// world.create_entity().with(Pos(1)).build();
+    ///
+    /// let mut chunk = World::default();
+    /// chunk.register_component::<Pos>();
+    /// let e = chunk.create_entity().with(Pos(2)).build();
+    ///
+    /// let entities = world.merge(chunk);
+    ///
+    /// let e = entities.get(e).unwrap();
+    /// assert_eq!(world.component::<Pos>().get(e), Some(&Pos(2)));
+    /// assert_eq!(world.component::<Pos>().count(), 2);
+    /// ```
+    pub fn merge(&mut self, mut other: World) -> EntityMap {
+        let entities = (&other.entities()).join().collect::<Vec<_>>();
+        let map = self.merge_entities(&mut other, &entities);
+
+        self.0.merge(other.0);
+
+        map
+    }
+
+    /// Moves the passed entities of `other` into this world, together with
+    /// their components. Storages that are not registered in this world yet
+    /// are registered automatically.
+    ///
+    /// The entities are deleted from `other`. Entities that are not alive
+    /// are skipped.
+    pub fn merge_entities(&mut self, other: &mut World, entities: &[Entity]) -> EntityMap {
+        let mut map = EntityMap::default();
+
+        for entity in entities {
+            if other.is_alive(*entity) && !map.0.contains_key(entity) {
+                map.0.insert(*entity, self.entities_mut().allocate());
+            }
+        }
+
+        other
+            .entry::<MetaTable<dyn AnyStorage>>()
+            .or_insert_with(Default::default);
+
+        for storage in other
+            .resource_mut::<MetaTable<dyn AnyStorage>>()
+            .iter_mut(other)
+        {
+            storage.move_to(self, &map);
+        }
+
+        let moved = map.0.keys().copied().collect::<Vec<_>>();
+        other
+            .delete_entities(&moved)
+            .expect("Merged entities are alive");
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{component::Component, entity::Builder, storage::VecStorage};
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Vel(u32);
+
+    impl Component for Vel {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    #[test]
+    fn merge_entities() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.insert(Name("world"));
+
+        let existing = world.create_entity().with(Pos(0)).build();
+
+        let mut chunk = World::default();
+        chunk.register_component::<Pos>();
+        chunk.register_component::<Vel>();
+
+        let e1 = chunk.create_entity().with(Pos(1)).with(Vel(1)).build();
+        let e2 = chunk.create_entity().with(Pos(2)).build();
+        let e3 = chunk.create_entity().with(Vel(3)).build();
+
+        let map = world.merge_entities(&mut chunk, &[e1, e3]);
+
+        assert_eq!(map.len(), 2);
+        assert!(!chunk.is_alive(e1));
+        assert!(chunk.is_alive(e2));
+        assert_eq!(chunk.component::<Pos>().count(), 1);
+        assert!(chunk.component::<Vel>().is_empty());
+
+        let m1 = map.get(e1).unwrap();
+        let m3 = map.get(e3).unwrap();
+        assert_eq!(world.component::<Pos>().get(existing), Some(&Pos(0)));
+        assert_eq!(world.component::<Pos>().get(m1), Some(&Pos(1)));
+        assert_eq!(world.component::<Vel>().get(m1), Some(&Vel(1)));
+        assert_eq!(world.component::<Vel>().get(m3), Some(&Vel(3)));
+
+        chunk.insert(Name("chunk"));
+        chunk.insert(5u32);
+
+        let map = world.merge(chunk);
+        let m2 = map.get(e2).unwrap();
+
+        assert_eq!(map.len(), 1);
+        assert_eq!(world.component::<Pos>().get(m2), Some(&Pos(2)));
+        assert_eq!(world.component::<Pos>().count(), 3);
+        assert_eq!(*world.resource::<Name>(), Name("world"));
+        assert_eq!(*world.resource::<u32>(), 5);
+    }
+}
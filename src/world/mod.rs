@@ -1,23 +1,60 @@
+mod clone;
+mod commands;
+mod dynamic;
+mod id;
 mod lazy;
+mod merge;
 mod meta;
+mod record;
+mod register;
 mod setup;
+mod singleton;
+mod snapshot;
+mod time;
+mod view;
 
 pub use self::meta::{CastFrom, MetaTable};
-pub use lazy::Lazy;
-pub use setup::{DefaultSetupHandler, PanicHandler, SetupHandler};
+pub use clone::CloneStorage;
+pub use commands::{CommandBuffer, CommandBuilder};
+pub use id::WorldId;
+pub use lazy::{DeferredBuilder, DeferredEntity, Lazy, LazyBuilder, LazyStage};
+pub use merge::EntityMap;
+pub use record::{Command, CommandLog, ComponentType, ComponentValue, Record};
+pub use register::RegistrationBuilder;
+pub use setup::{
+    DefaultSetupHandler, FnSetupHandler, PanicHandler, SetupHandler, SetupHandlerWith,
+};
+pub use singleton::SingletonEntity;
+pub use snapshot::WorldSnapshot;
+pub use time::Time;
+pub use view::{AnyInspect, ComponentView, WorldView};
 
+use std::any::type_name;
+use std::cmp::Reverse;
+use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 
+#[cfg(feature = "rayon")]
+use asparit::{Driver, IntoParallelIterator, ParallelIterator};
+use hibitset::BitSet;
+
 use crate::{
-    access::{Read, ReadStorage, WriteStorage},
-    component::Component,
-    entity::{Entities, Entity, EntityBuilder},
-    resource::{Cell, Ref, RefMut, Resource, ResourceId, Resources},
-    storage::MaskedStorage,
+    access::{LocalRead, LocalWrite, Read, ReadStorage, WriteStorage},
+    assets::Loader,
+    component::{Component, ComponentHooks, InspectComponent},
+    entity::{entities::Error as EntitiesError, BatchBuilder, Entities, Entity, EntityBuilder},
+    error::Error,
+    hierarchy::{Hierarchy, Parent},
+    misc::TryDefault,
+    prefab::{Prefab, PrefabStore},
+    resource::{Cell, LocalResources, Ref, RefMut, Resource, ResourceId, Resources},
+    storage::{AnyGroup, ComponentStats, Group, GroupComponents, MaskedStorage, Storage},
     system::SystemData,
 };
 
-pub struct World(Resources);
+use setup::{ResourceArgs, ResourceFactory};
+
+pub struct World(Resources, WorldId);
 
 impl World {
     pub fn register_component<T: Component>(&mut self)
@@ -32,18 +69,184 @@ impl World {
         T: Component,
         F: FnOnce() -> T::Storage,
     {
-        self.entry()
-            .or_insert_with(move || MaskedStorage::<T>::new(storage()));
+        if self.contains::<MaskedStorage<T>>() {
+            return;
+        }
+
+        self.insert(MaskedStorage::<T>::new(storage()));
         self.entry::<MetaTable<dyn AnyStorage>>()
             .or_insert_with(Default::default);
         self.resource_mut::<MetaTable<dyn AnyStorage>>()
             .register(&*self.resource::<MaskedStorage<T>>());
+
+        #[cfg(feature = "rayon")]
+        self.entry::<StorageDrops>()
+            .or_insert_with(Default::default)
+            .0
+            .push(drop_storage::<T>);
+
+        T::setup(self);
+    }
+
+    /// Registers the component `T` (if it is not registered yet) and sets
+    /// the hooks that are invoked by its storage whenever a component is
+    /// inserted, removed or dropped. See `ComponentHooks` for details.
+    pub fn register_component_with_hooks<T: Component>(&mut self, hooks: ComponentHooks<T>)
+    where
+        T::Storage: Default,
+    {
+        self.register_component::<T>();
+        self.resource_mut::<MaskedStorage<T>>().set_hooks(hooks);
+    }
+
+    /// Registers the component `T` (if it is not registered yet) and makes
+    /// it available for debug inspectors. See `InspectComponent` for details.
+    pub fn register_inspect<T: InspectComponent>(&mut self)
+    where
+        T::Storage: Default,
+    {
+        self.register_component::<T>();
+        self.entry::<MetaTable<dyn AnyInspect>>()
+            .or_insert_with(Default::default);
+        self.resource_mut::<MetaTable<dyn AnyInspect>>()
+            .register(&*self.resource::<MaskedStorage<T>>());
+    }
+
+    /// Returns a read-only view of the world, that allows debug inspectors to
+    /// iterate the entities and the fields of their inspectable components.
+    /// See `InspectComponent` for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entities are borrowed mutably.
+    pub fn fetch_all(&self) -> WorldView<'_> {
+        WorldView::new(self)
+    }
+
+    /// Registers a group of components, whose storages are packed together
+    /// to speed up joining them. The components are registered if they are
+    /// not registered yet. See `Group` for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the components is already part of another group, or
+    /// if the group contains a component more than once.
+    pub fn register_group<T: GroupComponents>(&mut self) {
+        if self.contains::<Group<T>>() {
+            return;
+        }
+
+        let components = T::components();
+        if components
+            .iter()
+            .enumerate()
+            .any(|(i, c)| components[..i].contains(c))
+        {
+            panic!(
+                "The group {} contains a component more than once!",
+                type_name::<T>()
+            );
+        }
+
+        T::register(self);
+
+        if self
+            .resource::<MetaTable<dyn AnyGroup>>()
+            .iter(self)
+            .any(|group| group.components().iter().any(|c| components.contains(c)))
+        {
+            panic!(
+                "A component of the group {} is already part of another group!",
+                type_name::<T>()
+            );
+        }
+
+        let mut group = Group::<T>::default();
+        group.pack(self);
+
+        self.insert(group);
+        self.resource_mut::<MetaTable<dyn AnyGroup>>()
+            .register(&*self.resource::<Group<T>>());
+    }
+
+    /// Packs the storages of all registered groups. This is done by
+    /// `World::maintain`, so it is only needed if components of a group were
+    /// inserted or removed after the world was maintained.
+    pub fn pack_groups(&self) {
+        for group in self.resource::<MetaTable<dyn AnyGroup>>().iter_mut(self) {
+            group.pack(self);
+        }
+    }
+
+    /// Registers the `Parent` component and the `Hierarchy` resource, that
+    /// is updated on each `World::maintain`. See `Hierarchy` for details.
+    pub fn register_hierarchy(&mut self) {
+        if self.contains::<Hierarchy>() {
+            return;
+        }
+
+        self.register_component::<Parent>();
+        self.insert(Hierarchy::new());
+        self.maintain_hierarchy();
+    }
+
+    /// Updates the `Hierarchy` with the changes of the `Parent` components.
+    /// This is done by `World::maintain`, so it is only needed if the
+    /// hierarchy is used before the world is maintained again.
+    pub fn maintain_hierarchy(&self) {
+        if let Ok(mut hierarchy) = self.try_resource_mut::<Hierarchy>() {
+            hierarchy.maintain(&self.entities(), &self.component());
+        }
+    }
+
+    /// Publishes the assets that were loaded by the `Loader` to their
+    /// `AssetStorage`. This is done by `World::maintain`.
+    pub fn maintain_assets(&mut self) {
+        let completed = match self.try_resource::<Loader>() {
+            Ok(loader) => loader.take_completed(),
+            Err(_) => return,
+        };
+
+        for publish in completed {
+            publish(self);
+        }
+    }
+
+    /// Registers a prefab with the passed name in the `PrefabStore`. The
+    /// store and the `Hierarchy` are registered if they are not registered
+    /// yet. See `Prefab` for details.
+    pub fn register_prefab<S: Into<String>>(&mut self, name: S, prefab: Prefab) {
+        self.register_hierarchy();
+        self.entry::<PrefabStore>()
+            .or_insert_with(Default::default)
+            .insert(name, prefab);
     }
 
     pub fn register_resource<T: Resource>(&mut self, res: T) {
         self.0.insert(res);
     }
 
+    /// Registers a closure that creates the resource `T`. The closure is
+    /// called by the `FnSetupHandler` the first time the resource is needed.
+    pub fn register_resource_with<T, F>(&mut self, f: F)
+    where
+        T: Resource,
+        F: FnOnce(&mut World) -> T + Send + Sync + 'static,
+    {
+        self.0.insert(ResourceFactory::<T>(Box::new(f)));
+    }
+
+    /// Registers the arguments to create the resource `T` using `From<Args>`.
+    /// The resource is created by the `SetupHandlerWith` the first time it is
+    /// needed.
+    pub fn register_resource_args<T, Args>(&mut self, args: Args)
+    where
+        T: Resource + From<Args>,
+        Args: Send + Sync + 'static,
+    {
+        self.0.insert(ResourceArgs::<T, Args>(args, PhantomData));
+    }
+
     pub fn resource<T: Resource>(&self) -> Ref<T> {
         self.0.borrow()
     }
@@ -52,10 +255,91 @@ impl World {
         self.0.borrow_mut()
     }
 
+    /// Same as `resource`, but returns an error instead of panicking if the
+    /// resource does not exist or is borrowed mutably.
+    pub fn try_resource<T: Resource>(&self) -> Result<Ref<'_, T>, Error> {
+        self.0.fetch()
+    }
+
+    /// Same as `resource_mut`, but returns an error instead of panicking if
+    /// the resource does not exist or is already borrowed.
+    pub fn try_resource_mut<T: Resource>(&self) -> Result<RefMut<'_, T>, Error> {
+        self.0.fetch_mut()
+    }
+
+    /// Inserts a resource that is not `Send` or `Sync` into the
+    /// `LocalResources` of the world. An existing local resource of the same
+    /// type is replaced.
+    ///
+    /// The local resources are bound to the thread the first of them was
+    /// inserted on. See `LocalResources` for details.
+    pub fn insert_local<T: 'static>(&mut self, res: T) {
+        self.0
+            .entry::<LocalResources>()
+            .or_insert_with(Default::default)
+            .insert(res);
+    }
+
+    /// Removes the local resource of type `T` from the world and returns it.
+    pub fn remove_local<T: 'static>(&mut self) -> Option<T> {
+        self.0.get_mut::<LocalResources>()?.remove()
+    }
+
+    pub fn local_resource<T: 'static>(&self) -> LocalRead<T> {
+        LocalRead::fetch(self)
+    }
+
+    pub fn local_resource_mut<T: 'static>(&self) -> LocalWrite<T> {
+        LocalWrite::fetch(self)
+    }
+
+    /// Temporarily removes the resource `R` from the world and passes it,
+    /// together with the world, to the passed closure. The resource is put
+    /// back into the world after the closure has finished.
+    ///
+    /// This allows to mutate a resource while accessing the rest of the
+    /// world mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist.
+    pub fn resource_scope<R, F, U>(&mut self, f: F) -> U
+    where
+        R: Resource,
+        F: FnOnce(&mut World, &mut R) -> U,
+    {
+        self.try_resource_scope(f).unwrap_or_else(|| {
+            panic!(
+                "Tried to fetch resource of type `{}` that does not exist",
+                type_name::<R>()
+            )
+        })
+    }
+
+    /// Same as `resource_scope`, but returns `None` if the resource does not
+    /// exist.
+    pub fn try_resource_scope<R, F, U>(&mut self, f: F) -> Option<U>
+    where
+        R: Resource,
+        F: FnOnce(&mut World, &mut R) -> U,
+    {
+        let mut resource = self.0.remove::<R>()?;
+        let ret = f(self, &mut resource);
+
+        self.0.insert(resource);
+
+        Some(ret)
+    }
+
     pub fn resource_raw(&self, id: &ResourceId) -> Option<&Cell<Box<dyn Resource>>> {
         self.0.get_raw(id)
     }
 
+    /// Returns the unique id of this world.
+    pub fn id(&self) -> WorldId {
+        self.1
+    }
+
     pub fn entities(&self) -> Read<Entities> {
         Read::fetch(&self)
     }
@@ -68,40 +352,277 @@ impl World {
         Read::fetch(&self)
     }
 
+    /// Returns `true` if the component `T` was registered, so its storage
+    /// can be fetched.
+    pub fn is_component_registered<T: Component>(&self) -> bool {
+        self.contains::<MaskedStorage<T>>()
+    }
+
+    /// Fetches the storage of the component `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is not registered or if its storage is
+    /// borrowed mutably.
     pub fn component<T: Component>(&self) -> ReadStorage<T> {
         ReadStorage::fetch(&self)
     }
 
+    /// Fetches the storage of the component `T` mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is not registered or if its storage is
+    /// already borrowed.
     pub fn component_mut<T: Component>(&self) -> WriteStorage<T> {
         WriteStorage::fetch(&self)
     }
 
+    /// Same as `component`, but returns an error instead of panicking if
+    /// the storage was not registered or is borrowed mutably.
+    pub fn try_component<T: Component>(&self) -> Result<ReadStorage<'_, T>, Error> {
+        ReadStorage::try_fetch(self)
+    }
+
+    /// Same as `component_mut`, but returns an error instead of panicking
+    /// if the storage was not registered or is already borrowed.
+    pub fn try_component_mut<T: Component>(&self) -> Result<WriteStorage<'_, T>, Error> {
+        WriteStorage::try_fetch(self)
+    }
+
+    /// Returns the memory usage of the storages of all registered
+    /// components, sorted by the number of allocated bytes (largest first).
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// struct Pos(f32, f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::default();
+    /// world.register_component::<Pos>();
+    /// world.create_entity().with(Pos(1.0, 2.0)).build();
+    ///
+    /// for stats in world.storage_stats() {
+    ///     println!("{}", stats);
+    /// }
+    ///
+    /// let stats = &world.storage_stats()[0];
+    /// assert!(stats.name.ends_with("Pos"));
+    /// assert_eq!(stats.len, 1);
+    /// assert!(stats.heap_bytes >= 8);
+    /// ```
+    pub fn storage_stats(&self) -> Vec<ComponentStats> {
+        let mut stats = self
+            .resource::<MetaTable<dyn AnyStorage>>()
+            .iter(self)
+            .map(|storage| storage.stats())
+            .collect::<Vec<_>>();
+
+        stats.sort_by_key(|stats| Reverse(stats.heap_bytes));
+
+        stats
+    }
+
     pub fn create_entity(&mut self) -> EntityBuilder {
         EntityBuilder::new(self)
     }
 
+    /// Creates `count` entities at once. Use the returned builder to attach
+    /// components to all of them.
+    pub fn create_entities(&mut self, count: usize) -> BatchBuilder<'_> {
+        BatchBuilder::new(self, count)
+    }
+
+    /// Returns an iterator that creates a new entity for each item. The
+    /// entities are persistent immediately.
+    pub fn create_iter(&mut self) -> CreateIter<'_> {
+        CreateIter(self)
+    }
+
     pub fn is_alive(&self, entity: Entity) -> bool {
         self.entities().is_alive(entity)
     }
 
     pub async fn maintain(&mut self) {
+        let mut created = Vec::new();
+        self.record(|log| {
+            created = self.entities().raised();
+
+            for entity in &created {
+                log.push(Command::Create(*entity));
+            }
+        });
+
+        for storage in self.resource::<MetaTable<dyn AnyStorage>>().iter_mut(self) {
+            storage.maintain();
+        }
+
+        self.maintain_assets();
+
         let lazy = self.resource_mut::<Lazy>().clone();
         lazy.maintain(self).await;
 
+        self.record(|log| {
+            for entity in self.entities().raised() {
+                if !created.contains(&entity) {
+                    log.push(Command::Create(entity));
+                }
+            }
+        });
+
         let deleted = self.entities_mut().maintain();
+        self.record(|log| {
+            for entity in &deleted {
+                log.push(Command::Delete(*entity));
+            }
+        });
+
         if !deleted.is_empty() {
+            self.drop_components(&deleted);
+        }
+
+        self.maintain_hierarchy();
+        self.pack_groups();
+    }
+
+    /// Executes the lazy updates queued in the passed stage, without waiting
+    /// for the next `World::maintain`. Entities deleted by the updates are
+    /// not removed until `World::maintain` is called.
+    pub async fn flush_lazy_stage(&mut self, stage: LazyStage) {
+        let lazy = self.resource_mut::<Lazy>().clone();
+        lazy.maintain_stage(stage, self).await;
+    }
+
+    /// Deletes the passed entity and all of its descendants immediately.
+    /// See `World::delete_entities` and `Hierarchy` for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the hierarchy is not registered.
+    pub fn delete_entity_with_descendants(&mut self, entity: Entity) -> Result<(), EntitiesError> {
+        self.maintain_hierarchy();
+
+        let mut delete = self.resource::<Hierarchy>().descendants(entity);
+        delete.insert(0, entity);
+        delete.retain(|entity| self.is_alive(*entity));
+
+        self.delete_entities(&delete)?;
+        self.maintain_hierarchy();
+
+        Ok(())
+    }
+
+    /// Deletes the passed entity immediately. The components of the entity
+    /// are removed from all registered storages, so there is no need to call
+    /// `World::maintain`.
+    pub fn delete_entity(&mut self, entity: Entity) -> Result<(), EntitiesError> {
+        self.delete_entities(&[entity])
+    }
+
+    /// Deletes all passed entities immediately. The components of the entities
+    /// are removed from all registered storages, so there is no need to call
+    /// `World::maintain`.
+    ///
+    /// If one of the entities is not alive, an error is returned and none of
+    /// the entities is deleted. Entities that are passed more than once are
+    /// only deleted once.
+    pub fn delete_entities(&mut self, delete: &[Entity]) -> Result<(), EntitiesError> {
+        let delete = {
+            let mut entities = self.entities_mut();
+
+            if let Some(entity) = delete.iter().find(|e| !entities.is_alive(**e)) {
+                return Err(EntitiesError::EntityIsDead {
+                    id: entity.id(),
+                    op: "delete_entities",
+                });
+            }
+
+            // All entities are alive, so entities with the same index are equal.
+            let mut seen = BitSet::new();
+            let delete = delete
+                .iter()
+                .copied()
+                .filter(|entity| !seen.add(entity.index()))
+                .collect::<Vec<_>>();
+
+            entities.kill(&delete)?;
+
+            delete
+        };
+
+        self.record(|log| {
+            for entity in &delete {
+                log.push(Command::Delete(*entity));
+            }
+        });
+
+        self.drop_components(&delete);
+
+        Ok(())
+    }
+
+    /// Drops the components of the passed entities.
+    ///
+    /// If the `rayon` feature is enabled, the storages are processed in
+    /// parallel on the thread pool of rayon. Otherwise they are processed
+    /// one after another.
+    fn drop_components(&mut self, entities: &[Entity]) {
+        let mask = entities.iter().map(Entity::index).collect::<BitSet>();
+
+        #[cfg(feature = "rayon")]
+        {
+            let world = &*self;
+            let drops = world
+                .try_resource::<StorageDrops>()
+                .map(|drops| drops.0.clone())
+                .unwrap_or_default();
+
+            // Each storage is a separate task, because the number of dropped
+            // components differs heavily between the storages.
+            let splits = drops.len();
+
+            drops
+                .into_par_iter()
+                .with_splits(splits)
+                .for_each(|drop| drop(world, &mask))
+                .exec();
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        {
             self.entry::<MetaTable<dyn AnyStorage>>()
                 .or_insert_with(Default::default);
-            for storage in self
-                .resource_mut::<MetaTable<dyn AnyStorage>>()
-                .iter_mut(&self)
-            {
-                storage.drop(&deleted);
+
+            for storage in self.resource::<MetaTable<dyn AnyStorage>>().iter_mut(self) {
+                storage.drop(&mask);
             }
         }
+
+        self.drop_dynamic_components(&mask);
     }
 }
 
+/// Functions that drop the components of the registered storages. In
+/// contrast to the `MetaTable` of the storages, the functions can be
+/// executed on different threads.
+#[cfg(feature = "rayon")]
+#[derive(Default)]
+struct StorageDrops(Vec<fn(&World, &BitSet)>);
+
+#[cfg(feature = "rayon")]
+fn drop_storage<T>(world: &World, entities: &BitSet)
+where
+    T: Component,
+{
+    world.resource_mut::<MaskedStorage<T>>().drop_many(entities);
+}
+
 impl Default for World {
     fn default() -> Self {
         let mut resources = Resources::default();
@@ -109,8 +630,11 @@ impl Default for World {
         resources.insert(Entities::default());
         resources.insert(Lazy::default());
         resources.insert(MetaTable::<dyn AnyStorage>::default());
+        resources.insert(MetaTable::<dyn AnyGroup>::default());
+        resources.insert(MetaTable::<dyn AnyInspect>::default());
+        resources.insert(Time::default());
 
-        Self(resources)
+        Self(resources, WorldId::next())
     }
 }
 
@@ -128,10 +652,39 @@ impl DerefMut for World {
     }
 }
 
+/* CreateIter */
+
+/// Iterator that creates new entities. Returned by `World::create_iter`.
+pub struct CreateIter<'a>(&'a World);
+
+impl<'a> Iterator for CreateIter<'a> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        let entity = self.0.entities_mut().allocate();
+
+        self.0.record(|log| log.push(Command::Create(entity)));
+
+        Some(entity)
+    }
+}
+
 /* AnyStorage */
 
 pub trait AnyStorage {
-    fn drop(&mut self, entities: &[Entity]);
+    /// Drops the components of the entities whose indices are contained in
+    /// the passed mask.
+    fn drop(&mut self, entities: &BitSet);
+
+    /// Moves the components of the mapped entities into the matching storage
+    /// of the passed world. The storage is registered if it does not exist.
+    fn move_to(&mut self, world: &mut World, entities: &EntityMap);
+
+    /// Returns the memory usage of the storage.
+    fn stats(&self) -> ComponentStats;
+
+    /// Maintains the storage, see `Storage::maintain`.
+    fn maintain(&mut self);
 }
 
 unsafe impl<T> CastFrom<T> for dyn AnyStorage
@@ -151,9 +704,245 @@ impl<T> AnyStorage for MaskedStorage<T>
 where
     T: Component,
 {
-    fn drop(&mut self, entities: &[Entity]) {
-        for entity in entities {
-            MaskedStorage::drop(self, entity.index());
+    fn drop(&mut self, entities: &BitSet) {
+        self.drop_many(entities);
+    }
+
+    fn move_to(&mut self, world: &mut World, entities: &EntityMap) {
+        if !world.contains::<MaskedStorage<T>>() {
+            world.register_component_with_storage::<T, _>(T::Storage::unwrap_default);
         }
+
+        let mut storage = world.component_mut::<T>();
+
+        for (source, target) in entities.iter() {
+            if let Some(component) = self.remove(source.index()) {
+                storage.insert(target, component).unwrap();
+            }
+        }
+    }
+
+    fn stats(&self) -> ComponentStats {
+        MaskedStorage::stats(self)
+    }
+
+    fn maintain(&mut self) {
+        self.storage_mut().maintain();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{entity::Builder, storage::VecStorage};
+
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Default)]
+    struct Counter(usize);
+
+    #[test]
+    fn resource_scope() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_resource(Counter::default());
+
+        let count = world.resource_scope(|world, counter: &mut Counter| {
+            assert!(!world.contains::<Counter>());
+
+            world.create_entity().with(Pos(1)).build();
+            counter.0 += world.component::<Pos>().count();
+
+            counter.0
+        });
+
+        assert_eq!(count, 1);
+        assert_eq!(world.resource::<Counter>().0, 1);
+        assert!(world.try_resource_scope(|_, _: &mut Pos| ()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Component `async_ecs::world::tests::Pos` is not registered")]
+    fn component_not_registered() {
+        let world = World::default();
+
+        world.component_mut::<Pos>();
+    }
+
+    #[test]
+    fn fetch_all() {
+        use crate::component::{Field, InspectComponent};
+
+        impl InspectComponent for Pos {
+            fn fields(&self) -> Vec<Field> {
+                vec![Field::new("0", self.0)]
+            }
+        }
+
+        let mut world = World::default();
+        world.register_inspect::<Pos>();
+
+        let a = world.create_entity().with(Pos(1)).build();
+        let b = world.create_entity().build();
+        world.delete_entity(b).unwrap();
+
+        let view = world.fetch_all();
+        assert_eq!(view.entities().collect::<Vec<_>>(), vec![a]);
+        assert_eq!(view.components(a)[0].fields, vec![Field::new("0", 1)]);
+        assert!(view.components(b).is_empty());
+    }
+
+    #[test]
+    fn try_fetch() {
+        let mut world = World::default();
+        world.register_resource(Counter::default());
+
+        assert!(!world.is_component_registered::<Pos>());
+        assert!(matches!(
+            world.try_component::<Pos>(),
+            Err(Error::ResourceNotFound(_))
+        ));
+
+        world.register_component::<Pos>();
+
+        assert!(world.is_component_registered::<Pos>());
+
+        {
+            let _counter = world.resource_mut::<Counter>();
+            let _pos = world.component::<Pos>();
+
+            assert!(matches!(
+                world.try_resource::<Counter>(),
+                Err(Error::BorrowConflict { mutably: true, .. })
+            ));
+            assert!(matches!(
+                world.try_component_mut::<Pos>(),
+                Err(Error::BorrowConflict { mutably: false, .. })
+            ));
+            assert!(world.try_component::<Pos>().is_ok());
+            assert!(<(Read<Counter>, ReadStorage<Pos>)>::try_fetch(&world).is_err());
+        }
+
+        assert!(<Option<Read<usize>>>::try_fetch(&world).unwrap().is_none());
+        assert!(<(Read<Counter>, ReadStorage<Pos>)>::try_fetch(&world).is_ok());
+    }
+
+    #[test]
+    fn delete_entities() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+
+        let e1 = world.create_entity().with(Pos(1)).build();
+        let e2 = world.create_entity().with(Pos(2)).build();
+        let e3 = world.create_entity().with(Pos(3)).build();
+
+        world.delete_entity(e1).unwrap();
+
+        assert!(!world.is_alive(e1));
+        assert_eq!(world.component::<Pos>().count(), 2);
+        assert!(world.delete_entity(e1).is_err());
+
+        assert!(world.delete_entities(&[e2, e1]).is_err());
+        assert!(world.is_alive(e2));
+
+        world.delete_entities(&[e2, e3, e2]).unwrap();
+
+        assert!(!world.is_alive(e2));
+        assert!(!world.is_alive(e3));
+        assert_eq!(world.entities().len(), 0);
+        assert!(world.component::<Pos>().is_empty());
+
+        // reused indices must not contain the old components
+        let e4 = world.create_entity().build();
+        assert!(world.component::<Pos>().get(e4).is_none());
+    }
+
+    #[tokio::test]
+    async fn mass_despawn() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        struct Vel(u32);
+
+        impl Component for Vel {
+            type Storage = VecStorage<Self>;
+        }
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let counter = dropped.clone();
+
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component_with_hooks(ComponentHooks::<Vel>::new().on_drop(move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }));
+
+        let entities = (0..1000)
+            .map(|i| {
+                let builder = world.create_entity().with(Pos(i));
+
+                if i % 2 == 0 {
+                    builder.with(Vel(i)).build()
+                } else {
+                    builder.build()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for entity in &entities[..900] {
+            world.entities().delete(*entity).unwrap();
+        }
+        world.maintain().await;
+
+        assert_eq!(world.component::<Pos>().count(), 100);
+        assert_eq!(world.component::<Vel>().count(), 50);
+        assert_eq!(dropped.load(Ordering::Relaxed), 450);
+        assert_eq!(world.component::<Pos>().get(entities[900]).unwrap().0, 900);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[tokio::test]
+    async fn parallel_despawn() {
+        use std::sync::{Arc, Mutex};
+        use std::thread::{current, ThreadId};
+
+        struct Vel(u32);
+
+        impl Component for Vel {
+            type Storage = VecStorage<Self>;
+        }
+
+        fn hooks<T: Component>(threads: &Arc<Mutex<Vec<ThreadId>>>) -> ComponentHooks<T> {
+            let threads = threads.clone();
+
+            ComponentHooks::new().on_drop(move |_, _| threads.lock().unwrap().push(current().id()))
+        }
+
+        let threads = Arc::new(Mutex::new(Vec::new()));
+
+        let mut world = World::default();
+        world.register_component_with_hooks(hooks::<Pos>(&threads));
+        world.register_component_with_hooks(hooks::<Vel>(&threads));
+
+        let entities = (0..100)
+            .map(|i| world.create_entity().with(Pos(i)).with(Vel(i)).build())
+            .collect::<Vec<_>>();
+
+        for entity in &entities {
+            world.entities().delete(*entity).unwrap();
+        }
+        world.maintain().await;
+
+        // the storages are dropped on the thread pool of rayon
+        let threads = threads.lock().unwrap();
+        assert_eq!(threads.len(), 200);
+        assert!(threads.iter().all(|thread| *thread != current().id()));
+        assert!(world.component::<Pos>().is_empty());
+        assert!(world.component::<Vel>().is_empty());
     }
 }
@@ -0,0 +1,526 @@
+use std::any::{type_name, Any, TypeId};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+use hashbrown::HashMap;
+
+use crate::{component::Component, entity::Entity};
+
+use super::{EntityMap, World};
+
+/// Log of the structural changes of a `World`, used to replay them onto
+/// another world (for deterministic replays or to reproduce bugs).
+///
+/// The log is a resource. While recording is enabled (see
+/// `CommandLog::set_recording`) the following changes are appended to the
+/// log, each with an increasing sequence number:
+///
+/// - Entities that are created using `World::create_entity`,
+///   `World::create_entities` and `World::create_iter`, or atomically
+///   (these are recorded on `World::maintain`).
+/// - Entities that are deleted using `World::delete_entities`, or
+///   atomically (these are recorded on `World::maintain`).
+/// - Components that are inserted by the entity builders or inserted and
+///   removed by `Lazy`. Only components that were registered using
+///   `World::register_record` are recorded.
+///
+/// Changes that are made directly to the storages or to the `Entities` are
+/// not recorded.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::*;
+/// #
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Pos(u32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+///
+///     fn setup(world: &mut World) {
+///         world.register_record::<Self>();
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// world.register_component::<Pos>();
+/// world.resource_mut::<CommandLog>().set_recording(true);
+///
+/// let entity = world.create_entity().with(Pos(1)).build();
+/// world.lazy().insert(entity, Pos(2));
+/// world.maintain().await;
+///
+/// let mut replay = World::default();
+/// replay.register_component::<Pos>();
+///
+/// let entities = world.resource::<CommandLog>().replay(&mut replay);
+/// let entity = entities.get(entity).unwrap();
+///
+/// assert_eq!(replay.component::<Pos>().get(entity), Some(&Pos(2)));
+/// # }
+/// ```
+#[derive(Default)]
+pub struct CommandLog {
+    records: Vec<Record>,
+    sequence: u64,
+    recording: bool,
+    recorders: HashMap<TypeId, Recorder>,
+}
+
+type Recorder = fn(&dyn Any) -> ComponentValue;
+
+impl CommandLog {
+    /// Create a new empty log, with recording disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new log from the passed records, with recording disabled.
+    pub fn from_records(records: Vec<Record>) -> Self {
+        let sequence = records.last().map_or(0, |record| record.sequence + 1);
+
+        Self {
+            records,
+            sequence,
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if changes are currently recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Enables or disables the recording of changes.
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    /// Registers the component `T`, so inserts and removes of this component
+    /// are recorded. See `World::register_record`.
+    pub fn register<T>(&mut self)
+    where
+        T: Component + Clone + Send + Sync,
+    {
+        self.recorders.insert(TypeId::of::<T>(), |component| {
+            ComponentValue::new(component.downcast_ref::<T>().unwrap().clone())
+        });
+    }
+
+    /// Returns all recorded changes, in the order they were recorded.
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Returns the number of recorded changes.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if no change was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Removes all recorded changes. The sequence numbers are not reset.
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    /// Applies all recorded changes to the passed world, in the order they
+    /// were recorded.
+    ///
+    /// Recorded entities are mapped to new entities of the passed world.
+    /// The returned map contains the entities that were created for the
+    /// recorded ones. Changes that reference entities that were created
+    /// before the recording started are skipped.
+    pub fn replay(&self, world: &mut World) -> EntityMap {
+        let mut entities = EntityMap::default();
+
+        for record in &self.records {
+            match &record.command {
+                Command::Create(entity) => {
+                    let created = world.entities_mut().allocate();
+
+                    entities.insert(*entity, created);
+                }
+                Command::Delete(entity) => {
+                    if let Some(entity) = entities.get(*entity) {
+                        let _ = world.delete_entity(entity);
+                    }
+                }
+                Command::Insert(entity, value) => {
+                    if let Some(entity) = entities.get(*entity) {
+                        value.0.insert(world, entity);
+                    }
+                }
+                Command::Remove(entity, component) => {
+                    if let Some(entity) = entities.get(*entity) {
+                        (component.remove)(world, entity);
+                    }
+                }
+            }
+        }
+
+        entities
+    }
+
+    /// Appends a change to the log.
+    pub fn push(&mut self, command: Command) {
+        self.records.push(Record {
+            sequence: self.sequence,
+            command,
+        });
+
+        self.sequence += 1;
+    }
+
+    pub(crate) fn insert<T>(&mut self, entity: Entity, component: &T)
+    where
+        T: Component,
+    {
+        if let Some(recorder) = self.recorders.get(&TypeId::of::<T>()) {
+            let value = recorder(component);
+
+            self.push(Command::Insert(entity, value));
+        }
+    }
+
+    pub(crate) fn remove<T>(&mut self, entity: Entity)
+    where
+        T: Component,
+    {
+        if self.recorders.contains_key(&TypeId::of::<T>()) {
+            self.push(Command::Remove(entity, ComponentType::of::<T>()));
+        }
+    }
+}
+
+impl Debug for CommandLog {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("CommandLog")
+            .field("records", &self.records)
+            .field("sequence", &self.sequence)
+            .field("recording", &self.recording)
+            .finish()
+    }
+}
+
+impl World {
+    /// Registers the component `T`, so inserts and removes of this component
+    /// are recorded in the `CommandLog`. The log is added to the world if it
+    /// does not exist yet.
+    ///
+    /// This is usually called in the `setup` of the component, so the
+    /// component is registered as soon as the storage is registered.
+    pub fn register_record<T>(&mut self)
+    where
+        T: Component + Clone + Send + Sync,
+    {
+        self.entry::<CommandLog>().or_insert_with(Default::default);
+        self.resource_mut::<CommandLog>().register::<T>();
+    }
+
+    /// Executes the passed function with the `CommandLog` of the world, if
+    /// the world has one and recording is enabled.
+    pub(crate) fn record<F>(&self, f: F)
+    where
+        F: FnOnce(&mut CommandLog),
+    {
+        if let Some(mut log) = self.try_borrow_mut::<CommandLog>() {
+            if log.recording {
+                f(&mut log);
+            }
+        }
+    }
+}
+
+/* Record */
+
+/// A single change, recorded by the `CommandLog`.
+#[derive(Debug, Clone)]
+pub struct Record {
+    sequence: u64,
+    command: Command,
+}
+
+impl Record {
+    /// Create a new record.
+    pub fn new(sequence: u64, command: Command) -> Self {
+        Self { sequence, command }
+    }
+
+    /// Returns the sequence number of the change.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Returns the recorded change.
+    pub fn command(&self) -> &Command {
+        &self.command
+    }
+}
+
+/* Command */
+
+/// Structural change of a `World`, see `CommandLog`.
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// The entity was created.
+    Create(Entity),
+
+    /// The entity was deleted.
+    Delete(Entity),
+
+    /// The component was inserted for the entity.
+    Insert(Entity, ComponentValue),
+
+    /// The component was removed from the entity.
+    Remove(Entity, ComponentType),
+}
+
+/* ComponentValue */
+
+/// Copy of a recorded component.
+pub struct ComponentValue(Box<dyn RecordedValue>);
+
+impl ComponentValue {
+    /// Create a new value from the passed component.
+    pub fn new<T>(component: T) -> Self
+    where
+        T: Component + Clone + Send + Sync,
+    {
+        Self(Box::new(Value(component)))
+    }
+
+    /// Returns the type of the component.
+    pub fn component_type(&self) -> ComponentType {
+        self.0.component_type()
+    }
+
+    /// Returns the component if it is of type `T`.
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: Component,
+    {
+        self.0
+            .as_any()
+            .downcast_ref::<Value<T>>()
+            .map(|value| &value.0)
+    }
+}
+
+impl Clone for ComponentValue {
+    fn clone(&self) -> Self {
+        self.0.clone_value()
+    }
+}
+
+impl Debug for ComponentValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("ComponentValue")
+            .field(&self.component_type().name())
+            .finish()
+    }
+}
+
+trait RecordedValue: Send + Sync {
+    fn insert(&self, world: &World, entity: Entity);
+
+    fn component_type(&self) -> ComponentType;
+
+    fn clone_value(&self) -> ComponentValue;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+struct Value<T>(T);
+
+impl<T> RecordedValue for Value<T>
+where
+    T: Component + Clone + Send + Sync,
+{
+    fn insert(&self, world: &World, entity: Entity) {
+        let _ = world.component_mut::<T>().insert(entity, self.0.clone());
+    }
+
+    fn component_type(&self) -> ComponentType {
+        ComponentType::of::<T>()
+    }
+
+    fn clone_value(&self) -> ComponentValue {
+        ComponentValue::new(self.0.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/* ComponentType */
+
+/// Type of a recorded component.
+#[derive(Clone, Copy)]
+pub struct ComponentType {
+    id: TypeId,
+    name: &'static str,
+    remove: fn(&World, Entity),
+}
+
+impl ComponentType {
+    /// Returns the type of the component `T`.
+    pub fn of<T>() -> Self
+    where
+        T: Component,
+    {
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+            remove: |world, entity| {
+                world.component_mut::<T>().remove(entity);
+            },
+        }
+    }
+
+    /// Returns the type id of the component.
+    pub fn id(&self) -> TypeId {
+        self.id
+    }
+
+    /// Returns the name of the component.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns `true` if this is the type of the component `T`.
+    pub fn is<T>(&self) -> bool
+    where
+        T: Component,
+    {
+        self.id == TypeId::of::<T>()
+    }
+}
+
+impl PartialEq for ComponentType {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for ComponentType {}
+
+impl Debug for ComponentType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("ComponentType").field(&self.name).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{entity::Builder, storage::VecStorage, world::Lazy};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+
+        fn setup(world: &mut World) {
+            world.register_record::<Self>();
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Name(&'static str);
+
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+    }
+
+    fn world() -> World {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Name>();
+
+        world
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let mut world = world();
+
+        let before = world.create_entity().with(Pos(0)).build();
+
+        world.resource_mut::<CommandLog>().set_recording(true);
+
+        let a = world.create_entity().with(Pos(1)).with(Name("a")).build();
+        let b = world.create_entity().build();
+
+        let lazy = Lazy::clone(&world.resource::<Lazy>());
+        let c = lazy.create_entity(&world).with(Pos(3)).build();
+        lazy.insert(b, Pos(2));
+        lazy.remove::<Pos>(a);
+        lazy.insert(before, Pos(10));
+        world.entities().delete(b).unwrap();
+        world.maintain().await;
+
+        world.delete_entity(a).unwrap();
+
+        {
+            let log = world.resource::<CommandLog>();
+            let commands = log
+                .records()
+                .iter()
+                .map(|record| match record.command() {
+                    Command::Create(e) => format!("create {}", e),
+                    Command::Delete(e) => format!("delete {}", e),
+                    Command::Insert(e, v) => {
+                        format!("insert {} {:?}", e, v.downcast_ref::<Pos>().unwrap())
+                    }
+                    Command::Remove(e, t) => {
+                        assert!(t.is::<Pos>());
+
+                        format!("remove {}", e)
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                commands,
+                vec![
+                    format!("create {}", a),
+                    format!("insert {} Pos(1)", a),
+                    format!("create {}", b),
+                    format!("create {}", c),
+                    format!("insert {} Pos(3)", c),
+                    format!("insert {} Pos(2)", b),
+                    format!("remove {}", a),
+                    format!("insert {} Pos(10)", before),
+                    format!("delete {}", b),
+                    format!("delete {}", a),
+                ]
+            );
+            assert!(log
+                .records()
+                .windows(2)
+                .all(|w| w[0].sequence() + 1 == w[1].sequence()));
+        }
+
+        let mut replay = self::world();
+        let entities = world.resource::<CommandLog>().replay(&mut replay);
+
+        assert_eq!(entities.len(), 3);
+        assert_eq!(replay.entities().len(), 1);
+
+        let c = entities.get(c).unwrap();
+        assert!(replay.is_alive(c));
+        assert_eq!(replay.component::<Pos>().get(c), Some(&Pos(3)));
+        assert_eq!(replay.component::<Pos>().count(), 1);
+        assert_eq!(replay.component::<Name>().count(), 0);
+        assert_eq!(entities.get(before), None);
+    }
+}
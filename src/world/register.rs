@@ -0,0 +1,223 @@
+use crate::{
+    component::{Component, ComponentHooks, InspectComponent},
+    misc::TryDefault,
+    storage::MaskedStorage,
+};
+
+use super::World;
+
+type StorageFn<'a, T> = Box<dyn FnOnce() -> <T as Component>::Storage + 'a>;
+
+impl World {
+    /// Returns a builder to register the component `T` together with its
+    /// options. The component is registered when `RegistrationBuilder::build`
+    /// is called.
+    ///
+    /// This is the composable variant of `register_component`,
+    /// `register_component_with_storage`, `register_component_with_hooks`,
+    /// `register_inspect` and `register_record`. Like these, the storage is
+    /// only created if the component is not registered yet.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use std::sync::{Arc, Mutex};
+    /// #
+    /// # use async_ecs::{component::ComponentHooks, *};
+    /// #
+    /// #[derive(Clone)]
+    /// struct Body(u32);
+    ///
+    /// impl Component for Body {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let bodies = Arc::new(Mutex::new(Vec::new()));
+    ///
+    /// let mut world = World::default();
+    /// world
+    ///     .register::<Body>()
+    ///     .with_storage(VecStorage::default)
+    ///     .with_hooks(ComponentHooks::new().on_insert({
+    ///         let bodies = bodies.clone();
+    ///         move |_, body: &mut Body| bodies.lock().unwrap().push(body.0)
+    ///     }))
+    ///     .recorded()
+    ///     .build();
+    ///
+    /// world.create_entity().with(Body(1)).build();
+    ///
+    /// assert_eq!(*bodies.lock().unwrap(), vec![1]);
+    /// assert!(world.contains::<CommandLog>());
+    /// ```
+    pub fn register<T>(&mut self) -> RegistrationBuilder<'_, T>
+    where
+        T: Component,
+    {
+        RegistrationBuilder {
+            world: self,
+            storage: None,
+            hooks: None,
+            extensions: Vec::new(),
+        }
+    }
+}
+
+/// Builder that registers a component with its options, returned by
+/// `World::register`.
+///
+/// The options can be set in any order, the component is registered when
+/// `build` is called.
+#[must_use = "the component is only registered when `build` is called"]
+pub struct RegistrationBuilder<'a, T>
+where
+    T: Component,
+{
+    world: &'a mut World,
+    storage: Option<StorageFn<'a, T>>,
+    hooks: Option<ComponentHooks<T>>,
+    extensions: Vec<fn(&mut World)>,
+}
+
+impl<'a, T> RegistrationBuilder<'a, T>
+where
+    T: Component,
+{
+    /// Sets the function that creates the storage of the component,
+    /// instead of using the default storage. This is required if the
+    /// storage does not implement `Default`.
+    pub fn with_storage<F>(mut self, storage: F) -> Self
+    where
+        F: FnOnce() -> T::Storage + 'a,
+    {
+        self.storage = Some(Box::new(storage));
+
+        self
+    }
+
+    /// Sets the hooks that are invoked by the storage whenever a component
+    /// is inserted, removed or dropped. See `ComponentHooks` for details.
+    pub fn with_hooks(mut self, hooks: ComponentHooks<T>) -> Self {
+        self.hooks = Some(hooks);
+
+        self
+    }
+
+    /// Records the inserts and removes of the component in the
+    /// `CommandLog`. See `World::register_record` for details.
+    pub fn recorded(mut self) -> Self
+    where
+        T: Clone + Send + Sync,
+    {
+        self.extensions.push(World::register_record::<T>);
+
+        self
+    }
+
+    /// Makes the component available for debug inspectors. See
+    /// `World::register_inspect` for details.
+    pub fn inspect(mut self) -> Self
+    where
+        T: InspectComponent,
+        T::Storage: Default,
+    {
+        self.extensions.push(World::register_inspect::<T>);
+
+        self
+    }
+
+    /// Registers the component with the configured options.
+    ///
+    /// Like `register_component_with_storage`, the storage is only created
+    /// if the component is not registered yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no storage was set using `with_storage` and the storage of
+    /// the component does not implement `Default`.
+    pub fn build(self) {
+        let Self {
+            world,
+            storage,
+            hooks,
+            extensions,
+        } = self;
+
+        match storage {
+            Some(storage) => world.register_component_with_storage::<T, _>(storage),
+            None => world.register_component_with_storage::<T, _>(TryDefault::unwrap_default),
+        }
+
+        if let Some(hooks) = hooks {
+            world.resource_mut::<MaskedStorage<T>>().set_hooks(hooks);
+        }
+
+        for extension in extensions {
+            extension(world);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use crate::{
+        entity::Builder,
+        storage::VecStorage,
+        world::{Command, CommandLog},
+    };
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn register_with_options() {
+        let inserted = Arc::new(AtomicUsize::new(0));
+        let created = Arc::new(AtomicUsize::new(0));
+
+        let mut world = World::default();
+        world
+            .register::<Pos>()
+            .recorded()
+            .with_hooks(ComponentHooks::new().on_insert({
+                let inserted = inserted.clone();
+                move |_, _: &mut Pos| {
+                    inserted.fetch_add(1, Ordering::Relaxed);
+                }
+            }))
+            .with_storage({
+                let created = created.clone();
+                move || {
+                    created.fetch_add(1, Ordering::Relaxed);
+
+                    VecStorage::default()
+                }
+            })
+            .build();
+
+        world
+            .register::<Pos>()
+            .with_storage(|| unreachable!())
+            .build();
+
+        world.resource_mut::<CommandLog>().set_recording(true);
+        let entity = world.create_entity().with(Pos(1)).build();
+
+        assert_eq!(inserted.load(Ordering::Relaxed), 1);
+        assert_eq!(created.load(Ordering::Relaxed), 1);
+        assert!(matches!(
+            world.resource::<CommandLog>().records()[1].command(),
+            Command::Insert(e, _) if *e == entity
+        ));
+    }
+}
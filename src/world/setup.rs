@@ -1,3 +1,6 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
 use crate::resource::Resource;
 
 use super::World;
@@ -30,3 +33,172 @@ where
 {
     fn setup(_: &mut World) {}
 }
+
+/// A setup handler that constructs the resource using the closure that was
+/// registered with `World::register_resource_with`. The closure is only
+/// called if the resource does not exist yet.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::{world::FnSetupHandler, *};
+///
+/// struct Config(u32);
+///
+/// struct Dummy;
+///
+/// impl<'a> System<'a> for Dummy {
+///     type SystemData = Read<'a, Config, FnSetupHandler>;
+///
+///     fn run(&mut self, _: Self::SystemData) {}
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// world.register_resource_with(|_| Config(42));
+///
+/// let _dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with(Dummy, "dummy", &[])
+///     .unwrap()
+///     .build();
+///
+/// assert_eq!(world.resource::<Config>().0, 42);
+/// # }
+/// ```
+pub struct FnSetupHandler;
+
+impl<T> SetupHandler<T> for FnSetupHandler
+where
+    T: Resource,
+{
+    fn setup(world: &mut World) {
+        if world.contains::<T>() {
+            return;
+        }
+
+        let factory = world
+            .remove::<ResourceFactory<T>>()
+            .unwrap_or_else(|| panic!("No factory registered for `{}`", type_name::<T>()));
+        let resource = (factory.0)(world);
+
+        world.insert(resource);
+    }
+}
+
+/// A setup handler that constructs the resource from the arguments that were
+/// registered with `World::register_resource_args`, using `From<Args>`. The
+/// arguments are only consumed if the resource does not exist yet.
+///
+/// This is useful for resources that require constructor arguments, like
+/// file paths or configuration structs, and therefore can not use the
+/// `DefaultSetupHandler`.
+///
+/// ## Examples
+///
+/// ```
+/// use async_ecs::{world::SetupHandlerWith, *};
+///
+/// struct Config {
+///     path: String,
+/// }
+///
+/// impl From<&'static str> for Config {
+///     fn from(path: &'static str) -> Self {
+///         Self { path: path.into() }
+///     }
+/// }
+///
+/// struct Dummy;
+///
+/// impl<'a> System<'a> for Dummy {
+///     type SystemData = Read<'a, Config, SetupHandlerWith<&'static str>>;
+///
+///     fn run(&mut self, _: Self::SystemData) {}
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// world.register_resource_args::<Config, _>("config.toml");
+///
+/// let _dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with(Dummy, "dummy", &[])
+///     .unwrap()
+///     .build();
+///
+/// assert_eq!(world.resource::<Config>().path, "config.toml");
+/// # }
+/// ```
+pub struct SetupHandlerWith<Args>(PhantomData<Args>);
+
+impl<T, Args> SetupHandler<T> for SetupHandlerWith<Args>
+where
+    T: Resource + From<Args>,
+    Args: Send + Sync + 'static,
+{
+    fn setup(world: &mut World) {
+        if world.contains::<T>() {
+            return;
+        }
+
+        let args = world
+            .remove::<ResourceArgs<T, Args>>()
+            .unwrap_or_else(|| panic!("No arguments registered for `{}`", type_name::<T>()));
+
+        world.insert(T::from(args.0));
+    }
+}
+
+/// Closure that creates a resource of type `T`. Used by the `FnSetupHandler`.
+pub(crate) struct ResourceFactory<T>(pub Box<dyn FnOnce(&mut World) -> T + Send + Sync>);
+
+/// Arguments to create a resource of type `T`. Used by the `SetupHandlerWith`.
+pub(crate) struct ResourceArgs<T, Args>(pub Args, pub PhantomData<fn() -> T>);
+
+#[cfg(test)]
+mod tests {
+    use crate::access::{Read, Write};
+    use crate::system::SystemData;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Config(u32);
+
+    #[test]
+    fn fn_setup_handler() {
+        let mut world = World::default();
+        world.register_resource(1usize);
+        world.register_resource_with(|world| Config(*world.resource::<usize>() as u32 + 1));
+
+        assert!(<Option<Write<Config>>>::fetch(&world).is_none());
+
+        <Read<Config, FnSetupHandler>>::setup(&mut world);
+
+        assert_eq!(*<Read<Config, FnSetupHandler>>::fetch(&world), Config(2));
+
+        <Option<Write<Config>>>::fetch(&world).unwrap().0 = 3;
+
+        assert_eq!(*world.resource::<Config>(), Config(3));
+    }
+
+    impl From<(u32, u32)> for Config {
+        fn from((a, b): (u32, u32)) -> Self {
+            Self(a * b)
+        }
+    }
+
+    #[test]
+    fn setup_handler_with() {
+        let mut world = World::default();
+        world.register_resource_args::<Config, _>((3u32, 4u32));
+
+        <Read<Config, SetupHandlerWith<(u32, u32)>>>::setup(&mut world);
+        assert_eq!(*world.resource::<Config>(), Config(12));
+
+        world.resource_mut::<Config>().0 = 1;
+        <Read<Config, SetupHandlerWith<(u32, u32)>>>::setup(&mut world);
+        assert_eq!(*world.resource::<Config>(), Config(1));
+    }
+}
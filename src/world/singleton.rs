@@ -0,0 +1,204 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use hibitset::BitSetLike;
+
+use crate::{
+    component::Component,
+    entity::{Builder, Entity, Index},
+    error::Error,
+    storage::MaskedStorage,
+};
+
+use super::World;
+
+/// Resource that stores the entity of the singleton component `T`. It is
+/// inserted by `World::insert_singleton` and used by `ReadSingleton` and
+/// `WriteSingleton` to access the component without a join.
+pub struct SingletonEntity<T> {
+    entity: Entity,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> SingletonEntity<T> {
+    /// Returns the entity the singleton component is attached to.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+}
+
+/// Index of the entity that owns the singleton component `T`. It is shared
+/// with the hooks of the storage that reject the component on any other
+/// entity.
+struct SingletonGuard<T> {
+    index: Arc<AtomicU32>,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// Value of the `SingletonGuard` if no entity owns the singleton.
+const NO_SINGLETON: Index = Index::MAX;
+
+impl World {
+    /// Inserts the component `T` as singleton and returns the entity it is
+    /// attached to. The component is registered if needed.
+    ///
+    /// If the singleton already exists, its value is replaced. Otherwise a
+    /// new entity is created for it. Use `ReadSingleton` and
+    /// `WriteSingleton` to access the component in a system.
+    ///
+    /// ## Errors
+    ///
+    /// The singleton requires that at most one entity has the component `T`.
+    /// If any other entity already has the component, nothing is inserted
+    /// and `Error::SingletonNotUnique` is returned.
+    ///
+    /// ## Panics
+    ///
+    /// The limit is enforced by an insert hook of the storage: until the
+    /// singleton is removed using `remove_singleton` or its entity is
+    /// deleted, adding the component to any other entity (e.g. using
+    /// `create_entity().with(..)` or `WriteStorage::insert`) panics.
+    /// The hook is chained with the hooks that were registered before, but
+    /// it is lost if the hooks are replaced by
+    /// `World::register_component_with_hooks` afterwards.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// struct Camera {
+    ///     zoom: f32,
+    /// }
+    ///
+    /// impl Component for Camera {
+    ///     type Storage = HashMapStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::default();
+    ///
+    /// let camera = world.insert_singleton(Camera { zoom: 1.0 }).unwrap();
+    /// assert_eq!(world.insert_singleton(Camera { zoom: 2.0 }).unwrap(), camera);
+    ///
+    /// assert_eq!(world.singleton_entity::<Camera>(), Some(camera));
+    /// assert_eq!(world.component::<Camera>().count(), 1);
+    ///
+    /// world.remove_singleton::<Camera>();
+    /// world.create_entity().with(Camera { zoom: 3.0 }).build();
+    /// assert!(world.insert_singleton(Camera { zoom: 4.0 }).is_err());
+    /// ```
+    pub fn insert_singleton<T>(&mut self, value: T) -> Result<Entity, Error>
+    where
+        T: Component,
+        T::Storage: Default,
+    {
+        self.register_component::<T>();
+
+        let entity = self.singleton_entity::<T>();
+        let count = self.component::<T>().mask().iter().take(2).count();
+        if count > 1 || (count == 1 && entity.is_none()) {
+            return Err(Error::SingletonNotUnique(type_name::<T>()));
+        }
+
+        if let Some(entity) = entity {
+            self.component_mut::<T>()
+                .insert(entity, value)
+                .expect("Singleton entity is not alive");
+
+            return Ok(entity);
+        }
+
+        let entity = self.create_entity().build();
+        self.guard_singleton::<T>(entity.index());
+        self.component_mut::<T>()
+            .insert(entity, value)
+            .expect("Created entity is not alive");
+
+        self.insert(SingletonEntity::<T> {
+            entity,
+            marker: PhantomData,
+        });
+
+        Ok(entity)
+    }
+
+    /// Returns the entity of the singleton component `T`, if it exists and
+    /// is still attached to the entity.
+    pub fn singleton_entity<T>(&self) -> Option<Entity>
+    where
+        T: Component,
+    {
+        let entity = self.try_resource::<SingletonEntity<T>>().ok()?.entity();
+
+        if self.component::<T>().contains(entity) {
+            Some(entity)
+        } else {
+            None
+        }
+    }
+
+    /// Removes the singleton component `T` from its entity and returns it.
+    /// The entity itself is not deleted.
+    pub fn remove_singleton<T>(&mut self) -> Option<T>
+    where
+        T: Component,
+    {
+        let singleton = self.remove::<SingletonEntity<T>>()?;
+
+        if let Ok(guard) = self.try_resource::<SingletonGuard<T>>() {
+            guard.index.store(NO_SINGLETON, Ordering::Release);
+        }
+
+        self.component_mut::<T>().remove(singleton.entity)
+    }
+
+    /// Makes the entity with the passed `index` the only entity that may
+    /// own the component `T`. The hooks that enforce this are registered
+    /// once, the guard is released again if the component of the singleton
+    /// entity is dropped.
+    fn guard_singleton<T>(&mut self, index: Index)
+    where
+        T: Component,
+    {
+        if let Ok(guard) = self.try_resource::<SingletonGuard<T>>() {
+            guard.index.store(index, Ordering::Release);
+
+            return;
+        }
+
+        let shared = Arc::new(AtomicU32::new(index));
+
+        {
+            let mut storage = self.resource_mut::<MaskedStorage<T>>();
+            let hooks = storage.hooks_mut();
+
+            let current = shared.clone();
+            hooks.prepend_insert(move |index, _| {
+                let singleton = current.load(Ordering::Acquire);
+                if singleton != NO_SINGLETON && singleton != index {
+                    panic!(
+                        "Singleton component {} can not be attached to a second entity!",
+                        type_name::<T>()
+                    );
+                }
+            });
+
+            let current = shared.clone();
+            hooks.append_drop(move |index, _| {
+                let _ = current.compare_exchange(
+                    index,
+                    NO_SINGLETON,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                );
+            });
+        }
+
+        self.insert(SingletonGuard::<T> {
+            index: shared,
+            marker: PhantomData,
+        });
+    }
+}
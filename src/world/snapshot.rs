@@ -0,0 +1,203 @@
+use std::any::{Any, TypeId};
+
+use hashbrown::HashMap;
+
+use crate::{
+    component::Component,
+    entity::{EntitiesSnapshot, Entity},
+    join::Join,
+};
+
+use super::{CloneStorage, MetaTable, World};
+
+/// Copy of the state of a `World`, created by `World::snapshot`.
+///
+/// The snapshot contains the state of the entity allocator and a copy of
+/// all components that were marked as cloneable (see
+/// `World::register_clone`).
+pub struct WorldSnapshot {
+    entities: EntitiesSnapshot,
+    components: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl WorldSnapshot {
+    /// Returns the state of the entity allocator.
+    pub fn entities(&self) -> &EntitiesSnapshot {
+        &self.entities
+    }
+
+    /// Returns the copied components of type `T`, or `None` if the
+    /// component was not marked as cloneable when the snapshot was taken.
+    pub fn components<T>(&self) -> Option<&[(Entity, T)]>
+    where
+        T: Component,
+    {
+        self.components
+            .get(&TypeId::of::<T>())
+            .and_then(|components| components.downcast_ref::<Vec<(Entity, T)>>())
+            .map(Vec::as_slice)
+    }
+
+    pub(crate) fn insert_components<T>(&mut self, components: Vec<(Entity, T)>)
+    where
+        T: Component + Send + Sync,
+    {
+        self.components
+            .insert(TypeId::of::<T>(), Box::new(components));
+    }
+}
+
+impl World {
+    /// Captures the current state of the world, so it can be rolled back
+    /// using `World::restore`. This is useful for client side prediction
+    /// or to replay a deterministic simulation.
+    ///
+    /// Only the entity allocator and the components that were marked as
+    /// cloneable (see `World::register_clone`) are captured. Resources and
+    /// pending changes (atomically created or deleted entities and lazy
+    /// updates) are not part of the snapshot, so call `World::maintain`
+    /// before taking it.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use async_ecs::*;
+    /// #
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Pos(u32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    ///
+    ///     fn setup(world: &mut World) {
+    ///         world.register_clone::<Self>();
+    ///     }
+    /// }
+    ///
+    /// let mut world = World::default();
+    /// world.register_component::<Pos>();
+    ///
+    /// let entity = world.create_entity().with(Pos(1)).build();
+    /// let snapshot = world.snapshot();
+    ///
+    /// world.component_mut::<Pos>().get_mut(entity).unwrap().0 = 2;
+    /// let spawned = world.create_entity().with(Pos(3)).build();
+    ///
+    /// world.restore(&snapshot);
+    ///
+    /// assert_eq!(world.component::<Pos>().get(entity), Some(&Pos(1)));
+    /// assert!(!world.is_alive(spawned));
+    /// ```
+    pub fn snapshot(&self) -> WorldSnapshot {
+        let entities = self.entities();
+
+        let mut snapshot = WorldSnapshot {
+            entities: entities.snapshot(),
+            components: HashMap::new(),
+        };
+
+        if let Some(table) = self.try_borrow::<MetaTable<dyn CloneStorage>>() {
+            for storage in table.iter(self) {
+                storage.snapshot(&entities, &mut snapshot);
+            }
+        }
+
+        snapshot
+    }
+
+    /// Rolls the world back to the state of the passed snapshot.
+    ///
+    /// Entities that were created after the snapshot was taken are deleted,
+    /// together with all their components. Entities that were deleted after
+    /// the snapshot was taken are alive again. The components that were
+    /// marked as cloneable are replaced with the copies of the snapshot,
+    /// all other components are left untouched.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) {
+        let created = (&self.entities())
+            .join()
+            .filter(|entity| !snapshot.entities.is_alive(*entity))
+            .collect::<Vec<_>>();
+        self.drop_components(&created);
+
+        self.entities_mut().restore(&snapshot.entities);
+
+        self.entry::<MetaTable<dyn CloneStorage>>()
+            .or_insert_with(Default::default);
+        for storage in self
+            .resource_mut::<MetaTable<dyn CloneStorage>>()
+            .iter_mut(self)
+        {
+            storage.restore(snapshot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{entity::Builder, storage::VecStorage};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+
+        fn setup(world: &mut World) {
+            world.register_clone::<Self>();
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[test]
+    fn rollback() {
+        let mut world = World::default();
+        world.register_component::<Pos>();
+        world.register_component::<Name>();
+
+        let kept = world
+            .create_entity()
+            .with(Pos(1))
+            .with(Name("kept"))
+            .build();
+        let deleted = world.create_entity().with(Pos(2)).build();
+
+        let snapshot = world.snapshot();
+        assert_eq!(snapshot.entities().len(), 2);
+        assert_eq!(snapshot.components::<Pos>().unwrap().len(), 2);
+        assert!(snapshot.components::<Name>().is_none());
+
+        world.delete_entity(deleted).unwrap();
+        world.component_mut::<Pos>().remove(kept);
+        let created = world
+            .create_entity()
+            .with(Pos(3))
+            .with(Name("created"))
+            .build();
+
+        world.restore(&snapshot);
+
+        assert!(world.is_alive(kept));
+        assert!(world.is_alive(deleted));
+        assert!(!world.is_alive(created));
+        assert_eq!(world.entities().len(), 2);
+        assert_eq!(world.component::<Pos>().get(kept), Some(&Pos(1)));
+        assert_eq!(world.component::<Pos>().get(deleted), Some(&Pos(2)));
+        assert_eq!(world.component::<Pos>().count(), 2);
+        assert_eq!(world.component::<Name>().get(kept), Some(&Name("kept")));
+        assert_eq!(world.component::<Name>().count(), 1);
+
+        let replayed = world.create_entity().build();
+        world.restore(&snapshot);
+
+        assert!(!world.is_alive(replayed));
+        assert_eq!(world.create_entity().build(), replayed);
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use crate::util::Instant;
+
+/// Resource that keeps track of the time between the dispatches of the
+/// `Dispatcher`.
+///
+/// The resource is added to the world by default and updated at the start
+/// of each `Dispatcher::dispatch` (nested dispatchers do not update it), so
+/// systems can read the time that elapsed since the last dispatch.
+///
+/// If multiple dispatchers are dispatched against the same world, each of
+/// them updates the resource. Each dispatch then counts as a frame, and the
+/// delta is the time since the last dispatch of any of the dispatchers.
+///
+/// ## Examples
+///
+/// ```
+/// # use async_ecs::*;
+/// #
+/// struct Velocity(f32);
+///
+/// impl Component for Velocity {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Position(f32);
+///
+/// impl Component for Position {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Move;
+///
+/// impl<'a> System<'a> for Move {
+///     type SystemData = (
+///         Read<'a, Time>,
+///         ReadStorage<'a, Velocity>,
+///         WriteStorage<'a, Position>,
+///     );
+///
+///     fn run(&mut self, (time, velocities, mut positions): Self::SystemData) {
+///         for (velocity, position) in (&velocities, &mut positions).join() {
+///             position.0 += velocity.0 * time.delta_seconds();
+///         }
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut world = World::default();
+/// let mut dispatcher = Dispatcher::setup_builder(&mut world)
+///     .with(Move, "move", &[])
+///     .unwrap()
+///     .build();
+///
+/// dispatcher.dispatch(&world).await.unwrap();
+/// dispatcher.dispatch(&world).await.unwrap();
+///
+/// assert_eq!(world.resource::<Time>().frame(), 2);
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Time {
+    delta: Duration,
+    elapsed: Duration,
+    frame: u64,
+    last: Option<Instant>,
+}
+
+impl Time {
+    /// Returns the time that elapsed between the last two updates. This is
+    /// zero after the first update.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Returns the time that elapsed between the last two updates in
+    /// seconds.
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Returns the time that elapsed since the first update.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the number of updates, which is the number of dispatches if
+    /// the resource is updated by the dispatcher.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Updates the time using the current instant.
+    pub fn update(&mut self) {
+        self.update_at(Instant::now());
+    }
+
+    /// Updates the time using the passed instant.
+    pub fn update_at(&mut self, now: Instant) {
+        let last = self.last.replace(now).unwrap_or(now);
+
+        self.advance(now.saturating_duration_since(last));
+    }
+
+    /// Updates the time by the passed delta, independent of the real time.
+    pub fn advance(&mut self, delta: Duration) {
+        self.delta = delta;
+        self.elapsed += delta;
+        self.frame += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update() {
+        let mut time = Time::default();
+        let now = Instant::now();
+
+        time.update_at(now);
+        assert_eq!(time.frame(), 1);
+        assert_eq!(time.delta(), Duration::default());
+
+        time.update_at(now + Duration::from_millis(10));
+        time.update_at(now + Duration::from_millis(25));
+
+        assert_eq!(time.frame(), 3);
+        assert_eq!(time.delta(), Duration::from_millis(15));
+        assert_eq!(time.elapsed(), Duration::from_millis(25));
+
+        time.advance(Duration::from_secs(1));
+
+        assert_eq!(time.frame(), 4);
+        assert_eq!(time.delta_seconds(), 1.0);
+        assert_eq!(time.elapsed(), Duration::from_millis(1025));
+    }
+}
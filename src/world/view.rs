@@ -0,0 +1,101 @@
+use std::any::type_name;
+
+use crate::{
+    access::Read,
+    component::{Field, InspectComponent},
+    entity::{Entities, Entity},
+    resource::Ref,
+    storage::{MaskedStorage, Storage},
+};
+
+use super::{CastFrom, MetaTable, World};
+
+/// Read-only view of a `World` for debug inspectors, returned by
+/// `World::fetch_all`.
+///
+/// The view iterates over the living entities and returns the fields of all
+/// components that were registered using `World::register_inspect`.
+pub struct WorldView<'a> {
+    world: &'a World,
+    entities: Read<'a, Entities>,
+    inspectors: Ref<'a, MetaTable<dyn AnyInspect>>,
+}
+
+/// Inspected component of an entity, returned by `WorldView::components`.
+#[derive(Clone, Debug)]
+pub struct ComponentView {
+    /// Type name of the component.
+    pub name: &'static str,
+
+    /// Fields of the component, see `InspectComponent::fields`.
+    pub fields: Vec<Field>,
+}
+
+impl<'a> WorldView<'a> {
+    pub(super) fn new(world: &'a World) -> Self {
+        Self {
+            world,
+            entities: world.entities(),
+            inspectors: world.resource(),
+        }
+    }
+
+    /// Returns an iterator over all living entities.
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter()
+    }
+
+    /// Returns the inspectable components of the passed entity. Returns an
+    /// empty list if the entity is not alive.
+    pub fn components(&self, entity: Entity) -> Vec<ComponentView> {
+        if !self.entities.is_alive(entity) {
+            return Vec::new();
+        }
+
+        self.inspectors
+            .iter(self.world)
+            .filter_map(|inspector| inspector.inspect(entity))
+            .collect()
+    }
+}
+
+/* AnyInspect */
+
+/// Type erased storage of an `InspectComponent`.
+pub trait AnyInspect {
+    /// Returns the inspected component of the passed entity, if it has one.
+    fn inspect(&self, entity: Entity) -> Option<ComponentView>;
+}
+
+unsafe impl<T> CastFrom<T> for dyn AnyInspect
+where
+    T: AnyInspect + 'static,
+{
+    fn cast(t: &T) -> &Self {
+        t
+    }
+
+    fn cast_mut(t: &mut T) -> &mut Self {
+        t
+    }
+}
+
+impl<T> AnyInspect for MaskedStorage<T>
+where
+    T: InspectComponent,
+{
+    fn inspect(&self, entity: Entity) -> Option<ComponentView> {
+        let index = entity.index();
+
+        if !self.mask().contains(index) {
+            return None;
+        }
+
+        let component = unsafe { self.storage().get(index) };
+
+        Some(ComponentView {
+            name: type_name::<T>(),
+            fields: component.fields(),
+        })
+    }
+}